@@ -35,6 +35,7 @@ use crate::sys::axuielement::{
 use crate::sys::enhanced_ui::with_enhanced_ui_disabled;
 use crate::sys::event;
 use crate::sys::executor::Executor;
+use crate::sys::geometry::IsWithin;
 use crate::sys::observer::Observer;
 use crate::sys::process::ProcessInfo;
 use crate::sys::skylight::{G_CONNECTION, SLSDisableUpdate, SLSReenableUpdate};
@@ -170,6 +171,24 @@ impl WindowId {
         })
     }
 
+    /// Parse a user-supplied WindowId: either the debug form accepted by
+    /// [`WindowId::from_debug_string`] or the compact "pid:idx" form.
+    pub fn parse(s: &str) -> Option<WindowId> {
+        let s = s.trim();
+        if let Some(wid) = Self::from_debug_string(s) {
+            return Some(wid);
+        }
+
+        let (pid_str, idx_str) = s.split_once(':')?;
+        let pid: pid_t = pid_str.trim().parse().ok()?;
+        let idx: u32 = idx_str.trim().parse().ok()?;
+
+        Some(WindowId {
+            pid,
+            idx: NonZeroU32::new(idx)?,
+        })
+    }
+
     pub fn to_debug_string(&self) -> String { format!("{:?}", self) }
 }
 
@@ -204,6 +223,12 @@ pub enum Request {
     SetBatchWindowFrame(Vec<(WindowId, CGRect)>, TransactionId),
     SetWindowPos(WindowId, CGPoint, TransactionId, bool),
 
+    /// Re-read the frames of the given windows and report any that differ from
+    /// the expected frame by more than the threshold as an unrequested
+    /// [`Event::WindowFrameChanged`]. Used for apps that don't reliably send
+    /// move/resize notifications.
+    PollWindowFrames(Vec<(WindowId, CGRect)>, f64),
+
     BeginWindowAnimation(WindowId),
     EndWindowAnimation(WindowId),
 
@@ -214,6 +239,10 @@ pub enum Request {
     /// parameter for the last window only. Events for other windows will be
     /// marked `Quiet::Yes` automatically.
     Raise(Vec<WindowId>, CancellationToken, u64, Quiet),
+
+    /// Hide or unhide the whole app, as Cmd-H does. The resulting window
+    /// changes are reported through the usual hidden/shown notifications.
+    SetHidden(bool),
 }
 
 struct RaiseRequest(Vec<WindowId>, CancellationToken, u64, Quiet);
@@ -642,6 +671,34 @@ impl State {
                     return Err(err);
                 }
             }
+            Request::PollWindowFrames(expected, threshold) => {
+                let threshold = *threshold;
+                for &(wid, expected) in expected.iter() {
+                    let Ok(window) = self.window(wid) else {
+                        continue;
+                    };
+                    if window.is_animating {
+                        continue;
+                    }
+                    let elem = window.elem.clone();
+                    let txid = self.txid_for_window_state(window);
+                    let frame = match self.handle_ax_result(wid, elem.frame())? {
+                        Some(frame) => frame,
+                        None => continue,
+                    };
+                    if frame.is_within(threshold, expected) {
+                        continue;
+                    }
+                    debug!(?wid, ?frame, ?expected, "Polled frame drifted from tracked");
+                    self.send_event(Event::WindowFrameChanged(
+                        wid,
+                        frame,
+                        txid,
+                        Requested(false),
+                        event::get_mouse_state(),
+                    ));
+                }
+            }
             &mut Request::BeginWindowAnimation(wid) => {
                 let had_animations = self.has_active_window_animations();
                 let (elem, started_animation) = {
@@ -695,6 +752,16 @@ impl State {
                 self.raises_tx
                     .send(RaiseRequest(wids.clone(), token.clone(), sequence_id, quiet));
             }
+            &mut Request::SetHidden(hidden) => {
+                let changed = if hidden {
+                    self.running_app.hide()
+                } else {
+                    self.running_app.unhide()
+                };
+                if !changed {
+                    debug!(pid = self.pid, hidden, "App hidden state did not change");
+                }
+            }
         }
         Ok(false)
     }
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 
 use crate::actor::app::WindowId;
 use crate::layout_engine::{LayoutKind, VirtualWorkspaceId};
+use crate::sys::app::pid_t;
 use crate::sys::screen::SpaceId;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -13,9 +15,37 @@ pub struct StackInfo {
     pub windows: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// What made a window start or stop being tiled.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ManageabilityReason {
+    Minimized,
+    Deminiaturized,
+    /// The window server reported a new layer, stickiness or level for the window.
+    WindowServerUpdate,
+    /// The app reported the window again with new attributes.
+    Rediscovered,
+}
+
+impl ManageabilityReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ManageabilityReason::Minimized => "minimized",
+            ManageabilityReason::Deminiaturized => "deminiaturized",
+            ManageabilityReason::WindowServerUpdate => "window_server_update",
+            ManageabilityReason::Rediscovered => "rediscovered",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, strum_macros::EnumDiscriminants)]
 #[serde(rename_all = "snake_case")]
 #[serde(tag = "type")]
+#[strum_discriminants(
+    name(BroadcastEventKind),
+    derive(strum_macros::EnumIter, strum_macros::IntoStaticStr),
+    strum(serialize_all = "snake_case")
+)]
 pub enum BroadcastEvent {
     WorkspaceChanged {
         space_id: SpaceId,
@@ -49,7 +79,183 @@ pub enum BroadcastEvent {
         space_id: SpaceId,
         display_uuid: Option<String>,
     },
+    /// A workspace lost its last window.
+    WorkspaceEmpty {
+        space_id: SpaceId,
+        workspace_id: VirtualWorkspaceId,
+        workspace_name: String,
+        display_uuid: Option<String>,
+    },
+    /// A previously empty workspace gained a window.
+    WorkspaceNonempty {
+        space_id: SpaceId,
+        workspace_id: VirtualWorkspaceId,
+        workspace_name: String,
+        display_uuid: Option<String>,
+    },
+    /// A workspace was given a new name.
+    WorkspaceRenamed {
+        space_id: SpaceId,
+        workspace_id: VirtualWorkspaceId,
+        workspace_index: Option<u64>,
+        previous_name: String,
+        workspace_name: String,
+        display_uuid: Option<String>,
+    },
+    /// A window settled into being managed (tiled) or not, after any rapid flips.
+    WindowManageabilityChanged {
+        window_id: WindowId,
+        is_manageable: bool,
+        reason: ManageabilityReason,
+    },
+    /// Rift started tracking an app.
+    AppLaunched {
+        pid: pid_t,
+        bundle_id: Option<String>,
+        app_name: Option<String>,
+    },
+    /// An app quit, or Rift lost its connection to it.
+    AppTerminated {
+        pid: pid_t,
+        bundle_id: Option<String>,
+        app_name: Option<String>,
+    },
+}
+
+impl BroadcastEvent {
+    /// The name clients pass to `Subscribe` to receive this event. It matches the
+    /// `type` tag of the serialized event.
+    pub fn name(&self) -> &'static str { BroadcastEventKind::from(self).into() }
+}
+
+impl BroadcastEventKind {
+    fn description(self) -> &'static str {
+        match self {
+            BroadcastEventKind::WorkspaceChanged => "The active workspace on a display changed",
+            BroadcastEventKind::WindowsChanged => "The set of windows in a workspace changed",
+            BroadcastEventKind::WindowTitleChanged => "A window's title changed",
+            BroadcastEventKind::StacksChanged => "Stacked containers in a workspace changed",
+            BroadcastEventKind::WorkspaceEmpty => "A workspace lost its last window",
+            BroadcastEventKind::WorkspaceNonempty => "A previously empty workspace gained a window",
+            BroadcastEventKind::WorkspaceRenamed => "A workspace was given a new name",
+            BroadcastEventKind::WindowManageabilityChanged => {
+                "A window started or stopped being managed"
+            }
+            BroadcastEventKind::AppLaunched => "Rift started tracking an app",
+            BroadcastEventKind::AppTerminated => "An app quit or stopped responding to Rift",
+        }
+    }
+
+    fn payload(self) -> &'static [&'static str] {
+        match self {
+            BroadcastEventKind::WorkspaceChanged
+            | BroadcastEventKind::WorkspaceEmpty
+            | BroadcastEventKind::WorkspaceNonempty => &[
+                "space_id",
+                "workspace_id",
+                "workspace_name",
+                "display_uuid?",
+            ],
+            BroadcastEventKind::WindowsChanged => &[
+                "workspace_id",
+                "workspace_name",
+                "windows",
+                "space_id",
+                "display_uuid?",
+            ],
+            BroadcastEventKind::WindowTitleChanged => &[
+                "window_id",
+                "workspace_id",
+                "workspace_index?",
+                "workspace_name",
+                "previous_title",
+                "new_title",
+                "space_id",
+                "display_uuid?",
+            ],
+            BroadcastEventKind::StacksChanged => &[
+                "workspace_id",
+                "workspace_index?",
+                "workspace_name",
+                "stacks",
+                "active_workspace_has_fullscreen",
+                "space_id",
+                "display_uuid?",
+            ],
+            BroadcastEventKind::WorkspaceRenamed => &[
+                "space_id",
+                "workspace_id",
+                "workspace_index?",
+                "previous_name",
+                "workspace_name",
+                "display_uuid?",
+            ],
+            BroadcastEventKind::WindowManageabilityChanged => {
+                &["window_id", "is_manageable", "reason"]
+            }
+            BroadcastEventKind::AppLaunched | BroadcastEventKind::AppTerminated => {
+                &["pid", "bundle_id?", "app_name?"]
+            }
+        }
+    }
+}
+
+/// Subscribing to this name delivers every event.
+pub const WILDCARD_EVENT: &str = "*";
+
+/// An entry in the `GetSubscribableEvents` response.
+#[derive(Serialize, Debug, Clone)]
+pub struct SubscribableEvent {
+    pub event: &'static str,
+    pub description: &'static str,
+    /// Payload fields besides `type`; a trailing `?` marks ones that may be null.
+    pub payload: &'static [&'static str],
+}
+
+/// Every event name accepted by `Subscribe`, including the wildcard.
+pub fn subscribable_events() -> Vec<SubscribableEvent> {
+    BroadcastEventKind::iter()
+        .map(|kind| SubscribableEvent {
+            event: kind.into(),
+            description: kind.description(),
+            payload: kind.payload(),
+        })
+        .chain(std::iter::once(SubscribableEvent {
+            event: WILDCARD_EVENT,
+            description: "Every event listed here",
+            payload: &[],
+        }))
+        .collect()
+}
+
+pub fn is_subscribable_event(event: &str) -> bool {
+    event == WILDCARD_EVENT || BroadcastEventKind::iter().any(|kind| <&str>::from(kind) == event)
 }
 
 pub type BroadcastSender = crate::actor::Sender<BroadcastEvent>;
 pub type BroadcastReceiver = crate::actor::Receiver<BroadcastEvent>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscription_names_match_serialized_event_type() {
+        let event = BroadcastEvent::WorkspaceRenamed {
+            space_id: SpaceId::new(1),
+            workspace_id: VirtualWorkspaceId::default(),
+            workspace_index: Some(0),
+            previous_name: "one".into(),
+            workspace_name: "web".into(),
+            display_uuid: None,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], event.name());
+
+        let catalog = subscribable_events();
+        assert!(catalog.iter().any(|entry| entry.event == "workspace_renamed"));
+        assert_eq!(catalog.last().map(|entry| entry.event), Some(WILDCARD_EVENT));
+        assert!(is_subscribable_event("window_manageability_changed"));
+        assert!(!is_subscribable_event("workspace_change"));
+    }
+}
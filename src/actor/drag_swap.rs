@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::time::{Duration, Instant};
 
 use objc2_core_foundation::{CGPoint, CGRect};
 
@@ -13,6 +14,11 @@ const OVERLAP_WEIGHT: f64 = 0.7;
 const CENTER_WEIGHT: f64 = 1.0 - OVERLAP_WEIGHT;
 // require only a modest improvement before switching to a new candidate.
 const SWITCH_DELTA: f64 = 0.04;
+// switching straight back to the window we just left needs a much clearer win,
+// otherwise a cursor wobbling over a tile boundary flips the swap target.
+const REVERT_DELTA: f64 = 0.12;
+// how long after a switch the stricter revert threshold applies.
+const REVERT_WINDOW: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone, Copy)]
 struct CandidateMetrics {
@@ -26,11 +32,18 @@ struct ActiveCandidate {
     window: WindowId,
 }
 
+#[derive(Debug, Clone, Copy)]
+struct LastSwitch {
+    from: Option<WindowId>,
+    at: Instant,
+}
+
 #[derive(Debug, Clone)]
 pub struct DragManager {
     dragged_window: Option<WindowId>,
     drag_origin_frame: Option<CGRect>,
     active_candidate: Option<ActiveCandidate>,
+    last_switch: Option<LastSwitch>,
     config: WindowSnappingSettings,
 }
 
@@ -44,6 +57,7 @@ impl DragManager {
             dragged_window: None,
             drag_origin_frame: None,
             active_candidate: None,
+            last_switch: None,
             config,
         }
     }
@@ -54,14 +68,21 @@ impl DragManager {
         new_frame: CGRect,
         candidates: &[(WindowId, CGRect)],
     ) -> Option<WindowId> {
-        if self.dragged_window.is_none() {
-            self.dragged_window = Some(wid);
-            self.drag_origin_frame = Some(new_frame);
-            self.active_candidate = None;
-        } else if self.dragged_window != Some(wid) {
+        self.on_frame_change_at(wid, new_frame, candidates, Instant::now())
+    }
+
+    fn on_frame_change_at(
+        &mut self,
+        wid: WindowId,
+        new_frame: CGRect,
+        candidates: &[(WindowId, CGRect)],
+        now: Instant,
+    ) -> Option<WindowId> {
+        if self.dragged_window != Some(wid) {
             self.dragged_window = Some(wid);
             self.drag_origin_frame = Some(new_frame);
             self.active_candidate = None;
+            self.last_switch = None;
         }
 
         let dragged_area = new_frame.size.width * new_frame.size.height;
@@ -135,10 +156,17 @@ impl DragManager {
                 return None;
             }
 
-            if best.overlap >= self.config.drag_swap_fraction
-                && best.score >= active.score + SWITCH_DELTA
+            let reverting = self.last_switch.is_some_and(|last| {
+                last.from == Some(best.window) && now.duration_since(last.at) < REVERT_WINDOW
+            });
+            let delta = if reverting {
+                REVERT_DELTA
+            } else {
+                SWITCH_DELTA
+            };
+            if best.overlap >= self.config.drag_swap_fraction && best.score >= active.score + delta
             {
-                self.active_candidate = Some(ActiveCandidate { window: best.window });
+                self.switch_to(best.window, now);
                 return Some(best.window);
             }
 
@@ -146,7 +174,7 @@ impl DragManager {
         }
 
         if best.overlap >= self.config.drag_swap_fraction {
-            self.active_candidate = Some(ActiveCandidate { window: best.window });
+            self.switch_to(best.window, now);
             return Some(best.window);
         }
 
@@ -154,10 +182,19 @@ impl DragManager {
         None
     }
 
+    fn switch_to(&mut self, window: WindowId, now: Instant) {
+        self.last_switch = Some(LastSwitch {
+            from: self.active_candidate.map(|candidate| candidate.window),
+            at: now,
+        });
+        self.active_candidate = Some(ActiveCandidate { window });
+    }
+
     pub fn reset(&mut self) {
         self.dragged_window = None;
         self.drag_origin_frame = None;
         self.active_candidate = None;
+        self.last_switch = None;
     }
 
     pub fn last_target(&self) -> Option<WindowId> {
@@ -198,9 +235,16 @@ mod tests {
         }
     }
 
+    fn snapping(drag_swap_fraction: f64) -> WindowSnappingSettings {
+        WindowSnappingSettings {
+            drag_swap_fraction,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn selects_candidate_based_on_scored_overlap() {
-        let mut dm = DragManager::new(WindowSnappingSettings { drag_swap_fraction: 0.3 });
+        let mut dm = DragManager::new(snapping(0.3));
 
         let dragged = rect(0.0, 0.0, 100.0, 100.0);
         let wid = WindowId::new(1, 1);
@@ -214,7 +258,7 @@ mod tests {
 
     #[test]
     fn respects_last_target_to_avoid_repeats() {
-        let mut dm = DragManager::new(WindowSnappingSettings { drag_swap_fraction: 0.25 });
+        let mut dm = DragManager::new(snapping(0.25));
         let wid = WindowId::new(1, 10);
         let dragged = rect(0.0, 0.0, 200.0, 100.0);
 
@@ -229,7 +273,7 @@ mod tests {
 
     #[test]
     fn clears_active_target_when_overlap_is_lost() {
-        let mut dm = DragManager::new(WindowSnappingSettings { drag_swap_fraction: 0.2 });
+        let mut dm = DragManager::new(snapping(0.2));
         let wid = WindowId::new(1, 42);
         let dragged = rect(0.0, 0.0, 100.0, 100.0);
         let cand = (WindowId::new(1, 99), rect(0.0, 0.0, 60.0, 100.0));
@@ -246,7 +290,7 @@ mod tests {
 
     #[test]
     fn hysteresis_keeps_candidate_when_overlap_drops_slightly() {
-        let mut dm = DragManager::new(WindowSnappingSettings { drag_swap_fraction: 0.4 });
+        let mut dm = DragManager::new(snapping(0.4));
         let wid = WindowId::new(5, 1);
         let dragged = rect(0.0, 0.0, 100.0, 100.0);
         let cand = (WindowId::new(5, 2), rect(0.0, 0.0, 50.0, 100.0)); // 50%
@@ -262,7 +306,7 @@ mod tests {
 
     #[test]
     fn switches_only_when_new_candidate_is_meaningfully_better() {
-        let mut dm = DragManager::new(WindowSnappingSettings { drag_swap_fraction: 0.3 });
+        let mut dm = DragManager::new(snapping(0.3));
         let wid = WindowId::new(7, 1);
         let dragged = rect(0.0, 0.0, 120.0, 100.0);
 
@@ -284,4 +328,34 @@ mod tests {
         assert_eq!(switched, Some(WindowId::new(7, 2)));
         assert_eq!(dm.last_target(), Some(WindowId::new(7, 2)));
     }
+
+    #[test]
+    fn wobbling_across_a_boundary_does_not_flip_back_immediately() {
+        let mut dm = DragManager::new(snapping(0.3));
+        let wid = WindowId::new(8, 1);
+        let left = (WindowId::new(8, 2), rect(0.0, 0.0, 100.0, 100.0));
+        let right = (WindowId::new(8, 3), rect(100.0, 0.0, 100.0, 100.0));
+        let start = Instant::now();
+        let mut drag_to = |x: f64, ms: u64| {
+            dm.on_frame_change_at(
+                wid,
+                rect(x, 0.0, 100.0, 100.0),
+                &[left, right],
+                start + Duration::from_millis(ms),
+            )
+        };
+
+        assert_eq!(drag_to(40.0, 0), Some(left.0));
+        assert_eq!(drag_to(55.0, 20), Some(right.0));
+
+        // Small wobbles around the boundary keep the target that was just chosen.
+        for (step, x) in [47.0, 55.0, 47.0, 55.0, 47.0].into_iter().enumerate() {
+            assert_eq!(drag_to(x, 40 + step as u64 * 20), None);
+        }
+
+        // A decisive move back still switches, as does settling once the window passes.
+        assert_eq!(drag_to(35.0, 200), Some(left.0));
+        assert_eq!(drag_to(55.0, 220), None);
+        assert_eq!(drag_to(55.0, 800), Some(right.0));
+    }
 }
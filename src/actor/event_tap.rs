@@ -27,7 +27,7 @@ use crate::sys::event::{self, Hotkey, KeyCode, MouseState, set_mouse_state};
 use crate::sys::geometry::CGRectExt;
 use crate::sys::hotkey::{
     Modifiers, is_modifier_key, key_code_from_event, modifier_flag_for_key,
-    modifiers_from_flags_with_keys,
+    modifier_keys_for_hotkey, modifiers_from_flags_with_keys,
 };
 use crate::sys::screen::{CoordinateConverter, SpaceId};
 use crate::sys::window_server::{self, WindowServerId, window_level};
@@ -42,6 +42,13 @@ const MOUSE_MOVE_MIN_INTERVAL_NS_NORMAL: u64 = 8_000_000; // 8ms ~= 125 Hz
 const MOUSE_MOVE_MIN_DISTANCE_PX_SQ_NORMAL: f64 = 4.0; // 2px^2
 const MOUSE_MOVE_MIN_INTERVAL_NS_LOW_POWER: u64 = 16_000_000; // 16ms ~= 62 Hz
 const MOUSE_MOVE_MIN_DISTANCE_PX_SQ_LOW_POWER: f64 = 9.0; // 3px^2
+// Modifiers that, held together with the double-tap modifier, mean the press is part
+// of some other shortcut.
+const DOUBLE_TAP_MODIFIER_MASK: CGEventFlags = CGEventFlags::MaskShift
+    .union(CGEventFlags::MaskControl)
+    .union(CGEventFlags::MaskAlternate)
+    .union(CGEventFlags::MaskCommand)
+    .union(CGEventFlags::MaskSecondaryFn);
 
 #[derive(Debug)]
 pub enum Request {
@@ -67,6 +74,7 @@ pub struct EventTap {
     disable_hotkey: RefCell<Option<Hotkey>>,
     swipe: RefCell<Option<SwipeHandler>>,
     scroll: RefCell<Option<ScrollHandler>>,
+    double_tap: RefCell<Option<DoubleTapDetector>>,
     hotkeys: RefCell<HashMap<Hotkey, Vec<WmCommand>>>,
     wm_sender: Option<wm_controller::Sender>,
     stack_line_tx: Option<stack_line::Sender>,
@@ -253,6 +261,92 @@ struct ScrollHandler {
     state: RefCell<ScrollState>,
 }
 
+/// Recognizes two quick taps of a lone modifier key, with nothing else pressed
+/// in between.
+#[derive(Debug)]
+struct DoubleTapDetector {
+    /// Keys that count as the modifier; only one side for e.g. "RightAlt"
+    keys: Vec<KeyCode>,
+    modifier: CGEventFlags,
+    interval_ns: u64,
+    pressed_at: Option<u64>,
+    last_tap_at: Option<u64>,
+}
+
+impl DoubleTapDetector {
+    fn from_config(config: &Config) -> Option<Self> {
+        let spec = config.settings.double_tap_float_modifier.as_ref()?;
+        let Some((hotkey, modifier)) = spec.to_hotkey().and_then(|hotkey| {
+            let flag = modifier_flag_for_key(hotkey.key_code)?;
+            DOUBLE_TAP_MODIFIER_MASK.contains(flag).then_some((hotkey, flag))
+        }) else {
+            warn!(
+                ?spec,
+                "double_tap_float_modifier is not a modifier key; ignoring it"
+            );
+            return None;
+        };
+        Some(Self {
+            keys: modifier_keys_for_hotkey(&hotkey),
+            modifier,
+            interval_ns: config.settings.double_tap_interval_ms.saturating_mul(1_000_000),
+            pressed_at: None,
+            last_tap_at: None,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.pressed_at = None;
+        self.last_tap_at = None;
+    }
+
+    /// A key pressed in between, e.g. as part of a shortcut, cancels the gesture.
+    fn on_key_down(&mut self) { self.reset(); }
+
+    /// Feeds a flags-changed event for modifier key `key`. Returns true when the
+    /// event completes a double tap.
+    fn on_flags_changed(
+        &mut self,
+        key: Option<KeyCode>,
+        flags: CGEventFlags,
+        timestamp: u64,
+    ) -> bool {
+        if !key.is_some_and(|key| self.keys.contains(&key)) {
+            self.reset();
+            return false;
+        }
+        let held = flags.intersection(DOUBLE_TAP_MODIFIER_MASK);
+        if held == self.modifier {
+            if self
+                .last_tap_at
+                .is_some_and(|at| timestamp.saturating_sub(at) > self.interval_ns)
+            {
+                self.last_tap_at = None;
+            }
+            self.pressed_at = Some(timestamp);
+            return false;
+        }
+        if !held.is_empty() {
+            self.reset();
+            return false;
+        }
+
+        let Some(pressed_at) = self.pressed_at.take() else {
+            return false;
+        };
+        if timestamp.saturating_sub(pressed_at) > self.interval_ns {
+            // Held too long to be a tap.
+            self.last_tap_at = None;
+            return false;
+        }
+        if self.last_tap_at.take().is_some() {
+            return true;
+        }
+        self.last_tap_at = Some(timestamp);
+        false
+    }
+}
+
 unsafe fn drop_mouse_ctx(ptr: *mut std::ffi::c_void) {
     unsafe { drop(Box::from_raw(ptr as *mut CallbackCtx)) };
 }
@@ -307,7 +401,9 @@ impl EventTap {
     }
 
     fn keyboard_handlers_enabled(&self) -> bool {
-        self.disable_hotkey.borrow().is_some() || !self.hotkeys.borrow().is_empty()
+        self.disable_hotkey.borrow().is_some()
+            || self.double_tap.borrow().is_some()
+            || !self.hotkeys.borrow().is_empty()
     }
 
     fn mouse_move_handlers_enabled(&self) -> bool {
@@ -379,6 +475,7 @@ impl EventTap {
             .clone()
             .and_then(|spec| spec.to_hotkey());
         let (swipe, scroll) = Self::build_gesture_handlers(&config, wm_sender.is_some());
+        let double_tap = DoubleTapDetector::from_config(&config);
         let mut state = State::default();
         state.mouse_hides_on_focus = config.settings.mouse_hides_on_focus;
         state.focus_follows_mouse_config_enabled = config.settings.focus_follows_mouse;
@@ -390,7 +487,7 @@ impl EventTap {
             .unwrap_or(false);
         let event_mask = build_event_mask(
             swipe.is_some() || scroll.is_some(),
-            disable_hotkey.is_some(),
+            disable_hotkey.is_some() || double_tap.is_some(),
             state.event_processing_enabled
                 && ((state.stack_line_enabled && stack_line_tx.is_some())
                     || Self::focus_follows_mouse_handler_enabled(&state)),
@@ -405,6 +502,7 @@ impl EventTap {
             disable_hotkey: RefCell::new(disable_hotkey),
             swipe: RefCell::new(swipe),
             scroll: RefCell::new(scroll),
+            double_tap: RefCell::new(double_tap),
             hotkeys: RefCell::new(HashMap::default()),
             wm_sender,
             stack_line_tx,
@@ -531,6 +629,7 @@ impl EventTap {
                     .focus_follows_mouse_disable_hotkey
                     .clone()
                     .and_then(|spec| spec.to_hotkey());
+                *self.double_tap.borrow_mut() = DoubleTapDetector::from_config(&new_config);
                 *self.config.borrow_mut() = new_config;
                 *self.disable_hotkey.borrow_mut() = disable_hotkey;
                 {
@@ -639,6 +738,9 @@ impl EventTap {
         match event_type {
             CGEventType::LeftMouseDown | CGEventType::RightMouseDown => {
                 set_mouse_state(MouseState::Down);
+                if let Some(detector) = self.double_tap.borrow_mut().as_mut() {
+                    detector.reset();
+                }
 
                 if let Some(tx) = &self.stack_line_tx {
                     let loc = CGEvent::location(Some(event));
@@ -698,7 +800,7 @@ impl EventTap {
                     if let Some(wsid) =
                         state.track_mouse_move(loc, window_from_mouse_event(event), ts)
                     {
-                        _ = self.events_tx.send(Event::MouseMovedOverWindow(wsid));
+                        _ = self.events_tx.send(Event::MouseMovedOverWindow(wsid, loc));
                     }
                 }
             }
@@ -988,6 +1090,22 @@ impl EventTap {
         state.current_flags = flags;
         self.refresh_disable_hotkey_state(state);
 
+        if let Some(detector) = self.double_tap.borrow_mut().as_mut() {
+            match event_type {
+                CGEventType::FlagsChanged => {
+                    let timestamp = CGEvent::timestamp(Some(event));
+                    if detector.on_flags_changed(key_code_opt, flags, timestamp) {
+                        debug!("Modifier double-tapped; toggling window floating");
+                        _ = self.events_tx.send(Event::Command(reactor::Command::Layout(
+                            LC::ToggleWindowFloating,
+                        )));
+                    }
+                }
+                CGEventType::KeyDown => detector.on_key_down(),
+                _ => {}
+            }
+        }
+
         if event_type == CGEventType::KeyDown {
             if let Some(key_code) = key_code_opt {
                 let hotkey = Hotkey::new(
@@ -1305,6 +1423,75 @@ fn build_event_mask(
 mod tests {
     use super::*;
 
+    #[test]
+    fn double_tap_detector_needs_two_quick_lone_taps() {
+        const MS: u64 = 1_000_000;
+        let ctrl = CGEventFlags::MaskControl;
+        let none = CGEventFlags::empty();
+        let mut detector = DoubleTapDetector {
+            keys: vec![KeyCode::ControlLeft, KeyCode::ControlRight],
+            modifier: ctrl,
+            interval_ns: 300 * MS,
+            pressed_at: None,
+            last_tap_at: None,
+        };
+        let tap = |detector: &mut DoubleTapDetector, at: u64| {
+            let key = Some(KeyCode::ControlLeft);
+            let pressed = detector.on_flags_changed(key, ctrl, at * MS);
+            let released = detector.on_flags_changed(key, none, (at + 50) * MS);
+            assert!(!pressed);
+            released
+        };
+
+        assert!(!tap(&mut detector, 0));
+        assert!(tap(&mut detector, 200));
+
+        // Too far apart.
+        assert!(!tap(&mut detector, 1000));
+        assert!(!tap(&mut detector, 2000));
+        detector.reset();
+
+        // A shortcut in between (e.g. Ctrl-C) cancels the gesture.
+        assert!(!tap(&mut detector, 3000));
+        detector.on_key_down();
+        assert!(!tap(&mut detector, 3200));
+
+        // Pressing another modifier along with it is not a tap.
+        assert!(!tap(&mut detector, 4000));
+        let both = ctrl.union(CGEventFlags::MaskShift);
+        assert!(!detector.on_flags_changed(Some(KeyCode::ShiftLeft), both, 4100 * MS));
+        assert!(!tap(&mut detector, 4200));
+    }
+
+    #[test]
+    fn double_tap_detector_tells_left_and_right_keys_apart() {
+        const MS: u64 = 1_000_000;
+        let alt = CGEventFlags::MaskAlternate;
+        let none = CGEventFlags::empty();
+        let right_alt = Hotkey::new(Modifiers::ALT_RIGHT, KeyCode::AltRight);
+        let mut detector = DoubleTapDetector {
+            keys: modifier_keys_for_hotkey(&right_alt),
+            modifier: alt,
+            interval_ns: 300 * MS,
+            pressed_at: None,
+            last_tap_at: None,
+        };
+        let mut tap = |key: KeyCode, at: u64| {
+            detector.on_flags_changed(Some(key), alt, at * MS);
+            detector.on_flags_changed(Some(key), none, (at + 50) * MS)
+        };
+
+        assert!(!tap(KeyCode::AltLeft, 0));
+        assert!(!tap(KeyCode::AltLeft, 200));
+        assert!(!tap(KeyCode::AltRight, 1000));
+        assert!(!tap(KeyCode::AltLeft, 1200));
+        assert!(!tap(KeyCode::AltRight, 2000));
+        assert!(tap(KeyCode::AltRight, 2200));
+
+        let either_alt = modifier_keys_for_hotkey(&Hotkey::new(Modifiers::ALT, KeyCode::AltLeft));
+        assert_eq!(either_alt, vec![KeyCode::AltLeft, KeyCode::AltRight]);
+    }
+
     #[test]
     fn layout_mode_at_point_uses_space_mapping() {
         let mut state = State::default();
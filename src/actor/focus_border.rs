@@ -0,0 +1,101 @@
+use objc2::MainThreadMarker;
+use objc2_core_foundation::CGRect;
+use tracing::instrument;
+
+use crate::actor;
+use crate::common::config::Config;
+use crate::ui::focus_border::{FocusBorderConfig, FocusBorderWindow};
+
+#[derive(Debug)]
+pub enum Event {
+    /// Outline the focused window, which now occupies `frame`
+    Show {
+        frame: CGRect,
+    },
+    /// No window should be outlined, e.g. while a window is being dragged
+    Hide,
+    ConfigUpdated(Config),
+}
+
+pub struct FocusBorder {
+    config: Config,
+    rx: Receiver,
+    #[allow(dead_code)]
+    mtm: MainThreadMarker,
+    border: Option<FocusBorderWindow>,
+}
+
+pub type Sender = actor::Sender<Event>;
+pub type Receiver = actor::Receiver<Event>;
+
+impl FocusBorder {
+    pub fn new(config: Config, rx: Receiver, mtm: MainThreadMarker) -> Self {
+        Self { config, rx, mtm, border: None }
+    }
+
+    pub async fn run(mut self) {
+        while let Some((span, event)) = self.rx.recv().await {
+            let _guard = span.enter();
+            self.handle_event(event);
+        }
+    }
+
+    fn is_enabled(&self) -> bool { self.config.settings.ui.focus_border.enabled }
+
+    fn border_config(&self) -> FocusBorderConfig {
+        FocusBorderConfig::from(&self.config.settings.ui.focus_border)
+    }
+
+    #[instrument(name = "focus_border::handle_event", skip(self))]
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Show { frame } => self.show(frame),
+            Event::Hide => self.hide(),
+            Event::ConfigUpdated(config) => self.handle_config_updated(config),
+        }
+    }
+
+    fn show(&mut self, frame: CGRect) {
+        if !self.is_enabled() {
+            return;
+        }
+        match &mut self.border {
+            Some(border) => {
+                if let Err(err) = border.show(frame) {
+                    tracing::warn!(?err, "failed to move focus border");
+                }
+            }
+            None => match FocusBorderWindow::new(frame, self.border_config()) {
+                Ok(mut border) => {
+                    if let Err(err) = border.show(frame) {
+                        tracing::warn!(?err, "failed to show focus border");
+                    }
+                    self.border = Some(border);
+                }
+                Err(err) => tracing::warn!(?err, "failed to create focus border window"),
+            },
+        }
+    }
+
+    fn hide(&mut self) {
+        if let Some(border) = &self.border
+            && let Err(err) = border.hide()
+        {
+            tracing::warn!(?err, "failed to hide focus border");
+        }
+    }
+
+    fn handle_config_updated(&mut self, config: Config) {
+        self.config = config;
+        if !self.is_enabled() {
+            self.hide();
+            self.border = None;
+            return;
+        }
+        let border_config = self.border_config();
+        if let Some(border) = &mut self.border {
+            border.set_config(border_config);
+        }
+        tracing::debug!("Updated focus border configuration");
+    }
+}
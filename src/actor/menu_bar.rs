@@ -372,6 +372,7 @@ mod tests {
             index: 0,
             name: "main".to_string(),
             layout_mode: layout_mode.to_string(),
+            is_frozen: false,
             is_active: true,
             window_count: 1,
             windows: Vec::new(),
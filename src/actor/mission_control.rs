@@ -3,14 +3,18 @@ use std::rc::Rc;
 use objc2_app_kit::NSScreen;
 use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 use objc2_foundation::MainThreadMarker;
-use tracing::instrument;
+use tracing::{debug, instrument};
 
+use crate::actor::app::WindowId;
 use crate::actor::{self, reactor};
 use crate::common::config::Config;
 use crate::sys::event::current_cursor_location;
 use crate::sys::geometry::CGRectExt;
 use crate::sys::screen::{NSScreenExt, ScreenCache, get_active_space_number};
-use crate::ui::mission_control::{MissionControlAction, MissionControlMode, MissionControlOverlay};
+use crate::sys::window_server::WindowServerId;
+use crate::ui::mission_control::{
+    MissionControlAction, MissionControlMode, MissionControlOverlay, WorkspaceStep,
+};
 
 #[derive(Debug)]
 pub enum Event {
@@ -37,6 +41,9 @@ pub struct MissionControlActor {
     mtm: MainThreadMarker,
     mission_control_active: bool,
     current_view_mode: Option<MissionControlViewMode>,
+    /// The window that had focus when the overlay opened, refocused when it is
+    /// dismissed without picking anything.
+    focus_before_show: Option<(WindowId, Option<WindowServerId>)>,
 }
 
 impl MissionControlActor {
@@ -54,12 +61,14 @@ impl MissionControlActor {
             mtm,
             mission_control_active: false,
             current_view_mode: None,
+            focus_before_show: None,
         }
     }
 
     pub async fn run(mut self) {
         while let Some((span, event)) = self.rx.recv().await {
             let _guard = span.enter();
+            self.drop_hidden_overlay();
             if self.config.settings.ui.mission_control.enabled {
                 self.handle_event(event);
             }
@@ -119,17 +128,55 @@ impl MissionControlActor {
     }
 
     fn dispose_overlay(&mut self) {
-        if let Some(overlay) = self.overlay.take() {
+        if let Some(overlay) = &self.overlay {
             overlay.hide();
         }
+        // An overlay that is still animating out stays alive until it finishes;
+        // showing again in the meantime reuses it and reverses the animation.
         self.mission_control_active = false;
         self.current_view_mode = None;
+        self.focus_before_show = None;
+        self.drop_hidden_overlay();
+    }
+
+    fn remember_focused_window(&mut self) {
+        self.focus_before_show = self
+            .reactor
+            .query_windows(None, false)
+            .into_iter()
+            .find(|window| window.is_focused)
+            .map(|window| (window.id, window.info.sys_id));
+    }
+
+    /// Closes the overlay and hands focus back to the window that had it before the
+    /// overlay opened, unless that window has since been closed.
+    fn dismiss_and_restore_focus(&mut self) {
+        let previous = self.focus_before_show.take();
+        self.dispose_overlay();
+        let Some((window_id, window_server_id)) = previous else {
+            return;
+        };
+        if self.reactor.query_window_info(window_id).is_none() {
+            debug!(?window_id, "Window focused before overlay is gone");
+            return;
+        }
+        let _ = self.reactor.try_send(reactor::Event::Command(reactor::Command::Reactor(
+            reactor::ReactorCommand::FocusWindow { window_id, window_server_id },
+        )));
+    }
+
+    fn drop_hidden_overlay(&mut self) {
+        if !self.mission_control_active
+            && self.overlay.as_ref().is_some_and(|overlay| overlay.is_hidden())
+        {
+            self.overlay = None;
+        }
     }
 
     fn handle_overlay_action(&mut self, action: MissionControlAction) {
         match action {
             MissionControlAction::Dismiss => {
-                self.dispose_overlay();
+                self.dismiss_and_restore_focus();
             }
             MissionControlAction::SwitchToWorkspace(index) => {
                 let _ = self.reactor.try_send(reactor::Event::Command(reactor::Command::Layout(
@@ -143,6 +190,49 @@ impl MissionControlActor {
                 )));
                 self.dispose_overlay();
             }
+            MissionControlAction::MoveWindowToWorkspace { window_id, target } => {
+                self.move_window_to_adjacent_workspace(window_id, target);
+            }
+        }
+    }
+
+    fn move_window_to_adjacent_workspace(&mut self, window_id: WindowId, target: WorkspaceStep) {
+        // Workspaces are listed per display, so stopping at either end instead of
+        // wrapping keeps the window on the display the overlay is showing.
+        let workspaces = self.reactor.query_workspaces(None, false);
+        let Some(current) = workspaces
+            .iter()
+            .position(|ws| ws.windows.iter().any(|window| window.id == window_id))
+        else {
+            return;
+        };
+        let target = match target {
+            WorkspaceStep::Previous => current.checked_sub(1),
+            WorkspaceStep::Next => Some(current + 1).filter(|&idx| idx < workspaces.len()),
+        };
+        let Some(workspace) = target.map(|idx| workspaces[idx].index) else {
+            return;
+        };
+        // Refocusing a window that was just sent elsewhere would drag the user along
+        // to its new workspace on dismiss.
+        if self.focus_before_show.is_some_and(|(wid, _)| wid == window_id) {
+            self.focus_before_show = None;
+        }
+
+        let _ = self.reactor.try_send(reactor::Event::Command(reactor::Command::Layout(
+            crate::layout_engine::LayoutCommand::MoveWindowToWorkspace {
+                workspace,
+                window_id: Some(window_id.idx.get()),
+                bundle_id: None,
+                title: None,
+                target_index: None,
+            },
+        )));
+
+        // Queries share the reactor's queue, so this sees the window already moved.
+        let windows = self.reactor.query_windows(None, false);
+        if let Some(overlay) = self.overlay.as_ref() {
+            overlay.update(MissionControlMode::CurrentWorkspace(windows));
         }
     }
 
@@ -151,19 +241,21 @@ impl MissionControlActor {
         match event {
             Event::ShowAll => {
                 if self.mission_control_active {
-                    self.dispose_overlay();
+                    self.dismiss_and_restore_focus();
                 } else {
+                    self.remember_focused_window();
                     self.show_all_workspaces();
                 }
             }
             Event::ShowCurrent => {
                 if self.mission_control_active {
-                    self.dispose_overlay();
+                    self.dismiss_and_restore_focus();
                 } else {
+                    self.remember_focused_window();
                     self.show_current_workspace();
                 }
             }
-            Event::Dismiss => self.dispose_overlay(),
+            Event::Dismiss => self.dismiss_and_restore_focus(),
             Event::RefreshCurrentWorkspace => {
                 if self.mission_control_active {
                     match self.current_view_mode {
@@ -188,7 +280,7 @@ impl MissionControlActor {
             overlay.update(MissionControlMode::AllWorkspaces(Vec::new()));
         }
 
-        let resp = self.reactor.query_workspaces(None);
+        let resp = self.reactor.query_workspaces(None, false);
         let overlay = self.ensure_overlay();
         overlay.update(MissionControlMode::AllWorkspaces(resp));
     }
@@ -201,7 +293,7 @@ impl MissionControlActor {
             overlay.update(MissionControlMode::CurrentWorkspace(Vec::new()));
         }
 
-        let windows = self.reactor.query_windows(None);
+        let windows = self.reactor.query_windows(None, false);
 
         let overlay = self.ensure_overlay();
         overlay.update(MissionControlMode::CurrentWorkspace(windows));
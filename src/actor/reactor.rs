@@ -13,6 +13,7 @@ mod query;
 mod replay;
 pub mod transaction_manager;
 mod utils;
+mod wakeups;
 
 #[cfg(test)]
 mod testing;
@@ -21,7 +22,7 @@ mod testing;
 mod tests;
 
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use events::app::AppEventHandler;
 use events::command::CommandEventHandler;
@@ -37,22 +38,23 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use tracing::{debug, info, instrument, trace, warn};
 use transaction_manager::TransactionId;
+use wakeups::{Wakeup, WakeupScheduler};
 
 use super::event_tap;
 use crate::actor::app::{AppInfo, AppThreadHandle, Quiet, Request, WindowId, WindowInfo, pid_t};
-use crate::actor::broadcast::{BroadcastEvent, BroadcastSender};
+use crate::actor::broadcast::{BroadcastEvent, BroadcastSender, ManageabilityReason};
 use crate::actor::raise_manager::{self, RaiseManager, RaiseRequest};
 use crate::actor::reactor::events::window_discovery::WindowDiscoveryHandler;
-use crate::actor::{self, menu_bar, stack_line};
+use crate::actor::{self, focus_border, menu_bar, stack_line};
 use crate::common::collections::{BTreeMap, HashMap, HashSet};
-use crate::common::config::Config;
+use crate::common::config::{Config, EmptyWorkspaceFocus, WorkspaceSwitchTarget};
 use crate::layout_engine::{self as layout, Direction, LayoutEngine, LayoutEvent};
 use crate::model::space_activation::{SpaceActivationConfig, SpaceActivationPolicy};
 use crate::model::tx_store::WindowTxStore;
 use crate::model::virtual_workspace::AppRuleResult;
 use crate::sys::event::MouseState;
 use crate::sys::executor::Executor;
-use crate::sys::geometry::{CGRectDef, CGRectExt};
+use crate::sys::geometry::{CGPointDef, CGRectDef, CGRectExt};
 pub use crate::sys::screen::ScreenInfo;
 use crate::sys::screen::{SpaceId, get_active_space_number, order_visible_spaces_by_position};
 use crate::sys::window_server::{
@@ -69,9 +71,9 @@ pub(crate) use crate::model::reactor::{
     WindowState,
 };
 pub use crate::model::reactor::{
-    Command, DisplaySelector, DragSession, DragState, MenuState, MissionControlState,
-    ReactorCommand, RefocusState, Requested, StaleCleanupState, WorkspaceSwitchOrigin,
-    WorkspaceSwitchState,
+    Command, DisplaySelector, DisplaySelectorError, DragSession, DragState, Edge,
+    EdgeResizeSession, MenuState, MissionControlState, ReactorCommand, RefocusState, Requested,
+    SharedMissionControlState, StaleCleanupState, WorkspaceSwitchOrigin, WorkspaceSwitchState,
 };
 
 #[derive(Clone)]
@@ -103,10 +105,11 @@ impl std::ops::Deref for ReactorHandle {
 
 use display_topology::{DisplaySnapshot, DisplayTopologyManager, WindowSnapshot};
 
-use crate::model::server::WindowData;
+use crate::model::server::{EventLogKind, WindowData};
 
 #[serde_as]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, strum_macros::IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
 pub enum Event {
     /// The screen layout, including resolution, changed. This is always the
     /// first event sent on startup.
@@ -191,9 +194,9 @@ pub enum Event {
     /// FIXME: This can be interleaved incorrectly with the MouseState in app
     /// actor events.
     MouseUp,
-    /// The mouse cursor moved over a new window. Only sent if focus-follows-
-    /// mouse is enabled.
-    MouseMovedOverWindow(WindowServerId),
+    /// The mouse cursor moved over a new window; carries the cursor location.
+    /// Only sent if focus-follows-mouse is enabled.
+    MouseMovedOverWindow(WindowServerId, #[serde(with = "CGPointDef")] CGPoint),
     /// System woke from sleep; used to re-subscribe SLS notifications.
     SystemWoke,
 
@@ -230,6 +233,18 @@ pub enum Event {
 
     #[serde(skip)]
     ConfigUpdated(Config),
+
+    /// The next frame poll for apps listed in `settings.frame_poll` is due.
+    #[serde(skip)]
+    PollWindowFrames,
+
+    /// A pending manageability change may have settled.
+    #[serde(skip)]
+    SettleManageability,
+
+    /// A held external move's grace period may have run out.
+    #[serde(skip)]
+    SettleExternalMoves,
 }
 
 pub struct Reactor {
@@ -265,6 +280,7 @@ impl Reactor {
         broadcast_tx: BroadcastSender,
         menu_tx: menu_bar::Sender,
         stack_line_tx: stack_line::Sender,
+        focus_border_tx: focus_border::Sender,
         window_notify: Option<(crate::actor::window_notify::Sender, WindowTxStore)>,
         one_space: bool,
     ) -> ReactorHandle {
@@ -281,8 +297,12 @@ impl Reactor {
         reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
         reactor.menu_manager.menu_tx = Some(menu_tx);
         reactor.communication_manager.stack_line_tx = Some(stack_line_tx);
+        reactor.communication_manager.focus_border_tx = Some(focus_border_tx);
         reactor.communication_manager.events_tx = Some(events_tx_clone.clone());
-        let query_handle = ReactorQueryHandle::new(events_tx_clone.clone());
+        let query_handle = ReactorQueryHandle::new(
+            events_tx_clone.clone(),
+            reactor.mission_control_manager.shared_state.clone(),
+        );
         thread::Builder::new()
             .name("reactor".to_string())
             .spawn(move || {
@@ -311,12 +331,26 @@ impl Reactor {
             config: config.clone(),
             one_space,
             app_manager: managers::AppManager::new(),
-            layout_manager: managers::LayoutManager { layout_engine },
+            layout_manager: managers::LayoutManager {
+                layout_engine,
+                layout_command_pending: false,
+                focus_lock: managers::FocusLock::default(),
+            },
             window_manager: managers::WindowManager {
                 windows: HashMap::default(),
                 window_ids: HashMap::default(),
                 visible_windows: HashSet::default(),
                 observed_window_server_ids: HashSet::default(),
+                last_frame_poll: None,
+                pending_manageability: HashMap::default(),
+                pending_external_moves: HashMap::default(),
+                pending_workspace_assignments: Vec::new(),
+                last_focused_at: HashMap::default(),
+                hidden_stack_line_titles: HashSet::default(),
+                minimized_window_workspaces: HashMap::default(),
+                floating_preset_index: HashMap::default(),
+                marks: HashMap::default(),
+                urgent_at: HashMap::default(),
             },
             window_server_info_manager: managers::WindowServerInfoManager {
                 window_server_info: HashMap::default(),
@@ -325,6 +359,8 @@ impl Reactor {
                 screens: vec![],
                 fullscreen_by_space: HashMap::default(),
                 has_seen_display_set: false,
+                last_cycled_display: None,
+                displaced_windows: HashMap::default(),
             },
             space_activation_policy: SpaceActivationPolicy::new(),
             main_window_tracker: MainWindowTracker::default(),
@@ -334,6 +370,8 @@ impl Reactor {
                     config.settings.window_snapping,
                 ),
                 skip_layout_for_window: None,
+                edge_resize: None,
+                press_origin: None,
             },
             workspace_switch_manager: managers::WorkspaceSwitchManager {
                 workspace_switch_state: WorkspaceSwitchState::Inactive,
@@ -342,14 +380,21 @@ impl Reactor {
                 pending_workspace_switch_origin: None,
                 pending_workspace_mouse_warp: None,
             },
-            recording_manager: managers::RecordingManager { record },
+            recording_manager: managers::RecordingManager {
+                record,
+                event_log: managers::EventLog::new(config.settings.event_log_size),
+                metrics: managers::HandlerMetrics::default(),
+            },
             communication_manager: managers::CommunicationManager {
                 event_tap_tx: None,
                 stack_line_tx: None,
+                focus_border_tx: None,
+                focus_border_frame: None,
                 raise_manager_tx,
                 event_broadcaster: broadcast_tx,
                 wm_sender: None,
                 events_tx: None,
+                wakeups: WakeupScheduler::default(),
             },
             notification_manager: managers::NotificationManager {
                 last_sls_notification_ids: Vec::new(),
@@ -363,6 +408,7 @@ impl Reactor {
             },
             mission_control_manager: managers::MissionControlManager {
                 mission_control_state: MissionControlState::Inactive,
+                shared_state: SharedMissionControlState::default(),
                 pending_mission_control_refresh: HashSet::default(),
             },
             refocus_manager: managers::RefocusManager {
@@ -749,10 +795,14 @@ impl Reactor {
     async fn run(mut reactor: Reactor, events: Receiver, events_tx: Sender) {
         let (raise_manager_tx, raise_manager_rx) = actor::channel();
         reactor.communication_manager.raise_manager_tx = raise_manager_tx.clone();
+        let (wakeups_tx, wakeups_rx) = actor::channel();
+        reactor.communication_manager.wakeups = WakeupScheduler::new(wakeups_tx);
+        reactor.schedule_wakeups();
         let event_tap_tx = reactor.communication_manager.event_tap_tx.clone();
         let reactor_task = Self::run_reactor_loop(reactor, events);
+        let wakeups_task = wakeups::run(wakeups_rx, events_tx.clone());
         let raise_manager_task = RaiseManager::run(raise_manager_rx, events_tx, event_tap_tx);
-        let _ = tokio::join!(reactor_task, raise_manager_task);
+        let _ = tokio::join!(reactor_task, raise_manager_task, wakeups_task);
     }
 
     async fn run_reactor_loop(mut reactor: Reactor, mut events: Receiver) {
@@ -773,9 +823,32 @@ impl Reactor {
     }
 
     fn handle_loop_event(&mut self, event: Event) {
-        if let Event::Query(req) = event {
-            self.handle_query_request(req);
-            return;
+        self.dispatch_loop_event(event);
+        self.schedule_wakeups();
+    }
+
+    fn dispatch_loop_event(&mut self, event: Event) {
+        match event {
+            Event::Query(req) => {
+                self.handle_query_request(req);
+                return;
+            }
+            Event::PollWindowFrames => {
+                self.communication_manager.wakeups.fired(Wakeup::FramePoll);
+                self.poll_window_frames();
+                return;
+            }
+            Event::SettleManageability => {
+                self.communication_manager.wakeups.fired(Wakeup::Manageability);
+                self.broadcast_settled_manageability_changes(Instant::now());
+                return;
+            }
+            Event::SettleExternalMoves => {
+                self.communication_manager.wakeups.fired(Wakeup::ExternalMoves);
+                WindowEventHandler::commit_settled_external_moves(self, Instant::now());
+                return;
+            }
+            _ => {}
         }
         if self.maybe_quarantine_during_churn(&event) {
             Self::note_windowserver_activity(&event);
@@ -786,6 +859,92 @@ impl Reactor {
         self.handle_event(event);
     }
 
+    fn broadcast_settled_manageability_changes(&mut self, now: Instant) {
+        for (window_id, is_manageable, reason) in
+            self.window_manager.take_settled_manageability_changes(now)
+        {
+            let event = BroadcastEvent::WindowManageabilityChanged {
+                window_id,
+                is_manageable,
+                reason,
+            };
+            let _ = self.communication_manager.event_broadcaster.send(event);
+        }
+    }
+
+    /// Arm a timer for each pending deadline, so the reactor is only woken up
+    /// when there is something to do.
+    fn schedule_wakeups(&mut self) {
+        let frame_poll = self.next_frame_poll();
+        let manageability = self.window_manager.next_manageability_settle();
+        let grace = Duration::from_millis(self.config.settings.external_move_grace_ms);
+        let external_moves = self.window_manager.next_external_move_settle(grace);
+        let wakeups = &mut self.communication_manager.wakeups;
+        wakeups.schedule(Wakeup::FramePoll, frame_poll);
+        wakeups.schedule(Wakeup::Manageability, manageability);
+        wakeups.schedule(Wakeup::ExternalMoves, external_moves);
+    }
+
+    /// When frames should next be polled, or `None` if no app is polled.
+    fn next_frame_poll(&self) -> Option<Instant> {
+        if self.config.settings.frame_poll.apps.is_empty() {
+            return None;
+        }
+        let interval = self.frame_poll_interval();
+        let last = self.window_manager.last_frame_poll;
+        Some(last.map_or_else(Instant::now, |last| last + interval))
+    }
+
+    fn frame_poll_interval(&self) -> Duration {
+        // Polls are never closer together than this, however low the configured
+        // interval is.
+        const MIN_INTERVAL: Duration = Duration::from_millis(250);
+        Duration::from_millis(self.config.settings.frame_poll.interval_ms).max(MIN_INTERVAL)
+    }
+
+    fn poll_window_frames(&mut self) {
+        if self.config.settings.frame_poll.apps.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let interval = self.frame_poll_interval();
+        if self
+            .window_manager
+            .last_frame_poll
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return;
+        }
+        // Skipped polls still count, so the next one is an interval away.
+        self.window_manager.last_frame_poll = Some(now);
+        if self.is_in_drag() || self.is_mission_control_active() {
+            return;
+        }
+
+        let settings = &self.config.settings.frame_poll;
+        let threshold = settings.threshold;
+        for (&pid, app) in &self.app_manager.apps {
+            if !settings.is_enabled_for(app.info.bundle_id.as_deref()) {
+                continue;
+            }
+            let expected = self
+                .window_manager
+                .windows
+                .iter()
+                .filter(|(wid, window)| wid.pid == pid && window.is_manageable)
+                .filter(|(_, window)| {
+                    self.best_space_for_window_state(window)
+                        .is_some_and(|space| self.is_space_active(space))
+                })
+                .map(|(&wid, window)| (wid, window.frame_monotonic))
+                .collect::<Vec<_>>();
+            if expected.is_empty() {
+                continue;
+            }
+            _ = app.handle.send(Request::PollWindowFrames(expected, threshold));
+        }
+    }
+
     fn note_windowserver_activity(event: &Event) {
         let wsid = match event {
             Event::WindowFrameChanged(wid, ..) => Some(wid.idx.get()),
@@ -793,7 +952,7 @@ impl Reactor {
             Event::WindowDestroyed(wid) => Some(wid.idx.get()),
             Event::WindowMinimized(wid) => Some(wid.idx.get()),
             Event::WindowDeminiaturized(wid) => Some(wid.idx.get()),
-            Event::MouseMovedOverWindow(wsid) => Some(wsid.as_u32()),
+            Event::MouseMovedOverWindow(wsid, _) => Some(wsid.as_u32()),
             Event::ResyncAppForWindow(wsid) => Some(wsid.as_u32()),
             Event::WindowServerDestroyed(wsid, _) => Some(wsid.as_u32()),
             Event::WindowServerAppeared(wsid, _) => Some(wsid.as_u32()),
@@ -890,8 +1049,20 @@ impl Reactor {
         self.recompute_and_set_active_spaces_from_current_screens();
     }
 
-    #[instrument(name = "reactor::handle_event", skip(self), fields(event=?event))]
     fn handle_event(&mut self, event: Event) {
+        let kind: &'static str = (&event).into();
+        let start = Instant::now();
+        if crate::common::log::should_time_event(kind) {
+            let span = tracing::info_span!("reactor::handle_event", event = ?event);
+            let _guard = span.enter();
+            self.dispatch_event(event);
+        } else {
+            self.dispatch_event(event);
+        }
+        self.recording_manager.metrics.record_event(kind, start.elapsed());
+    }
+
+    fn dispatch_event(&mut self, event: Event) {
         self.log_event(&event);
         self.recording_manager.record.on_event(&event);
 
@@ -1045,8 +1216,8 @@ impl Reactor {
             }
             Event::MenuOpened(pid) => SystemEventHandler::handle_menu_opened(self, pid),
             Event::MenuClosed(pid) => SystemEventHandler::handle_menu_closed(self, pid),
-            Event::MouseMovedOverWindow(wsid) => {
-                WindowEventHandler::handle_mouse_moved_over_window(self, wsid);
+            Event::MouseMovedOverWindow(wsid, cursor) => {
+                WindowEventHandler::handle_mouse_moved_over_window(self, wsid, cursor);
             }
             Event::SystemWoke => SystemEventHandler::handle_system_woke(self),
             Event::MissionControlNativeEntered => {
@@ -1076,6 +1247,9 @@ impl Reactor {
             window_was_destroyed,
             should_update_notifications,
         );
+        // Only the layout pass run for the command itself may arm the focus
+        // lock; a command whose pass was skipped mustn't arm it for a later one.
+        self.layout_manager.layout_command_pending = false;
     }
 
     fn finalize_event_processing(
@@ -1096,6 +1270,9 @@ impl Reactor {
         }
 
         let mut layout_changed = false;
+        if !self.is_in_drag() {
+            self.layout_manager.layout_engine.apply_pending_auto_balance();
+        }
         if !self.is_in_drag() || window_was_destroyed {
             layout_changed = self.update_layout_or_warn(
                 is_resize,
@@ -1107,6 +1284,10 @@ impl Reactor {
             self.maybe_send_menu_update();
         }
 
+        if layout_changed || raised_window.is_some() {
+            self.raise_floating_windows_above_tiled();
+        }
+
         self.workspace_switch_manager.mark_workspace_switch_inactive();
         if self.workspace_switch_manager.active_workspace_switch.is_some() && !layout_changed {
             self.workspace_switch_manager.active_workspace_switch = None;
@@ -1133,6 +1314,7 @@ impl Reactor {
                 self.notification_manager.last_sls_notification_ids = ids;
             }
         }
+        self.update_focus_border();
         self.update_event_tap_layout_mode();
     }
 
@@ -1141,6 +1323,12 @@ impl Reactor {
         if !window_state.matches_filter(WindowFilter::EffectivelyManageable) {
             return None;
         }
+        self.window_data_unfiltered(window_id)
+    }
+
+    /// Like [`Self::create_window_data`], but also for windows Rift does not manage.
+    fn window_data_unfiltered(&self, window_id: WindowId) -> Option<WindowData> {
+        let window_state = self.window_manager.windows.get(&window_id)?;
         let app = self.app_manager.apps.get(&window_id.pid)?;
 
         let app_name = app.info.localized_name.clone();
@@ -1179,6 +1367,7 @@ impl Reactor {
                     if let Some(window) = self.window_manager.windows.get_mut(&wid) {
                         if info.layer == 0 {
                             window.frame_monotonic = info.frame;
+                            window.reported_frame = info.frame;
                         }
                         (
                             window.info.sys_id,
@@ -1189,16 +1378,25 @@ impl Reactor {
                     } else {
                         continue;
                     };
+                let is_auto_float = self.window_manager.windows.get(&wid).is_some_and(|window| {
+                    self.is_auto_float_window(
+                        window.info.ax_role.as_deref(),
+                        window.info.ax_subrole.as_deref(),
+                    )
+                });
                 let manageable = utils::compute_window_manageability(
                     server_id,
                     is_minimized,
                     is_ax_standard,
                     is_ax_root,
+                    is_auto_float,
                     &self.window_server_info_manager.window_server_info,
                 );
-                if let Some(window) = self.window_manager.windows.get_mut(&wid) {
-                    window.is_manageable = manageable;
-                }
+                self.window_manager.set_manageable(
+                    wid,
+                    manageable,
+                    ManageabilityReason::WindowServerUpdate,
+                );
             }
         }
     }
@@ -1419,6 +1617,22 @@ impl Reactor {
         }
     }
 
+    /// Re-applies app rule size limits to a window after its title changed, even
+    /// when the rest of its app rule isn't reapplied.
+    fn refresh_window_constraints(&mut self, window_id: WindowId) {
+        let Some(window) = self.window_manager.windows.get(&window_id) else {
+            return;
+        };
+        let app_info = self.app_manager.apps.get(&window_id.pid).map(|app| &app.info);
+        if self.layout_manager.layout_engine.refresh_window_constraints(
+            window_id,
+            &window.info,
+            app_info,
+        ) {
+            let _ = self.update_layout_or_warn(false, false);
+        }
+    }
+
     fn maybe_reapply_app_rules_for_window(&mut self, window_id: WindowId) {
         if !self.config.virtual_workspaces.reapply_app_rules_on_title_change {
             return;
@@ -1560,10 +1774,39 @@ impl Reactor {
                 layout_dirty: false,
             };
             self.drag_manager.drag_state = DragState::Active { session };
+            if let Some(window) = self.window_manager.windows.get_mut(&wid) {
+                window.unlock_position();
+            }
         }
         self.drag_manager.skip_layout_for_window = Some(wid);
     }
 
+    /// Whether `frame` has moved far enough from where `wid` was grabbed to
+    /// start a drag. The first call for a window records `origin` as the
+    /// grab position.
+    fn drag_exceeds_deadzone(&mut self, wid: WindowId, origin: &CGRect, frame: &CGRect) -> bool {
+        let deadzone = self.config.settings.window_snapping.drag_deadzone;
+        if deadzone <= 0.0 {
+            return true;
+        }
+        let origin = match self.drag_manager.press_origin {
+            Some((pressed, origin)) if pressed == wid => origin,
+            _ => {
+                self.drag_manager.press_origin = Some((wid, *origin));
+                *origin
+            }
+        };
+        let moved = (frame.origin.x - origin.origin.x).hypot(frame.origin.y - origin.origin.y);
+        let resized = (frame.size.width - origin.size.width)
+            .abs()
+            .max((frame.size.height - origin.size.height).abs());
+        if moved.max(resized) <= deadzone {
+            return false;
+        }
+        self.drag_manager.press_origin = None;
+        true
+    }
+
     fn update_active_drag(&mut self, wid: WindowId, new_frame: &CGRect) {
         let resolved_space = match self.get_active_drag_session() {
             Some(session) if session.window == wid => self.resolve_drag_space(session, new_frame),
@@ -1679,7 +1922,30 @@ impl Reactor {
 
     fn window_center_on_known_screen(&self, wid: WindowId) -> Option<CGPoint> {
         let window_center = self.window_manager.windows.get(&wid)?.frame_monotonic.mid();
-        self.screen_for_point(window_center).map(|_| window_center)
+        self.screen_for_point(window_center)
+            .filter(|screen| self.cursor_warp_allowed(screen))
+            .map(|_| window_center)
+    }
+
+    fn cursor_warp_allowed(&self, screen: &ScreenInfo) -> bool {
+        let settings = &self.config.settings;
+        !settings.disable_cursor_warp
+            && !screen.display_uuid_opt().is_some_and(|uuid| {
+                settings.disable_cursor_warp_displays.iter().any(|disabled| disabled == uuid)
+            })
+    }
+
+    /// Warps the cursor to the center of `screen` unless warps are disabled there.
+    fn warp_mouse_to_screen_center(&self, screen: &ScreenInfo) -> bool {
+        if !self.cursor_warp_allowed(screen) {
+            trace!(display_uuid = %screen.display_uuid, "Cursor warp suppressed");
+            return false;
+        }
+        let Some(event_tap_tx) = self.communication_manager.event_tap_tx.as_ref() else {
+            return false;
+        };
+        event_tap_tx.send(crate::actor::event_tap::Request::Warp(screen.frame.mid()));
+        true
     }
 
     fn has_visible_window_server_ids_for_pid(&self, pid: pid_t) -> bool {
@@ -1693,11 +1959,17 @@ impl Reactor {
         let Some(screen) = self.space_manager.screen_by_space(space) else {
             return false;
         };
-        let Some(event_tap_tx) = self.communication_manager.event_tap_tx.as_ref() else {
-            return false;
-        };
-        event_tap_tx.send(crate::actor::event_tap::Request::Warp(screen.frame.mid()));
-        true
+        self.warp_mouse_to_screen_center(screen)
+    }
+
+    /// Applies `empty_workspace_focus` after a switch left `space` showing an empty
+    /// workspace. Returns whether focus was handled.
+    fn apply_empty_workspace_focus(&mut self, space: SpaceId) -> bool {
+        match self.config.settings.empty_workspace_focus {
+            EmptyWorkspaceFocus::Keep => false,
+            EmptyWorkspaceFocus::Defocus => crate::sys::app::resign_key_window(),
+            EmptyWorkspaceFocus::WarpToCenter => self.warp_mouse_to_space_center(space),
+        }
     }
 
     fn try_focus_or_warp_without_raise(
@@ -1739,6 +2011,14 @@ impl Reactor {
         }
     }
 
+    fn is_auto_float_window(&self, ax_role: Option<&str>, ax_subrole: Option<&str>) -> bool {
+        self.layout_manager
+            .layout_engine
+            .virtual_workspace_manager()
+            .auto_float_subrole(ax_role, ax_subrole)
+            .is_some()
+    }
+
     fn window_is_standard(&self, id: WindowId) -> bool {
         self.window_manager
             .windows
@@ -1774,8 +2054,12 @@ impl Reactor {
     }
 
     fn send_layout_event(&mut self, event: LayoutEvent) {
+        self.window_manager.record_focus_event(&event);
         let event_clone = event.clone();
         let response = self.layout_manager.layout_engine.handle_event(event);
+        self.recording_manager
+            .event_log
+            .record(EventLogKind::Event, &event_clone, Some(&response));
         self.prepare_refocus_after_layout_event(&event_clone);
         self.handle_layout_response(response, None);
         for space in self.space_manager.iter_known_spaces() {
@@ -1783,6 +2067,26 @@ impl Reactor {
         }
     }
 
+    // Returns true if an app rule turns focus-follows-mouse off for the window.
+    fn app_rule_disables_focus_follows_mouse(&self, wid: WindowId) -> bool {
+        let (Some(window), Some(app)) = (
+            self.window_manager.windows.get(&wid),
+            self.app_manager.apps.get(&wid.pid),
+        ) else {
+            return false;
+        };
+        self.layout_manager
+            .layout_engine
+            .virtual_workspace_manager()
+            .app_rule_disables_focus_follows_mouse(
+                app.info.bundle_id.as_deref(),
+                app.info.localized_name.as_deref(),
+                Some(window.info.title.as_str()),
+                window.info.ax_role.as_deref(),
+                window.info.ax_subrole.as_deref(),
+            )
+    }
+
     // Returns true if the window should be raised on mouse over considering
     // active workspace membership and potential occlusion of floating windows above it.
     fn should_raise_on_mouse_over(&self, wid: WindowId) -> bool {
@@ -1888,6 +2192,7 @@ impl Reactor {
             let Some(space) = self.best_space_for_window_state(state) else {
                 continue;
             };
+            let space = self.apply_app_rule_display(wid, space, &app_info);
             windows_by_space.entry(space).or_default().push(wid);
         }
 
@@ -1898,6 +2203,7 @@ impl Reactor {
             let mut manageable_windows: Vec<WindowId> = Vec::new();
 
             for wid in &wids {
+                self.apply_app_rule_opacity(*wid, &app_info);
                 let assign_result = {
                     let window = self.window_manager.windows.get(wid);
                     self.layout_manager
@@ -1995,6 +2301,135 @@ impl Reactor {
         }
     }
 
+    /// Applies the opacity from the window's app rule. A window whose rule no
+    /// longer sets one goes back to fully opaque.
+    fn apply_app_rule_opacity(&mut self, wid: WindowId, app_info: &AppInfo) {
+        let Some(window) = self.window_manager.windows.get(&wid) else {
+            return;
+        };
+        let opacity = self
+            .layout_manager
+            .layout_engine
+            .virtual_workspace_manager()
+            .app_rule_opacity(
+                app_info.bundle_id.as_deref(),
+                app_info.localized_name.as_deref(),
+                Some(window.info.title.as_str()),
+                window.info.ax_role.as_deref(),
+                window.info.ax_subrole.as_deref(),
+            )
+            .map(|opacity| opacity.clamp(0.0, 1.0));
+        if window.opacity == opacity {
+            return;
+        }
+        let Some(wsid) = window.info.sys_id else {
+            return;
+        };
+        if self.app_manager.opacity_refused_apps.contains(&wid.pid) {
+            return;
+        }
+        if let Err(err) = window_server::set_window_alpha(wsid, opacity.unwrap_or(1.0)) {
+            // The window server only lets a window's owner change its alpha on
+            // some macOS versions; once it refuses, it keeps refusing.
+            warn!(
+                ?wid,
+                ?err,
+                bundle_id = ?app_info.bundle_id,
+                "Window server refused to set window opacity; not trying again for this app"
+            );
+            self.app_manager.opacity_refused_apps.insert(wid.pid);
+            return;
+        }
+        if let Some(window) = self.window_manager.windows.get_mut(&wid) {
+            window.opacity = opacity;
+        }
+    }
+
+    /// Moves a window that is not yet assigned to a workspace onto the display named
+    /// by its app rule, returning the space it should be assigned in. The rule's
+    /// `workspace` is later resolved against that space.
+    fn apply_app_rule_display(
+        &mut self,
+        wid: WindowId,
+        space: SpaceId,
+        app_info: &AppInfo,
+    ) -> SpaceId {
+        let vwm = self.layout_manager.layout_engine.virtual_workspace_manager();
+        if vwm.workspace_for_window(space, wid).is_some() {
+            return space;
+        }
+        let Some(window) = self.window_manager.windows.get(&wid) else {
+            return space;
+        };
+        let Some(display) = vwm
+            .app_rule_display(
+                app_info.bundle_id.as_deref(),
+                app_info.localized_name.as_deref(),
+                Some(window.info.title.as_str()),
+                window.info.ax_role.as_deref(),
+                window.info.ax_subrole.as_deref(),
+            )
+            .map(str::to_owned)
+        else {
+            return space;
+        };
+
+        let Some(screen) = self.screen_for_display_rule(&display).cloned() else {
+            warn!(?wid, %display, "App rule display not found; keeping window in place");
+            return space;
+        };
+        let Some(target_space) = screen.space.filter(|s| self.is_space_active(*s)) else {
+            debug!(?wid, %display, "App rule display has no active space");
+            return space;
+        };
+        if target_space != space {
+            self.move_window_frame_to_screen(wid, &screen);
+        }
+        target_space
+    }
+
+    /// Sends a window back to `previous_frame` when its app moved it off the display
+    /// of `assigned_space` and an app rule confines it there. Returns whether the
+    /// window was moved back.
+    fn confine_window_to_assigned_display(
+        &mut self,
+        wid: WindowId,
+        assigned_space: SpaceId,
+        previous_frame: CGRect,
+    ) -> bool {
+        if self.is_in_drag() || !self.is_space_active(assigned_space) {
+            return false;
+        }
+        let vwm = self.layout_manager.layout_engine.virtual_workspace_manager();
+        if vwm.workspace_for_window(assigned_space, wid).is_none() {
+            return false;
+        }
+        let (Some(window), Some(app)) = (
+            self.window_manager.windows.get(&wid),
+            self.app_manager.apps.get(&wid.pid),
+        ) else {
+            return false;
+        };
+        if !vwm.app_rule_confines_to_display(
+            app.info.bundle_id.as_deref(),
+            app.info.localized_name.as_deref(),
+            Some(window.info.title.as_str()),
+            window.info.ax_role.as_deref(),
+            window.info.ax_subrole.as_deref(),
+        ) {
+            return false;
+        }
+
+        debug!(
+            ?wid,
+            ?assigned_space,
+            "App moved confined window off its display; moving it back"
+        );
+        let window_server_id = window.info.sys_id;
+        self.request_window_frame(wid, window_server_id, previous_frame);
+        true
+    }
+
     fn handle_app_activation_workspace_switch(&mut self, pid: pid_t) {
         use objc2_app_kit::NSRunningApplication;
 
@@ -2152,6 +2587,7 @@ impl Reactor {
                     &layout::LayoutCommand::SwitchToWorkspace(workspace_index),
                 );
                 self.handle_layout_response(response, Some(window_space));
+                self.restore_floating_stacking_order(window_space);
                 self.update_event_tap_layout_mode();
             }
         }
@@ -2238,20 +2674,24 @@ impl Reactor {
                     }
                     false
                 } else {
-                    let skip_center_warp = workspace_switch_space
-                        .map(|space| {
-                            self.layout_manager
-                                .layout_engine
-                                .windows_in_active_workspace(space)
-                                .is_empty()
-                        })
-                        .unwrap_or(false);
-                    let warp_space = if skip_center_warp {
-                        None
+                    let empty_switch_space = workspace_switch_space.filter(|&space| {
+                        self.layout_manager
+                            .layout_engine
+                            .windows_in_active_workspace(space)
+                            .is_empty()
+                    });
+                    if let Some(space) = empty_switch_space
+                        && self.apply_empty_workspace_focus(space)
+                    {
+                        true
                     } else {
-                        workspace_switch_space.or_else(|| self.workspace_command_space())
-                    };
-                    self.try_focus_or_warp_without_raise(warp_space, &mut focus_window)
+                        let warp_space = if empty_switch_space.is_some() {
+                            None
+                        } else {
+                            workspace_switch_space.or_else(|| self.workspace_command_space())
+                        };
+                        self.try_focus_or_warp_without_raise(warp_space, &mut focus_window)
+                    }
                 }
             } else if let Some(space) = pending_refocus_space.take() {
                 if let Some(wid) = self.last_focused_window_in_space(space) {
@@ -2387,6 +2827,73 @@ impl Reactor {
             .collect()
     }
 
+    /// Origin and destination spaces of a pending drag swap whose target sits
+    /// on a different display than the one the drag started on.
+    fn pending_cross_display_swap(&self) -> Option<(SpaceId, SpaceId)> {
+        let DragState::PendingSwap { session, target } = &self.drag_manager.drag_state else {
+            return None;
+        };
+        if !self.config.settings.window_snapping.across_displays {
+            return None;
+        }
+        let origin = session.origin_space?;
+        let settled = session.settled_space?;
+        if origin == settled || self.best_space_for_window_id(*target) != Some(settled) {
+            return None;
+        }
+        Some((origin, settled))
+    }
+
+    /// Exchange a tiled window with a tile on another display: `dragged` takes
+    /// `target`'s slot and `target` moves into `dragged`'s original layout.
+    fn swap_windows_across_spaces(
+        &mut self,
+        dragged: WindowId,
+        origin_space: SpaceId,
+        target: WindowId,
+        target_space: SpaceId,
+    ) {
+        let screen_size = |reactor: &Self, space| {
+            reactor
+                .space_manager
+                .screen_by_space(space)
+                .map(|screen| screen.frame.size)
+                .unwrap_or_else(|| CGSize::new(0.0, 0.0))
+        };
+        let origin_size = screen_size(self, origin_space);
+        let target_size = screen_size(self, target_space);
+
+        let response = self.layout_manager.layout_engine.move_window_to_space(
+            origin_space,
+            target_space,
+            target_size,
+            dragged,
+        );
+        self.handle_layout_response(response, None);
+
+        let (visible_spaces, visible_space_centers) = self.visible_spaces_for_layout(true);
+        let response = self.layout_manager.layout_engine.handle_command(
+            Some(target_space),
+            &visible_spaces,
+            &visible_space_centers,
+            layout::LayoutCommand::SwapWindows(dragged, target),
+        );
+        self.handle_layout_response(response, None);
+
+        let response = self.layout_manager.layout_engine.move_window_to_space(
+            target_space,
+            origin_space,
+            origin_size,
+            target,
+        );
+        self.handle_layout_response(response, None);
+        if let Some(screen) = self.space_manager.screen_by_space(origin_space).cloned() {
+            self.move_window_frame_to_screen(target, &screen);
+        }
+
+        self.send_layout_event(LayoutEvent::WindowFocused(target_space, dragged));
+    }
+
     fn maybe_swap_on_drag(&mut self, wid: WindowId, new_frame: CGRect) {
         if !self.is_in_drag() {
             trace!(?wid, "Skipping swap: not in drag (mouse up received)");
@@ -2417,8 +2924,14 @@ impl Reactor {
                     .and_then(|frame| self.best_space_for_window(&frame, server_id))
             });
 
+        let crossed_display = origin_space_hint.is_some_and(|origin| origin != space);
+        let swap_across_displays = crossed_display
+            && self.config.settings.window_snapping.across_displays
+            && !self.layout_manager.layout_engine.is_window_floating(wid);
+
         if let Some(origin_space) = origin_space_hint
-            && origin_space != space
+            && crossed_display
+            && !swap_across_displays
         {
             if let Some((pending_wid, pending_target)) = self.get_pending_drag_swap()
                 && pending_wid == wid
@@ -2442,7 +2955,14 @@ impl Reactor {
             return;
         }
 
-        if !self.layout_manager.layout_engine.is_window_in_active_workspace(space, wid) {
+        // A window dragged onto another display still belongs to its origin
+        // workspace until the drop, so only same-display drags are checked.
+        if !crossed_display
+            && !self.layout_manager.layout_engine.is_window_in_active_workspace(space, wid)
+        {
+            return;
+        }
+        if crossed_display && !self.is_space_active(space) {
             return;
         }
 
@@ -2616,6 +3136,111 @@ impl Reactor {
             }));
     }
 
+    /// Re-raises floating windows in the active workspaces when `floating_always_above` is
+    /// set. Each window gets its own request so the raise manager applies them strictly
+    /// bottom to top and the floating stacking order survives.
+    fn raise_floating_windows_above_tiled(&mut self) {
+        if !self.config.settings.floating_always_above {
+            return;
+        }
+        let spaces: Vec<SpaceId> = self.iter_active_spaces().collect();
+        for space in spaces {
+            let order = self.layout_manager.layout_engine.floating_windows_in_stacking_order(space);
+            self.raise_windows_bottom_to_top(order);
+        }
+    }
+
+    /// Re-raises the floating windows of the workspace now active on `space` in the
+    /// order they had when it was last left, when `preserve_floating_stacking_order`
+    /// is set. The switch itself raises them grouped by app, which loses that order.
+    fn restore_floating_stacking_order(&mut self, space: SpaceId) {
+        if !self.config.settings.preserve_floating_stacking_order {
+            return;
+        }
+        let order = self.layout_manager.layout_engine.take_floating_stacking_order(space);
+        self.raise_windows_bottom_to_top(order);
+    }
+
+    /// Record whether the stack line should draw `wid`'s title and let it know.
+    fn set_stack_line_title_visible(&mut self, wid: WindowId, visible: bool) {
+        let changed = if visible {
+            self.window_manager.hidden_stack_line_titles.remove(&wid)
+        } else {
+            self.window_manager.hidden_stack_line_titles.insert(wid)
+        };
+        if !changed {
+            return;
+        }
+        if let Some(tx) = &self.communication_manager.stack_line_tx
+            && let Err(e) = tx.try_send(stack_line::Event::WindowTitleVisibilityChanged {
+                window_id: wid,
+                visible,
+            })
+        {
+            warn!("Failed to send title visibility to stack line: {}", e);
+        }
+    }
+
+    /// Keep the focus border around the focused window, sending it only when
+    /// the frame it should outline changes.
+    fn update_focus_border(&mut self) {
+        let frame = self.focus_border_target();
+        if frame == self.communication_manager.focus_border_frame {
+            return;
+        }
+        let Some(tx) = &self.communication_manager.focus_border_tx else {
+            return;
+        };
+        let event = match frame {
+            Some(frame) => focus_border::Event::Show { frame },
+            None => focus_border::Event::Hide,
+        };
+        if let Err(e) = tx.try_send(event) {
+            warn!("Failed to send focus border update: {}", e);
+        }
+        self.communication_manager.focus_border_frame = frame;
+    }
+
+    /// The frame the focus border should outline, or `None` to hide it: when it is
+    /// disabled, during drags, and while the focused window is on a display where
+    /// it is turned off.
+    fn focus_border_target(&self) -> Option<CGRect> {
+        let settings = &self.config.settings.ui.focus_border;
+        if !settings.enabled || self.is_in_drag() {
+            return None;
+        }
+        let wid = self.main_window()?;
+        let window = self.window_manager.windows.get(&wid)?;
+        if window.info.is_minimized || !self.is_window_on_active_space(wid) {
+            return None;
+        }
+        let frame = window.frame_monotonic;
+        let screen = self.screen_for_point(frame.mid())?;
+        if screen
+            .display_uuid_opt()
+            .is_some_and(|uuid| settings.disabled_displays.iter().any(|disabled| disabled == uuid))
+        {
+            return None;
+        }
+        Some(frame)
+    }
+
+    fn raise_windows_bottom_to_top(&mut self, windows: Vec<WindowId>) {
+        for wid in windows {
+            let mut app_handles = HashMap::default();
+            self.insert_app_handle_for_window(&mut app_handles, wid);
+            let msg = raise_manager::Event::RaiseRequest(RaiseRequest {
+                raise_windows: vec![vec![wid]],
+                focus_window: None,
+                app_handles,
+                focus_quiet: Quiet::Yes,
+            });
+            if let Err(e) = self.communication_manager.raise_manager_tx.try_send(msg) {
+                warn!("Failed to send floating raise request to raise manager: {}", e);
+            }
+        }
+    }
+
     fn clear_menu_state_for_pid(&mut self, pid: pid_t) {
         if matches!(self.menu_manager.menu_state, MenuState::Open(owner) if owner == pid) {
             debug!(pid, "Clearing menu-open state for deactivated app");
@@ -2691,6 +3316,7 @@ impl Reactor {
             return;
         }
         self.mission_control_manager.mission_control_state = new_state;
+        self.mission_control_manager.shared_state.set(new_state);
         self.update_focus_follows_mouse_state();
     }
 
@@ -2733,6 +3359,16 @@ impl Reactor {
         candidate.filter(|space| self.is_space_active(*space))
     }
 
+    fn workspace_switch_command_space(&self) -> Option<SpaceId> {
+        match self.config.settings.workspace_switch_target {
+            WorkspaceSwitchTarget::Auto => self.workspace_command_space(),
+            WorkspaceSwitchTarget::Focused => self
+                .main_window_space()
+                .or_else(|| self.space_for_cursor_screen())
+                .filter(|space| self.is_space_active(*space)),
+        }
+    }
+
     fn space_for_cursor_screen(&self) -> Option<SpaceId> {
         current_cursor_location().ok().and_then(|point| self.space_for_point(point))
     }
@@ -2747,6 +3383,20 @@ impl Reactor {
         self.space_manager.screens.iter().find(|screen| screen.frame.contains(point))
     }
 
+    fn in_focus_exclusion_zone(&self, point: CGPoint) -> bool {
+        let Some(screen) = self.screen_for_point(point) else {
+            return false;
+        };
+        self.config.settings.focus_follows_mouse_exclusion_zones.iter().any(|zone| {
+            if zone.display.as_ref().is_some_and(|uuid| *uuid != screen.display_uuid) {
+                return false;
+            }
+            let origin =
+                CGPoint::new(screen.frame.origin.x + zone.x, screen.frame.origin.y + zone.y);
+            CGRect::new(origin, CGSize::new(zone.width, zone.height)).contains(point)
+        })
+    }
+
     fn closest_screen_to_point(&self, point: CGPoint) -> Option<&ScreenInfo> {
         self.space_manager.screens.iter().min_by(|a, b| {
             let da = Self::rectangle_distance_sq(a.frame, point);
@@ -2802,6 +3452,11 @@ impl Reactor {
         self.space_manager.screens.first().map(|screen| screen.frame.mid())
     }
 
+    /// Finds the display physically in `direction` from the one containing `origin`.
+    ///
+    /// Displays that share an edge span with the current one (e.g. the one directly
+    /// above) win over diagonal neighbours, even when a diagonal display is closer,
+    /// so L-shaped and staggered arrangements resolve the way they look.
     fn screen_for_direction_from_point(
         &self,
         origin: CGPoint,
@@ -2817,7 +3472,13 @@ impl Reactor {
             }
         }
 
-        let mut best: Option<(f64, f64, &ScreenInfo)> = None;
+        let source = self
+            .screen_for_point(origin)
+            .map(|screen| screen.frame)
+            .unwrap_or(CGRect::new(origin, CGSize::new(0.0, 0.0)));
+        let (src_min, src_max, src_mid) = (source.min(), source.max(), source.mid());
+
+        let mut best: Option<((bool, f64, f64), &ScreenInfo)> = None;
 
         for screen in &self.space_manager.screens {
             let frame = screen.frame;
@@ -2826,64 +3487,186 @@ impl Reactor {
                 continue;
             }
 
-            let min = frame.min();
-            let max = frame.max();
+            let (min, max, mid) = (frame.min(), frame.max(), frame.mid());
 
             let (primary_dist, orth_gap) = match direction {
                 Direction::Left => {
-                    if max.x > origin.x {
+                    if mid.x >= src_mid.x || max.x > src_max.x {
                         continue;
                     }
-                    (origin.x - max.x, interval_gap(min.y, max.y, origin.y, origin.y))
+                    (
+                        (src_min.x - max.x).max(0.0),
+                        interval_gap(min.y, max.y, src_min.y, src_max.y),
+                    )
                 }
                 Direction::Right => {
-                    if min.x < origin.x {
+                    if mid.x <= src_mid.x || min.x < src_min.x {
                         continue;
                     }
-                    (min.x - origin.x, interval_gap(min.y, max.y, origin.y, origin.y))
+                    (
+                        (min.x - src_max.x).max(0.0),
+                        interval_gap(min.y, max.y, src_min.y, src_max.y),
+                    )
                 }
                 Direction::Up => {
                     // Smaller y means visually "up".
-                    if max.y > origin.y {
+                    if mid.y >= src_mid.y || max.y > src_max.y {
                         continue;
                     }
-                    (origin.y - max.y, interval_gap(min.x, max.x, origin.x, origin.x))
+                    (
+                        (src_min.y - max.y).max(0.0),
+                        interval_gap(min.x, max.x, src_min.x, src_max.x),
+                    )
                 }
                 Direction::Down => {
-                    if min.y < origin.y {
+                    if mid.y <= src_mid.y || min.y < src_min.y {
                         continue;
                     }
-                    (min.y - origin.y, interval_gap(min.x, max.x, origin.x, origin.x))
+                    (
+                        (min.y - src_max.y).max(0.0),
+                        interval_gap(min.x, max.x, src_min.x, src_max.x),
+                    )
                 }
             };
 
-            let should_replace = best.as_ref().map_or(true, |(best_primary, best_orth, _)| {
-                primary_dist < *best_primary
-                    || (primary_dist == *best_primary && orth_gap < *best_orth)
-            });
-
-            if should_replace {
-                best = Some((primary_dist, orth_gap, screen));
+            let shares_span = match direction {
+                Direction::Left | Direction::Right => {
+                    max.y.min(src_max.y) - min.y.max(src_min.y) > 0.0
+                }
+                Direction::Up | Direction::Down => {
+                    max.x.min(src_max.x) - min.x.max(src_min.x) > 0.0
+                }
+            };
+            let key = (!shares_span, primary_dist, orth_gap);
+            if best.as_ref().is_none_or(|(best_key, _)| key < *best_key) {
+                best = Some((key, screen));
             }
         }
 
-        best.map(|(_, _, screen)| screen)
+        best.map(|(_, screen)| screen)
     }
 
     fn screen_for_selector(
         &self,
         selector: &DisplaySelector,
         origin_override: Option<CGPoint>,
-    ) -> Option<&ScreenInfo> {
+    ) -> Result<&ScreenInfo, DisplaySelectorError> {
         match selector {
-            DisplaySelector::Direction(direction) => {
-                let origin = origin_override.or_else(|| self.current_screen_center())?;
-                self.screen_for_direction_from_point(origin, *direction)
-            }
-            DisplaySelector::Index(index) => self.screens_in_physical_order().get(*index).copied(),
-            DisplaySelector::Uuid(uuid) => {
-                self.space_manager.screens.iter().find(|screen| screen.display_uuid == *uuid)
+            DisplaySelector::Direction(direction) => origin_override
+                .or_else(|| self.current_screen_center())
+                .and_then(|origin| self.screen_for_direction_from_point(origin, *direction))
+                .ok_or(DisplaySelectorError::NoDisplayInDirection(*direction)),
+            DisplaySelector::Index(index) => {
+                let screens = self.screens_in_physical_order();
+                let count = screens.len();
+                let resolved = if self.config.settings.display_index_wraps && count > 0 {
+                    *index % count
+                } else {
+                    *index
+                };
+                screens
+                    .get(resolved)
+                    .copied()
+                    .ok_or(DisplaySelectorError::IndexOutOfRange { index: *index, count })
             }
+            DisplaySelector::Uuid(uuid) => self
+                .space_manager
+                .screens
+                .iter()
+                .find(|screen| screen.display_uuid == *uuid)
+                .ok_or_else(|| DisplaySelectorError::UnknownUuid(uuid.clone())),
+        }
+    }
+
+    /// Resolves an app rule `display` value, matching the UUID first and then the
+    /// display name case-insensitively.
+    fn screen_for_display_rule(&self, display: &str) -> Option<&ScreenInfo> {
+        let screens = &self.space_manager.screens;
+        screens.iter().find(|screen| screen.display_uuid == display).or_else(|| {
+            screens.iter().find(|screen| {
+                screen.name.as_deref().is_some_and(|name| name.eq_ignore_ascii_case(display))
+            })
+        })
+    }
+
+    /// Moves the window from `source_space` to `screen`'s space, onto the workspace
+    /// at `workspace_index` (the active one when that is `None` or missing), and onto
+    /// the screen itself when that workspace is showing. Focus is left alone.
+    /// Returns whether the window moved.
+    fn relocate_window_to_screen(
+        &mut self,
+        window_id: WindowId,
+        source_space: SpaceId,
+        screen: &ScreenInfo,
+        workspace_index: Option<usize>,
+    ) -> bool {
+        let Some(target_space) = screen.space else {
+            return false;
+        };
+        let engine = &mut self.layout_manager.layout_engine;
+        if !engine.relocate_window_to_space_workspace(
+            source_space,
+            target_space,
+            screen.frame.size,
+            window_id,
+            workspace_index,
+        ) {
+            return false;
+        }
+        if engine.windows_in_active_workspace(target_space).contains(&window_id) {
+            self.move_window_frame_to_screen(window_id, screen);
+        }
+        true
+    }
+
+    /// Centers the window on `screen`, clamped to its frame, and asks the owning app
+    /// to move it there. Returns the requested frame.
+    fn move_window_frame_to_screen(
+        &mut self,
+        window_id: WindowId,
+        screen: &ScreenInfo,
+    ) -> Option<CGRect> {
+        let state = self.window_manager.windows.get(&window_id)?;
+        let window_server_id = state.info.sys_id;
+        let mut target_frame = state.frame_monotonic;
+        let size = target_frame.size;
+        let dest_rect = screen.frame;
+        let mut origin = dest_rect.mid();
+        origin.x -= size.width / 2.0;
+        origin.y -= size.height / 2.0;
+        let min = dest_rect.min();
+        let max = dest_rect.max();
+        origin.x = origin.x.max(min.x).min(max.x - size.width);
+        origin.y = origin.y.max(min.y).min(max.y - size.height);
+        target_frame.origin = origin;
+
+        self.request_window_frame(window_id, window_server_id, target_frame);
+        Some(target_frame)
+    }
+
+    fn request_window_frame(
+        &mut self,
+        window_id: WindowId,
+        window_server_id: Option<WindowServerId>,
+        target_frame: CGRect,
+    ) {
+        if self.window_manager.windows.get(&window_id).is_some_and(|w| w.position_locked) {
+            return;
+        }
+        if let Some(app) = self.app_manager.apps.get(&window_id.pid) {
+            let txid = match window_server_id {
+                Some(wsid) => {
+                    let txid = self.transaction_manager.generate_next_txid(wsid);
+                    self.transaction_manager.set_last_sent_txid(wsid, txid);
+                    txid
+                }
+                None => TransactionId::default(),
+            };
+            let _ = app.handle.send(Request::SetWindowFrame(window_id, target_frame, txid, true));
+        }
+
+        if let Some(state) = self.window_manager.windows.get_mut(&window_id) {
+            state.frame_monotonic = target_frame;
         }
     }
 
@@ -2920,6 +3703,9 @@ impl Reactor {
                 .layout_engine
                 .store_floating_window_positions(space, &floating_windows_in_workspace);
         }
+        if self.config.settings.preserve_floating_stacking_order {
+            self.layout_manager.layout_engine.store_floating_stacking_order(space);
+        }
     }
 
     pub(crate) fn update_layout_or_warn(
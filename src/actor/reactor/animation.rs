@@ -179,7 +179,7 @@ impl AnimationManager {
                 match reactor.window_manager.windows.get_mut(&wid) {
                     Some(window) => {
                         let current_frame = window.frame_monotonic;
-                        if target_frame.same_as(current_frame) {
+                        if window.position_locked || target_frame.same_as(current_frame) {
                             continue;
                         }
                         any_frame_changed = true;
@@ -275,7 +275,7 @@ impl AnimationManager {
             };
             let target_frame = target_frame.round();
             let current_frame = window.frame_monotonic;
-            if target_frame.same_as(current_frame) {
+            if window.position_locked || target_frame.same_as(current_frame) {
                 continue;
             }
             any_frame_changed = true;
@@ -1,6 +1,7 @@
 use tracing::{debug, warn};
 
 use crate::actor::app::{AppInfo, AppThreadHandle, Quiet, WindowId};
+use crate::actor::broadcast::BroadcastEvent;
 use crate::actor::reactor::{AppState, Reactor};
 use crate::layout_engine::LayoutEvent;
 use crate::sys::app::WindowInfo;
@@ -19,7 +20,19 @@ impl AppEventHandler {
         _is_frontmost: bool,
         _main_window: Option<WindowId>,
     ) {
-        reactor.app_manager.apps.insert(pid, AppState { info: info.clone(), handle });
+        let event = BroadcastEvent::AppLaunched {
+            pid,
+            bundle_id: info.bundle_id.clone(),
+            app_name: info.localized_name.clone(),
+        };
+        if reactor
+            .app_manager
+            .apps
+            .insert(pid, AppState { info: info.clone(), handle })
+            .is_none()
+        {
+            let _ = reactor.communication_manager.event_broadcaster.send(event);
+        }
         reactor.update_partial_window_server_info(window_server_info);
         reactor.on_windows_discovered_with_app_info(pid, visible_windows, vec![], Some(info));
     }
@@ -27,13 +40,41 @@ impl AppEventHandler {
     pub fn handle_application_terminated(reactor: &mut Reactor, pid: i32) {
         if let Some(app) = reactor.app_manager.apps.get_mut(&pid) {
             if let Err(e) = app.handle.send(crate::actor::app::Request::Terminate) {
+                // The app thread is already gone and won't report back, so clean up here.
                 warn!("Failed to send Terminate to app {}: {}", pid, e);
+                Self::handle_application_thread_terminated(reactor, pid);
             }
         }
     }
 
     pub fn handle_application_thread_terminated(reactor: &mut Reactor, pid: i32) {
-        reactor.app_manager.apps.remove(&pid);
+        if let Some(app) = reactor.app_manager.apps.remove(&pid) {
+            let event = BroadcastEvent::AppTerminated {
+                pid,
+                bundle_id: app.info.bundle_id,
+                app_name: app.info.localized_name,
+            };
+            let _ = reactor.communication_manager.event_broadcaster.send(event);
+        }
+        reactor.app_manager.hidden_apps.remove(&pid);
+        reactor.app_manager.opacity_refused_apps.remove(&pid);
+        reactor.app_manager.hidden_window_workspaces.retain(|wid, _| wid.pid != pid);
+        reactor
+            .window_manager
+            .minimized_window_workspaces
+            .retain(|wid, _| wid.pid != pid);
+        reactor.window_manager.floating_preset_index.retain(|wid, _| wid.pid != pid);
+        reactor.window_manager.marks.retain(|_, wid| wid.pid != pid);
+        let hidden_titles: Vec<WindowId> = reactor
+            .window_manager
+            .hidden_stack_line_titles
+            .iter()
+            .copied()
+            .filter(|wid| wid.pid == pid)
+            .collect();
+        for wid in hidden_titles {
+            reactor.set_stack_line_title_visible(wid, true);
+        }
         reactor.send_layout_event(LayoutEvent::AppClosed(pid));
     }
 
@@ -1,18 +1,25 @@
-use tracing::{error, info, warn};
+use std::time::Instant;
+
+use objc2_core_foundation::CGRect;
+use serde::Deserialize;
+use tracing::{debug, error, info, warn};
 
 use super::super::ScreenInfo;
-use crate::actor::app::{AppThreadHandle, Quiet, WindowId};
-use crate::actor::reactor::transaction_manager::TransactionId;
+use crate::actor::app::{AppThreadHandle, Quiet, Request, WindowId, pid_t};
+use crate::actor::reactor::events::system::SystemEventHandler;
+use crate::actor::reactor::managers::PendingWorkspaceAssignment;
 use crate::actor::reactor::{
-    Command, DisplaySelector, Reactor, ReactorCommand, WorkspaceSwitchOrigin,
+    Command, DisplaySelector, Edge, EdgeResizeSession, Reactor, ReactorCommand, WindowFilter,
+    WorkspaceSwitchOrigin,
 };
 use crate::actor::stack_line::Event as StackLineEvent;
 use crate::actor::wm_controller::WmEvent;
-use crate::actor::{menu_bar, raise_manager};
-use crate::common::collections::HashMap;
-use crate::common::config::{self as config, Config};
+use crate::actor::{focus_border, menu_bar, raise_manager};
+use crate::common::collections::{HashMap, HashSet};
+use crate::common::config::{Config, DisplayBalance, InactiveSpaceFocus, SpaceActivationFocus};
 use crate::common::log::{MetricsCommand, handle_command};
-use crate::layout_engine::{EventResponse, LayoutCommand, LayoutEvent};
+use crate::layout_engine::{Anchor, Direction, EventResponse, LayoutCommand, LayoutEvent};
+use crate::model::server::{DeserializeStateData, EventLogKind};
 use crate::sys::window_server::{self as window_server, WindowServerId};
 
 pub struct CommandEventHandler;
@@ -39,28 +46,44 @@ impl CommandEventHandler {
 
     pub fn handle_command_layout(reactor: &mut Reactor, cmd: LayoutCommand) {
         info!(?cmd);
+        reactor.recording_manager.metrics.record_layout_command((&cmd).into());
+        let log_entry =
+            reactor.recording_manager.event_log.record(EventLogKind::Command, &cmd, None);
         let is_workspace_switch = matches!(
             cmd,
             LayoutCommand::NextWorkspace(_)
                 | LayoutCommand::PrevWorkspace(_)
                 | LayoutCommand::SwitchToWorkspace(_)
                 | LayoutCommand::SwitchToLastWorkspace
+                | LayoutCommand::MoveWindowToNextWorkspace { follow: true }
+                | LayoutCommand::MoveWindowToPrevWorkspace { follow: true }
+                | LayoutCommand::SendToLastWorkspace { follow: true }
         );
         let requires_workspace_space = matches!(
             cmd,
             LayoutCommand::NextWorkspace(_)
                 | LayoutCommand::PrevWorkspace(_)
                 | LayoutCommand::SwitchToWorkspace(_)
+                | LayoutCommand::MoveWindowToNextWorkspace { follow: true }
+                | LayoutCommand::MoveWindowToPrevWorkspace { follow: true }
+                | LayoutCommand::SendToLastWorkspace { follow: true }
                 | LayoutCommand::SetWorkspaceLayout { .. }
+                | LayoutCommand::CycleLayoutMode
                 | LayoutCommand::CreateWorkspace
                 | LayoutCommand::SwitchToLastWorkspace
+                | LayoutCommand::RenameWorkspace { .. }
         );
         let command_space = reactor.workspace_command_space();
         let workspace_space = if requires_workspace_space {
-            if let Some(space) = command_space {
+            let space = if is_workspace_switch {
+                reactor.workspace_switch_command_space()
+            } else {
+                command_space
+            };
+            if let Some(space) = space {
                 reactor.store_current_floating_positions(space);
             }
-            command_space
+            space
         } else {
             None
         };
@@ -72,13 +95,38 @@ impl CommandEventHandler {
             reactor.workspace_switch_manager.mark_workspace_switch_inactive();
         }
 
+        if let (LayoutCommand::SwitchToWorkspace(workspace_index), Some(space)) =
+            (&cmd, workspace_space)
+        {
+            Self::switch_linked_workspaces(reactor, space, *workspace_index);
+        }
+
+        if let (LayoutCommand::TileAllFloating, Some(space)) = (&cmd, command_space) {
+            // Keep the current floating frames so `FloatAllTiled` can restore them.
+            reactor.store_current_floating_positions(space);
+        }
+
+        // Toggling floating is how a user hands a locked window back to rift.
+        if matches!(cmd, LayoutCommand::ToggleWindowFloating)
+            && let Some(window) = reactor
+                .main_window()
+                .and_then(|wid| reactor.window_manager.windows.get_mut(&wid))
+        {
+            window.unlock_position();
+        }
+
         let response = match &cmd {
             LayoutCommand::NextWorkspace(_)
             | LayoutCommand::PrevWorkspace(_)
             | LayoutCommand::SwitchToWorkspace(_)
             | LayoutCommand::SetWorkspaceLayout { .. }
+            | LayoutCommand::CycleLayoutMode
             | LayoutCommand::CreateWorkspace
-            | LayoutCommand::SwitchToLastWorkspace => {
+            | LayoutCommand::SwitchToLastWorkspace
+            | LayoutCommand::RenameWorkspace { .. }
+            | LayoutCommand::MoveWindowToNextWorkspace { follow: true }
+            | LayoutCommand::MoveWindowToPrevWorkspace { follow: true }
+            | LayoutCommand::SendToLastWorkspace { follow: true } => {
                 if let Some(space) = workspace_space {
                     reactor
                         .layout_manager
@@ -88,7 +136,67 @@ impl CommandEventHandler {
                     EventResponse::default()
                 }
             }
-            LayoutCommand::MoveWindowToWorkspace { .. } => {
+            LayoutCommand::ResizeFloatingToFraction {
+                width_frac,
+                height_frac,
+                anchor,
+            } => {
+                Self::resize_floating_to_fraction(reactor, *width_frac, *height_frac, *anchor);
+                return;
+            }
+            LayoutCommand::CycleFloatingPreset => {
+                Self::cycle_floating_preset(reactor);
+                return;
+            }
+            LayoutCommand::GrowToFill(direction) => {
+                Self::grow_to_fill(reactor, command_space, *direction);
+                return;
+            }
+            LayoutCommand::FocusLargestWindow => {
+                Self::focus_largest_window(reactor, command_space);
+                return;
+            }
+            LayoutCommand::FocusNextSpatial | LayoutCommand::FocusPrevSpatial => {
+                let forward = matches!(cmd, LayoutCommand::FocusNextSpatial);
+                Self::focus_spatial(reactor, command_space, forward);
+                return;
+            }
+            LayoutCommand::ToggleWindowTitleVisible => {
+                Self::toggle_window_title_visible(reactor);
+                return;
+            }
+            LayoutCommand::MarkWindow(mark) => {
+                Self::mark_window(reactor, mark);
+                return;
+            }
+            LayoutCommand::FocusMark(mark) => {
+                Self::focus_mark(reactor, mark);
+                return;
+            }
+            LayoutCommand::FocusAppUrgent { bundle_id } => {
+                Self::focus_app_urgent(reactor, bundle_id);
+                return;
+            }
+            LayoutCommand::MoveWindowToWorkspace {
+                workspace,
+                window_id: None,
+                bundle_id,
+                title,
+                target_index,
+            } if bundle_id.is_some() || title.is_some() => {
+                Self::move_app_window_to_workspace(
+                    reactor,
+                    *workspace,
+                    bundle_id.clone(),
+                    title.clone(),
+                    *target_index,
+                );
+                return;
+            }
+            LayoutCommand::MoveWindowToWorkspace { .. }
+            | LayoutCommand::MoveWindowToNextWorkspace { .. }
+            | LayoutCommand::MoveWindowToPrevWorkspace { .. }
+            | LayoutCommand::SendToLastWorkspace { .. } => {
                 if let Some(space) = command_space {
                     reactor
                         .layout_manager
@@ -114,12 +222,352 @@ impl CommandEventHandler {
             }
         };
 
+        if let Some(seq) = log_entry {
+            reactor.recording_manager.event_log.set_response(seq, &response);
+        }
+        reactor.layout_manager.layout_command_pending = true;
         reactor.handle_layout_response(response, workspace_space);
+        if is_workspace_switch && let Some(space) = workspace_space {
+            reactor.restore_floating_stacking_order(space);
+        }
         if requires_workspace_space {
             reactor.update_event_tap_layout_mode();
         }
     }
 
+    /// Moves the first window matching `bundle_id`/`title`, or holds the move
+    /// until such a window is created if none is tracked yet.
+    fn move_app_window_to_workspace(
+        reactor: &mut Reactor,
+        workspace: usize,
+        bundle_id: Option<String>,
+        title: Option<String>,
+        target_index: Option<usize>,
+    ) {
+        let pending = PendingWorkspaceAssignment {
+            bundle_id,
+            title,
+            workspace,
+            target_index,
+            queued_at: Instant::now(),
+        };
+        let mut matching: Vec<WindowId> = reactor
+            .window_manager
+            .windows
+            .iter()
+            .filter(|(wid, window)| {
+                let bundle_id = reactor
+                    .app_manager
+                    .apps
+                    .get(&wid.pid)
+                    .and_then(|app| app.info.bundle_id.as_deref());
+                window.matches_filter(WindowFilter::EffectivelyManageable)
+                    && pending.matches(bundle_id, &window.info.title)
+            })
+            .map(|(wid, _)| *wid)
+            .collect();
+        matching.sort_by_key(|wid| (wid.pid, wid.idx));
+
+        let target = matching
+            .into_iter()
+            .find_map(|wid| Some((Self::assigned_space_for_window(reactor, wid)?, wid)));
+        let Some((space, wid)) = target else {
+            debug!(?pending, "No matching window yet; holding workspace move");
+            reactor.window_manager.queue_workspace_assignment(pending);
+            return;
+        };
+        if reactor.layout_manager.layout_engine.move_window_to_workspace_index(
+            space,
+            wid,
+            workspace,
+            target_index,
+        ) {
+            let _ = reactor.update_layout_or_warn(false, false);
+        }
+    }
+
+    fn focus_largest_window(reactor: &mut Reactor, space: Option<crate::sys::screen::SpaceId>) {
+        let Some(space) = space else {
+            warn!("Focus largest window ignored: no active space");
+            return;
+        };
+        let largest = reactor
+            .layout_manager
+            .layout_engine
+            .windows_in_active_workspace(space)
+            .into_iter()
+            .filter_map(|wid| {
+                let window = reactor.window_manager.windows.get(&wid)?;
+                if !window.matches_filter(WindowFilter::EffectivelyManageable) {
+                    return None;
+                }
+                let size = window.frame_monotonic.size;
+                let focused_at = reactor.window_manager.last_focused_at.get(&wid).copied();
+                Some((size.width * size.height, focused_at, wid))
+            })
+            .max_by(|(area_a, at_a, _), (area_b, at_b, _)| {
+                area_a.total_cmp(area_b).then_with(|| at_a.cmp(at_b))
+            });
+        let Some((_, _, wid)) = largest else {
+            debug!(?space, "Focus largest window ignored: workspace has no windows");
+            return;
+        };
+        Self::handle_command_reactor_focus_window(reactor, wid, None);
+    }
+
+    fn focus_spatial(
+        reactor: &mut Reactor,
+        space: Option<crate::sys::screen::SpaceId>,
+        forward: bool,
+    ) {
+        let Some(space) = space else {
+            warn!("Spatial focus ignored: no active space");
+            return;
+        };
+        let mut frames: Vec<(WindowId, CGRect)> = reactor
+            .layout_manager
+            .layout_engine
+            .windows_in_active_workspace(space)
+            .into_iter()
+            .filter_map(|wid| {
+                let window = reactor.window_manager.windows.get(&wid)?;
+                window
+                    .matches_filter(WindowFilter::EffectivelyManageable)
+                    .then_some((wid, window.frame_monotonic))
+            })
+            .collect();
+        if frames.is_empty() {
+            debug!(?space, "Spatial focus ignored: workspace has no windows");
+            return;
+        }
+
+        // Group windows into rows: a window starts a new row once its top edge is
+        // below the vertical center of the first window in the current row.
+        frames.sort_by(|(_, a), (_, b)| {
+            a.origin.y.total_cmp(&b.origin.y).then(a.origin.x.total_cmp(&b.origin.x))
+        });
+        let mut rows: Vec<Vec<(WindowId, CGRect)>> = Vec::new();
+        for entry in frames {
+            match rows.last_mut() {
+                Some(row) if entry.1.origin.y < row[0].1.mid().y => row.push(entry),
+                _ => rows.push(vec![entry]),
+            }
+        }
+        let order: Vec<WindowId> = rows
+            .into_iter()
+            .flat_map(|mut row| {
+                row.sort_by(|(_, a), (_, b)| a.origin.x.total_cmp(&b.origin.x));
+                row.into_iter().map(|(wid, _)| wid)
+            })
+            .collect();
+
+        let current = reactor.main_window().and_then(|wid| order.iter().position(|&w| w == wid));
+        let at_end = match current {
+            Some(idx) if forward => idx + 1 == order.len(),
+            Some(idx) => idx == 0,
+            None => false,
+        };
+        if at_end && !reactor.layout_manager.layout_engine.focus_wraps(space) {
+            return;
+        }
+        let next = match current {
+            Some(idx) if forward => (idx + 1) % order.len(),
+            Some(idx) => (idx + order.len() - 1) % order.len(),
+            None if forward => 0,
+            None => order.len() - 1,
+        };
+        Self::handle_command_reactor_focus_window(reactor, order[next], None);
+    }
+
+    fn grow_to_fill(
+        reactor: &mut Reactor,
+        space: Option<crate::sys::screen::SpaceId>,
+        direction: Direction,
+    ) {
+        let Some(space) = space else {
+            warn!("Grow to fill ignored: no active space");
+            return;
+        };
+        let Some(screen) = reactor.space_manager.screen_by_space(space).map(|s| s.frame) else {
+            return;
+        };
+        let stack_line = &reactor.config.settings.ui.stack_line;
+        if reactor.layout_manager.layout_engine.grow_to_fill(
+            space,
+            screen,
+            direction,
+            stack_line.thickness(),
+            stack_line.horiz_placement,
+            stack_line.vert_placement,
+        ) {
+            let _ = reactor.update_layout_or_warn(false, false);
+        }
+    }
+
+    fn toggle_window_title_visible(reactor: &mut Reactor) {
+        let Some(wid) = reactor.main_window() else {
+            debug!("Toggle window title ignored: no focused window");
+            return;
+        };
+        let hidden = reactor.window_manager.hidden_stack_line_titles.contains(&wid);
+        reactor.set_stack_line_title_visible(wid, hidden);
+    }
+
+    fn mark_window(reactor: &mut Reactor, mark: &str) {
+        if mark.is_empty() {
+            warn!("Mark window ignored: empty mark");
+            return;
+        }
+        let Some(wid) = reactor.main_window() else {
+            debug!("Mark window ignored: no focused window");
+            return;
+        };
+        if let Some(previous) = reactor.window_manager.marks.insert(mark.to_string(), wid)
+            && previous != wid
+        {
+            debug!(mark, ?previous, ?wid, "Moved mark to another window");
+        }
+    }
+
+    fn focus_mark(reactor: &mut Reactor, mark: &str) {
+        let Some(&wid) = reactor.window_manager.marks.get(mark) else {
+            warn!(mark, "Focus mark ignored: no window has this mark");
+            return;
+        };
+        if !reactor.window_manager.windows.contains_key(&wid) {
+            warn!(mark, ?wid, "Focus mark ignored: marked window is gone");
+            reactor.window_manager.marks.remove(mark);
+            return;
+        }
+
+        let spaces: Vec<_> = reactor.iter_active_spaces().collect();
+        let target = spaces.into_iter().find_map(|space| {
+            let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+            let ws_id = vwm.workspace_for_window(space, wid)?;
+            let index = vwm.list_workspaces(space).iter().position(|(id, _)| *id == ws_id)?;
+            Some((space, index))
+        });
+        match target {
+            Some((space, index)) => Self::focus_window_on_workspace(reactor, space, index, wid),
+            None => Self::handle_command_reactor_focus_window(reactor, wid, None),
+        }
+    }
+
+    /// Focus the matching app's most recently urgent window; focusing it clears
+    /// its urgency.
+    fn focus_app_urgent(reactor: &mut Reactor, bundle_id: &str) {
+        let candidates = Self::app_workspace_windows(reactor, Some(bundle_id), None);
+        let urgent = candidates
+            .into_iter()
+            .filter_map(|candidate| {
+                let urgent_at = reactor.window_manager.urgent_at.get(&candidate.2)?;
+                Some((*urgent_at, candidate))
+            })
+            .max_by_key(|(urgent_at, _)| *urgent_at);
+        let Some((_, (space, index, window_id))) = urgent else {
+            debug!(bundle_id, "Focus app urgent ignored: no urgent window");
+            return;
+        };
+        Self::focus_window_on_workspace(reactor, space, index, window_id);
+    }
+
+    fn resize_floating_to_fraction(
+        reactor: &mut Reactor,
+        width_frac: f64,
+        height_frac: f64,
+        anchor: Anchor,
+    ) {
+        let Some(wid) = reactor.main_window() else {
+            return;
+        };
+        if !reactor.layout_manager.layout_engine.is_window_floating(wid) {
+            debug!(?wid, "Resize to fraction ignored: window is tiled");
+            return;
+        }
+        let Some(space) = reactor.best_space_for_window_id(wid) else {
+            return;
+        };
+        let Some(bounds) = reactor.space_manager.screen_by_space(space).map(|s| s.frame) else {
+            return;
+        };
+        let frame = reactor.layout_manager.layout_engine.resize_floating_to_fraction(
+            space,
+            wid,
+            bounds,
+            width_frac,
+            height_frac,
+            anchor,
+        );
+        if frame.is_some() {
+            let _ = reactor.update_layout_or_warn(false, false);
+        }
+    }
+
+    fn cycle_floating_preset(reactor: &mut Reactor) {
+        let Some(wid) = reactor.main_window() else {
+            return;
+        };
+        if !reactor.layout_manager.layout_engine.is_window_floating(wid) {
+            debug!(?wid, "Cycle floating preset ignored: window is tiled");
+            return;
+        }
+        let presets = &reactor.config.settings.floating_presets;
+        if presets.is_empty() {
+            debug!("Cycle floating preset ignored: no floating_presets configured");
+            return;
+        }
+        let index = reactor
+            .window_manager
+            .floating_preset_index
+            .get(&wid)
+            .map_or(0, |index| (index + 1) % presets.len());
+        let preset = presets[index];
+        reactor.window_manager.floating_preset_index.insert(wid, index);
+        Self::resize_floating_to_fraction(
+            reactor,
+            preset.width_frac,
+            preset.height_frac,
+            preset.anchor,
+        );
+    }
+
+    /// Switches the other displays in any workspace group containing the
+    /// target workspace. Runs before the commanded display is switched so
+    /// that focus ends up there.
+    fn switch_linked_workspaces(
+        reactor: &mut Reactor,
+        space: crate::sys::screen::SpaceId,
+        workspace_index: usize,
+    ) {
+        let Some(display) = reactor.display_uuid_for_space(space) else {
+            return;
+        };
+        let displays = reactor
+            .space_manager
+            .screens
+            .iter()
+            .filter_map(|screen| {
+                let other_space = screen.space.filter(|s| reactor.is_space_active(*s))?;
+                Some((other_space, screen.display_uuid_owned()?))
+            })
+            .collect::<Vec<_>>();
+        let targets = reactor
+            .layout_manager
+            .layout_engine
+            .virtual_workspace_manager_mut()
+            .linked_workspace_switches(space, &display, workspace_index, &displays);
+        for (other_space, other_index) in targets {
+            debug!(?other_space, other_index, "Switching linked workspace");
+            reactor.store_current_floating_positions(other_space);
+            let response = reactor.layout_manager.layout_engine.handle_virtual_workspace_command(
+                other_space,
+                &LayoutCommand::SwitchToWorkspace(other_index),
+            );
+            reactor.handle_layout_response(response, Some(other_space));
+            reactor.restore_floating_stacking_order(other_space);
+        }
+    }
+
     pub fn handle_command_metrics(_reactor: &mut Reactor, cmd: MetricsCommand) {
         handle_command(cmd);
     }
@@ -139,6 +587,10 @@ impl CommandEventHandler {
             .update_virtual_workspace_settings(&reactor.config.virtual_workspaces);
 
         reactor.drag_manager.update_config(reactor.config.settings.window_snapping);
+        reactor
+            .recording_manager
+            .event_log
+            .set_capacity(reactor.config.settings.event_log_size);
 
         if let Some(tx) = &reactor.communication_manager.stack_line_tx {
             if let Err(e) = tx.try_send(StackLineEvent::ConfigUpdated(reactor.config.clone())) {
@@ -146,6 +598,15 @@ impl CommandEventHandler {
             }
         }
 
+        if let Some(tx) = &reactor.communication_manager.focus_border_tx {
+            if let Err(e) = tx.try_send(focus_border::Event::ConfigUpdated(reactor.config.clone()))
+            {
+                warn!("Failed to send config update to focus border: {}", e);
+            }
+        }
+        // The border may have changed color or thickness, so redraw it.
+        reactor.communication_manager.focus_border_frame = None;
+
         if let Some(tx) = &reactor.menu_manager.menu_tx {
             if let Err(e) = tx.try_send(menu_bar::Event::ConfigUpdated(reactor.config.clone())) {
                 warn!("Failed to send config update to menu bar: {}", e);
@@ -173,6 +634,11 @@ impl CommandEventHandler {
         match cmd {
             ReactorCommand::Debug => Self::handle_command_reactor_debug(reactor),
             ReactorCommand::Serialize => Self::handle_command_reactor_serialize(reactor),
+            ReactorCommand::Deserialize { state } => {
+                if let Err(e) = Self::handle_command_reactor_deserialize(reactor, &state) {
+                    error!("Could not restore state: {e}");
+                }
+            }
             ReactorCommand::SaveAndExit => Self::handle_command_reactor_save_and_exit(reactor),
             ReactorCommand::SwitchSpace(dir) => unsafe { window_server::switch_space(dir) },
             ReactorCommand::ToggleSpaceActivated => {
@@ -210,10 +676,426 @@ impl CommandEventHandler {
             ReactorCommand::CloseWindow { window_server_id } => {
                 Self::handle_command_reactor_close_window(reactor, window_server_id);
             }
-            ReactorCommand::MoveWindowToDisplay { selector, window_id } => {
-                Self::handle_command_reactor_move_window_to_display(reactor, &selector, window_id);
+            ReactorCommand::MoveWindowToDisplay { selector, window_id, origin } => {
+                Self::handle_command_reactor_move_window_to_display(
+                    reactor,
+                    &selector,
+                    window_id,
+                    origin.as_ref(),
+                );
+            }
+            ReactorCommand::ReprobeAllConstraints => {
+                Self::handle_command_reactor_reprobe_all_constraints(reactor);
+            }
+            ReactorCommand::BeginEdgeResize { window_server_id, edge } => {
+                Self::handle_command_reactor_begin_edge_resize(reactor, window_server_id, edge);
+            }
+            ReactorCommand::EdgeResizeBy { delta } => {
+                Self::handle_command_reactor_edge_resize_by(reactor, delta);
+            }
+            ReactorCommand::EndEdgeResize => Self::handle_command_reactor_end_edge_resize(reactor),
+            ReactorCommand::NudgeWindow { dx, dy } => {
+                Self::handle_command_reactor_nudge_window(reactor, dx, dy);
+            }
+            ReactorCommand::FocusAppWorkspace { bundle_id, pid } => {
+                Self::handle_command_reactor_focus_app_workspace(
+                    reactor,
+                    bundle_id.as_deref(),
+                    pid,
+                );
+            }
+            ReactorCommand::ToggleAppHidden { bundle_id, pid } => {
+                Self::handle_command_reactor_toggle_app_hidden(reactor, bundle_id.as_deref(), pid);
+            }
+            ReactorCommand::CycleDisplays { move_mouse } => {
+                Self::handle_command_reactor_cycle_displays(reactor, move_mouse);
+            }
+            ReactorCommand::EvacuateDisplay { selector, target } => {
+                Self::handle_command_reactor_evacuate_display(reactor, &selector, target.as_ref());
+            }
+            ReactorCommand::DistributeWindows => {
+                Self::handle_command_reactor_distribute_windows(reactor);
+            }
+            ReactorCommand::BalanceDisplays => {
+                Self::handle_command_reactor_balance_displays(reactor);
+            }
+            ReactorCommand::RetileSpace { space_id } => {
+                Self::handle_command_reactor_retile_space(reactor, space_id);
+            }
+            ReactorCommand::ReapplyAppRules => {
+                Self::handle_command_reactor_reapply_app_rules(reactor);
+            }
+            ReactorCommand::ResetMenuState => SystemEventHandler::handle_menu_state_reset(reactor),
+        }
+    }
+
+    /// Windows of the matching app that are assigned to a workspace, ordered
+    /// with the focused display first, then by workspace and window.
+    fn app_workspace_windows(
+        reactor: &mut Reactor,
+        bundle_id: Option<&str>,
+        pid: Option<pid_t>,
+    ) -> Vec<(crate::sys::screen::SpaceId, usize, WindowId)> {
+        let app_matches = |wid: WindowId| {
+            if pid.is_some_and(|pid| pid != wid.pid) {
+                return false;
+            }
+            match bundle_id {
+                Some(bundle_id) => reactor
+                    .app_manager
+                    .apps
+                    .get(&wid.pid)
+                    .and_then(|app| app.info.bundle_id.as_deref())
+                    .is_some_and(|id| id.eq_ignore_ascii_case(bundle_id)),
+                None => true,
+            }
+        };
+        let mut window_ids: Vec<WindowId> = reactor
+            .window_manager
+            .windows
+            .iter()
+            .filter(|(wid, window)| {
+                app_matches(**wid) && window.matches_filter(WindowFilter::EffectivelyManageable)
+            })
+            .map(|(wid, _)| *wid)
+            .collect();
+        window_ids.sort_by_key(|wid| (wid.pid, wid.idx));
+
+        let mut candidates = Vec::new();
+        let spaces: Vec<_> = reactor.iter_active_spaces().collect();
+        for space in spaces {
+            let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+            let workspaces = vwm.list_workspaces(space);
+            for &wid in &window_ids {
+                let Some(ws_id) = vwm.workspace_for_window(space, wid) else {
+                    continue;
+                };
+                if let Some(index) = workspaces.iter().position(|(id, _)| *id == ws_id) {
+                    candidates.push((space, index, wid));
+                }
+            }
+        }
+        let focused_space = reactor.workspace_command_space();
+        candidates.sort_by_key(|&(space, index, wid)| {
+            (
+                Some(space) != focused_space,
+                space.get(),
+                index,
+                wid.pid,
+                wid.idx,
+            )
+        });
+        candidates
+    }
+
+    pub fn handle_command_reactor_focus_app_workspace(
+        reactor: &mut Reactor,
+        bundle_id: Option<&str>,
+        pid: Option<pid_t>,
+    ) {
+        if bundle_id.is_none() && pid.is_none() {
+            warn!("Focus app workspace ignored: no bundle id or pid given");
+            return;
+        }
+        let candidates = Self::app_workspace_windows(reactor, bundle_id, pid);
+        if candidates.is_empty() {
+            warn!(
+                ?bundle_id,
+                ?pid,
+                "Focus app workspace ignored: no windows on any workspace"
+            );
+            return;
+        }
+
+        // Continue from whichever of the app's windows is currently focused so
+        // that repeated invocations walk through all of them.
+        let current = reactor.workspace_command_space().and_then(|space| {
+            let engine = &reactor.layout_manager.layout_engine;
+            let ws_id = engine.active_workspace(space)?;
+            engine.virtual_workspace_manager().last_focused_window(space, ws_id)
+        });
+        let next = current
+            .or_else(|| reactor.main_window())
+            .and_then(|wid| candidates.iter().position(|(_, _, w)| *w == wid))
+            .map_or(0, |pos| (pos + 1) % candidates.len());
+        let (space, index, window_id) = candidates[next];
+        Self::focus_window_on_workspace(reactor, space, index, window_id);
+    }
+
+    /// Switch `space` to the workspace at `index` unless it is already active, then
+    /// focus `window_id` there.
+    fn focus_window_on_workspace(
+        reactor: &mut Reactor,
+        space: crate::sys::screen::SpaceId,
+        index: usize,
+        window_id: WindowId,
+    ) {
+        if reactor.layout_manager.layout_engine.active_workspace_idx(space) != Some(index as u64) {
+            reactor
+                .workspace_switch_manager
+                .start_workspace_switch(WorkspaceSwitchOrigin::Manual);
+            Self::switch_linked_workspaces(reactor, space, index);
+            reactor.store_current_floating_positions(space);
+            let response = reactor
+                .layout_manager
+                .layout_engine
+                .handle_virtual_workspace_command(space, &LayoutCommand::SwitchToWorkspace(index));
+            reactor.handle_layout_response(response, Some(space));
+            reactor.restore_floating_stacking_order(space);
+        }
+
+        reactor.send_layout_event(LayoutEvent::WindowFocused(space, window_id));
+        reactor.raise_window(window_id, Quiet::No, None);
+    }
+
+    pub fn handle_command_reactor_toggle_app_hidden(
+        reactor: &mut Reactor,
+        bundle_id: Option<&str>,
+        pid: Option<pid_t>,
+    ) {
+        if bundle_id.is_none() && pid.is_none() {
+            warn!("Toggle app hidden ignored: no bundle id or pid given");
+            return;
+        }
+        let pids: Vec<pid_t> = reactor
+            .app_manager
+            .apps
+            .iter()
+            .filter(|(app_pid, app)| {
+                pid.is_none_or(|pid| pid == **app_pid)
+                    && bundle_id.is_none_or(|bundle_id| {
+                        app.info
+                            .bundle_id
+                            .as_deref()
+                            .is_some_and(|id| id.eq_ignore_ascii_case(bundle_id))
+                    })
+            })
+            .map(|(app_pid, _)| *app_pid)
+            .collect();
+        if pids.is_empty() {
+            warn!(?bundle_id, ?pid, "Toggle app hidden ignored: no matching app");
+            return;
+        }
+
+        for pid in pids {
+            let hide = reactor.app_manager.hidden_apps.insert(pid);
+            if hide {
+                let spaces: Vec<_> = reactor.iter_active_spaces().collect();
+                let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+                for wid in reactor.window_manager.windows.keys().filter(|wid| wid.pid == pid) {
+                    let Some((space, ws_id)) = spaces.iter().find_map(|&space| {
+                        vwm.workspace_for_window(space, *wid).map(|ws_id| (space, ws_id))
+                    }) else {
+                        continue;
+                    };
+                    reactor.app_manager.hidden_window_workspaces.insert(*wid, (space, ws_id));
+                }
+            } else {
+                reactor.app_manager.hidden_apps.remove(&pid);
+            }
+            if let Some(app) = reactor.app_manager.apps.get(&pid)
+                && let Err(e) = app.handle.send(Request::SetHidden(hide))
+            {
+                warn!(pid, ?e, "Failed to send hide request to app");
+            }
+        }
+    }
+
+    pub fn handle_command_reactor_begin_edge_resize(
+        reactor: &mut Reactor,
+        window_server_id: WindowServerId,
+        edge: Edge,
+    ) {
+        let Some(&window_id) = reactor.window_manager.window_ids.get(&window_server_id) else {
+            warn!(?window_server_id, "Begin edge resize ignored: unknown window");
+            return;
+        };
+        if !reactor.layout_manager.layout_engine.is_window_floating(window_id) {
+            warn!(?window_id, "Begin edge resize ignored: window is not floating");
+            return;
+        }
+        let Some(frame) = reactor.window_manager.windows.get(&window_id).map(|w| w.frame_monotonic)
+        else {
+            return;
+        };
+
+        let anchor = match edge {
+            Edge::Left => frame.max().x,
+            Edge::Right => frame.min().x,
+            Edge::Top => frame.max().y,
+            Edge::Bottom => frame.min().y,
+        };
+        reactor.drag_manager.edge_resize = Some(EdgeResizeSession {
+            window: window_id,
+            window_server_id,
+            edge,
+            anchor,
+            frame,
+        });
+    }
+
+    pub fn handle_command_reactor_edge_resize_by(reactor: &mut Reactor, delta: f64) {
+        let Some(session) = reactor.drag_manager.edge_resize.as_mut() else {
+            warn!("Edge resize ignored: no resize in progress");
+            return;
+        };
+        let Some(window) = reactor.window_manager.windows.get(&session.window) else {
+            reactor.drag_manager.edge_resize = None;
+            return;
+        };
+
+        let min_size = window.info.min_size;
+        let max_size = window.info.max_size;
+        let clamp = |value: f64, min: Option<f64>, max: Option<f64>| {
+            let value = max.filter(|max| *max > 0.0).map_or(value, |max| value.min(max));
+            value.max(min.unwrap_or(0.0).max(1.0))
+        };
+
+        // Always derive the frame from the anchor rather than the previous frame so the
+        // opposite edge cannot drift across incremental resizes.
+        let mut frame = session.frame;
+        match session.edge {
+            Edge::Left | Edge::Right => {
+                let width = clamp(
+                    frame.size.width + delta,
+                    min_size.map(|s| s.width),
+                    max_size.map(|s| s.width),
+                );
+                frame.size.width = width;
+                frame.origin.x = match session.edge {
+                    Edge::Left => session.anchor - width,
+                    _ => session.anchor,
+                };
+            }
+            Edge::Top | Edge::Bottom => {
+                let height = clamp(
+                    frame.size.height + delta,
+                    min_size.map(|s| s.height),
+                    max_size.map(|s| s.height),
+                );
+                frame.size.height = height;
+                frame.origin.y = match session.edge {
+                    Edge::Top => session.anchor - height,
+                    _ => session.anchor,
+                };
+            }
+        }
+        session.frame = frame;
+        let (window_id, wsid) = (session.window, session.window_server_id);
+
+        let txid = reactor.transaction_manager.generate_next_txid(wsid);
+        reactor.transaction_manager.store_txid(wsid, txid, frame);
+        if let Some(app) = reactor.app_manager.apps.get(&window_id.pid) {
+            let _ = app.handle.send(Request::SetWindowFrame(window_id, frame, txid, true));
+        }
+        if let Some(state) = reactor.window_manager.windows.get_mut(&window_id) {
+            state.frame_monotonic = frame;
+        }
+    }
+
+    pub fn handle_command_reactor_end_edge_resize(reactor: &mut Reactor) {
+        let Some(session) = reactor.drag_manager.edge_resize.take() else {
+            return;
+        };
+        if let Some(space) =
+            reactor.best_space_for_window(&session.frame, Some(session.window_server_id))
+        {
+            reactor.store_current_floating_positions(space);
+        }
+    }
+
+    pub fn handle_command_reactor_nudge_window(reactor: &mut Reactor, dx: f64, dy: f64) {
+        /// How much of a nudged window has to stay on its display, per axis.
+        const MIN_VISIBLE: f64 = 40.0;
+
+        if reactor.is_in_drag() {
+            warn!("Ignoring nudge_window while a drag is active");
+            return;
+        }
+        let Some(window_id) = reactor.main_window() else {
+            warn!("Nudge window ignored: no focused window");
+            return;
+        };
+        let Some((window_server_id, frame)) = reactor
+            .window_manager
+            .windows
+            .get(&window_id)
+            .map(|w| (w.info.sys_id, w.frame_monotonic))
+        else {
+            return;
+        };
+        let Some(space) = reactor.best_space_for_window(&frame, window_server_id) else {
+            warn!(?window_id, "Nudge window ignored: space unknown");
+            return;
+        };
+        if !reactor.is_space_active(space) {
+            warn!(?window_id, ?space, "Nudge window ignored: space is inactive");
+            return;
+        }
+        let Some(screen) = reactor.space_manager.screen_by_space(space).map(|s| s.frame) else {
+            return;
+        };
+
+        let visible_x = MIN_VISIBLE.min(frame.size.width);
+        let visible_y = MIN_VISIBLE.min(frame.size.height);
+        let mut target = frame;
+        target.origin.x = (frame.origin.x + dx)
+            .min(screen.max().x - visible_x)
+            .max(screen.min().x - frame.size.width + visible_x);
+        target.origin.y = (frame.origin.y + dy)
+            .min(screen.max().y - visible_y)
+            .max(screen.min().y - frame.size.height + visible_y);
+
+        let was_tiled = !reactor.layout_manager.layout_engine.is_window_floating(window_id);
+        reactor.request_window_frame(window_id, window_server_id, target);
+        reactor.layout_manager.layout_engine.float_window_at(space, window_id, target);
+        if was_tiled {
+            let _ = reactor.update_layout_or_warn(false, false);
+        }
+    }
+
+    pub fn handle_command_reactor_reprobe_all_constraints(reactor: &mut Reactor) {
+        if reactor.is_in_drag() {
+            warn!("Ignoring reprobe_all_constraints while a drag is active");
+            return;
+        }
+
+        let windows: Vec<WindowId> = reactor
+            .window_manager
+            .windows
+            .iter()
+            .filter(|(_, state)| state.matches_filter(WindowFilter::Manageable))
+            .filter(|(_, state)| {
+                reactor
+                    .best_space_for_window_state(state)
+                    .is_some_and(|space| reactor.is_space_active(space))
+            })
+            .map(|(&wid, _)| wid)
+            .collect();
+
+        reactor.layout_manager.layout_engine.clear_window_layout_constraints(&windows);
+        for wid in &windows {
+            if let Some(window) = reactor.window_manager.windows.get_mut(wid) {
+                window.unlock_position();
+            }
+        }
+
+        // Window info (and with it the size constraints) is re-read for every
+        // known window when an app reports its visible windows.
+        let pids: HashSet<pid_t> = windows.iter().map(|wid| wid.pid).collect();
+        for pid in &pids {
+            if let Some(app) = reactor.app_manager.apps.get(pid)
+                && let Err(e) = app.handle.send(Request::GetVisibleWindows)
+            {
+                warn!("Failed to send GetVisibleWindows to app {}: {}", pid, e);
             }
         }
+
+        info!(
+            windows = windows.len(),
+            apps = pids.len(),
+            "Re-probing window constraints"
+        );
     }
 
     pub fn handle_command_reactor_serialize(reactor: &mut Reactor) {
@@ -222,8 +1104,116 @@ impl CommandEventHandler {
         }
     }
 
+    /// Restores a snapshot from `serialize_state`. A window is matched when it is
+    /// still open under the same id, with the same window server id if both are
+    /// known, on a space that still exists. Returns an error without changing
+    /// anything if the snapshot is malformed.
+    pub fn handle_command_reactor_deserialize(
+        reactor: &mut Reactor,
+        state: &str,
+    ) -> Result<DeserializeStateData, String> {
+        #[derive(Deserialize)]
+        struct SnapshotWindow {
+            id: WindowId,
+            #[serde(default)]
+            window_server_id: Option<u32>,
+        }
+        #[derive(Deserialize)]
+        struct SnapshotAssignment {
+            window: SnapshotWindow,
+        }
+
+        let snapshot: serde_json::Value =
+            serde_json::from_str(state).map_err(|e| format!("state is not JSON: {e}"))?;
+        let Some(layout) = snapshot.get("layout_engine_ron").and_then(|v| v.as_str()) else {
+            return Err("state has no layout_engine_ron".to_string());
+        };
+        let server_ids: HashMap<WindowId, u32> = match snapshot.get("window_to_workspace") {
+            Some(mapping) => Vec::<SnapshotAssignment>::deserialize(mapping)
+                .map_err(|e| format!("invalid window_to_workspace: {e}"))?
+                .into_iter()
+                .filter_map(|entry| Some((entry.window.id, entry.window.window_server_id?)))
+                .collect(),
+            None => HashMap::default(),
+        };
+        reactor
+            .layout_manager
+            .layout_engine
+            .import_state(layout)
+            .map_err(|e| format!("invalid layout_engine_ron: {e}"))?;
+
+        let known_spaces: HashSet<_> = reactor.space_manager.iter_known_spaces().collect();
+        let mut restored = HashSet::default();
+        let mut unmatched = HashSet::default();
+        let assignments: Vec<_> = reactor
+            .layout_manager
+            .layout_engine
+            .virtual_workspace_manager()
+            .window_to_workspace
+            .keys()
+            .copied()
+            .collect();
+        for (space, wid) in assignments {
+            let matches = reactor.window_manager.windows.get(&wid).is_some_and(|window| {
+                let same_window = match (window.info.sys_id, server_ids.get(&wid)) {
+                    (Some(current), Some(&recorded)) => current.as_u32() == recorded,
+                    _ => true,
+                };
+                same_window && known_spaces.contains(&space)
+            });
+            if matches {
+                restored.insert(wid);
+            } else {
+                unmatched.insert(wid);
+            }
+        }
+        let mut unmatched: Vec<WindowId> = unmatched.difference(&restored).copied().collect();
+        unmatched.sort();
+        for &wid in &unmatched {
+            reactor.send_layout_event(LayoutEvent::WindowRemoved(wid));
+        }
+
+        // Open windows the snapshot has nowhere to put go through the usual path
+        // for new windows.
+        let unplaced: Vec<_> = reactor
+            .window_manager
+            .windows
+            .iter()
+            .filter(|(_, window)| window.matches_filter(WindowFilter::EffectivelyManageable))
+            .filter_map(|(&wid, window)| {
+                let space = reactor.best_space_for_window_state(window)?;
+                let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+                (reactor.is_space_active(space) && vwm.workspace_for_window(space, wid).is_none())
+                    .then_some((space, wid))
+            })
+            .collect();
+        for &(space, wid) in &unplaced {
+            reactor.send_layout_event(LayoutEvent::WindowAdded(space, wid));
+        }
+        let _ = reactor.update_layout_or_warn(false, false);
+
+        info!(
+            restored = restored.len(),
+            unmatched = unmatched.len(),
+            added = unplaced.len(),
+            "Restored state"
+        );
+        if !unmatched.is_empty() {
+            warn!(
+                ?unmatched,
+                "Snapshot windows not found; left out of the restored layout"
+            );
+        }
+        Ok(DeserializeStateData {
+            restored_windows: restored.len(),
+            unmatched_windows: unmatched,
+            added_windows: unplaced.len(),
+        })
+    }
+
     pub fn handle_command_reactor_save_and_exit(reactor: &mut Reactor) {
-        match reactor.layout_manager.layout_engine.save(config::restore_file()) {
+        let path = reactor.config.settings.restore_file_path();
+        match reactor.layout_manager.layout_engine.save(path) {
             Ok(()) => std::process::exit(0),
             Err(e) => {
                 error!("Could not save layout: {e}");
@@ -248,12 +1238,49 @@ impl CommandEventHandler {
             .screen_by_space(space)
             .and_then(|screen| screen.display_uuid_owned());
 
+        let was_active = reactor.is_space_active(space);
         reactor.space_activation_policy.toggle_space_activated(
             cfg,
             crate::model::space_activation::ToggleSpaceContext { space, display_uuid },
         );
 
         reactor.recompute_and_set_active_spaces_from_current_screens();
+
+        let is_active = reactor.is_space_active(space);
+        let focus = reactor.config.settings.space_activation_focus;
+        if was_active == is_active || focus == SpaceActivationFocus::None {
+            return;
+        }
+
+        // Follow a newly activated space; after a deactivation, move on to any
+        // display that is still managed.
+        let target = if is_active {
+            reactor.space_manager.screen_by_space(space).cloned()
+        } else {
+            reactor
+                .space_manager
+                .screens
+                .iter()
+                .find(|screen| screen.space.is_some_and(|s| reactor.is_space_active(s)))
+                .cloned()
+        };
+        let Some(screen) = target else {
+            return;
+        };
+
+        match focus {
+            SpaceActivationFocus::None => {}
+            SpaceActivationFocus::FocusFirstWindow => {
+                if let Some(window_id) = Self::window_to_focus_on_screen(reactor, &screen) {
+                    Self::handle_command_reactor_focus_window(reactor, window_id, None);
+                } else {
+                    reactor.warp_mouse_to_screen_center(&screen);
+                }
+            }
+            SpaceActivationFocus::WarpCursor => {
+                reactor.warp_mouse_to_screen_center(&screen);
+            }
+        }
     }
 
     pub fn handle_command_reactor_focus_window(
@@ -268,7 +1295,7 @@ impl CommandEventHandler {
                 warn!(?window_id, "Focus window ignored: space unknown");
                 return;
             };
-            if !reactor.is_space_active(space) {
+            if !reactor.is_space_active(space) && !Self::activate_space_for_focus(reactor, space) {
                 warn!(?window_id, ?space, "Focus window ignored: space is inactive");
                 return;
             }
@@ -294,20 +1321,43 @@ impl CommandEventHandler {
         }
     }
 
+    /// Activates `space` when `settings.inactive_space_focus` allows it; returns
+    /// whether the space is active afterwards.
+    fn activate_space_for_focus(reactor: &mut Reactor, space: crate::sys::screen::SpaceId) -> bool {
+        if reactor.config.settings.inactive_space_focus != InactiveSpaceFocus::Activate {
+            return false;
+        }
+        let cfg = reactor.activation_cfg();
+        let display_uuid = reactor
+            .space_manager
+            .screen_by_space(space)
+            .and_then(|screen| screen.display_uuid_owned());
+        reactor.space_activation_policy.activate_space(
+            cfg,
+            crate::model::space_activation::ToggleSpaceContext { space, display_uuid },
+        );
+        reactor.recompute_and_set_active_spaces_from_current_screens();
+        reactor.is_space_active(space)
+    }
+
+    fn window_to_focus_on_screen(reactor: &Reactor, screen: &ScreenInfo) -> Option<WindowId> {
+        let space = screen.space?;
+        reactor.last_focused_window_in_space(space).or_else(|| {
+            reactor
+                .layout_manager
+                .layout_engine
+                .windows_in_active_workspace(space)
+                .into_iter()
+                .next()
+        })
+    }
+
     fn focus_first_window_on_screen(reactor: &mut Reactor, screen: &ScreenInfo) -> bool {
-        if let Some(space) = screen.space {
-            let focus_target = reactor.last_focused_window_in_space(space).or_else(|| {
-                reactor
-                    .layout_manager
-                    .layout_engine
-                    .windows_in_active_workspace(space)
-                    .into_iter()
-                    .next()
-            });
-            if let Some(window_id) = focus_target {
-                reactor.send_layout_event(LayoutEvent::WindowFocused(space, window_id));
-                return true;
-            }
+        if let (Some(space), Some(window_id)) =
+            (screen.space, Self::window_to_focus_on_screen(reactor, screen))
+        {
+            reactor.send_layout_event(LayoutEvent::WindowFocused(space, window_id));
+            return true;
         }
         false
     }
@@ -316,29 +1366,32 @@ impl CommandEventHandler {
         reactor: &mut Reactor,
         selector: &DisplaySelector,
     ) {
-        let target_screen = reactor.screen_for_selector(selector, None).cloned();
-
-        if let Some(screen) = target_screen {
-            if screen.space.is_some_and(|space| !reactor.is_space_active(space)) {
-                warn!(
-                    ?selector,
-                    ?screen.space,
-                    "Move mouse ignored: target display space is inactive"
-                );
+        let screen = match reactor.screen_for_selector(selector, None).cloned() {
+            Ok(s) => s,
+            Err(err) => {
+                warn!(code = err.code(), %err, "Move mouse ignored");
                 return;
             }
-            let center = screen.frame.mid();
-            if let Some(event_tap_tx) = reactor.communication_manager.event_tap_tx.as_ref() {
-                event_tap_tx.send(crate::actor::event_tap::Request::Warp(center));
-            }
-            let _ = Self::focus_first_window_on_screen(reactor, &screen);
+        };
+        if screen.space.is_some_and(|space| !reactor.is_space_active(space)) {
+            warn!(
+                ?selector,
+                ?screen.space,
+                "Move mouse ignored: target display space is inactive"
+            );
+            return;
         }
+        reactor.warp_mouse_to_screen_center(&screen);
+        let _ = Self::focus_first_window_on_screen(reactor, &screen);
     }
 
     pub fn handle_command_reactor_focus_display(reactor: &mut Reactor, selector: &DisplaySelector) {
         let screen = match reactor.screen_for_selector(selector, None).cloned() {
-            Some(s) => s,
-            None => return,
+            Ok(s) => s,
+            Err(err) => {
+                warn!(code = err.code(), %err, "Focus display ignored");
+                return;
+            }
         };
         if screen.space.is_some_and(|space| !reactor.is_space_active(space)) {
             warn!(
@@ -353,8 +1406,35 @@ impl CommandEventHandler {
             return;
         }
 
-        if let Some(event_tap_tx) = reactor.communication_manager.event_tap_tx.as_ref() {
-            event_tap_tx.send(crate::actor::event_tap::Request::Warp(screen.frame.mid()));
+        reactor.warp_mouse_to_screen_center(&screen);
+    }
+
+    pub fn handle_command_reactor_cycle_displays(reactor: &mut Reactor, move_mouse: bool) {
+        let screens = reactor.screens_in_physical_order();
+        let count = screens.len();
+        let start = reactor
+            .space_manager
+            .last_cycled_display
+            .filter(|index| *index < count)
+            .or_else(|| {
+                let center = reactor.current_screen_center()?;
+                screens.iter().position(|screen| screen.frame.contains(center))
+            })
+            .unwrap_or(count.saturating_sub(1));
+        let next = (1..=count).map(|step| (start + step) % count).find(|index| {
+            screens[*index].space.is_some_and(|space| reactor.is_space_active(space))
+        });
+        let Some(index) = next else {
+            debug!("Cycle displays ignored: no display with an active space");
+            return;
+        };
+
+        reactor.space_manager.last_cycled_display = Some(index);
+        let selector = DisplaySelector::Index(index);
+        if move_mouse {
+            Self::handle_command_reactor_move_mouse_to_display(reactor, &selector);
+        } else {
+            Self::handle_command_reactor_focus_display(reactor, &selector);
         }
     }
 
@@ -362,6 +1442,7 @@ impl CommandEventHandler {
         reactor: &mut Reactor,
         selector: &DisplaySelector,
         window_idx: Option<u32>,
+        origin: Option<&DisplaySelector>,
     ) {
         if reactor.is_in_drag() {
             warn!("Ignoring move-window-to-display while a drag is active");
@@ -424,18 +1505,26 @@ impl CommandEventHandler {
             return;
         }
 
-        let origin_screen = reactor.space_manager.screen_by_space(source_space);
-
-        let origin_point =
-            origin_screen.map(|s| s.frame.mid()).or_else(|| reactor.current_screen_center());
-        let target_screen = reactor.screen_for_selector(selector, origin_point).cloned();
-
-        let Some(target_screen) = target_screen else {
-            warn!(
-                ?selector,
-                "Move window to display ignored: target display not found"
-            );
-            return;
+        let origin_point = match origin {
+            Some(origin) => match reactor.screen_for_selector(origin, None) {
+                Ok(screen) => Some(screen.frame.mid()),
+                Err(err) => {
+                    warn!(code = err.code(), %err, "Move window to display ignored: bad origin");
+                    return;
+                }
+            },
+            None => reactor
+                .space_manager
+                .screen_by_space(source_space)
+                .map(|s| s.frame.mid())
+                .or_else(|| reactor.current_screen_center()),
+        };
+        let target_screen = match reactor.screen_for_selector(selector, origin_point).cloned() {
+            Ok(s) => s,
+            Err(err) => {
+                warn!(code = err.code(), %err, "Move window to display ignored");
+                return;
+            }
         };
         let Some(target_space) = target_screen.space else {
             warn!(
@@ -457,42 +1546,7 @@ impl CommandEventHandler {
             return;
         }
 
-        let mut target_frame = window_frame;
-        let size = window_frame.size;
-        let dest_rect = target_screen.frame;
-        let mut origin = dest_rect.mid();
-        origin.x -= size.width / 2.0;
-        origin.y -= size.height / 2.0;
-        let min = dest_rect.min();
-        let max = dest_rect.max();
-        origin.x = origin.x.max(min.x).min(max.x - size.width);
-        origin.y = origin.y.max(min.y).min(max.y - size.height);
-        target_frame.origin = origin;
-
-        if let Some(app) = reactor.app_manager.apps.get(&window_id.pid) {
-            if let Some(wsid) = window_server_id {
-                let txid = reactor.transaction_manager.generate_next_txid(wsid);
-                reactor.transaction_manager.set_last_sent_txid(wsid, txid);
-                let _ = app.handle.send(crate::actor::app::Request::SetWindowFrame(
-                    window_id,
-                    target_frame,
-                    txid,
-                    true,
-                ));
-            } else {
-                let txid = TransactionId::default();
-                let _ = app.handle.send(crate::actor::app::Request::SetWindowFrame(
-                    window_id,
-                    target_frame,
-                    txid,
-                    true,
-                ));
-            }
-        }
-
-        if let Some(state) = reactor.window_manager.windows.get_mut(&window_id) {
-            state.frame_monotonic = target_frame;
-        }
+        reactor.move_window_frame_to_screen(window_id, &target_screen);
 
         let response = reactor.layout_manager.layout_engine.move_window_to_space(
             source_space,
@@ -506,6 +1560,351 @@ impl CommandEventHandler {
         let _ = reactor.update_layout_or_warn(false, false);
     }
 
+    /// Returns how many windows were moved off the display.
+    pub fn handle_command_reactor_evacuate_display(
+        reactor: &mut Reactor,
+        selector: &DisplaySelector,
+        target: Option<&DisplaySelector>,
+    ) -> usize {
+        if reactor.is_in_drag() {
+            warn!("Ignoring evacuate-display while a drag is active");
+            return 0;
+        }
+
+        let source_screen = match reactor.screen_for_selector(selector, None).cloned() {
+            Ok(s) => s,
+            Err(err) => {
+                warn!(code = err.code(), %err, "Evacuate display ignored");
+                return 0;
+            }
+        };
+        let target_screen = match target {
+            Some(target) => {
+                match reactor.screen_for_selector(target, Some(source_screen.frame.mid())) {
+                    Ok(s) => s.clone(),
+                    Err(err) => {
+                        warn!(code = err.code(), %err, "Evacuate display ignored");
+                        return 0;
+                    }
+                }
+            }
+            None => match reactor.space_manager.screens.first() {
+                Some(s) => s.clone(),
+                None => return 0,
+            },
+        };
+        let (Some(source_space), Some(target_space)) = (source_screen.space, target_screen.space)
+        else {
+            warn!("Evacuate display ignored: display has no space");
+            return 0;
+        };
+        if source_space == target_space {
+            debug!(
+                ?source_space,
+                "Evacuate display ignored: destination is the source"
+            );
+            return 0;
+        }
+        if !reactor.is_space_active(target_space) {
+            warn!(
+                ?target_space,
+                "Evacuate display ignored: target display space is inactive"
+            );
+            return 0;
+        }
+
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+        let windows_by_workspace: Vec<(usize, Vec<WindowId>)> = vwm
+            .list_workspaces(source_space)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (id, _))| (index, vwm.workspace_windows(source_space, id)))
+            .collect();
+
+        let mut moved = 0;
+        for (index, windows) in windows_by_workspace {
+            for window_id in windows {
+                if reactor.relocate_window_to_screen(
+                    window_id,
+                    source_space,
+                    &target_screen,
+                    Some(index),
+                ) {
+                    moved += 1;
+                }
+            }
+        }
+
+        info!(
+            moved,
+            from = %source_screen.display_uuid,
+            to = %target_screen.display_uuid,
+            "Evacuated display"
+        );
+        let _ = reactor.update_layout_or_warn(false, false);
+        moved
+    }
+
+    pub fn handle_command_reactor_distribute_windows(reactor: &mut Reactor) -> Vec<usize> {
+        if reactor.is_in_drag() {
+            warn!("Distribute windows ignored during a drag");
+            return Vec::new();
+        }
+        let Some(space) = reactor.workspace_command_space() else {
+            warn!("Distribute windows ignored: no active space for the current display");
+            return Vec::new();
+        };
+
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+        let keep: HashSet<WindowId> = vwm
+            .window_to_workspace
+            .keys()
+            .filter(|(window_space, _)| *window_space == space)
+            .map(|&(_, wid)| wid)
+            .filter(|&wid| Self::window_is_pinned(reactor, wid))
+            .collect();
+
+        let (counts, response) =
+            reactor.layout_manager.layout_engine.distribute_windows(space, &keep);
+        info!(
+            ?space,
+            ?counts,
+            kept = keep.len(),
+            "Distributed windows across workspaces"
+        );
+        reactor.handle_layout_response(response, None);
+        let _ = reactor.update_layout_or_warn(false, false);
+        counts
+    }
+
+    /// Whether a window is sticky or pinned to its workspace by an app rule, and so
+    /// should not be redistributed.
+    fn window_is_pinned(reactor: &Reactor, wid: WindowId) -> bool {
+        let (Some(window), Some(app)) = (
+            reactor.window_manager.windows.get(&wid),
+            reactor.app_manager.apps.get(&wid.pid),
+        ) else {
+            return false;
+        };
+        let sticky = window.info.sys_id.is_some_and(|wsid| {
+            reactor
+                .window_server_info_manager
+                .window_server_info
+                .get(&wsid)
+                .is_some_and(|info| info.sticky)
+        });
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+        sticky
+            || vwm.app_rule_pins_workspace(
+                app.info.bundle_id.as_deref(),
+                app.info.localized_name.as_deref(),
+                Some(window.info.title.as_str()),
+                window.info.ax_role.as_deref(),
+                window.info.ax_subrole.as_deref(),
+            )
+    }
+
+    /// Returns the number of tiled windows laid out again, or `None` if the space
+    /// isn't on a display or isn't active.
+    pub fn handle_command_reactor_retile_space(
+        reactor: &mut Reactor,
+        space_id: Option<crate::sys::screen::SpaceId>,
+    ) -> Option<usize> {
+        let Some(space) = space_id.or_else(|| reactor.workspace_command_space()) else {
+            warn!("Retile space ignored: no active space");
+            return None;
+        };
+        if !reactor.space_manager.iter_known_spaces().any(|known| known == space) {
+            warn!(?space, "Retile space ignored: space is not on any display");
+            return None;
+        }
+        if !reactor.is_space_active(space) {
+            warn!(?space, "Retile space ignored: space is inactive");
+            return None;
+        }
+
+        // Windows sitting on the space that the layout engine lost track of.
+        let unassigned: Vec<WindowId> = reactor
+            .window_manager
+            .windows
+            .iter()
+            .filter(|(_, window)| window.matches_filter(WindowFilter::EffectivelyManageable))
+            .filter(|(_, window)| reactor.best_space_for_window_state(window) == Some(space))
+            .map(|(&wid, _)| wid)
+            .filter(|&wid| {
+                reactor
+                    .layout_manager
+                    .layout_engine
+                    .virtual_workspace_manager()
+                    .workspace_for_window(space, wid)
+                    .is_none()
+            })
+            .collect();
+        for &wid in &unassigned {
+            reactor.send_layout_event(LayoutEvent::WindowAdded(space, wid));
+        }
+
+        // Trust the frames apps last reported over the ones rift expects, so the
+        // layout pass moves every tiled window that isn't really in its tile.
+        let tiled: Vec<WindowId> = reactor
+            .layout_manager
+            .layout_engine
+            .windows_in_active_workspace(space)
+            .into_iter()
+            .filter(|&wid| !reactor.layout_manager.layout_engine.is_window_floating(wid))
+            .collect();
+        for wid in &tiled {
+            if let Some(window) = reactor.window_manager.windows.get_mut(wid) {
+                window.frame_monotonic = window.reported_frame;
+            }
+        }
+        let _ = reactor.update_layout_or_warn(false, false);
+
+        info!(
+            ?space,
+            windows = tiled.len(),
+            reassigned = unassigned.len(),
+            "Retiled space"
+        );
+        Some(tiled.len())
+    }
+
+    /// Runs app rules again for every manageable window on an active space, e.g.
+    /// after editing them, without waiting for windows to reappear. Windows a rule
+    /// now leaves unmanaged are taken out of the layout and ones it manages again
+    /// are put back. Returns how many windows changed classification: managed or
+    /// not, floating or not, or workspace.
+    pub fn handle_command_reactor_reapply_app_rules(reactor: &mut Reactor) -> usize {
+        let classify = |reactor: &Reactor, wid: WindowId| {
+            let engine = &reactor.layout_manager.layout_engine;
+            (
+                reactor.window_manager.windows.get(&wid).map(|w| w.ignore_app_rule),
+                engine.is_window_floating(wid),
+                engine.virtual_workspace_manager().workspace_for_window_any(wid),
+            )
+        };
+
+        let mut windows_by_pid: HashMap<pid_t, Vec<WindowId>> = HashMap::default();
+        for (&wid, window) in &reactor.window_manager.windows {
+            let on_active_space = reactor
+                .best_space_for_window_state(window)
+                .is_some_and(|space| reactor.is_space_active(space));
+            if on_active_space && window.matches_filter(WindowFilter::Manageable) {
+                windows_by_pid.entry(wid.pid).or_default().push(wid);
+            }
+        }
+        let before: HashMap<WindowId, _> = windows_by_pid
+            .values()
+            .flatten()
+            .map(|&wid| (wid, classify(reactor, wid)))
+            .collect();
+
+        for (pid, window_ids) in windows_by_pid {
+            let Some(app_info) = reactor.app_manager.apps.get(&pid).map(|app| app.info.clone())
+            else {
+                continue;
+            };
+            let wsids: Vec<WindowServerId> = window_ids
+                .iter()
+                .filter_map(|wid| reactor.window_manager.windows.get(wid)?.info.sys_id)
+                .collect();
+            reactor.app_manager.mark_wsids_recent(wsids);
+            reactor.process_windows_for_app_rules(pid, window_ids, app_info);
+        }
+        let _ = reactor.update_layout_or_warn(false, false);
+
+        let changed = before
+            .into_iter()
+            .filter(|&(wid, classification)| classify(reactor, wid) != classification)
+            .count();
+        info!(windows = changed, "Reapplied app rules");
+        changed
+    }
+
+    /// Returns the window count of each active display before and after balancing.
+    pub fn handle_command_reactor_balance_displays(
+        reactor: &mut Reactor,
+    ) -> (Vec<usize>, Vec<usize>) {
+        if reactor.is_in_drag() {
+            warn!("Balance displays ignored during a drag");
+            return (Vec::new(), Vec::new());
+        }
+
+        let displays: Vec<(ScreenInfo, crate::sys::screen::SpaceId)> = reactor
+            .space_manager
+            .screens
+            .iter()
+            .filter_map(|screen| {
+                let space = screen.space.filter(|&space| reactor.is_space_active(space))?;
+                Some((screen.clone(), space))
+            })
+            .collect();
+        let mut windows: Vec<Vec<WindowId>> = displays
+            .iter()
+            .map(|&(_, space)| {
+                reactor.layout_manager.layout_engine.windows_in_active_workspace(space)
+            })
+            .collect();
+        let before: Vec<usize> = windows.iter().map(Vec::len).collect();
+
+        let weights: Vec<f64> = match reactor.config.settings.display_balance {
+            DisplayBalance::Even => vec![1.0; displays.len()],
+            DisplayBalance::Area => displays
+                .iter()
+                .map(|(screen, _)| screen.frame.size.width * screen.frame.size.height)
+                .collect(),
+        };
+        let mut targets = balanced_counts(&before, &weights);
+        let mut counts = before.clone();
+
+        loop {
+            let Some(from) = (0..displays.len())
+                .filter(|&i| counts[i] > targets[i])
+                .max_by_key(|&i| counts[i] - targets[i])
+            else {
+                break;
+            };
+            let Some(to) = (0..displays.len())
+                .filter(|&i| counts[i] < targets[i])
+                .max_by_key(|&i| targets[i] - counts[i])
+            else {
+                break;
+            };
+
+            let movable = windows[from].iter().rposition(|&wid| {
+                !Self::window_is_pinned(reactor, wid)
+                    && !reactor.window_manager.windows.get(&wid).is_some_and(|w| w.position_locked)
+            });
+            let Some(pos) = movable else {
+                // Everything left on this display has to stay, so stop drawing from it.
+                targets[from] = counts[from];
+                continue;
+            };
+            let window_id = windows[from].remove(pos);
+            if !reactor.relocate_window_to_screen(
+                window_id,
+                displays[from].1,
+                &displays[to].0,
+                None,
+            ) {
+                continue;
+            }
+            counts[from] -= 1;
+            counts[to] += 1;
+        }
+
+        let uuids: Vec<&str> =
+            displays.iter().map(|(screen, _)| screen.display_uuid.as_str()).collect();
+        info!(
+            displays = ?uuids,
+            ?before,
+            after = ?counts,
+            "Balanced windows across displays"
+        );
+        let _ = reactor.update_layout_or_warn(false, false);
+        (before, counts)
+    }
+
     pub fn handle_command_reactor_close_window(
         reactor: &mut Reactor,
         window_server_id: Option<WindowServerId>,
@@ -521,6 +1920,29 @@ impl CommandEventHandler {
     }
 }
 
+/// Splits the windows counted in `current` between displays in proportion to
+/// `weights`. Leftover windows go to the displays with the largest remainders,
+/// preferring ones that already hold more windows so fewer need to move.
+fn balanced_counts(current: &[usize], weights: &[f64]) -> Vec<usize> {
+    let total: usize = current.iter().sum();
+    let weight_sum: f64 = weights.iter().sum();
+    if weight_sum <= 0.0 {
+        return current.to_vec();
+    }
+    let shares: Vec<f64> = weights.iter().map(|w| total as f64 * w / weight_sum).collect();
+    let mut counts: Vec<usize> = shares.iter().map(|share| share.floor() as usize).collect();
+    let leftover = total.saturating_sub(counts.iter().sum());
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (ra, rb) = (shares[a] - shares[a].floor(), shares[b] - shares[b].floor());
+        rb.total_cmp(&ra).then(current[b].cmp(&current[a]))
+    });
+    for idx in order.into_iter().take(leftover) {
+        counts[idx] += 1;
+    }
+    counts
+}
+
 fn send_wm_cmd(reactor: &mut Reactor, cmd: crate::actor::wm_controller::WmCmd) -> bool {
     if let Some(wm) = reactor.communication_manager.wm_sender.as_ref() {
         let _ = wm.send(crate::actor::wm_controller::WmEvent::Command(
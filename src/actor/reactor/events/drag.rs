@@ -24,6 +24,23 @@ impl DragEventHandler {
                     ?target_wid,
                     "Skipping deferred swap; one of the windows no longer exists"
                 );
+            } else if let Some((origin_space, target_space)) = reactor.pending_cross_display_swap()
+            {
+                trace!(
+                    ?origin_space,
+                    ?target_space,
+                    "Swapping dragged window across displays"
+                );
+                reactor.swap_windows_across_spaces(
+                    dragged_wid,
+                    origin_space,
+                    target_wid,
+                    target_space,
+                );
+                // Both windows are already placed; don't let the drag finalizer
+                // move the dragged window into the target layout a second time.
+                reactor.drag_manager.drag_state = DragState::Inactive;
+                need_layout_refresh = true;
             } else {
                 let (visible_spaces, visible_space_centers) =
                     reactor.visible_spaces_for_layout(true);
@@ -53,6 +70,11 @@ impl DragEventHandler {
             }
         }
 
+        // A window nudged inside the deadzone never started a drag; put it
+        // back where the layout wants it.
+        if reactor.drag_manager.press_origin.take().is_some() {
+            need_layout_refresh = true;
+        }
         let finalize_needs_layout = reactor.finalize_active_drag();
 
         reactor.drag_manager.reset();
@@ -1,16 +1,17 @@
 use std::collections::hash_map::Entry;
 
 use objc2_app_kit::NSRunningApplication;
-use objc2_core_foundation::CGSize;
+use objc2_core_foundation::{CGRect, CGSize};
 use tracing::{debug, info, trace, warn};
 
-use crate::actor::app::Request;
+use crate::actor::app::{Request, WindowId};
 use crate::actor::reactor::{
     Event, FullscreenSpaceTrack, FullscreenWindowTrack, LayoutEvent, PendingSpaceChange, Reactor,
     ScreenInfo, StaleCleanupState,
 };
 use crate::actor::wm_controller::WmEvent;
 use crate::common::collections::{HashMap, HashSet};
+use crate::common::config::DisplayGravity;
 use crate::sys::app::AppInfo;
 use crate::sys::screen::{ScreenId, SpaceId};
 use crate::sys::window_server::WindowServerId;
@@ -283,6 +284,9 @@ impl SpaceEventHandler {
                 && !has_duplicate_spaces
                 && spaces.iter().all(|space| space.is_some());
             reactor.reconcile_spaces_with_display_history(&spaces, allow_space_remap);
+            if allow_space_remap {
+                apply_display_gravity(reactor, &previous_screens);
+            }
             if !resized_screens.is_empty() {
                 let resized_info: Vec<(SpaceId, CGSize)> = reactor
                     .space_manager
@@ -456,6 +460,116 @@ fn record_fullscreen_window(
     });
 }
 
+/// Moves windows off displays that disappeared since `previous_screens`, and
+/// back onto displays that returned, according to the display gravity settings.
+fn apply_display_gravity(reactor: &mut Reactor, previous_screens: &[ScreenInfo]) {
+    let mut moved = false;
+    if reactor.config.settings.restore_windows_on_display_return {
+        moved |= restore_displaced_windows(reactor);
+    }
+    let gravity = reactor.config.settings.display_gravity;
+    if gravity != DisplayGravity::Off {
+        moved |= displace_windows_from_removed_displays(reactor, previous_screens, gravity);
+    }
+    if moved {
+        let _ = reactor.update_layout_or_warn(false, false);
+    }
+}
+
+/// Returns whether any window was moved off a removed display.
+fn displace_windows_from_removed_displays(
+    reactor: &mut Reactor,
+    previous_screens: &[ScreenInfo],
+    gravity: DisplayGravity,
+) -> bool {
+    let current: HashSet<&str> =
+        reactor.space_manager.screens.iter().map(|s| s.display_uuid.as_str()).collect();
+    let removed: Vec<(String, SpaceId, CGRect)> = previous_screens
+        .iter()
+        .filter(|screen| !current.contains(screen.display_uuid.as_str()))
+        .filter_map(|screen| Some((screen.display_uuid.clone(), screen.space?, screen.frame)))
+        .collect();
+
+    let mut moved = false;
+    for (display_uuid, source_space, frame) in removed {
+        let target = match gravity {
+            DisplayGravity::Off => None,
+            DisplayGravity::Primary => reactor.space_manager.screens.first(),
+            DisplayGravity::Nearest => reactor.closest_screen_to_point(frame.mid()),
+        };
+        let Some(target) = target.cloned() else {
+            continue;
+        };
+        if !target.space.is_some_and(|space| reactor.is_space_active(space)) {
+            continue;
+        }
+
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+        let windows: Vec<(WindowId, usize)> = vwm
+            .list_workspaces(source_space)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, (id, _))| {
+                vwm.workspace_windows(source_space, id).into_iter().map(move |wid| (wid, index))
+            })
+            .collect();
+
+        let mut displaced = Vec::new();
+        for (wid, index) in windows {
+            if reactor.relocate_window_to_screen(wid, source_space, &target, None) {
+                displaced.push((wid, index));
+            }
+        }
+
+        if !displaced.is_empty() {
+            info!(
+                count = displaced.len(),
+                from = %display_uuid,
+                to = %target.display_uuid,
+                "Moved windows off disconnected display"
+            );
+            reactor.space_manager.displaced_windows.insert(display_uuid, displaced);
+            moved = true;
+        }
+    }
+    moved
+}
+
+/// Returns whether any window was moved back.
+fn restore_displaced_windows(reactor: &mut Reactor) -> bool {
+    let returned: Vec<ScreenInfo> = reactor
+        .space_manager
+        .screens
+        .iter()
+        .filter(|screen| reactor.space_manager.displaced_windows.contains_key(&screen.display_uuid))
+        .cloned()
+        .collect();
+
+    let mut moved = false;
+    for screen in returned {
+        if !screen.space.is_some_and(|space| reactor.is_space_active(space)) {
+            continue;
+        }
+        let Some(windows) = reactor.space_manager.displaced_windows.remove(&screen.display_uuid)
+        else {
+            continue;
+        };
+        for (wid, index) in windows {
+            let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+            let Some(source_space) = reactor
+                .space_manager
+                .iter_known_spaces()
+                .find(|space| vwm.workspace_for_window(*space, wid).is_some())
+            else {
+                continue;
+            };
+            moved |= reactor.relocate_window_to_screen(wid, source_space, &screen, Some(index));
+        }
+        debug!(display = %screen.display_uuid, "Restored windows to reconnected display");
+    }
+    moved
+}
+
 fn request_visible_windows(reactor: &Reactor, pid: i32, context: &str) {
     if let Some(app_state) = reactor.app_manager.apps.get(&pid) {
         if let Err(e) = app_state.handle.send(Request::GetVisibleWindows) {
@@ -56,6 +56,14 @@ impl SystemEventHandler {
         }
     }
 
+    pub fn handle_menu_state_reset(reactor: &mut Reactor) {
+        if let MenuState::Open(owner) = reactor.menu_manager.menu_state {
+            debug!(owner, "resetting menu-open state on request");
+        }
+        reactor.menu_manager.menu_state = MenuState::Closed;
+        reactor.update_focus_follows_mouse_state();
+    }
+
     pub fn handle_system_woke(reactor: &mut Reactor) {
         let ids: Vec<u32> =
             reactor.window_manager.window_ids.keys().map(|wsid| wsid.as_u32()).collect();
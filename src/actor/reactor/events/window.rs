@@ -1,13 +1,17 @@
-use objc2_core_foundation::CGRect;
+use std::time::{Duration, Instant};
+
+use objc2_core_foundation::{CGPoint, CGRect};
 use tracing::{debug, trace, warn};
 
 use crate::actor::app::WindowId;
+use crate::actor::broadcast::ManageabilityReason;
 use crate::actor::reactor::events::drag::DragEventHandler;
+use crate::actor::reactor::managers::{PendingExternalMove, WindowManager};
 use crate::actor::reactor::{
     DragState, Quiet, Reactor, Requested, TransactionId, WindowFilter, WindowState, utils,
 };
-use crate::common::config::LayoutMode;
-use crate::layout_engine::LayoutEvent;
+use crate::common::config::{ExternalMoveBehavior, LayoutMode};
+use crate::layout_engine::{LayoutCommand, LayoutEvent};
 use crate::sys::app::WindowInfo as Window;
 use crate::sys::event::{MouseState, get_mouse_state};
 use crate::sys::geometry::SameAs;
@@ -35,14 +39,18 @@ impl WindowEventHandler {
 
         let frame = window.frame;
         let mut window_state: WindowState = window.into();
+        window_state.created_at = Some(Instant::now());
         let is_manageable = utils::compute_window_manageability(
             window_state.info.sys_id,
             window_state.info.is_minimized,
             window_state.info.is_standard,
             window_state.info.is_root,
+            reactor.is_auto_float_window(
+                window_state.info.ax_role.as_deref(),
+                window_state.info.ax_subrole.as_deref(),
+            ),
             &reactor.window_server_info_manager.window_server_info,
         );
-        window_state.is_manageable = is_manageable;
         if let Some(wsid) = window_state.info.sys_id {
             reactor.transaction_manager.store_txid(
                 wsid,
@@ -52,7 +60,7 @@ impl WindowEventHandler {
         }
 
         let server_id = window_state.info.sys_id;
-        reactor.window_manager.windows.insert(wid, window_state);
+        reactor.window_manager.insert_window(wid, window_state, is_manageable);
 
         if is_manageable {
             let active_space = active_space_for_window(reactor, &frame, server_id);
@@ -65,7 +73,15 @@ impl WindowEventHandler {
                     }
                     reactor.process_windows_for_app_rules(wid.pid, vec![wid], app_info);
                 }
+                // An app rule may have pinned the window to another display.
+                let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+                let space = reactor
+                    .space_manager
+                    .iter_known_spaces()
+                    .find(|sp| vwm.workspace_for_window(*sp, wid).is_some())
+                    .unwrap_or(space);
                 maybe_dispatch_window_added_in_space(reactor, wid, space);
+                apply_pending_workspace_assignment(reactor, wid);
             }
         }
         // TODO: drag state is maybe managed by ensure_active_drag
@@ -75,8 +91,8 @@ impl WindowEventHandler {
     }
 
     pub fn handle_window_destroyed(reactor: &mut Reactor, wid: WindowId) -> bool {
-        let window_server_id = match reactor.window_manager.windows.get(&wid) {
-            Some(window) => window.info.sys_id,
+        let (window_server_id, created_at) = match reactor.window_manager.windows.get(&wid) {
+            Some(window) => (window.info.sys_id, window.created_at),
             None => return false,
         };
         if let Some(ws_id) = window_server_id {
@@ -84,11 +100,31 @@ impl WindowEventHandler {
             reactor.window_manager.window_ids.remove(&ws_id);
             reactor.window_server_info_manager.window_server_info.remove(&ws_id);
             reactor.window_manager.visible_windows.remove(&ws_id);
+            reactor.window_manager.observed_window_server_ids.remove(&ws_id);
+            reactor.app_manager.app_rules_recent_targets.remove(&ws_id);
         } else {
             debug!(?wid, "Received WindowDestroyed for unknown window - ignoring");
         }
         reactor.window_manager.windows.remove(&wid);
-        reactor.send_layout_event(LayoutEvent::WindowRemoved(wid));
+        reactor.window_manager.minimized_window_workspaces.remove(&wid);
+        reactor.window_manager.floating_preset_index.remove(&wid);
+        reactor.window_manager.pending_manageability.remove(&wid);
+        reactor.window_manager.pending_external_moves.remove(&wid);
+        reactor.window_manager.marks.retain(|_, marked| *marked != wid);
+        reactor.window_manager.urgent_at.remove(&wid);
+        reactor.set_stack_line_title_visible(wid, true);
+
+        // A throwaway window the layout never picked up needs no removal event;
+        // sending one would only make the engine search and rebalance for nothing.
+        let transient = created_at.is_some_and(|created_at| {
+            created_at.elapsed() < WindowManager::TRANSIENT_WINDOW_LIFETIME
+        });
+        if transient && !reactor.layout_manager.layout_engine.tracks_window(wid) {
+            trace!(?wid, "Transient window destroyed before it was laid out");
+            reactor.window_manager.last_focused_at.remove(&wid);
+        } else {
+            reactor.send_layout_event(LayoutEvent::WindowRemoved(wid));
+        }
 
         if let DragState::PendingSwap { session, target } = &reactor.drag_manager.drag_state {
             if session.window == wid || *target == wid {
@@ -121,10 +157,19 @@ impl WindowEventHandler {
                 return;
             }
             window.info.is_minimized = true;
-            window.is_manageable = false;
             if let Some(ws_id) = window.info.sys_id {
                 reactor.window_manager.visible_windows.remove(&ws_id);
             }
+            reactor
+                .window_manager
+                .set_manageable(wid, false, ManageabilityReason::Minimized);
+            let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+            let workspace = reactor
+                .iter_active_spaces()
+                .find_map(|space| vwm.workspace_for_window(space, wid).map(|ws_id| (space, ws_id)));
+            if let Some(entry) = workspace {
+                reactor.window_manager.minimized_window_workspaces.insert(wid, entry);
+            }
             reactor.send_layout_event(LayoutEvent::WindowRemoved(wid));
         } else {
             debug!(?wid, "Received WindowMinimized for unknown window - ignoring");
@@ -139,6 +184,7 @@ impl WindowEventHandler {
                         return;
                     }
                     window.info.is_minimized = false;
+                    reactor.window_manager.minimized_window_workspaces.remove(&wid);
                     (
                         window.frame_monotonic,
                         window.info.sys_id,
@@ -154,18 +200,43 @@ impl WindowEventHandler {
                     return;
                 }
             };
+        let is_auto_float = reactor.window_manager.windows.get(&wid).is_some_and(|window| {
+            reactor.is_auto_float_window(
+                window.info.ax_role.as_deref(),
+                window.info.ax_subrole.as_deref(),
+            )
+        });
         let is_manageable = utils::compute_window_manageability(
             server_id,
             false,
             is_ax_standard,
             is_ax_root,
+            is_auto_float,
             &reactor.window_server_info_manager.window_server_info,
         );
-        if let Some(window) = reactor.window_manager.windows.get_mut(&wid) {
-            window.is_manageable = is_manageable;
+        reactor.window_manager.set_manageable(
+            wid,
+            is_manageable,
+            ManageabilityReason::Deminiaturized,
+        );
+
+        let hidden_with_app = reactor.app_manager.hidden_window_workspaces.remove(&wid);
+        if hidden_with_app.is_some() {
+            reactor.app_manager.hidden_apps.remove(&wid.pid);
         }
 
         if is_manageable {
+            if let Some((space, ws_id)) = hidden_with_app
+                && reactor.is_space_active(space)
+            {
+                reactor
+                    .layout_manager
+                    .layout_engine
+                    .virtual_workspace_manager_mut()
+                    .assign_window_to_workspace(space, wid, ws_id);
+                maybe_dispatch_window_added_in_space(reactor, wid, space);
+                return;
+            }
             let active_space = active_space_for_window(reactor, &frame, server_id);
             if let Some(space) = active_space {
                 maybe_dispatch_window_added_in_space(reactor, wid, space);
@@ -191,6 +262,12 @@ impl WindowEventHandler {
             "WindowFrameChanged event"
         );
 
+        if last_seen.is_some()
+            && let Some(window) = reactor.window_manager.windows.get_mut(&wid)
+        {
+            window.last_seen_txid = last_seen;
+        }
+
         let effective_mouse_state = mouse_state.or_else(|| get_mouse_state());
         let result = (|| -> bool {
             let (server_id, old_frame) = {
@@ -230,6 +307,11 @@ impl WindowEventHandler {
                 return false;
             }
 
+            let reported_frame = match reactor.window_manager.windows.get_mut(&wid) {
+                Some(window) => std::mem::replace(&mut window.reported_frame, new_frame),
+                None => return false,
+            };
+
             if triggered_by_rift {
                 let Some(window) = reactor.window_manager.windows.get_mut(&wid) else {
                     return false;
@@ -241,7 +323,17 @@ impl WindowEventHandler {
                             debug!(?wid, ?new_frame, "Final frame matches Rift request");
                             window.frame_monotonic = new_frame;
                         }
+                        window.move_refusals = None;
                         reactor.transaction_manager.clear_target_for_window(wsid);
+                    } else if refused_move(reported_frame, target, new_frame) {
+                        reactor.transaction_manager.clear_target_for_window(wsid);
+                        if window.note_refused_move(new_frame.origin) {
+                            lock_window_position(reactor, wid, new_frame);
+                        } else {
+                            // Record where it really is so the next layout pass
+                            // asks again.
+                            window.frame_monotonic = new_frame;
+                        }
                     } else {
                         trace!(
                             ?wid,
@@ -308,6 +400,13 @@ impl WindowEventHandler {
             }
 
             if dragging {
+                reactor.window_manager.pending_external_moves.remove(&wid);
+                if !reactor.is_in_drag()
+                    && !reactor.drag_exceeds_deadzone(wid, &old_frame, &new_frame)
+                {
+                    trace!(?wid, ?new_frame, "Frame change within drag deadzone; ignoring");
+                    return false;
+                }
                 reactor.ensure_active_drag(wid, &old_frame);
                 reactor.update_active_drag(wid, &new_frame);
                 let is_resize = !old_frame.size.same_as(new_frame.size);
@@ -332,75 +431,24 @@ impl WindowEventHandler {
                 } else {
                     reactor.maybe_swap_on_drag(wid, new_frame);
                 }
+            } else if reactor.config.settings.external_move_grace_ms > 0
+                && old_frame.size.same_as(new_frame.size)
+            {
+                hold_external_move(reactor, wid, old_frame, new_frame);
             } else {
-                if old_space != new_space {
-                    let keep_assigned_for_scrolling = old_space.is_some_and(|space| {
-                        reactor.layout_manager.layout_engine.active_layout_mode_at(space)
-                            == LayoutMode::Scrolling
-                            && !reactor.layout_manager.layout_engine.is_window_floating(wid)
-                            && reactor
-                                .layout_manager
-                                .layout_engine
-                                .virtual_workspace_manager()
-                                .workspace_for_window(space, wid)
-                                .is_some()
-                    });
-                    if keep_assigned_for_scrolling {
-                        debug!(
-                            ?wid,
-                            ?old_space,
-                            ?new_space,
-                            "Ignoring geometry-only space change for scrolling tiled window"
-                        );
-                        return false;
-                    }
-
-                    reactor.send_layout_event(LayoutEvent::WindowRemovedPreserveFloating(wid));
-                    if let Some(space) = new_space {
-                        if reactor.is_space_active(space) {
-                            if let Some(active_ws) =
-                                reactor.layout_manager.layout_engine.active_workspace(space)
-                            {
-                                let assigned = reactor
-                                    .layout_manager
-                                    .layout_engine
-                                    .virtual_workspace_manager_mut()
-                                    .assign_window_to_workspace(space, wid, active_ws);
-                                if !assigned {
-                                    warn!(
-                                        "Failed to assign window {:?} to workspace {:?}",
-                                        wid, active_ws
-                                    );
-                                }
-                            }
-                            reactor.send_layout_event(LayoutEvent::WindowAdded(space, wid));
-                        }
-                    }
-                    let _ = reactor.update_layout_or_warn(false, false);
-                } else if !old_frame.size.same_as(new_frame.size) {
-                    if let Some(space) = old_space {
-                        if reactor.is_space_active(space) {
-                            let screens = reactor
-                                .space_manager
-                                .screens
-                                .iter()
-                                .filter_map(|screen| {
-                                    let space = screen.space?;
-                                    let display_uuid = screen.display_uuid_owned();
-                                    Some((space, screen.frame, display_uuid))
-                                })
-                                .collect::<Vec<_>>();
-                            reactor.send_layout_event(LayoutEvent::WindowResized {
-                                wid,
-                                old_frame,
-                                new_frame,
-                                screens,
-                            });
-                            return true;
-                        }
-                    }
-                    return false;
-                }
+                // A resize is applied straight away, starting from wherever a
+                // move still being held began.
+                let (old_frame, old_space) =
+                    match reactor.window_manager.pending_external_moves.remove(&wid) {
+                        Some(pending) => (
+                            pending.from,
+                            reactor.best_space_for_window(&pending.from, server_id),
+                        ),
+                        None => (old_frame, old_space),
+                    };
+                return apply_external_frame_change(
+                    reactor, wid, server_id, old_frame, new_frame, old_space, new_space,
+                );
             }
             false
         })();
@@ -408,6 +456,42 @@ impl WindowEventHandler {
         result
     }
 
+    /// Acts on held external moves whose window has stayed put for
+    /// `external_move_grace_ms`.
+    pub fn commit_settled_external_moves(reactor: &mut Reactor, now: Instant) {
+        let grace = Duration::from_millis(reactor.config.settings.external_move_grace_ms);
+        let settled: Vec<(WindowId, CGRect)> = reactor
+            .window_manager
+            .pending_external_moves
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.moved_at) >= grace)
+            .map(|(&wid, pending)| (wid, pending.from))
+            .collect();
+        if settled.is_empty() {
+            return;
+        }
+
+        let mut is_resize = false;
+        for (wid, from) in settled {
+            reactor.window_manager.pending_external_moves.remove(&wid);
+            let Some(window) = reactor.window_manager.windows.get(&wid) else {
+                continue;
+            };
+            let (server_id, frame) = (window.info.sys_id, window.frame_monotonic);
+            if frame.same_as(from) {
+                continue;
+            }
+            debug!(?wid, ?from, ?frame, "External move settled; applying");
+            let old_space = reactor.best_space_for_window(&from, server_id);
+            let new_space = reactor.best_space_for_window(&frame, server_id);
+            reactor.drag_manager.skip_layout_for_window = Some(wid);
+            is_resize |= apply_external_frame_change(
+                reactor, wid, server_id, from, frame, old_space, new_space,
+            );
+        }
+        let _ = reactor.update_layout_or_warn(is_resize, false);
+    }
+
     pub fn handle_window_title_changed(reactor: &mut Reactor, wid: WindowId, new_title: String) {
         if let Some(window) = reactor.window_manager.windows.get_mut(&wid) {
             let previous_title = window.info.title.clone();
@@ -415,15 +499,50 @@ impl WindowEventHandler {
                 return;
             }
             window.info.title = new_title.clone();
+            let manageable = window.matches_filter(WindowFilter::EffectivelyManageable);
+            // Chat and mail apps put unread counts in the title, so a background
+            // window whose count appears or goes up is asking for attention. Other
+            // retitles (terminals, browsers, players) are ignored.
+            let badge_raised = title_badge(&new_title) > title_badge(&previous_title);
             reactor.broadcast_window_title_changed(wid, previous_title, new_title);
+            reactor.refresh_window_constraints(wid);
             reactor.maybe_reapply_app_rules_for_window(wid);
+            if badge_raised && manageable && reactor.main_window() != Some(wid) {
+                reactor.window_manager.urgent_at.insert(wid, Instant::now());
+            }
+        }
+        // Many apps open a window before giving it its real title, so a held move
+        // naming that title can only match once it changes.
+        let recently_created = reactor.window_manager.windows.get(&wid).is_some_and(|window| {
+            window.created_at.is_some_and(|created_at| {
+                created_at.elapsed() < WindowManager::PENDING_ASSIGNMENT_TTL
+            })
+        });
+        if recently_created {
+            apply_pending_workspace_assignment(reactor, wid);
         }
     }
 
-    pub fn handle_mouse_moved_over_window(reactor: &mut Reactor, wsid: WindowServerId) {
+    pub fn handle_mouse_moved_over_window(
+        reactor: &mut Reactor,
+        wsid: WindowServerId,
+        cursor: CGPoint,
+    ) {
         let Some(&wid) = reactor.window_manager.window_ids.get(&wsid) else {
             return;
         };
+        if reactor.in_focus_exclusion_zone(cursor) {
+            trace!(?wid, ?cursor, "Skipping autoraise inside focus exclusion zone");
+            return;
+        }
+        if reactor.layout_manager.focus_lock.is_active(&reactor.transaction_manager) {
+            trace!(?wid, "Skipping autoraise while layout settles");
+            return;
+        }
+        if reactor.app_rule_disables_focus_follows_mouse(wid) {
+            trace!(?wid, "Skipping autoraise for app excluded by an app rule");
+            return;
+        }
         if !reactor.should_raise_on_mouse_over(wid) {
             return;
         }
@@ -440,6 +559,199 @@ impl WindowEventHandler {
     }
 }
 
+/// The unread count shown in a window title as `(N)`, e.g. "Inbox (3)" or
+/// "(2) Gmail".
+fn title_badge(title: &str) -> Option<u32> {
+    title.match_indices('(').find_map(|(start, _)| {
+        let rest = &title[start + 1..];
+        rest[..rest.find(')')?].parse().ok()
+    })
+}
+
+/// Whether a confirmed frame shows the window staying at its old origin even
+/// though we asked it to move elsewhere. Frames that don't match the requested
+/// size are left alone, since those are still catching up on a resize.
+fn refused_move(reported: CGRect, target: CGRect, confirmed: CGRect) -> bool {
+    !target.origin.same_as(reported.origin)
+        && confirmed.origin.same_as(reported.origin)
+        && confirmed.size.same_as(target.size)
+}
+
+fn lock_window_position(reactor: &mut Reactor, wid: WindowId, frame: CGRect) {
+    let Some(window) = reactor.window_manager.windows.get_mut(&wid) else {
+        return;
+    };
+    window.frame_monotonic = frame;
+    if window.position_locked {
+        return;
+    }
+    window.position_locked = true;
+    warn!(?wid, ?frame, "Window ignores moves; floating it in place");
+
+    let Some(space) = reactor.best_space_for_window_id(wid) else {
+        return;
+    };
+    reactor.layout_manager.layout_engine.float_window_at(space, wid, frame);
+    let _ = reactor.update_layout_or_warn(false, false);
+}
+
+/// Acts on a move of `wid` from `old_frame` to `new_frame` made by something
+/// other than rift outside of a drag. Returns whether it was a resize.
+fn apply_external_frame_change(
+    reactor: &mut Reactor,
+    wid: WindowId,
+    server_id: Option<WindowServerId>,
+    old_frame: CGRect,
+    new_frame: CGRect,
+    old_space: Option<SpaceId>,
+    new_space: Option<SpaceId>,
+) -> bool {
+    if old_space != new_space {
+        if let Some(space) = old_space
+            && reactor.confine_window_to_assigned_display(wid, space, old_frame)
+        {
+            if reactor.drag_manager.skip_layout_for_window == Some(wid) {
+                reactor.drag_manager.skip_layout_for_window = None;
+            }
+            return false;
+        }
+
+        let keep_assigned_for_scrolling = old_space.is_some_and(|space| {
+            reactor.layout_manager.layout_engine.active_layout_mode_at(space)
+                == LayoutMode::Scrolling
+                && !reactor.layout_manager.layout_engine.is_window_floating(wid)
+                && reactor
+                    .layout_manager
+                    .layout_engine
+                    .virtual_workspace_manager()
+                    .workspace_for_window(space, wid)
+                    .is_some()
+        });
+        if keep_assigned_for_scrolling {
+            debug!(
+                ?wid,
+                ?old_space,
+                ?new_space,
+                "Ignoring geometry-only space change for scrolling tiled window"
+            );
+            return false;
+        }
+
+        reactor.send_layout_event(LayoutEvent::WindowRemovedPreserveFloating(wid));
+        if let Some(space) = new_space {
+            if reactor.is_space_active(space) {
+                if let Some(active_ws) =
+                    reactor.layout_manager.layout_engine.active_workspace(space)
+                {
+                    let assigned = reactor
+                        .layout_manager
+                        .layout_engine
+                        .virtual_workspace_manager_mut()
+                        .assign_window_to_workspace(space, wid, active_ws);
+                    if !assigned {
+                        warn!("Failed to assign window {:?} to workspace {:?}", wid, active_ws);
+                    }
+                }
+                reactor.send_layout_event(LayoutEvent::WindowAdded(space, wid));
+            }
+        }
+        let _ = reactor.update_layout_or_warn(false, false);
+    } else if !old_frame.size.same_as(new_frame.size) {
+        if let Some(space) = old_space {
+            if reactor.is_space_active(space) {
+                let screens = reactor
+                    .space_manager
+                    .screens
+                    .iter()
+                    .filter_map(|screen| {
+                        let space = screen.space?;
+                        let display_uuid = screen.display_uuid_owned();
+                        Some((space, screen.frame, display_uuid))
+                    })
+                    .collect::<Vec<_>>();
+                reactor.send_layout_event(LayoutEvent::WindowResized {
+                    wid,
+                    old_frame,
+                    new_frame,
+                    screens,
+                });
+                return true;
+            }
+        }
+        return false;
+    } else if let Some(space) = old_space
+        && reactor.is_space_active(space)
+        && !reactor.layout_manager.layout_engine.is_window_floating(wid)
+        && reactor.layout_manager.layout_engine.is_window_in_active_workspace(space, wid)
+    {
+        handle_external_move_of_tiled_window(reactor, wid, space, new_frame);
+    }
+    false
+}
+
+/// Holds an external move (same size, new position) until it has stayed put for
+/// `external_move_grace_ms`, dropping it if the window returns to where it started.
+fn hold_external_move(reactor: &mut Reactor, wid: WindowId, old_frame: CGRect, new_frame: CGRect) {
+    let pending = &mut reactor.window_manager.pending_external_moves;
+    let from = pending.get(&wid).map_or(old_frame, |pending| pending.from);
+    if new_frame.same_as(from) {
+        debug!(
+            ?wid,
+            ?new_frame,
+            "External move reverted within grace period; ignoring"
+        );
+        pending.remove(&wid);
+    } else {
+        pending.insert(wid, PendingExternalMove { from, moved_at: Instant::now() });
+    }
+}
+
+/// A tiled window was moved, without being resized, by something other than
+/// rift or a mouse drag.
+fn handle_external_move_of_tiled_window(
+    reactor: &mut Reactor,
+    wid: WindowId,
+    space: SpaceId,
+    new_frame: CGRect,
+) {
+    match reactor.config.settings.tiled_window_external_move {
+        ExternalMoveBehavior::SnapBack => {
+            debug!(
+                ?wid,
+                ?new_frame,
+                "Snapping externally moved window back to its tile"
+            );
+        }
+        ExternalMoveBehavior::Retile => {
+            let center = new_frame.mid();
+            let target = reactor
+                .collect_drag_swap_candidates(wid, space)
+                .into_iter()
+                .find(|(_, frame)| frame.contains(center))
+                .map(|(target, _)| target);
+            if let Some(target) = target {
+                debug!(?wid, ?target, "Retiling externally moved window");
+                let (visible_spaces, visible_space_centers) =
+                    reactor.visible_spaces_for_layout(true);
+                let response = reactor.layout_manager.layout_engine.handle_command(
+                    Some(space),
+                    &visible_spaces,
+                    &visible_space_centers,
+                    LayoutCommand::SwapWindows(wid, target),
+                );
+                reactor.handle_layout_response(response, None);
+            } else {
+                debug!(?wid, ?new_frame, "No tile under moved window; snapping back");
+            }
+        }
+    }
+    // Either way the window's tile is authoritative, so let the next layout
+    // pass place it instead of leaving it where it was dropped.
+    if reactor.drag_manager.skip_layout_for_window == Some(wid) {
+        reactor.drag_manager.skip_layout_for_window = None;
+    }
+}
+
 fn active_space_for_window(
     reactor: &Reactor,
     frame: &CGRect,
@@ -471,6 +783,52 @@ fn maybe_dispatch_window_added_in_space(reactor: &mut Reactor, wid: WindowId, sp
     }
 }
 
+/// Move a newly tracked window to the workspace of the first held
+/// `MoveWindowToWorkspace` it matches, if any.
+pub(super) fn apply_pending_workspace_assignment(reactor: &mut Reactor, wid: WindowId) {
+    if reactor.window_manager.pending_workspace_assignments.is_empty() {
+        return;
+    }
+    let Some(window) = reactor.window_manager.windows.get(&wid) else {
+        return;
+    };
+    if !window.matches_filter(WindowFilter::EffectivelyManageable) {
+        return;
+    }
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+    let Some(space) = reactor
+        .space_manager
+        .iter_known_spaces()
+        .find(|sp| vwm.workspace_for_window(*sp, wid).is_some())
+    else {
+        return;
+    };
+    let title = window.info.title.clone();
+    let bundle_id = reactor
+        .app_manager
+        .apps
+        .get(&wid.pid)
+        .and_then(|app| app.info.bundle_id.clone());
+    let Some(pending) = reactor.window_manager.take_pending_workspace_assignment(
+        bundle_id.as_deref(),
+        &title,
+        Instant::now(),
+    ) else {
+        return;
+    };
+    debug!(
+        ?wid,
+        workspace = pending.workspace,
+        "Applying held workspace move to new window"
+    );
+    reactor.layout_manager.layout_engine.move_window_to_workspace_index(
+        space,
+        wid,
+        pending.workspace,
+        pending.target_index,
+    );
+}
+
 fn handle_mouse_up_if_needed(reactor: &mut Reactor, mouse_state: Option<MouseState>) {
     if mouse_state == Some(MouseState::Up)
         && (matches!(
@@ -1,6 +1,10 @@
+use std::time::Instant;
+
 use tracing::{trace, warn};
 
 use crate::actor::app::{AppInfo, WindowId, WindowInfo, pid_t};
+use crate::actor::broadcast::ManageabilityReason;
+use crate::actor::reactor::events::window::apply_pending_workspace_assignment;
 use crate::actor::reactor::{Event, LayoutEvent, Reactor, WindowFilter, WindowState, utils};
 use crate::common::collections::{BTreeMap, HashSet};
 use crate::model::virtual_workspace::AppRuleResult;
@@ -30,9 +34,13 @@ impl WindowDiscoveryHandler {
             Self::identify_stale_windows(reactor, pid, &known_visible);
         Self::cleanup_stale_windows(reactor, pid, stale_windows, pending_refresh);
         let new_windows = Self::process_window_list(reactor, new, &app_info);
+        let discovered: Vec<WindowId> = new_windows.iter().map(|(wid, _)| *wid).collect();
         Self::update_window_states(reactor, new_windows, &app_info);
 
         Self::emit_layout_events(reactor, pid, &known_visible, &app_info);
+        for wid in discovered {
+            apply_pending_workspace_assignment(reactor, wid);
+        }
     }
 
     fn sync_window_server_id_mapping(
@@ -231,12 +239,17 @@ impl WindowDiscoveryHandler {
                         info.is_minimized,
                         info.is_standard,
                         info.is_root,
+                        reactor.is_auto_float_window(
+                            info.ax_role.as_deref(),
+                            info.ax_subrole.as_deref(),
+                        ),
                         &reactor.window_server_info_manager.window_server_info,
                     );
                     if let Some(existing) = reactor.window_manager.windows.get_mut(wid) {
                         existing.info.title = info.title.clone();
                         if info.frame.size.width != 0.0 || info.frame.size.height != 0.0 {
                             existing.frame_monotonic = info.frame;
+                            existing.reported_frame = info.frame;
                         }
                         existing.info.is_standard = info.is_standard;
                         existing.info.is_root = info.is_root;
@@ -249,19 +262,26 @@ impl WindowDiscoveryHandler {
                         existing.info.path = info.path.clone();
                         existing.info.ax_role = info.ax_role.clone();
                         existing.info.ax_subrole = info.ax_subrole.clone();
-                        existing.is_manageable = manageable;
                     }
+                    reactor.window_manager.set_manageable(
+                        *wid,
+                        manageable,
+                        ManageabilityReason::Rediscovered,
+                    );
                 } else {
-                    let mut state: WindowState = WindowState::from((*info).clone());
+                    let state: WindowState = WindowState::from((*info).clone());
                     let manageable = utils::compute_window_manageability(
                         state.info.sys_id,
                         state.info.is_minimized,
                         state.info.is_standard,
                         state.info.is_root,
+                        reactor.is_auto_float_window(
+                            state.info.ax_role.as_deref(),
+                            state.info.ax_subrole.as_deref(),
+                        ),
                         &reactor.window_server_info_manager.window_server_info,
                     );
-                    state.is_manageable = manageable;
-                    reactor.window_manager.windows.insert(*wid, state);
+                    reactor.window_manager.insert_window(*wid, state, manageable);
                 }
                 Self::sync_window_server_id_mapping(reactor, *wid, None, info.sys_id);
             }
@@ -282,12 +302,15 @@ impl WindowDiscoveryHandler {
                     info.is_minimized,
                     info.is_standard,
                     info.is_root,
+                    reactor
+                        .is_auto_float_window(info.ax_role.as_deref(), info.ax_subrole.as_deref()),
                     &reactor.window_server_info_manager.window_server_info,
                 );
                 if let Some(existing) = reactor.window_manager.windows.get_mut(&wid) {
                     existing.info.title = info.title.clone();
                     if info.frame.size.width != 0.0 || info.frame.size.height != 0.0 {
                         existing.frame_monotonic = info.frame;
+                        existing.reported_frame = info.frame;
                     }
                     existing.info.is_standard = info.is_standard;
                     existing.info.is_root = info.is_root;
@@ -300,8 +323,12 @@ impl WindowDiscoveryHandler {
                     existing.info.path = info.path.clone();
                     existing.info.ax_role = info.ax_role.clone();
                     existing.info.ax_subrole = info.ax_subrole.clone();
-                    existing.is_manageable = manageable;
                 }
+                reactor.window_manager.set_manageable(
+                    wid,
+                    manageable,
+                    ManageabilityReason::Rediscovered,
+                );
             } else {
                 Self::sync_window_server_id_mapping(reactor, wid, None, info.sys_id);
                 new_windows.push((wid, info));
@@ -320,15 +347,19 @@ impl WindowDiscoveryHandler {
         // Update or insert window states
         for (wid, info) in new_windows {
             let mut state: WindowState = info.into();
+            state.created_at = Some(Instant::now());
             let manageable = utils::compute_window_manageability(
                 state.info.sys_id,
                 state.info.is_minimized,
                 state.info.is_standard,
                 state.info.is_root,
+                reactor.is_auto_float_window(
+                    state.info.ax_role.as_deref(),
+                    state.info.ax_subrole.as_deref(),
+                ),
                 &reactor.window_server_info_manager.window_server_info,
             );
-            state.is_manageable = manageable;
-            reactor.window_manager.windows.insert(wid, state);
+            reactor.window_manager.insert_window(wid, state, manageable);
         }
     }
 
@@ -1,4 +1,6 @@
-use std::time::Instant;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Debug;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use objc2_core_foundation::{CGPoint, CGRect};
 use tracing::trace;
@@ -10,14 +12,19 @@ use super::{
 };
 use crate::actor;
 use crate::actor::app::{WindowId, pid_t};
-use crate::actor::broadcast::{BroadcastEvent, BroadcastSender, StackInfo};
+use crate::actor::broadcast::{BroadcastEvent, BroadcastSender, ManageabilityReason, StackInfo};
 use crate::actor::drag_swap::DragManager as DragSwapManager;
 use crate::actor::reactor::Reactor;
 use crate::actor::reactor::animation::AnimationManager;
-use crate::actor::{event_tap, menu_bar, raise_manager, stack_line, window_notify, wm_controller};
+use crate::actor::reactor::transaction_manager::TransactionManager;
+use crate::actor::reactor::wakeups::WakeupScheduler;
+use crate::actor::{
+    event_tap, focus_border, menu_bar, raise_manager, stack_line, window_notify, wm_controller,
+};
 use crate::common::collections::{HashMap, HashSet};
 use crate::common::config::{LayoutMode, WindowSnappingSettings};
-use crate::layout_engine::LayoutEngine;
+use crate::layout_engine::{EventResponse, LayoutEngine, LayoutEvent, VirtualWorkspaceId};
+use crate::model::server::{EventLogEntryData, EventLogKind, HandlerTimingData};
 use crate::sys::screen::SpaceId;
 use crate::sys::window_server::{WindowServerId, WindowServerInfo};
 
@@ -27,12 +34,225 @@ pub struct WindowManager {
     pub window_ids: HashMap<WindowServerId, WindowId>,
     pub visible_windows: HashSet<WindowServerId>,
     pub observed_window_server_ids: HashSet<WindowServerId>,
+    /// When frames were last polled for apps configured in `settings.frame_poll`
+    pub last_frame_poll: Option<Instant>,
+    /// Manageability flips not yet reported, keyed by window
+    pub pending_manageability: HashMap<WindowId, PendingManageabilityChange>,
+    /// External moves waiting out `external_move_grace_ms`, keyed by window
+    pub pending_external_moves: HashMap<WindowId, PendingExternalMove>,
+    /// Workspace moves requested for windows that haven't appeared yet
+    pub pending_workspace_assignments: Vec<PendingWorkspaceAssignment>,
+    /// When each window last gained focus, for most-recently-focused tie-breaks
+    pub last_focused_at: HashMap<WindowId, Instant>,
+    /// Windows whose title the stack line leaves out
+    pub hidden_stack_line_titles: HashSet<WindowId>,
+    /// Where minimized windows were before leaving the layout, so queries can
+    /// still report them under that workspace.
+    pub minimized_window_workspaces: HashMap<WindowId, (SpaceId, VirtualWorkspaceId)>,
+    /// Index into `floating_presets` each window was last cycled to
+    pub floating_preset_index: HashMap<WindowId, usize>,
+    /// Window each user-defined mark points at
+    pub marks: HashMap<String, WindowId>,
+    /// When each window last asked for attention; cleared once it is focused
+    pub urgent_at: HashMap<WindowId, Instant>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PendingManageabilityChange {
+    /// Manageability subscribers last saw (the value before the first flip)
+    reported: bool,
+    reason: ManageabilityReason,
+    changed_at: Instant,
+}
+
+/// An external move held back until the window has stayed put for the grace
+/// period.
+#[derive(Debug, Clone, Copy)]
+pub struct PendingExternalMove {
+    /// Frame the window had before the move began
+    pub from: CGRect,
+    pub moved_at: Instant,
+}
+
+/// A `MoveWindowToWorkspace` naming a window that wasn't tracked yet, applied
+/// once a matching window is created.
+#[derive(Debug, Clone)]
+pub struct PendingWorkspaceAssignment {
+    pub bundle_id: Option<String>,
+    /// Substring the window title must contain
+    pub title: Option<String>,
+    /// Workspace index, applied on whichever display the window opens on
+    pub workspace: usize,
+    /// Position in the workspace's window order, as in `MoveWindowToWorkspace`
+    pub target_index: Option<usize>,
+    pub queued_at: Instant,
+}
+
+impl PendingWorkspaceAssignment {
+    pub fn matches(&self, bundle_id: Option<&str>, title: &str) -> bool {
+        let bundle_matches = self
+            .bundle_id
+            .as_deref()
+            .is_none_or(|expected| bundle_id.is_some_and(|id| id.eq_ignore_ascii_case(expected)));
+        bundle_matches && self.title.as_deref().is_none_or(|expected| title.contains(expected))
+    }
+}
+
+impl WindowManager {
+    /// Apps often flip a launching window's manageability a few times before it
+    /// settles; changes are only reported once they have held this long.
+    const MANAGEABILITY_DEBOUNCE: Duration = Duration::from_millis(300);
+    /// Launchers usually route a window within a second or two of opening the
+    /// app; assignments for windows that never show up are dropped after this.
+    pub const PENDING_ASSIGNMENT_TTL: Duration = Duration::from_secs(10);
+    /// Apps that are launching often open and close throwaway windows; a window
+    /// destroyed this soon after it was created is treated as one of those.
+    pub const TRANSIENT_WINDOW_LIFETIME: Duration = Duration::from_millis(500);
+
+    /// Hold an assignment until a matching window shows up, discarding any that
+    /// have expired so unmatched moves don't pile up.
+    pub fn queue_workspace_assignment(&mut self, pending: PendingWorkspaceAssignment) {
+        self.prune_pending_workspace_assignments(pending.queued_at);
+        self.pending_workspace_assignments.push(pending);
+    }
+
+    /// Remove and return the oldest queued assignment matching a new window,
+    /// discarding any that have expired.
+    pub fn take_pending_workspace_assignment(
+        &mut self,
+        bundle_id: Option<&str>,
+        title: &str,
+        now: Instant,
+    ) -> Option<PendingWorkspaceAssignment> {
+        self.prune_pending_workspace_assignments(now);
+        let index = self
+            .pending_workspace_assignments
+            .iter()
+            .position(|pending| pending.matches(bundle_id, title))?;
+        Some(self.pending_workspace_assignments.remove(index))
+    }
+
+    fn prune_pending_workspace_assignments(&mut self, now: Instant) {
+        self.pending_workspace_assignments
+            .retain(|pending| now.duration_since(pending.queued_at) < Self::PENDING_ASSIGNMENT_TTL);
+    }
+
+    /// Start tracking a window. Its first manageability is not a change, so it
+    /// is never reported.
+    pub fn insert_window(&mut self, wid: WindowId, mut state: WindowState, manageable: bool) {
+        state.is_manageable = manageable;
+        self.pending_manageability.remove(&wid);
+        self.windows.insert(wid, state);
+    }
+
+    /// Every later change to a tracked window's manageability goes through
+    /// here, so that it gets debounced and reported.
+    pub fn set_manageable(&mut self, wid: WindowId, manageable: bool, reason: ManageabilityReason) {
+        let Some(window) = self.windows.get_mut(&wid) else {
+            return;
+        };
+        if window.is_manageable == manageable {
+            return;
+        }
+        let reported = window.is_manageable;
+        window.is_manageable = manageable;
+
+        let now = Instant::now();
+        self.pending_manageability
+            .entry(wid)
+            .and_modify(|pending| {
+                pending.reason = reason;
+                pending.changed_at = now;
+            })
+            .or_insert(PendingManageabilityChange {
+                reported,
+                reason,
+                changed_at: now,
+            });
+    }
+
+    /// When the oldest pending manageability change will have settled.
+    pub fn next_manageability_settle(&self) -> Option<Instant> {
+        self.pending_manageability
+            .values()
+            .map(|pending| pending.changed_at + Self::MANAGEABILITY_DEBOUNCE)
+            .min()
+    }
+
+    /// When the oldest held external move's grace period runs out.
+    pub fn next_external_move_settle(&self, grace: Duration) -> Option<Instant> {
+        self.pending_external_moves
+            .values()
+            .map(|pending| pending.moved_at + grace)
+            .min()
+    }
+
+    /// Drain changes that have settled, dropping those that flipped back.
+    pub fn take_settled_manageability_changes(
+        &mut self,
+        now: Instant,
+    ) -> Vec<(WindowId, bool, ManageabilityReason)> {
+        let settled: Vec<WindowId> = self
+            .pending_manageability
+            .iter()
+            .filter(|(_, pending)| {
+                now.duration_since(pending.changed_at) >= Self::MANAGEABILITY_DEBOUNCE
+            })
+            .map(|(&wid, _)| wid)
+            .collect();
+        settled
+            .into_iter()
+            .filter_map(|wid| {
+                let pending = self.pending_manageability.remove(&wid)?;
+                let manageable = self.windows.get(&wid)?.is_manageable;
+                (manageable != pending.reported).then_some((wid, manageable, pending.reason))
+            })
+            .collect()
+    }
+
+    /// Keep `last_focused_at` in step with the focus and removal events sent to
+    /// the layout engine.
+    pub fn record_focus_event(&mut self, event: &LayoutEvent) {
+        match event {
+            LayoutEvent::WindowFocused(_, wid) => {
+                self.last_focused_at.insert(*wid, Instant::now());
+                self.urgent_at.remove(wid);
+            }
+            LayoutEvent::WindowRemoved(wid) => {
+                self.last_focused_at.remove(wid);
+                self.urgent_at.remove(wid);
+            }
+            LayoutEvent::AppClosed(pid) => {
+                self.last_focused_at.retain(|wid, _| wid.pid != *pid);
+                self.urgent_at.retain(|wid, _| wid.pid != *pid);
+            }
+            _ => {}
+        }
+    }
+
+    /// Marks pointing at `wid`, sorted.
+    pub fn marks_for(&self, wid: WindowId) -> Vec<String> {
+        let mut marks: Vec<String> = self
+            .marks
+            .iter()
+            .filter(|(_, marked)| **marked == wid)
+            .map(|(mark, _)| mark.clone())
+            .collect();
+        marks.sort();
+        marks
+    }
 }
 
 /// Manages application state and rules
 pub struct AppManager {
     pub apps: HashMap<pid_t, AppState>,
     pub app_rules_recent_targets: HashMap<crate::sys::window_server::WindowServerId, Instant>,
+    /// Apps hidden with `ToggleAppHidden`.
+    pub hidden_apps: HashSet<pid_t>,
+    /// Where windows of hidden apps were, so they can go back there once shown.
+    pub hidden_window_workspaces: HashMap<WindowId, (SpaceId, VirtualWorkspaceId)>,
+    /// Apps whose windows the window server wouldn't change the opacity of.
+    pub opacity_refused_apps: HashSet<pid_t>,
 }
 
 impl AppManager {
@@ -40,6 +260,9 @@ impl AppManager {
         AppManager {
             apps: HashMap::default(),
             app_rules_recent_targets: HashMap::default(),
+            hidden_apps: HashSet::default(),
+            hidden_window_workspaces: HashMap::default(),
+            opacity_refused_apps: HashSet::default(),
         }
     }
 
@@ -81,6 +304,11 @@ pub struct SpaceManager {
     pub screens: Vec<ScreenInfo>,
     pub fullscreen_by_space: HashMap<u64, FullscreenSpaceTrack>,
     pub has_seen_display_set: bool,
+    /// Display index last reached through `CycleDisplays`.
+    pub last_cycled_display: Option<usize>,
+    /// Windows moved off each disconnected display, with the index of the
+    /// workspace they were on there.
+    pub displaced_windows: HashMap<String, Vec<(WindowId, usize)>>,
 }
 
 impl SpaceManager {
@@ -100,6 +328,10 @@ pub struct DragManager {
     pub drag_state: super::DragState,
     pub drag_swap_manager: DragSwapManager,
     pub skip_layout_for_window: Option<WindowId>,
+    pub edge_resize: Option<super::EdgeResizeSession>,
+    /// Window moving under the held button and its frame when it started
+    /// moving, kept until the movement leaves the drag deadzone
+    pub press_origin: Option<(WindowId, CGRect)>,
 }
 
 impl DragManager {
@@ -132,6 +364,8 @@ pub struct MenuManager {
 /// Manages Mission Control state
 pub struct MissionControlManager {
     pub mission_control_state: super::MissionControlState,
+    /// Copy of `mission_control_state` readable from query handles
+    pub shared_state: super::SharedMissionControlState,
     pub pending_mission_control_refresh: HashSet<pid_t>,
 }
 
@@ -173,20 +407,159 @@ pub struct RefocusManager {
 pub struct CommunicationManager {
     pub event_tap_tx: Option<event_tap::Sender>,
     pub stack_line_tx: Option<stack_line::Sender>,
+    pub focus_border_tx: Option<focus_border::Sender>,
+    /// Frame last sent to the focus border, or `None` if it was last hidden
+    pub focus_border_frame: Option<CGRect>,
     pub raise_manager_tx: raise_manager::Sender,
     pub event_broadcaster: BroadcastSender,
     pub wm_sender: Option<wm_controller::Sender>,
     pub events_tx: Option<actor::Sender<Event>>,
+    pub wakeups: WakeupScheduler,
 }
 
 /// Manages recording state
 pub struct RecordingManager {
     pub record: Record,
+    pub event_log: EventLog,
+    pub metrics: HandlerMetrics,
+}
+
+/// Counts and handling times of reactor events, and counts of layout commands,
+/// by kind since startup; reported by `GetMetrics`.
+#[derive(Default)]
+pub struct HandlerMetrics {
+    events: BTreeMap<&'static str, HandlerTimingData>,
+    layout_commands: BTreeMap<&'static str, u64>,
+}
+
+impl HandlerMetrics {
+    pub fn record_event(&mut self, kind: &'static str, elapsed: Duration) {
+        let micros = elapsed.as_micros() as u64;
+        let timing = self.events.entry(kind).or_default();
+        timing.count += 1;
+        timing.total_us += micros;
+        timing.max_us = timing.max_us.max(micros);
+    }
+
+    pub fn record_layout_command(&mut self, kind: &'static str) {
+        *self.layout_commands.entry(kind).or_default() += 1;
+    }
+
+    pub fn events(&self) -> &BTreeMap<&'static str, HandlerTimingData> { &self.events }
+
+    pub fn layout_commands(&self) -> &BTreeMap<&'static str, u64> { &self.layout_commands }
+}
+
+/// The most recent layout events and commands, kept for `GetEventLog`. Nothing
+/// is recorded, or even formatted, while the capacity is 0.
+#[derive(Default)]
+pub struct EventLog {
+    entries: VecDeque<(u64, EventLogEntryData)>,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self { Self { capacity, ..Default::default() } }
+
+    pub fn is_enabled(&self) -> bool { self.capacity > 0 }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Appends an entry, evicting the oldest when full. Returns a handle for
+    /// attaching the response later, or `None` while the log is off.
+    pub fn record(
+        &mut self,
+        kind: EventLogKind,
+        item: &impl Debug,
+        response: Option<&EventResponse>,
+    ) -> Option<u64> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((seq, EventLogEntryData {
+            timestamp_ms,
+            kind,
+            description: format!("{item:?}"),
+            response: response.cloned(),
+        }));
+        Some(seq)
+    }
+
+    /// Fills in the response of an entry recorded before it was known. Does
+    /// nothing if the entry has already been evicted.
+    pub fn set_response(&mut self, seq: u64, response: &EventResponse) {
+        if let Some((_, entry)) = self.entries.iter_mut().rev().find(|(s, _)| *s == seq) {
+            entry.response = Some(response.clone());
+        }
+    }
+
+    /// The newest `limit` entries (all of them if `None`), oldest first.
+    pub fn recent(&self, limit: Option<usize>) -> Vec<EventLogEntryData> {
+        let skip = limit.map_or(0, |limit| self.entries.len().saturating_sub(limit));
+        self.entries.iter().skip(skip).map(|(_, entry)| entry.clone()).collect()
+    }
 }
 
 /// Manages layout engine state
 pub struct LayoutManager {
     pub layout_engine: LayoutEngine,
+    /// Set while a layout command is handled so the next applied layout arms the focus lock.
+    pub layout_command_pending: bool,
+    pub focus_lock: FocusLock,
+}
+
+/// Suppresses focus-follows-mouse raises while frames requested by a layout
+/// command are still in flight, so windows sliding under the cursor don't steal focus.
+#[derive(Default)]
+pub struct FocusLock {
+    pending: HashSet<WindowServerId>,
+    deadline: Option<Instant>,
+}
+
+impl FocusLock {
+    /// Upper bound on how long the lock is held if an app never reports its final frame.
+    const MAX_DURATION: Duration = Duration::from_secs(2);
+
+    pub fn arm(&mut self, windows: impl IntoIterator<Item = WindowServerId>) {
+        self.pending.extend(windows);
+        if !self.pending.is_empty() {
+            self.deadline = Some(Instant::now() + Self::MAX_DURATION);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.deadline = None;
+    }
+
+    /// Returns true while any locked window still has a pending frame target.
+    pub fn is_active(&mut self, transaction_manager: &TransactionManager) -> bool {
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.clear();
+            return false;
+        }
+        self.pending
+            .retain(|wsid| transaction_manager.get_target_frame(*wsid).is_some());
+        if self.pending.is_empty() {
+            self.deadline = None;
+            return false;
+        }
+        true
+    }
 }
 
 pub type LayoutResult = Vec<(SpaceId, Vec<(WindowId, CGRect)>)>;
@@ -320,7 +693,7 @@ impl LayoutManager {
         let mut any_frame_changed = false;
 
         let active_space = reactor.main_window_space();
-        for (space, layout) in layout_result {
+        for (space, mut layout) in layout_result {
             if let Some(screen) = reactor.space_manager.screen_by_space(space) {
                 let screen_frame = screen.frame;
                 let display_uuid = screen.display_uuid_owned();
@@ -358,6 +731,19 @@ impl LayoutManager {
                             total_count: g.total_count,
                             selected_index: g.selected_index,
                             window_ids: g.window_ids.clone(),
+                            window_titles: g
+                                .window_ids
+                                .iter()
+                                .map(|wid| {
+                                    reactor
+                                        .window_manager
+                                        .windows
+                                        .get(wid)
+                                        .map(|window| window.info.title.clone())
+                                        .unwrap_or_default()
+                                })
+                                .collect(),
+                            is_collapsed: g.is_collapsed,
                         })
                         .collect();
                     let active_space_ids: Vec<crate::sys::screen::SpaceId> =
@@ -410,6 +796,21 @@ impl LayoutManager {
                 }
             }
 
+            // A window with a held external move stays where it was put until
+            // the move settles or reverts. Once it leaves the active workspace
+            // the move no longer matters and the layout hides it as usual.
+            let held: Vec<WindowId> = layout
+                .iter()
+                .map(|(wid, _)| *wid)
+                .filter(|wid| reactor.window_manager.pending_external_moves.contains_key(wid))
+                .collect();
+            for wid in held {
+                if reactor.layout_manager.layout_engine.is_window_in_active_workspace(space, wid) {
+                    layout.retain(|(other, _)| *other != wid);
+                } else {
+                    reactor.window_manager.pending_external_moves.remove(&wid);
+                }
+            }
             let suppress_animation = is_workspace_switch
                 || reactor.workspace_switch_manager.active_workspace_switch.is_some();
             if suppress_animation {
@@ -420,6 +821,20 @@ impl LayoutManager {
             }
         }
 
+        if std::mem::take(&mut reactor.layout_manager.layout_command_pending)
+            && any_frame_changed
+            && reactor.config.settings.focus_lock_during_layout
+        {
+            let in_flight: Vec<WindowServerId> = reactor
+                .window_manager
+                .windows
+                .values()
+                .filter_map(|window| window.info.sys_id)
+                .filter(|wsid| reactor.transaction_manager.get_target_frame(*wsid).is_some())
+                .collect();
+            reactor.layout_manager.focus_lock.arm(in_flight);
+        }
+
         reactor.maybe_send_menu_update();
         Ok(any_frame_changed)
     }
@@ -4,21 +4,34 @@ use objc2_core_foundation::CGRect;
 
 use crate::actor::app::WindowId;
 use crate::actor::menu_bar;
-use crate::actor::reactor::{Event, Reactor, Sender};
+use crate::actor::reactor::events::command::CommandEventHandler;
+use crate::actor::reactor::events::system::SystemEventHandler;
+use crate::actor::reactor::{
+    Event, MenuState, MissionControlState, Reactor, Sender, SharedMissionControlState, WindowFilter,
+};
 use crate::common::collections::HashSet;
+use crate::common::config::LayoutMode;
+use crate::layout_engine::LayoutCommand;
 use crate::model::server::{
-    ApplicationData, DisplayData, LayoutStateData, WindowData, WorkspaceData, WorkspaceLayoutData,
+    ApplicationData, DeserializeStateData, DisplayData, EventLogEntryData, LayoutModeData,
+    LayoutStateData, MenuStateData, MetricsData, PendingSplitData, ReapplyAppRulesData,
+    RetileSpaceData, WindowData, WindowInfoData, WindowTransactionData, WorkspaceData,
+    WorkspaceLayoutData,
 };
 use crate::model::virtual_workspace::VirtualWorkspaceId;
 use crate::sys::screen::{ScreenInfo, SpaceId, get_active_space_number, managed_display_space_ids};
+use crate::sys::window_server::WindowServerId;
 
 #[derive(Clone)]
 pub struct ReactorQueryHandle {
     tx: Sender,
+    mission_control: SharedMissionControlState,
 }
 
 impl ReactorQueryHandle {
-    pub(super) fn new(tx: Sender) -> Self { Self { tx } }
+    pub(super) fn new(tx: Sender, mission_control: SharedMissionControlState) -> Self {
+        Self { tx, mission_control }
+    }
 
     fn send_query<T>(
         &self,
@@ -31,14 +44,30 @@ impl ReactorQueryHandle {
         rx.recv().map_err(|_| RecvError)
     }
 
-    pub fn query_workspaces(&self, space_id: Option<SpaceId>) -> Vec<WorkspaceData> {
-        self.send_query(|resp| QueryRequest::Workspaces { space_id, resp })
-            .unwrap_or_default()
+    pub fn query_workspaces(
+        &self,
+        space_id: Option<SpaceId>,
+        include_minimized: bool,
+    ) -> Vec<WorkspaceData> {
+        self.send_query(|resp| QueryRequest::Workspaces {
+            space_id,
+            include_minimized,
+            resp,
+        })
+        .unwrap_or_default()
     }
 
-    pub fn query_windows(&self, space_id: Option<SpaceId>) -> Vec<WindowData> {
-        self.send_query(|resp| QueryRequest::Windows { space_id, resp })
-            .unwrap_or_default()
+    pub fn query_windows(
+        &self,
+        space_id: Option<SpaceId>,
+        include_minimized: bool,
+    ) -> Vec<WindowData> {
+        self.send_query(|resp| QueryRequest::Windows {
+            space_id,
+            include_minimized,
+            resp,
+        })
+        .unwrap_or_default()
     }
 
     pub fn query_active_workspace(&self, space_id: Option<SpaceId>) -> Option<VirtualWorkspaceId> {
@@ -51,21 +80,85 @@ impl ReactorQueryHandle {
         self.send_query(QueryRequest::Displays).unwrap_or_default()
     }
 
+    /// Read without going through the reactor, so this answers even while the
+    /// reactor is busy handling a Mission Control transition.
+    pub fn query_mission_control_state(&self) -> MissionControlState { self.mission_control.get() }
+
     pub fn query_workspace_layouts(
         &self,
         space_id: Option<SpaceId>,
         workspace_id: Option<usize>,
-    ) -> Vec<WorkspaceLayoutData> {
+    ) -> Option<Vec<WorkspaceLayoutData>> {
         self.send_query(|resp| QueryRequest::WorkspaceLayouts { space_id, workspace_id, resp })
-            .unwrap_or_default()
+            .ok()
+            .flatten()
     }
 
-    pub fn query_window_info(&self, window_id: WindowId) -> Option<WindowData> {
+    pub fn query_layout_mode(&self, space_id: Option<SpaceId>) -> Option<LayoutModeData> {
+        self.send_query(|resp| QueryRequest::LayoutMode { space_id, resp })
+            .ok()
+            .flatten()
+    }
+
+    pub fn query_pending_split(&self, space_id: Option<SpaceId>) -> Option<PendingSplitData> {
+        self.send_query(|resp| QueryRequest::PendingSplit { space_id, resp })
+            .ok()
+            .flatten()
+    }
+
+    pub fn set_layout_mode(
+        &self,
+        space_id: Option<SpaceId>,
+        mode: LayoutMode,
+    ) -> Option<LayoutModeData> {
+        self.send_query(|resp| QueryRequest::SetLayoutMode { space_id, mode, resp })
+            .ok()
+            .flatten()
+    }
+
+    pub fn retile_space(&self, space_id: Option<SpaceId>) -> Option<RetileSpaceData> {
+        self.send_query(|resp| QueryRequest::RetileSpace { space_id, resp })
+            .ok()
+            .flatten()
+    }
+
+    pub fn reapply_app_rules(&self) -> Option<ReapplyAppRulesData> {
+        self.send_query(QueryRequest::ReapplyAppRules).ok()
+    }
+
+    pub fn query_menu_state(&self) -> Option<MenuStateData> {
+        self.send_query(QueryRequest::MenuState).ok()
+    }
+
+    pub fn reset_menu_state(&self) -> Option<MenuStateData> {
+        self.send_query(QueryRequest::ResetMenuState).ok()
+    }
+
+    /// `None` while `settings.event_log_size` is 0.
+    pub fn query_event_log(&self, limit: Option<usize>) -> Option<Vec<EventLogEntryData>> {
+        self.send_query(|resp| QueryRequest::EventLog { limit, resp }).ok().flatten()
+    }
+
+    pub fn deserialize_state(&self, state: String) -> Result<DeserializeStateData, String> {
+        self.send_query(|resp| QueryRequest::DeserializeState { state, resp })
+            .map_err(|_| "reactor is not running".to_string())?
+    }
+
+    pub fn query_window_info(&self, window_id: WindowId) -> Option<WindowInfoData> {
         self.send_query(|resp| QueryRequest::WindowInfo { window_id, resp })
             .ok()
             .flatten()
     }
 
+    pub fn query_window_transaction(
+        &self,
+        window_server_id: WindowServerId,
+    ) -> Option<WindowTransactionData> {
+        self.send_query(|resp| QueryRequest::WindowTransaction { window_server_id, resp })
+            .ok()
+            .flatten()
+    }
+
     pub fn query_applications(&self) -> Vec<ApplicationData> {
         self.send_query(QueryRequest::Applications).unwrap_or_default()
     }
@@ -79,16 +172,22 @@ impl ReactorQueryHandle {
     pub fn query_metrics(&self) -> serde_json::Value {
         self.send_query(QueryRequest::Metrics).unwrap_or_else(|_| serde_json::json!({}))
     }
+
+    pub fn query_metrics_prometheus(&self) -> Option<String> {
+        self.send_query(QueryRequest::MetricsPrometheus).ok()
+    }
 }
 
 #[derive(Debug)]
 pub enum QueryRequest {
     Workspaces {
         space_id: Option<SpaceId>,
+        include_minimized: bool,
         resp: SyncSender<Vec<WorkspaceData>>,
     },
     Windows {
         space_id: Option<SpaceId>,
+        include_minimized: bool,
         resp: SyncSender<Vec<WindowData>>,
     },
     ActiveWorkspace {
@@ -99,11 +198,44 @@ pub enum QueryRequest {
     WorkspaceLayouts {
         space_id: Option<SpaceId>,
         workspace_id: Option<usize>,
-        resp: SyncSender<Vec<WorkspaceLayoutData>>,
+        resp: SyncSender<Option<Vec<WorkspaceLayoutData>>>,
+    },
+    LayoutMode {
+        space_id: Option<SpaceId>,
+        resp: SyncSender<Option<LayoutModeData>>,
+    },
+    SetLayoutMode {
+        space_id: Option<SpaceId>,
+        mode: LayoutMode,
+        resp: SyncSender<Option<LayoutModeData>>,
+    },
+    PendingSplit {
+        space_id: Option<SpaceId>,
+        resp: SyncSender<Option<PendingSplitData>>,
+    },
+    RetileSpace {
+        space_id: Option<SpaceId>,
+        resp: SyncSender<Option<RetileSpaceData>>,
+    },
+    ReapplyAppRules(SyncSender<ReapplyAppRulesData>),
+    MenuState(SyncSender<MenuStateData>),
+    /// Replies with the state from before the reset.
+    ResetMenuState(SyncSender<MenuStateData>),
+    EventLog {
+        limit: Option<usize>,
+        resp: SyncSender<Option<Vec<EventLogEntryData>>>,
+    },
+    DeserializeState {
+        state: String,
+        resp: SyncSender<Result<DeserializeStateData, String>>,
     },
     WindowInfo {
         window_id: WindowId,
-        resp: SyncSender<Option<WindowData>>,
+        resp: SyncSender<Option<WindowInfoData>>,
+    },
+    WindowTransaction {
+        window_server_id: WindowServerId,
+        resp: SyncSender<Option<WindowTransactionData>>,
     },
     Applications(SyncSender<Vec<ApplicationData>>),
     LayoutState {
@@ -111,16 +243,25 @@ pub enum QueryRequest {
         resp: SyncSender<Option<LayoutStateData>>,
     },
     Metrics(SyncSender<serde_json::Value>),
+    MetricsPrometheus(SyncSender<String>),
 }
 
 impl Reactor {
     pub(super) fn handle_query_request(&mut self, req: QueryRequest) {
         match req {
-            QueryRequest::Workspaces { space_id, resp } => {
-                let _ = resp.send(self.query_workspaces(space_id));
+            QueryRequest::Workspaces {
+                space_id,
+                include_minimized,
+                resp,
+            } => {
+                let _ = resp.send(self.query_workspaces(space_id, include_minimized));
             }
-            QueryRequest::Windows { space_id, resp } => {
-                let _ = resp.send(self.query_windows(space_id));
+            QueryRequest::Windows {
+                space_id,
+                include_minimized,
+                resp,
+            } => {
+                let _ = resp.send(self.query_windows(space_id, include_minimized));
             }
             QueryRequest::ActiveWorkspace { space_id, resp } => {
                 let _ = resp.send(self.query_active_workspace(space_id));
@@ -131,9 +272,39 @@ impl Reactor {
             QueryRequest::WorkspaceLayouts { space_id, workspace_id, resp } => {
                 let _ = resp.send(self.query_workspace_layouts(space_id, workspace_id));
             }
+            QueryRequest::LayoutMode { space_id, resp } => {
+                let _ = resp.send(self.query_layout_mode(space_id));
+            }
+            QueryRequest::SetLayoutMode { space_id, mode, resp } => {
+                let _ = resp.send(self.set_layout_mode(space_id, mode));
+            }
+            QueryRequest::PendingSplit { space_id, resp } => {
+                let _ = resp.send(self.query_pending_split(space_id));
+            }
+            QueryRequest::RetileSpace { space_id, resp } => {
+                let _ = resp.send(self.retile_space(space_id));
+            }
+            QueryRequest::ReapplyAppRules(resp) => {
+                let _ = resp.send(self.reapply_app_rules());
+            }
+            QueryRequest::MenuState(resp) => {
+                let _ = resp.send(self.query_menu_state());
+            }
+            QueryRequest::ResetMenuState(resp) => {
+                let _ = resp.send(self.reset_menu_state());
+            }
+            QueryRequest::EventLog { limit, resp } => {
+                let _ = resp.send(self.query_event_log(limit));
+            }
+            QueryRequest::DeserializeState { state, resp } => {
+                let _ = resp.send(self.deserialize_state(&state));
+            }
             QueryRequest::WindowInfo { window_id, resp } => {
                 let _ = resp.send(self.query_window_info(window_id));
             }
+            QueryRequest::WindowTransaction { window_server_id, resp } => {
+                let _ = resp.send(self.query_window_transaction(window_server_id));
+            }
             QueryRequest::Applications(resp) => {
                 let _ = resp.send(self.query_applications());
             }
@@ -143,6 +314,9 @@ impl Reactor {
             QueryRequest::Metrics(resp) => {
                 let _ = resp.send(self.query_metrics());
             }
+            QueryRequest::MetricsPrometheus(resp) => {
+                let _ = resp.send(self.query_metrics_prometheus());
+            }
         }
     }
 
@@ -152,12 +326,20 @@ impl Reactor {
             .or_else(|| self.space_manager.screens.first().and_then(|s| s.space))
     }
 
-    pub fn query_workspaces(&mut self, space_id: Option<SpaceId>) -> Vec<WorkspaceData> {
-        self.handle_workspace_query(space_id)
+    pub fn query_workspaces(
+        &mut self,
+        space_id: Option<SpaceId>,
+        include_minimized: bool,
+    ) -> Vec<WorkspaceData> {
+        self.handle_workspace_query(space_id, include_minimized)
     }
 
-    pub fn query_windows(&self, space_id: Option<SpaceId>) -> Vec<WindowData> {
-        self.handle_windows_query(space_id)
+    pub fn query_windows(
+        &self,
+        space_id: Option<SpaceId>,
+        include_minimized: bool,
+    ) -> Vec<WindowData> {
+        self.handle_windows_query(space_id, include_minimized)
     }
 
     pub fn query_active_workspace(&self, space_id: Option<SpaceId>) -> Option<VirtualWorkspaceId> {
@@ -170,21 +352,89 @@ impl Reactor {
         &mut self,
         space_id: Option<SpaceId>,
         workspace_id: Option<usize>,
-    ) -> Vec<WorkspaceLayoutData> {
+    ) -> Option<Vec<WorkspaceLayoutData>> {
         self.handle_workspace_layouts_query(space_id, workspace_id)
     }
 
-    pub fn query_window_info(&self, window_id: WindowId) -> Option<WindowData> {
+    pub fn query_layout_mode(&self, space_id: Option<SpaceId>) -> Option<LayoutModeData> {
+        self.handle_layout_mode_query(space_id)
+    }
+
+    pub fn query_pending_split(&self, space_id: Option<SpaceId>) -> Option<PendingSplitData> {
+        self.handle_pending_split_query(space_id)
+    }
+
+    pub fn set_layout_mode(
+        &mut self,
+        space_id: Option<SpaceId>,
+        mode: LayoutMode,
+    ) -> Option<LayoutModeData> {
+        self.handle_set_layout_mode(space_id, mode)
+    }
+
+    pub fn retile_space(&mut self, space_id: Option<SpaceId>) -> Option<RetileSpaceData> {
+        let space = space_id.or_else(|| self.workspace_command_space());
+        let windows = CommandEventHandler::handle_command_reactor_retile_space(self, space)?;
+        Some(RetileSpaceData {
+            space_id: space?.get(),
+            windows,
+        })
+    }
+
+    pub fn deserialize_state(&mut self, state: &str) -> Result<DeserializeStateData, String> {
+        CommandEventHandler::handle_command_reactor_deserialize(self, state)
+    }
+
+    pub fn reapply_app_rules(&mut self) -> ReapplyAppRulesData {
+        ReapplyAppRulesData {
+            changed_windows: CommandEventHandler::handle_command_reactor_reapply_app_rules(self),
+        }
+    }
+
+    pub fn query_menu_state(&self) -> MenuStateData {
+        let owner_pid = match self.menu_manager.menu_state {
+            MenuState::Open(owner) => Some(owner),
+            MenuState::Closed => None,
+        };
+        MenuStateData {
+            open: owner_pid.is_some(),
+            owner_pid,
+        }
+    }
+
+    pub fn reset_menu_state(&mut self) -> MenuStateData {
+        let previous = self.query_menu_state();
+        SystemEventHandler::handle_menu_state_reset(self);
+        previous
+    }
+
+    pub fn query_event_log(&self, limit: Option<usize>) -> Option<Vec<EventLogEntryData>> {
+        let log = &self.recording_manager.event_log;
+        log.is_enabled().then(|| log.recent(limit))
+    }
+
+    pub fn query_window_info(&self, window_id: WindowId) -> Option<WindowInfoData> {
         self.handle_window_info_query(window_id)
     }
 
+    pub fn query_window_transaction(
+        &self,
+        window_server_id: WindowServerId,
+    ) -> Option<WindowTransactionData> {
+        self.handle_window_transaction_query(window_server_id)
+    }
+
     pub fn query_applications(&self) -> Vec<ApplicationData> { self.handle_applications_query() }
 
     pub fn query_layout_state(&self, space_id: u64) -> Option<LayoutStateData> {
         self.handle_layout_state_query(space_id)
     }
 
-    pub fn query_metrics(&self) -> serde_json::Value { self.handle_metrics_query() }
+    pub fn query_metrics(&self) -> serde_json::Value {
+        serde_json::to_value(self.handle_metrics_query()).unwrap_or_default()
+    }
+
+    pub fn query_metrics_prometheus(&self) -> String { self.handle_metrics_query().to_prometheus() }
 
     pub(super) fn maybe_send_menu_update(&mut self) {
         let menu_tx = match self.menu_manager.menu_tx.as_ref() {
@@ -197,12 +447,12 @@ impl Reactor {
             None => return,
         };
 
-        let workspaces = self.handle_workspace_query(Some(active_space));
+        let workspaces = self.handle_workspace_query(Some(active_space), false);
         let active_space_is_activated = self.is_space_active(active_space);
         let active_workspace = self.layout_manager.layout_engine.active_workspace(active_space);
         let active_workspace_idx =
             self.layout_manager.layout_engine.active_workspace_idx(active_space);
-        let windows = self.handle_windows_query(Some(active_space));
+        let windows = self.handle_windows_query(Some(active_space), false);
 
         menu_tx.send(menu_bar::Event::Update(menu_bar::Update {
             active_space,
@@ -214,7 +464,11 @@ impl Reactor {
         }));
     }
 
-    fn handle_workspace_query(&mut self, space_id_param: Option<SpaceId>) -> Vec<WorkspaceData> {
+    fn handle_workspace_query(
+        &mut self,
+        space_id_param: Option<SpaceId>,
+        include_minimized: bool,
+    ) -> Vec<WorkspaceData> {
         let mut workspaces = Vec::new();
 
         let space_id = space_id_param.or_else(|| self.default_query_space());
@@ -298,6 +552,15 @@ impl Reactor {
                     windows.push(wd);
                 }
             }
+            if include_minimized {
+                if let Some(space) = space_id {
+                    windows.extend(
+                        self.minimized_windows_in(space, Some(*workspace_id))
+                            .into_iter()
+                            .filter_map(|wid| self.window_data_unfiltered(wid)),
+                    );
+                }
+            }
 
             let layout_mode = space_id
                 .and_then(|space| {
@@ -313,6 +576,7 @@ impl Reactor {
                 id: format!("{:?}", workspace_id),
                 name: workspace_name.to_string(),
                 layout_mode,
+                is_frozen: self.layout_manager.layout_engine.is_workspace_frozen(*workspace_id),
                 is_active,
                 window_count: windows.len(),
                 windows,
@@ -327,10 +591,14 @@ impl Reactor {
         &mut self,
         space_id_param: Option<SpaceId>,
         workspace_id: Option<usize>,
-    ) -> Vec<WorkspaceLayoutData> {
+    ) -> Option<Vec<WorkspaceLayoutData>> {
         let Some(space) = space_id_param.or_else(|| self.default_query_space()) else {
-            return Vec::new();
+            return Some(Vec::new());
         };
+        let stale = !self.is_space_live(space);
+        if stale && !self.has_retained_layout(space) {
+            return None;
+        }
 
         let workspace_list = self
             .layout_manager
@@ -339,7 +607,7 @@ impl Reactor {
             .list_workspaces(space);
         let active_workspace = self.layout_manager.layout_engine.active_workspace(space);
 
-        workspace_list
+        let layouts = workspace_list
             .iter()
             .enumerate()
             .filter(|(index, _)| workspace_id.map(|target| target == *index).unwrap_or(true))
@@ -357,9 +625,25 @@ impl Reactor {
                     name: name.clone(),
                     layout_mode,
                     is_active: active_workspace == Some(*id),
+                    stale,
                 })
             })
-            .collect()
+            .collect();
+        Some(layouts)
+    }
+
+    /// Whether queries about `space` reflect what is on screen right now.
+    fn is_space_live(&self, space: SpaceId) -> bool {
+        self.is_space_active(space) && self.space_manager.iter_known_spaces().any(|s| s == space)
+    }
+
+    /// Whether the layout engine still holds workspaces for `space` from when
+    /// it was last managed.
+    fn has_retained_layout(&self, space: SpaceId) -> bool {
+        self.layout_manager
+            .layout_engine
+            .virtual_workspace_manager()
+            .is_space_initialized(space)
     }
 
     fn handle_active_workspace_query(
@@ -413,30 +697,172 @@ impl Reactor {
             .collect()
     }
 
-    fn handle_windows_query(&self, space_id: Option<SpaceId>) -> Vec<WindowData> {
+    fn handle_windows_query(
+        &self,
+        space_id: Option<SpaceId>,
+        include_minimized: bool,
+    ) -> Vec<WindowData> {
         let target_space = space_id
             .or_else(|| self.default_query_space())
             .or_else(|| self.space_manager.first_known_space());
 
         if let Some(space) = target_space {
-            let active_windows =
-                self.layout_manager.layout_engine.windows_in_active_workspace(space);
-
-            active_windows
+            let mut windows: Vec<WindowData> = self
+                .layout_manager
+                .layout_engine
+                .windows_in_active_workspace(space)
                 .into_iter()
                 .filter_map(|wid| self.create_window_data(wid))
-                .collect()
+                .collect();
+            if include_minimized {
+                windows.extend(
+                    self.minimized_windows_in(space, None)
+                        .into_iter()
+                        .filter_map(|wid| self.window_data_unfiltered(wid)),
+                );
+            }
+            windows
         } else {
             self.window_manager
                 .windows
-                .keys()
-                .filter_map(|&wid| self.create_window_data(wid))
+                .iter()
+                .filter(|(_, window)| include_minimized || !window.info.is_minimized)
+                .filter_map(|(&wid, window)| {
+                    if window.info.is_minimized {
+                        self.window_data_unfiltered(wid)
+                    } else {
+                        self.create_window_data(wid)
+                    }
+                })
                 .collect()
         }
     }
 
-    fn handle_window_info_query(&self, window_id: WindowId) -> Option<WindowData> {
-        self.create_window_data(window_id)
+    /// Minimized windows that were in `workspace` (or any workspace, if `None`)
+    /// on `space` when they were minimized, in a stable order.
+    fn minimized_windows_in(
+        &self,
+        space: SpaceId,
+        workspace: Option<VirtualWorkspaceId>,
+    ) -> Vec<WindowId> {
+        let mut wids: Vec<WindowId> = self
+            .window_manager
+            .minimized_window_workspaces
+            .iter()
+            .filter(|(_, (ws_space, ws_id))| {
+                *ws_space == space && workspace.is_none_or(|workspace| workspace == *ws_id)
+            })
+            .map(|(&wid, _)| wid)
+            .filter(|wid| self.window_manager.windows.get(wid).is_some_and(|w| w.info.is_minimized))
+            .collect();
+        wids.sort();
+        wids
+    }
+
+    fn handle_layout_mode_query(&self, space_id_param: Option<SpaceId>) -> Option<LayoutModeData> {
+        let space = space_id_param.or_else(|| self.default_query_space())?;
+        let workspace_id = self.layout_manager.layout_engine.active_workspace(space)?;
+
+        Some(LayoutModeData {
+            space_id: space.get(),
+            workspace_id: format!("{:?}", workspace_id),
+            mode: self.layout_manager.layout_engine.active_layout_mode_at(space),
+        })
+    }
+
+    fn handle_pending_split_query(
+        &self,
+        space_id_param: Option<SpaceId>,
+    ) -> Option<PendingSplitData> {
+        let space = space_id_param.or_else(|| self.default_query_space())?;
+        let workspace_id = self.layout_manager.layout_engine.active_workspace(space)?;
+
+        let pending = self.layout_manager.layout_engine.pending_split(space);
+        Some(PendingSplitData {
+            space_id: space.get(),
+            workspace_id: format!("{:?}", workspace_id),
+            window_id: pending.map(|(wid, _)| wid),
+            orientation: pending.map(|(_, orientation)| orientation),
+        })
+    }
+
+    fn handle_set_layout_mode(
+        &mut self,
+        space_id_param: Option<SpaceId>,
+        mode: LayoutMode,
+    ) -> Option<LayoutModeData> {
+        let space = space_id_param.or_else(|| self.default_query_space())?;
+        self.layout_manager.layout_engine.active_workspace(space)?;
+
+        let response = self.layout_manager.layout_engine.handle_virtual_workspace_command(
+            space,
+            &LayoutCommand::SetWorkspaceLayout { workspace: None, mode },
+        );
+        self.handle_layout_response(response, Some(space));
+        self.update_layout_or_warn(false, false);
+        self.update_event_tap_layout_mode();
+
+        self.handle_layout_mode_query(Some(space))
+    }
+
+    fn handle_window_info_query(&self, window_id: WindowId) -> Option<WindowInfoData> {
+        let window = self.window_data_unfiltered(window_id)?;
+        let window_state = self.window_manager.windows.get(&window_id)?;
+        let layout_engine = &self.layout_manager.layout_engine;
+
+        let space = self.best_space_for_window_state(window_state);
+        let workspace_id = match space {
+            Some(space) => {
+                layout_engine.virtual_workspace_manager().workspace_for_window(space, window_id)
+            }
+            None => layout_engine.virtual_workspace_manager().workspace_for_window_any(window_id),
+        };
+        let index_in_workspace = space.zip(workspace_id).and_then(|(space, workspace_id)| {
+            layout_engine.window_index_in_workspace(space, workspace_id, window_id)
+        });
+        let auto_float_subrole = if window_state.info.is_standard {
+            None
+        } else {
+            layout_engine
+                .virtual_workspace_manager()
+                .auto_float_subrole(
+                    window_state.info.ax_role.as_deref(),
+                    window_state.info.ax_subrole.as_deref(),
+                )
+                .map(str::to_owned)
+        };
+
+        Some(WindowInfoData {
+            window,
+            is_manageable: window_state.matches_filter(WindowFilter::EffectivelyManageable),
+            ax_role: window_state.info.ax_role.clone(),
+            ax_subrole: window_state.info.ax_subrole.clone(),
+            auto_float_subrole,
+            workspace_id: workspace_id.map(|id| format!("{:?}", id)),
+            index_in_workspace,
+            constraints: layout_engine.window_layout_constraints(window_id),
+            marks: self.window_manager.marks_for(window_id),
+            is_sticky: layout_engine.is_window_sticky(window_id),
+        })
+    }
+
+    fn handle_window_transaction_query(
+        &self,
+        window_server_id: WindowServerId,
+    ) -> Option<WindowTransactionData> {
+        let record = self.transaction_manager.store.get(&window_server_id)?;
+        let window_id = self.window_manager.window_ids.get(&window_server_id).copied();
+        let last_seen_txid = window_id
+            .and_then(|wid| self.window_manager.windows.get(&wid))
+            .and_then(|window| window.last_seen_txid);
+
+        Some(WindowTransactionData {
+            window_server_id: window_server_id.as_u32(),
+            window_id,
+            last_sent_txid: record.txid,
+            last_seen_txid,
+            pending_target: record.target,
+        })
     }
 
     fn handle_applications_query(&self) -> Vec<ApplicationData> {
@@ -469,7 +895,8 @@ impl Reactor {
             return None;
         }
         let space_id = SpaceId::new(space_id_u64);
-        if !self.space_manager.iter_known_spaces().any(|space| space == space_id) {
+        let stale = !self.is_space_live(space_id);
+        if stale && !self.has_retained_layout(space_id) {
             return None;
         }
 
@@ -489,7 +916,7 @@ impl Reactor {
             .copied()
             .collect();
 
-        let focused_window = self.main_window();
+        let focused_window = if stale { None } else { self.main_window() };
 
         Some(LayoutStateData {
             space_id: space_id_u64,
@@ -497,25 +924,36 @@ impl Reactor {
             floating_windows,
             tiled_windows,
             focused_window,
+            stale,
         })
     }
 
-    fn handle_metrics_query(&self) -> serde_json::Value {
+    fn handle_metrics_query(&self) -> MetricsData {
         let stats = self.layout_manager.layout_engine.virtual_workspace_manager().get_stats();
-
-        let workspace_stats: crate::common::collections::HashMap<String, usize> = stats
-            .workspace_window_counts
-            .iter()
-            .map(|(id, count)| (format!("{:?}", id), *count))
-            .collect();
-
-        serde_json::json!({
-               "windows_managed": self.window_manager.windows.len(),
-            "workspaces": stats.total_workspaces,
-            "applications": self.app_manager.apps.len(),
-            "screens": self.space_manager.screens.len(),
-            "workspace_stats": workspace_stats,
-        })
+        let metrics = &self.recording_manager.metrics;
+
+        MetricsData {
+            windows_managed: self.window_manager.windows.len(),
+            workspaces: stats.total_workspaces,
+            applications: self.app_manager.apps.len(),
+            screens: self.space_manager.screens.len(),
+            workspace_stats: stats
+                .workspace_window_counts
+                .iter()
+                .map(|(id, count)| (format!("{:?}", id), *count))
+                .collect(),
+            render_failures: crate::ui::common::render_failure_stats(),
+            events: metrics
+                .events()
+                .iter()
+                .map(|(kind, timing)| (kind.to_string(), *timing))
+                .collect(),
+            layout_commands: metrics
+                .layout_commands()
+                .iter()
+                .map(|(kind, count)| (kind.to_string(), *count))
+                .collect(),
+        }
     }
 
     pub(crate) fn serialize_state(&mut self) -> Result<String, serde_json::Error> {
@@ -8,7 +8,7 @@ use crate::common::collections::BTreeMap;
 use crate::common::config::Config;
 use crate::layout_engine::LayoutEngine;
 use crate::sys::app::{AppInfo, WindowInfo, pid_t};
-use crate::sys::geometry::SameAs;
+use crate::sys::geometry::{IsWithin, SameAs};
 use crate::sys::screen::SpaceId;
 use crate::sys::window_server::{WindowServerId, WindowServerInfo};
 
@@ -111,6 +111,9 @@ pub struct TestWindowState {
     pub last_sent_txid: TransactionId,
     pub animating: bool,
     pub frame: CGRect,
+    /// Simulates a window that ignores moves: frame requests keep the current
+    /// origin but are still confirmed.
+    pub position_locked: bool,
 }
 
 impl Apps {
@@ -162,6 +165,7 @@ impl Apps {
                         frame: info.frame,
                         min_frame: CGSize::ZERO,
                         max_frame: CGSize::ZERO,
+                        sticky: false,
                     })
                     .collect()
             } else {
@@ -224,10 +228,15 @@ impl Apps {
                     window.last_seen_txid = txid;
                     let old_frame = window.frame;
                     window.frame = frame;
-                    if !window.animating && !old_frame.same_as(frame) {
+                    if window.position_locked {
+                        window.frame.origin = old_frame.origin;
+                    }
+                    if !window.animating
+                        && (window.position_locked || !old_frame.same_as(window.frame))
+                    {
                         events.push(Event::WindowFrameChanged(
                             wid,
-                            frame,
+                            window.frame,
                             Some(txid),
                             Requested(true),
                             None,
@@ -240,10 +249,15 @@ impl Apps {
                         window.last_seen_txid = txid;
                         let old_frame = window.frame;
                         window.frame = frame;
-                        if !window.animating && !old_frame.same_as(frame) {
+                        if window.position_locked {
+                            window.frame.origin = old_frame.origin;
+                        }
+                        if !window.animating
+                            && (window.position_locked || !old_frame.same_as(window.frame))
+                        {
                             events.push(Event::WindowFrameChanged(
                                 wid,
-                                frame,
+                                window.frame,
                                 Some(txid),
                                 Requested(true),
                                 None,
@@ -255,8 +269,12 @@ impl Apps {
                     let window = self.windows.entry(wid).or_default();
                     window.last_seen_txid = txid;
                     let old_frame = window.frame;
-                    window.frame.origin = pos;
-                    if !window.animating && !old_frame.same_as(window.frame) {
+                    if !window.position_locked {
+                        window.frame.origin = pos;
+                    }
+                    if !window.animating
+                        && (window.position_locked || !old_frame.same_as(window.frame))
+                    {
                         events.push(Event::WindowFrameChanged(
                             wid,
                             window.frame,
@@ -266,6 +284,23 @@ impl Apps {
                         ));
                     }
                 }
+                Request::PollWindowFrames(expected, threshold) => {
+                    for (wid, expected) in expected {
+                        let Some(window) = self.windows.get(&wid) else {
+                            continue;
+                        };
+                        if window.animating || window.frame.is_within(threshold, expected) {
+                            continue;
+                        }
+                        events.push(Event::WindowFrameChanged(
+                            wid,
+                            window.frame,
+                            Some(window.last_seen_txid),
+                            Requested(false),
+                            None,
+                        ));
+                    }
+                }
                 Request::BeginWindowAnimation(wid) => {
                     self.windows.entry(wid).or_default().animating = true;
                 }
@@ -280,6 +315,9 @@ impl Apps {
                         None,
                     ));
                 }
+                // Hiding is reported by the app's own notifications, which
+                // tests send explicitly.
+                Request::SetHidden(_) => {}
                 Request::Raise(..) => todo!(),
                 Request::CloseWindow(..) => todo!(),
             }
@@ -6,7 +6,9 @@ use super::testing::*;
 use super::*;
 use crate::actor::app::Request;
 use crate::layout_engine::{Direction, LayoutCommand, LayoutEngine};
+use crate::model::server::WorkspaceData;
 use crate::sys::app::WindowInfo;
+use crate::sys::geometry::SameAs;
 use crate::sys::window_server::WindowServerId;
 
 #[test]
@@ -105,6 +107,80 @@ fn it_manages_windows_on_enabled_spaces() {
     );
 }
 
+#[test]
+fn manageability_changes_are_broadcast_once_settled() {
+    use crate::actor::broadcast::{BroadcastEvent, ManageabilityReason};
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (broadcast_tx, mut broadcast_rx) = actor::channel();
+    reactor.communication_manager.event_broadcaster = broadcast_tx;
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![full_screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+    let wid = WindowId::new(1, 1);
+    let settled = || Instant::now() + Duration::from_secs(1);
+    while broadcast_rx.try_recv().is_ok() {}
+
+    // A new window's first manageability is not a change.
+    reactor.broadcast_settled_manageability_changes(settled());
+    assert!(broadcast_rx.try_recv().is_err());
+
+    // A flip that reverts before settling is never reported.
+    reactor.handle_event(Event::WindowMinimized(wid));
+    reactor.handle_event(Event::WindowDeminiaturized(wid));
+    reactor.broadcast_settled_manageability_changes(settled());
+    assert!(broadcast_rx.try_recv().is_err());
+
+    reactor.handle_event(Event::WindowMinimized(wid));
+    reactor.broadcast_settled_manageability_changes(Instant::now());
+    assert!(broadcast_rx.try_recv().is_err());
+    reactor.broadcast_settled_manageability_changes(settled());
+    match broadcast_rx.try_recv() {
+        Ok((
+            _,
+            BroadcastEvent::WindowManageabilityChanged {
+                window_id,
+                is_manageable,
+                reason,
+            },
+        )) => {
+            assert_eq!(window_id, wid);
+            assert!(!is_manageable);
+            assert_eq!(reason, ManageabilityReason::Minimized);
+        }
+        other => panic!("expected a window_manageability_changed event, got {other:?}"),
+    }
+    reactor.broadcast_settled_manageability_changes(settled());
+    assert!(broadcast_rx.try_recv().is_err());
+}
+
+#[test]
+fn mission_control_state_is_visible_to_query_handles() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let shared = reactor.mission_control_manager.shared_state.clone();
+    assert_eq!(shared.get(), MissionControlState::Inactive);
+
+    reactor.handle_event(Event::MissionControlNativeEntered);
+    assert_eq!(shared.get(), MissionControlState::Active);
+
+    reactor.set_mission_control_active(false);
+    assert_eq!(shared.get(), MissionControlState::Inactive);
+}
+
 #[test]
 fn it_clears_screen_state_when_no_displays_are_reported() {
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
@@ -187,6 +263,89 @@ fn it_ignores_windows_on_disabled_spaces() {
     reactor.handle_event(Event::WindowDestroyed(WindowId::new(1, 2)));
 }
 
+#[test]
+fn create_then_immediate_destroy_leaves_no_state() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![full_screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let mut popup = make_window(2);
+    popup.is_standard = false;
+    let tiled = make_window(3);
+    for (idx, window) in [(2, popup), (3, tiled)] {
+        let wid = WindowId::new(1, idx);
+        let wsid = WindowServerId::new(idx);
+        reactor.handle_event(Event::WindowCreated(wid, window, None, Some(MouseState::Up)));
+        reactor.app_manager.mark_wsids_recent(std::iter::once(wsid));
+        reactor.handle_event(Event::WindowDestroyed(wid));
+
+        assert!(!reactor.window_manager.windows.contains_key(&wid));
+        assert!(!reactor.window_manager.window_ids.contains_key(&wsid));
+        assert!(!reactor.window_manager.observed_window_server_ids.contains(&wsid));
+        assert!(!reactor.window_manager.pending_manageability.contains_key(&wid));
+        assert!(!reactor.window_manager.last_focused_at.contains_key(&wid));
+        assert!(!reactor.app_manager.app_rules_recent_targets.contains_key(&wsid));
+        assert_eq!(reactor.transaction_manager.get_target_frame(wsid), None);
+        assert!(!reactor.layout_manager.layout_engine.tracks_window(wid));
+    }
+
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(apps.windows[&WindowId::new(1, 1)].frame, full_screen);
+}
+
+#[test]
+fn app_lifecycle_is_broadcast() {
+    use crate::actor::broadcast::BroadcastEvent;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (broadcast_tx, mut broadcast_rx) = actor::channel();
+    reactor.communication_manager.event_broadcaster = broadcast_tx;
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![full_screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+    let launched = std::iter::from_fn(|| broadcast_rx.try_recv().ok())
+        .filter(|(_, event)| matches!(event, BroadcastEvent::AppLaunched { .. }))
+        .count();
+    assert_eq!(launched, 1);
+
+    reactor.handle_event(Event::ApplicationThreadTerminated(1));
+    let terminated: Vec<_> = std::iter::from_fn(|| broadcast_rx.try_recv().ok())
+        .filter_map(|(_, event)| match event {
+            BroadcastEvent::AppTerminated { pid, .. } => Some(pid),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(terminated, vec![1]);
+
+    // A second termination for an app that is already gone is not reported again.
+    reactor.handle_event(Event::ApplicationThreadTerminated(1));
+    assert!(
+        std::iter::from_fn(|| broadcast_rx.try_recv().ok())
+            .all(|(_, event)| !matches!(event, BroadcastEvent::AppTerminated { .. }))
+    );
+}
+
 #[test]
 fn it_keeps_discovered_windows_on_their_initial_screen() {
     let mut apps = Apps::new();
@@ -237,6 +396,7 @@ fn it_ignores_windows_on_nonzero_layers() {
             frame: CGRect::ZERO,
             min_frame: CGSize::ZERO,
             max_frame: CGSize::ZERO,
+            sticky: false,
         }],
     ));
 
@@ -258,102 +418,269 @@ fn it_ignores_windows_on_nonzero_layers() {
 }
 
 #[test]
-fn handle_layout_response_groups_windows_by_app_and_screen() {
+fn it_ignores_windows_present_on_all_spaces() {
     let mut apps = Apps::new();
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
         &crate::common::config::VirtualWorkspaceSettings::default(),
         &crate::common::config::LayoutSettings::default(),
         None,
     ));
-    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
-    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let sticky = WindowId::new(1, 2);
+    let window = make_window(2);
+    let sticky_info = WindowServerInfo {
+        id: window.sys_id.unwrap(),
+        pid: 1,
+        layer: 0,
+        frame: window.frame,
+        min_frame: CGSize::ZERO,
+        max_frame: CGSize::ZERO,
+        sticky: true,
+    };
+    apps.windows.insert(sticky, TestWindowState {
+        frame: window.frame,
+        ..Default::default()
+    });
+    reactor.handle_event(Event::WindowCreated(
+        sticky,
+        window,
+        Some(sticky_info),
+        Some(MouseState::Up),
+    ));
+    let state_before = apps.windows.clone();
+    apps.simulate_until_quiet(&mut reactor);
+
+    assert!(!reactor.window_manager.windows[&sticky].is_manageable);
+    let tiled = reactor.layout_manager.layout_engine.windows_in_active_workspace(space);
+    assert!(!tiled.contains(&sticky));
+    assert_eq!(state_before, apps.windows, "Sticky window was moved");
+}
+
+#[test]
+fn frame_poll_reconciles_drift_missed_by_notifications() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.frame_poll.apps = vec!["com.testapp1".to_string()];
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    // The app moved one window without telling us, and nudged the other by
+    // less than the threshold.
+    let drifted = WindowId::new(1, 1);
+    let nudged = WindowId::new(1, 2);
+    apps.windows.get_mut(&drifted).unwrap().frame.origin.y += 50.;
+    apps.windows.get_mut(&nudged).unwrap().frame.origin.x += 1.;
+
+    reactor.handle_loop_event(Event::PollWindowFrames);
+    let requests = apps.requests();
+    assert!(matches!(requests.as_slice(), [Request::PollWindowFrames(..)]));
+    let events = apps.simulate_events_for_requests(requests);
+    assert!(matches!(
+        events.as_slice(),
+        [Event::WindowFrameChanged(wid, _, _, Requested(false), _)] if *wid == drifted
+    ));
+    reactor.handle_events(events);
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(
+        reactor.window_manager.windows[&drifted]
+            .frame_monotonic
+            .same_as(apps.windows[&drifted].frame)
+    );
+
+    // Polls are throttled to the configured interval.
+    reactor.handle_loop_event(Event::PollWindowFrames);
+    assert!(apps.requests().is_empty());
+}
+
+#[test]
+fn switching_to_grouped_workspace_switches_linked_displays() {
+    use crate::common::config::{
+        VirtualWorkspaceSettings, WorkspaceGroup, WorkspaceGroupMember, WorkspaceSelector,
+    };
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.workspace_groups = vec![WorkspaceGroup {
+        members: vec![
+            WorkspaceGroupMember {
+                display: "test-display-0".to_string(),
+                workspace: WorkspaceSelector::Index(1),
+            },
+            WorkspaceGroupMember {
+                display: "test-display-1".to_string(),
+                workspace: WorkspaceSelector::Index(1),
+            },
+        ],
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space1 = SpaceId::new(1);
+    let space2 = SpaceId::new(2);
     let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
     let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
     reactor.handle_event(screen_params_event(
         vec![screen1, screen2],
-        vec![Some(SpaceId::new(1)), Some(SpaceId::new(2))],
+        vec![Some(space1), Some(space2)],
         vec![],
     ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
 
-    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    let active_index = |reactor: &mut Reactor, space: SpaceId| {
+        let engine = &mut reactor.layout_manager.layout_engine;
+        let active = engine.active_workspace(space);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        workspaces.iter().position(|(id, _)| Some(*id) == active)
+    };
+    assert_eq!(active_index(&mut reactor, space1), Some(0));
+    assert_eq!(active_index(&mut reactor, space2), Some(0));
 
-    let mut windows = make_windows(2);
-    windows[1].frame.origin = CGPoint::new(1100., 100.);
-    reactor.handle_events(apps.make_app(2, windows));
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::SwitchToWorkspace(1),
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(active_index(&mut reactor, space1), Some(1));
+    assert_eq!(active_index(&mut reactor, space2), Some(1));
 
-    let _events = apps.simulate_events();
-    while raise_manager_rx.try_recv().is_ok() {}
+    // Workspaces outside the group still switch independently.
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::SwitchToWorkspace(2),
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+    let switched = [space1, space2]
+        .into_iter()
+        .filter(|space| active_index(&mut reactor, *space) == Some(2))
+        .count();
+    assert_eq!(switched, 1);
+}
 
-    reactor.handle_layout_response(
-        layout::EventResponse {
-            raise_windows: vec![
-                WindowId::new(1, 1),
-                WindowId::new(1, 2),
-                WindowId::new(2, 1),
-                WindowId::new(2, 2),
-            ],
-            focus_window: None,
-            boundary_hit: None,
-        },
-        None,
-    );
-    let msg = raise_manager_rx.try_recv().expect("Should have sent an event").1;
-    match msg {
-        raise_manager::Event::RaiseRequest(RaiseRequest {
-            raise_windows, focus_window, ..
-        }) => {
-            let raise_windows: HashSet<Vec<WindowId>> = raise_windows.into_iter().collect();
-            let expected = [
-                vec![WindowId::new(1, 1), WindowId::new(1, 2)],
-                vec![WindowId::new(2, 1)],
-                vec![WindowId::new(2, 2)],
-            ]
-            .into_iter()
-            .collect();
-            assert_eq!(raise_windows, expected);
-            assert!(focus_window.is_none());
-        }
-        _ => panic!("Unexpected event: {msg:?}"),
-    }
+#[test]
+fn workspace_switch_target_picks_cursor_or_focused_display() {
+    use crate::common::config::WorkspaceSwitchTarget;
+    use crate::sys::window_server::set_test_cursor_location;
+
+    let space1 = SpaceId::new(1);
+    let space2 = SpaceId::new(2);
+    // Returns the active workspace index on each display after switching with
+    // the cursor on the second display and the focused window on the first.
+    let switch_with = |target: WorkspaceSwitchTarget| {
+        let mut apps = Apps::new();
+        let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+            &crate::common::config::VirtualWorkspaceSettings::default(),
+            &crate::common::config::LayoutSettings::default(),
+            None,
+        ));
+        reactor.config.settings.workspace_switch_target = target;
+        let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+        let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+        reactor.handle_event(screen_params_event(
+            vec![screen1, screen2],
+            vec![Some(space1), Some(space2)],
+            vec![],
+        ));
+        reactor.handle_events(apps.make_app_with_opts(
+            1,
+            make_windows(1),
+            Some(WindowId::new(1, 1)),
+            true,
+            true,
+        ));
+        reactor.handle_event(Event::ApplicationGloballyActivated(1));
+        apps.simulate_until_quiet(&mut reactor);
+
+        set_test_cursor_location(Some(CGPoint::new(1500., 500.)));
+        reactor.handle_event(Event::Command(Command::Layout(
+            LayoutCommand::SwitchToWorkspace(1),
+        )));
+        apps.simulate_until_quiet(&mut reactor);
+        set_test_cursor_location(None);
+
+        [space1, space2].map(|space| {
+            let engine = &mut reactor.layout_manager.layout_engine;
+            let active = engine.active_workspace(space);
+            let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+            workspaces.iter().position(|(id, _)| Some(*id) == active)
+        })
+    };
+
+    assert_eq!(switch_with(WorkspaceSwitchTarget::Auto), [Some(0), Some(1)]);
+    assert_eq!(switch_with(WorkspaceSwitchTarget::Focused), [Some(1), Some(0)]);
 }
 
 #[test]
-fn handle_layout_response_includes_handles_for_raise_and_focus_windows() {
+fn switch_to_last_workspace_keeps_separate_history_per_display() {
+    use crate::sys::window_server::set_test_cursor_location;
+
     let mut apps = Apps::new();
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
         &crate::common::config::VirtualWorkspaceSettings::default(),
         &crate::common::config::LayoutSettings::default(),
         None,
     ));
-    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
-    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
-
+    let space1 = SpaceId::new(1);
+    let space2 = SpaceId::new(2);
+    let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![screen1, screen2],
+        vec![Some(space1), Some(space2)],
+        vec![],
+    ));
     reactor.handle_events(apps.make_app(1, make_windows(1)));
-    reactor.handle_events(apps.make_app(2, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
 
-    let _events = apps.simulate_events();
-    while raise_manager_rx.try_recv().is_ok() {}
-    reactor.handle_layout_response(
-        layout::EventResponse {
-            raise_windows: vec![WindowId::new(1, 1)],
-            focus_window: Some(WindowId::new(2, 1)),
-            boundary_hit: None,
-        },
-        None,
-    );
-    let msg = raise_manager_rx.try_recv().expect("Should have sent an event").1;
-    match msg {
-        raise_manager::Event::RaiseRequest(RaiseRequest { app_handles, .. }) => {
-            assert!(app_handles.contains_key(&1));
-            assert!(app_handles.contains_key(&2));
-        }
-        _ => panic!("Unexpected event: {msg:?}"),
-    }
+    let active_index = |reactor: &mut Reactor, space: SpaceId| {
+        let engine = &mut reactor.layout_manager.layout_engine;
+        let active = engine.active_workspace(space);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        workspaces.iter().position(|(id, _)| Some(*id) == active)
+    };
+    let mut run_on = |reactor: &mut Reactor, cursor: CGPoint, cmd: LayoutCommand| {
+        set_test_cursor_location(Some(cursor));
+        reactor.handle_event(Event::Command(Command::Layout(cmd)));
+        apps.simulate_until_quiet(reactor);
+        set_test_cursor_location(None);
+    };
+    let on_first = CGPoint::new(500., 500.);
+    let on_second = CGPoint::new(1500., 500.);
+
+    run_on(&mut reactor, on_first, LayoutCommand::SwitchToWorkspace(1));
+    run_on(&mut reactor, on_first, LayoutCommand::SwitchToWorkspace(2));
+    run_on(&mut reactor, on_second, LayoutCommand::SwitchToWorkspace(3));
+    assert_eq!(active_index(&mut reactor, space1), Some(2));
+    assert_eq!(active_index(&mut reactor, space2), Some(3));
+
+    run_on(&mut reactor, on_first, LayoutCommand::SwitchToLastWorkspace);
+    assert_eq!(active_index(&mut reactor, space1), Some(1));
+    assert_eq!(active_index(&mut reactor, space2), Some(3));
+
+    run_on(&mut reactor, on_second, LayoutCommand::SwitchToLastWorkspace);
+    assert_eq!(active_index(&mut reactor, space1), Some(1));
+    assert_eq!(active_index(&mut reactor, space2), Some(0));
+
+    run_on(&mut reactor, on_first, LayoutCommand::SwitchToLastWorkspace);
+    run_on(&mut reactor, on_second, LayoutCommand::SwitchToLastWorkspace);
+    assert_eq!(active_index(&mut reactor, space1), Some(2));
+    assert_eq!(active_index(&mut reactor, space2), Some(3));
 }
 
 #[test]
-fn it_preserves_layout_after_login_screen() {
-    // TODO: This would be better tested with a more complete simulation.
+fn focus_app_workspace_cycles_through_workspaces_holding_the_app() {
     let mut apps = Apps::new();
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
         &crate::common::config::VirtualWorkspaceSettings::default(),
@@ -361,151 +688,2846 @@ fn it_preserves_layout_after_login_screen() {
         None,
     ));
     let space = SpaceId::new(1);
-    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
-    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
-
-    reactor.handle_events(apps.make_app_with_opts(
-        1,
-        make_windows(3),
-        Some(WindowId::new(1, 1)),
-        true,
-        true,
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
     ));
-    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
     apps.simulate_until_quiet(&mut reactor);
-    let default = reactor.layout_manager.layout_engine.calculate_layout(
-        space,
-        full_screen,
-        &reactor.config.settings.layout.gaps,
-        0.0,
-        crate::common::config::HorizontalPlacement::Top,
-        crate::common::config::VerticalPlacement::Right,
-    );
 
-    assert!(reactor.layout_manager.layout_engine.selected_window(space).is_some());
-    reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::MoveNode(
-        Direction::Up,
-    ))));
+    let first = WindowId::new(1, 1);
+    let second = WindowId::new(1, 2);
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::MoveWindowToWorkspace {
+            workspace: 1,
+            window_id: Some(2),
+            bundle_id: None,
+            title: None,
+            target_index: None,
+        },
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+    reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::FocusWindow {
+        window_id: first,
+        window_server_id: None,
+    })));
     apps.simulate_until_quiet(&mut reactor);
-    let modified = reactor.layout_manager.layout_engine.calculate_layout(
-        space,
-        full_screen,
-        &reactor.config.settings.layout.gaps,
-        0.0,
-        crate::common::config::HorizontalPlacement::Top,
-        crate::common::config::VerticalPlacement::Right,
-    );
-    assert_ne!(default, modified);
 
-    reactor.handle_event(screen_params_event(vec![CGRect::ZERO], vec![None], vec![]));
-    reactor.handle_event(screen_params_event(
-        vec![full_screen],
-        vec![Some(space)],
-        (1..=3)
-            .map(|n| WindowServerInfo {
-                pid: 1,
-                id: WindowServerId::new(n),
-                layer: 0,
-                frame: CGRect::ZERO,
-                min_frame: CGSize::ZERO,
-                max_frame: CGSize::ZERO,
-            })
-            .collect(),
-    ));
+    let active_index = |reactor: &mut Reactor| {
+        let engine = &mut reactor.layout_manager.layout_engine;
+        let active = engine.active_workspace(space);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        workspaces.iter().position(|(id, _)| Some(*id) == active)
+    };
+    let focused = |reactor: &Reactor| {
+        let engine = &reactor.layout_manager.layout_engine;
+        let ws_id = engine.active_workspace(space)?;
+        engine.virtual_workspace_manager().last_focused_window(space, ws_id)
+    };
+    assert_eq!(active_index(&mut reactor), Some(0));
+
+    let focus_app = || {
+        Event::Command(Command::Reactor(ReactorCommand::FocusAppWorkspace {
+            bundle_id: Some("com.testapp1".into()),
+            pid: None,
+        }))
+    };
+    reactor.handle_event(focus_app());
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(active_index(&mut reactor), Some(1));
+    assert_eq!(focused(&reactor), Some(second));
+
+    reactor.handle_event(focus_app());
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(active_index(&mut reactor), Some(0));
+    assert_eq!(focused(&reactor), Some(first));
+}
+
+#[test]
+fn focus_mark_switches_to_the_marked_window_and_drops_stale_marks() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    let first = WindowId::new(1, 1);
+    let second = WindowId::new(1, 2);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(2), Some(second), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let layout = |cmd| Event::Command(Command::Layout(cmd));
+    reactor.handle_event(layout(LayoutCommand::MarkWindow("mail".into())));
+    assert_eq!(reactor.window_manager.marks_for(second), vec![
+        "mail".to_string()
+    ]);
+
+    reactor.handle_event(layout(LayoutCommand::MoveWindowToWorkspace {
+        workspace: 1,
+        window_id: Some(2),
+        bundle_id: None,
+        title: None,
+        target_index: None,
+    }));
+    apps.simulate_until_quiet(&mut reactor);
+    reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::FocusWindow {
+        window_id: first,
+        window_server_id: None,
+    })));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let active_index = |reactor: &mut Reactor| {
+        let engine = &mut reactor.layout_manager.layout_engine;
+        let active = engine.active_workspace(space);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        workspaces.iter().position(|(id, _)| Some(*id) == active)
+    };
+    assert_eq!(active_index(&mut reactor), Some(0));
+
+    reactor.handle_event(layout(LayoutCommand::FocusMark("mail".into())));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(active_index(&mut reactor), Some(1));
+    let engine = &reactor.layout_manager.layout_engine;
+    let ws_id = engine.active_workspace(space).unwrap();
+    assert_eq!(
+        engine.virtual_workspace_manager().last_focused_window(space, ws_id),
+        Some(second)
+    );
+
+    // A mark left behind by a window Rift no longer knows is dropped on use.
+    reactor.window_manager.marks.insert("gone".into(), WindowId::new(9, 9));
+    reactor.handle_event(layout(LayoutCommand::FocusMark("gone".into())));
+    assert!(!reactor.window_manager.marks.contains_key("gone"));
+
+    reactor.handle_event(Event::WindowDestroyed(second));
+    assert!(reactor.window_manager.marks.is_empty());
+}
+
+#[test]
+fn focus_app_urgent_focuses_the_apps_latest_urgent_window() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    let main = WindowId::new(1, 1);
+    let mail = WindowId::new(1, 2);
+    let chat = WindowId::new(2, 1);
+    let thread = WindowId::new(2, 2);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(2), Some(main), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    reactor.handle_events(apps.make_app(2, vec![make_window(3), make_window(4)]));
+    apps.simulate_until_quiet(&mut reactor);
+    let engine = &mut reactor.layout_manager.layout_engine;
+    for wid in [mail, thread] {
+        engine.move_window_to_workspace_index(space, wid, 1, None);
+    }
+    apps.simulate_until_quiet(&mut reactor);
+
+    // Neither the focused window nor a retitle without a rising unread count
+    // asks for attention.
+    reactor.handle_event(Event::WindowTitleChanged(main, "Inbox (4)".into()));
+    reactor.handle_event(Event::WindowTitleChanged(chat, "random".into()));
+    assert!(reactor.window_manager.urgent_at.is_empty());
+    for (wid, title) in [
+        (mail, "Inbox (3)"),
+        (chat, "general (2)"),
+        (thread, "(3) general"),
+    ] {
+        reactor.handle_event(Event::WindowTitleChanged(wid, title.into()));
+    }
+    let urgent = |reactor: &Reactor| {
+        let mut urgent: Vec<_> = reactor.window_manager.urgent_at.keys().copied().collect();
+        urgent.sort();
+        urgent
+    };
+    assert_eq!(urgent(&reactor), vec![mail, chat, thread]);
+
+    let active_index = |reactor: &mut Reactor| {
+        let engine = &mut reactor.layout_manager.layout_engine;
+        let active = engine.active_workspace(space);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        workspaces.iter().position(|(id, _)| Some(*id) == active)
+    };
+    let focused = |reactor: &Reactor| {
+        let engine = &reactor.layout_manager.layout_engine;
+        let ws_id = engine.active_workspace(space).unwrap();
+        engine.virtual_workspace_manager().last_focused_window(space, ws_id)
+    };
+    let focus_app_urgent = |reactor: &mut Reactor, apps: &mut Apps, bundle_id: &str| {
+        reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::FocusAppUrgent {
+            bundle_id: bundle_id.into(),
+        })));
+        apps.simulate_until_quiet(reactor);
+    };
+
+    focus_app_urgent(&mut reactor, &mut apps, "com.testapp2");
+    assert_eq!(active_index(&mut reactor), Some(1));
+    assert_eq!(focused(&reactor), Some(thread));
+    assert_eq!(urgent(&reactor), vec![mail, chat]);
+
+    focus_app_urgent(&mut reactor, &mut apps, "com.testapp2");
+    assert_eq!(active_index(&mut reactor), Some(0));
+    assert_eq!(focused(&reactor), Some(chat));
+    assert_eq!(urgent(&reactor), vec![mail]);
+
+    // With nothing left to answer, the app's command does nothing.
+    focus_app_urgent(&mut reactor, &mut apps, "com.testapp2");
+    assert_eq!(active_index(&mut reactor), Some(0));
+    assert_eq!(focused(&reactor), Some(chat));
+
+    focus_app_urgent(&mut reactor, &mut apps, "com.testapp1");
+    assert_eq!(active_index(&mut reactor), Some(1));
+    assert_eq!(focused(&reactor), Some(mail));
+    assert!(urgent(&reactor).is_empty());
+}
+
+#[test]
+fn toggle_app_hidden_restores_windows_to_their_workspaces() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let first = WindowId::new(1, 1);
+    let second = WindowId::new(1, 2);
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::MoveWindowToWorkspace {
+            workspace: 1,
+            window_id: Some(2),
+            bundle_id: None,
+            title: None,
+            target_index: None,
+        },
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let workspace_index = |reactor: &mut Reactor, wid: WindowId| {
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+        let ws_id = vwm.workspace_for_window(space, wid)?;
+        vwm.list_workspaces(space).iter().position(|(id, _)| *id == ws_id)
+    };
+    let first_frame = reactor.window_manager.windows[&first].frame_monotonic;
+    assert_eq!(workspace_index(&mut reactor, second), Some(1));
+
+    let toggle = || {
+        Event::Command(Command::Reactor(ReactorCommand::ToggleAppHidden {
+            bundle_id: Some("com.testapp1".into()),
+            pid: None,
+        }))
+    };
+    reactor.handle_event(toggle());
+    assert!(apps.requests().iter().any(|r| matches!(r, Request::SetHidden(true))));
+    reactor.handle_event(Event::WindowMinimized(first));
+    reactor.handle_event(Event::WindowMinimized(second));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(workspace_index(&mut reactor, first), None);
+    assert_eq!(workspace_index(&mut reactor, second), None);
+
+    reactor.handle_event(toggle());
+    assert!(apps.requests().iter().any(|r| matches!(r, Request::SetHidden(false))));
+    reactor.handle_event(Event::WindowDeminiaturized(second));
+    reactor.handle_event(Event::WindowDeminiaturized(first));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(workspace_index(&mut reactor, first), Some(0));
+    assert_eq!(workspace_index(&mut reactor, second), Some(1));
+    assert_eq!(
+        reactor.window_manager.windows[&first].frame_monotonic,
+        first_frame
+    );
+    assert!(reactor.app_manager.hidden_apps.is_empty());
+}
+
+#[test]
+fn workspace_move_for_unopened_window_applies_when_it_appears() {
+    use crate::sys::event::MouseState;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::MoveWindowToWorkspace {
+            workspace: 1,
+            window_id: None,
+            bundle_id: Some("com.testapp1".into()),
+            title: Some("Report".into()),
+            target_index: None,
+        },
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(reactor.window_manager.pending_workspace_assignments.len(), 1);
+
+    let report = WindowId::new(1, 2);
+    let mut window = make_window(2);
+    window.title = "Quarterly Report".into();
+    let info = WindowServerInfo {
+        id: window.sys_id.unwrap(),
+        pid: 1,
+        layer: 0,
+        frame: window.frame,
+        min_frame: CGSize::ZERO,
+        max_frame: CGSize::ZERO,
+        sticky: false,
+    };
+    apps.windows.insert(report, TestWindowState {
+        frame: window.frame,
+        ..Default::default()
+    });
+    reactor.handle_event(Event::WindowCreated(
+        report,
+        window,
+        Some(info),
+        Some(MouseState::Up),
+    ));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+    let workspaces = vwm.list_workspaces(space);
+    assert_eq!(vwm.workspace_for_window(space, report), Some(workspaces[1].0));
+    assert_eq!(
+        vwm.workspace_for_window(space, WindowId::new(1, 1)),
+        Some(workspaces[0].0)
+    );
+    assert!(reactor.window_manager.pending_workspace_assignments.is_empty());
+}
+
+#[test]
+fn workspace_move_for_unopened_window_applies_to_discovered_window() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::MoveWindowToWorkspace {
+            workspace: 1,
+            window_id: None,
+            bundle_id: Some("com.testapp1".into()),
+            title: None,
+            target_index: None,
+        },
+    )));
+    assert_eq!(reactor.window_manager.pending_workspace_assignments.len(), 1);
+
+    // The app's first window arrives through discovery rather than WindowCreated.
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+    let workspaces = vwm.list_workspaces(space);
+    assert_eq!(
+        vwm.workspace_for_window(space, WindowId::new(1, 1)),
+        Some(workspaces[1].0)
+    );
+    assert!(reactor.window_manager.pending_workspace_assignments.is_empty());
+}
+
+#[test]
+fn workspace_move_for_unopened_window_applies_after_title_change() {
+    use crate::sys::event::MouseState;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::MoveWindowToWorkspace {
+            workspace: 1,
+            window_id: None,
+            bundle_id: Some("com.testapp1".into()),
+            title: Some("Report".into()),
+            target_index: None,
+        },
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let report = WindowId::new(1, 2);
+    let mut window = make_window(2);
+    window.title = "Untitled".into();
+    let info = WindowServerInfo {
+        id: window.sys_id.unwrap(),
+        pid: 1,
+        layer: 0,
+        frame: window.frame,
+        min_frame: CGSize::ZERO,
+        max_frame: CGSize::ZERO,
+        sticky: false,
+    };
+    apps.windows.insert(report, TestWindowState {
+        frame: window.frame,
+        ..Default::default()
+    });
+    reactor.handle_event(Event::WindowCreated(
+        report,
+        window,
+        Some(info),
+        Some(MouseState::Up),
+    ));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(reactor.window_manager.pending_workspace_assignments.len(), 1);
+
+    reactor.handle_event(Event::WindowTitleChanged(report, "Quarterly Report".into()));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+    let workspaces = vwm.list_workspaces(space);
+    assert_eq!(vwm.workspace_for_window(space, report), Some(workspaces[1].0));
+    assert!(reactor.window_manager.pending_workspace_assignments.is_empty());
+}
+
+#[test]
+fn pending_workspace_assignments_expire() {
+    use std::time::{Duration, Instant};
+
+    use super::managers::PendingWorkspaceAssignment;
+
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let queued_at = Instant::now();
+    reactor
+        .window_manager
+        .pending_workspace_assignments
+        .push(PendingWorkspaceAssignment {
+            bundle_id: Some("com.testapp1".into()),
+            title: None,
+            workspace: 1,
+            target_index: None,
+            queued_at,
+        });
+    let later = queued_at + Duration::from_secs(60);
+    assert!(
+        reactor
+            .window_manager
+            .take_pending_workspace_assignment(Some("com.testapp1"), "Window1", later)
+            .is_none()
+    );
+    assert!(reactor.window_manager.pending_workspace_assignments.is_empty());
+}
+
+#[test]
+fn queueing_workspace_assignment_drops_expired_ones() {
+    use std::time::{Duration, Instant};
+
+    use super::managers::PendingWorkspaceAssignment;
+
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let pending = |bundle_id: &str, queued_at| PendingWorkspaceAssignment {
+        bundle_id: Some(bundle_id.into()),
+        title: None,
+        workspace: 1,
+        target_index: None,
+        queued_at,
+    };
+    let queued_at = Instant::now();
+    let later = queued_at + Duration::from_secs(60);
+    let window_manager = &mut reactor.window_manager;
+    window_manager.queue_workspace_assignment(pending("com.testapp1", queued_at));
+    window_manager.queue_workspace_assignment(pending("com.testapp2", later));
+    let queued = &window_manager.pending_workspace_assignments;
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].bundle_id.as_deref(), Some("com.testapp2"));
+}
+
+#[test]
+fn app_rule_pins_new_window_to_display_by_name() {
+    use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings, WorkspaceSelector};
+    use crate::sys::geometry::CGRectExt;
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![AppWorkspaceRule {
+        app_id: Some("com.testapp1".into()),
+        workspace: Some(WorkspaceSelector::Index(1)),
+        floating: false,
+        manage: true,
+        app_name: None,
+        title_regex: None,
+        title_substring: None,
+        ax_role: None,
+        ax_subrole: None,
+        display: Some("studio display".into()),
+        confine_to_display: false,
+        opacity: None,
+        ..Default::default()
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space1 = SpaceId::new(1);
+    let space2 = SpaceId::new(2);
+    let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    let mut screens =
+        make_screen_snapshots(vec![screen1, screen2], vec![Some(space1), Some(space2)]);
+    screens[1].name = Some("Studio Display".into());
+    reactor.handle_event(Event::ScreenParametersChanged(screens));
+
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+
+    let wid = WindowId::new(1, 1);
+    let requests = apps.requests();
+    let moved = requests.iter().any(|request| {
+        matches!(request, Request::SetWindowFrame(id, frame, _, _)
+            if *id == wid && screen2.contains(frame.mid()))
+    });
+    assert!(moved, "expected the window to be moved onto the named display");
+    for event in apps.simulate_events_for_requests(requests) {
+        reactor.handle_event(event);
+    }
+    apps.simulate_until_quiet(&mut reactor);
+
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+    let second_workspace = vwm.list_workspaces(space2)[1].0;
+    assert_eq!(vwm.workspace_for_window(space2, wid), Some(second_workspace));
+    assert_eq!(vwm.workspace_for_window(space1, wid), None);
+}
+
+#[test]
+fn dragging_tiled_window_onto_other_display_tile_swaps_across_displays() {
+    use crate::sys::event::MouseState;
+    use crate::sys::geometry::CGRectExt;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.window_snapping.across_displays = true;
+    let space1 = SpaceId::new(1);
+    let space2 = SpaceId::new(2);
+    let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![screen1, screen2],
+        vec![Some(space1), Some(space2)],
+        vec![],
+    ));
+
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    let mut other = make_window(1);
+    other.frame.origin.x += 1000.;
+    other.sys_id = Some(WindowServerId::new(2));
+    reactor.handle_events(apps.make_app(2, vec![other]));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let dragged = WindowId::new(1, 1);
+    let target = WindowId::new(2, 1);
+    let target_frame = reactor.window_manager.windows[&target].frame_monotonic;
+    assert!(screen2.contains(target_frame.mid()));
+
+    // The dragged window lands squarely on the other display's tile.
+    apps.windows.get_mut(&dragged).unwrap().frame = target_frame;
+    reactor.handle_event(Event::WindowFrameChanged(
+        dragged,
+        target_frame,
+        None,
+        Requested(false),
+        Some(MouseState::Down),
+    ));
+    reactor.handle_event(Event::MouseUp);
+    apps.simulate_until_quiet(&mut reactor);
+
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+    assert!(vwm.workspace_for_window(space2, dragged).is_some());
+    assert!(vwm.workspace_for_window(space1, target).is_some());
+    assert!(screen2.contains(reactor.window_manager.windows[&dragged].frame_monotonic.mid()));
+    assert!(screen1.contains(reactor.window_manager.windows[&target].frame_monotonic.mid()));
+}
+
+#[test]
+fn small_moves_with_button_held_do_not_start_a_drag() {
+    use crate::sys::event::MouseState;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.window_snapping.drag_deadzone = 5.0;
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let wid = WindowId::new(1, 1);
+    let tiled_frame = reactor.window_manager.windows[&wid].frame_monotonic;
+
+    for offset in [1., 3., 2.] {
+        let mut nudged = tiled_frame;
+        nudged.origin.x += offset;
+        nudged.origin.y += offset;
+        apps.windows.get_mut(&wid).unwrap().frame = nudged;
+        reactor.handle_event(Event::WindowFrameChanged(
+            wid,
+            nudged,
+            None,
+            Requested(false),
+            Some(MouseState::Down),
+        ));
+        assert!(!reactor.is_in_drag());
+    }
+
+    reactor.handle_event(Event::MouseUp);
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(!reactor.is_in_drag());
+    assert_eq!(reactor.drag_manager.press_origin, None);
+    assert_eq!(reactor.window_manager.windows[&wid].frame_monotonic, tiled_frame);
+}
+
+#[test]
+fn app_moving_confined_window_to_other_display_is_moved_back() {
+    use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings};
+    use crate::sys::event::MouseState;
+    use crate::sys::geometry::CGRectExt;
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![AppWorkspaceRule {
+        app_id: Some("com.testapp1".into()),
+        workspace: None,
+        floating: false,
+        manage: true,
+        app_name: None,
+        title_regex: None,
+        title_substring: None,
+        ax_role: None,
+        ax_subrole: None,
+        display: None,
+        confine_to_display: true,
+        opacity: None,
+        ..Default::default()
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space1 = SpaceId::new(1);
+    let space2 = SpaceId::new(2);
+    let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![screen1, screen2],
+        vec![Some(space1), Some(space2)],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let wid = WindowId::new(1, 1);
+    let tiled_frame = reactor.window_manager.windows[&wid].frame_monotonic;
+    assert!(screen1.contains(tiled_frame.mid()));
+
+    // The app jumps its own window onto the other display; no button is held.
+    let mut jumped = tiled_frame;
+    jumped.origin.x += 1000.;
+    apps.windows.get_mut(&wid).unwrap().frame = jumped;
+    reactor.handle_event(Event::WindowFrameChanged(
+        wid,
+        jumped,
+        None,
+        Requested(false),
+        Some(MouseState::Up),
+    ));
+
+    let requests = apps.requests();
+    let moved_back = requests.iter().any(|request| {
+        matches!(request, Request::SetWindowFrame(id, frame, _, _)
+            if *id == wid && *frame == tiled_frame)
+    });
+    assert!(moved_back, "expected the window to be sent back to its display");
+    for event in apps.simulate_events_for_requests(requests) {
+        reactor.handle_event(event);
+    }
+    apps.simulate_until_quiet(&mut reactor);
+
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+    assert!(vwm.workspace_for_window(space1, wid).is_some());
+    assert!(vwm.workspace_for_window(space2, wid).is_none());
+    assert!(screen1.contains(reactor.window_manager.windows[&wid].frame_monotonic.mid()));
+}
+
+fn move_tiled_window_externally(
+    behavior: crate::common::config::ExternalMoveBehavior,
+) -> (Apps, CGRect, CGRect) {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.tiled_window_external_move = behavior;
+    let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let moved = WindowId::new(1, 1);
+    let other = WindowId::new(1, 2);
+    let moved_tile = reactor.window_manager.windows[&moved].frame_monotonic;
+    let other_tile = reactor.window_manager.windows[&other].frame_monotonic;
+    assert!(!moved_tile.contains(other_tile.mid()));
+
+    // Something other than rift slides the window over its neighbour's tile.
+    let mut shifted = moved_tile;
+    shifted.origin.x += other_tile.mid().x - moved_tile.mid().x;
+    shifted.origin.y += other_tile.mid().y - moved_tile.mid().y;
+    apps.windows.get_mut(&moved).unwrap().frame = shifted;
+    reactor.handle_event(Event::WindowFrameChanged(
+        moved,
+        shifted,
+        None,
+        Requested(false),
+        Some(MouseState::Up),
+    ));
+    apps.simulate_until_quiet(&mut reactor);
+    (apps, moved_tile, other_tile)
+}
+
+#[test]
+fn externally_moved_tiled_window_snaps_back() {
+    use crate::common::config::ExternalMoveBehavior;
+    let (apps, moved_tile, other_tile) =
+        move_tiled_window_externally(ExternalMoveBehavior::SnapBack);
+
+    assert_eq!(apps.windows[&WindowId::new(1, 1)].frame, moved_tile);
+    assert_eq!(apps.windows[&WindowId::new(1, 2)].frame, other_tile);
+}
+
+#[test]
+fn externally_moved_tiled_window_retiles_around_new_position() {
+    use crate::common::config::ExternalMoveBehavior;
+    let (apps, moved_tile, other_tile) = move_tiled_window_externally(ExternalMoveBehavior::Retile);
+
+    assert_eq!(apps.windows[&WindowId::new(1, 1)].frame, other_tile);
+    assert_eq!(apps.windows[&WindowId::new(1, 2)].frame, moved_tile);
+}
+
+#[test]
+fn external_move_reverted_within_grace_period_is_ignored() {
+    use crate::common::config::ExternalMoveBehavior;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.tiled_window_external_move = ExternalMoveBehavior::Retile;
+    reactor.config.settings.external_move_grace_ms = 500;
+    let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let moved = WindowId::new(1, 1);
+    let other = WindowId::new(1, 2);
+    let moved_tile = reactor.window_manager.windows[&moved].frame_monotonic;
+    let other_tile = reactor.window_manager.windows[&other].frame_monotonic;
+    let mut shifted = moved_tile;
+    shifted.origin.x += other_tile.mid().x - moved_tile.mid().x;
+    let move_externally = |apps: &mut Apps, reactor: &mut Reactor, frame: CGRect| {
+        apps.windows.get_mut(&moved).unwrap().frame = frame;
+        reactor.handle_event(Event::WindowFrameChanged(
+            moved,
+            frame,
+            None,
+            Requested(false),
+            Some(MouseState::Up),
+        ));
+        apps.requests()
+    };
+    let sets_frames = |requests: &[Request]| {
+        requests.iter().any(|request| matches!(request, Request::SetWindowFrame(..)))
+    };
+
+    // The app jumps over its neighbour and back before the grace period ends.
+    assert!(!sets_frames(&move_externally(&mut apps, &mut reactor, shifted)));
+    assert!(!sets_frames(&move_externally(
+        &mut apps,
+        &mut reactor,
+        moved_tile
+    )));
+    assert!(reactor.window_manager.pending_external_moves.is_empty());
+    WindowEventHandler::commit_settled_external_moves(
+        &mut reactor,
+        Instant::now() + Duration::from_secs(1),
+    );
+    assert!(!sets_frames(&apps.requests()));
+
+    // A move that stays put is acted on once the grace period has passed.
+    assert!(!sets_frames(&move_externally(&mut apps, &mut reactor, shifted)));
+    WindowEventHandler::commit_settled_external_moves(&mut reactor, Instant::now());
+    assert!(!sets_frames(&apps.requests()));
+    WindowEventHandler::commit_settled_external_moves(
+        &mut reactor,
+        Instant::now() + Duration::from_secs(1),
+    );
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(apps.windows[&moved].frame, other_tile);
+    assert_eq!(apps.windows[&other].frame, moved_tile);
+}
+
+#[test]
+fn held_external_move_does_not_keep_window_on_screen_after_workspace_switch() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.external_move_grace_ms = 500;
+    let screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let moved = WindowId::new(1, 1);
+    let move_externally = |apps: &mut Apps, reactor: &mut Reactor, frame: CGRect| {
+        apps.windows.get_mut(&moved).unwrap().frame = frame;
+        reactor.handle_event(Event::WindowFrameChanged(
+            moved,
+            frame,
+            None,
+            Requested(false),
+            Some(MouseState::Up),
+        ));
+    };
+    let tile = reactor.window_manager.windows[&moved].frame_monotonic;
+
+    // Resizes aren't held.
+    let mut resized = tile;
+    resized.size.width -= 50.;
+    move_externally(&mut apps, &mut reactor, resized);
+    assert!(reactor.window_manager.pending_external_moves.is_empty());
+    apps.simulate_until_quiet(&mut reactor);
+
+    let mut shifted = reactor.window_manager.windows[&moved].frame_monotonic;
+    shifted.origin.y += 100.;
+    move_externally(&mut apps, &mut reactor, shifted);
+    assert!(reactor.window_manager.pending_external_moves.contains_key(&moved));
+
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::SwitchToWorkspace(1),
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(reactor.window_manager.pending_external_moves.is_empty());
+    assert!(
+        !apps.windows[&moved].frame.same_as(shifted),
+        "the window should be hidden with the rest of its workspace"
+    );
+}
+
+#[test]
+fn dialog_subrole_windows_are_tracked_as_floating() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![full_screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+
+    let mut dialog = make_window(2);
+    dialog.is_standard = false;
+    dialog.ax_role = Some("AXWindow".into());
+    dialog.ax_subrole = Some("AXDialog".into());
+    reactor.handle_events(apps.make_app(1, vec![make_window(1), dialog]));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let main = WindowId::new(1, 1);
+    let dialog = WindowId::new(1, 2);
+    assert!(reactor.window_manager.windows[&dialog].is_manageable);
+    assert!(reactor.layout_manager.layout_engine.is_window_floating(dialog));
+    assert!(!reactor.layout_manager.layout_engine.is_window_floating(main));
+    assert_eq!(apps.windows[&main].frame, full_screen);
+
+    let window_info = |reactor: &mut Reactor, window_id| {
+        let (resp, rx) = std::sync::mpsc::sync_channel(1);
+        reactor.handle_query_request(query::QueryRequest::WindowInfo { window_id, resp });
+        rx.recv().unwrap().unwrap()
+    };
+    let dialog_info = window_info(&mut reactor, dialog);
+    assert_eq!(dialog_info.auto_float_subrole.as_deref(), Some("AXDialog"));
+    assert_eq!(dialog_info.ax_role.as_deref(), Some("AXWindow"));
+    assert_eq!(dialog_info.ax_subrole.as_deref(), Some("AXDialog"));
+    assert_eq!(window_info(&mut reactor, main).auto_float_subrole, None);
+}
+
+#[test]
+fn handle_layout_response_groups_windows_by_app_and_screen() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let screen1 = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let screen2 = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![screen1, screen2],
+        vec![Some(SpaceId::new(1)), Some(SpaceId::new(2))],
+        vec![],
+    ));
+
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+
+    let mut windows = make_windows(2);
+    windows[1].frame.origin = CGPoint::new(1100., 100.);
+    reactor.handle_events(apps.make_app(2, windows));
+
+    let _events = apps.simulate_events();
+    while raise_manager_rx.try_recv().is_ok() {}
+
+    reactor.handle_layout_response(
+        layout::EventResponse {
+            raise_windows: vec![
+                WindowId::new(1, 1),
+                WindowId::new(1, 2),
+                WindowId::new(2, 1),
+                WindowId::new(2, 2),
+            ],
+            focus_window: None,
+            boundary_hit: None,
+        },
+        None,
+    );
+    let msg = raise_manager_rx.try_recv().expect("Should have sent an event").1;
+    match msg {
+        raise_manager::Event::RaiseRequest(RaiseRequest {
+            raise_windows, focus_window, ..
+        }) => {
+            let raise_windows: HashSet<Vec<WindowId>> = raise_windows.into_iter().collect();
+            let expected = [
+                vec![WindowId::new(1, 1), WindowId::new(1, 2)],
+                vec![WindowId::new(2, 1)],
+                vec![WindowId::new(2, 2)],
+            ]
+            .into_iter()
+            .collect();
+            assert_eq!(raise_windows, expected);
+            assert!(focus_window.is_none());
+        }
+        _ => panic!("Unexpected event: {msg:?}"),
+    }
+}
+
+#[test]
+fn handle_layout_response_includes_handles_for_raise_and_focus_windows() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    reactor.handle_events(apps.make_app(2, make_windows(1)));
+
+    let _events = apps.simulate_events();
+    while raise_manager_rx.try_recv().is_ok() {}
+    reactor.handle_layout_response(
+        layout::EventResponse {
+            raise_windows: vec![WindowId::new(1, 1)],
+            focus_window: Some(WindowId::new(2, 1)),
+            boundary_hit: None,
+        },
+        None,
+    );
+    let msg = raise_manager_rx.try_recv().expect("Should have sent an event").1;
+    match msg {
+        raise_manager::Event::RaiseRequest(RaiseRequest { app_handles, .. }) => {
+            assert!(app_handles.contains_key(&1));
+            assert!(app_handles.contains_key(&2));
+        }
+        _ => panic!("Unexpected event: {msg:?}"),
+    }
+}
+
+#[test]
+fn it_preserves_layout_after_login_screen() {
+    // TODO: This would be better tested with a more complete simulation.
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+
+    reactor.handle_events(apps.make_app_with_opts(
+        1,
+        make_windows(3),
+        Some(WindowId::new(1, 1)),
+        true,
+        true,
+    ));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+    let default = reactor.layout_manager.layout_engine.calculate_layout(
+        space,
+        full_screen,
+        &reactor.config.settings.layout.gaps,
+        0.0,
+        crate::common::config::HorizontalPlacement::Top,
+        crate::common::config::VerticalPlacement::Right,
+    );
+
+    assert!(reactor.layout_manager.layout_engine.selected_window(space).is_some());
+    reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::MoveNode(
+        Direction::Up,
+    ))));
+    apps.simulate_until_quiet(&mut reactor);
+    let modified = reactor.layout_manager.layout_engine.calculate_layout(
+        space,
+        full_screen,
+        &reactor.config.settings.layout.gaps,
+        0.0,
+        crate::common::config::HorizontalPlacement::Top,
+        crate::common::config::VerticalPlacement::Right,
+    );
+    assert_ne!(default, modified);
+
+    reactor.handle_event(screen_params_event(vec![CGRect::ZERO], vec![None], vec![]));
+    reactor.handle_event(screen_params_event(
+        vec![full_screen],
+        vec![Some(space)],
+        (1..=3)
+            .map(|n| WindowServerInfo {
+                pid: 1,
+                id: WindowServerId::new(n),
+                layer: 0,
+                frame: CGRect::ZERO,
+                min_frame: CGSize::ZERO,
+                max_frame: CGSize::ZERO,
+                sticky: false,
+            })
+            .collect(),
+    ));
+    let requests = apps.requests();
+    for request in requests {
+        match request {
+            Request::GetVisibleWindows => {
+                // Simulate the login screen condition: No windows are
+                // considered visible by the accessibility API, but they are
+                // from the window server API in the event above.
+                reactor.handle_event(Event::WindowsDiscovered {
+                    pid: 1,
+                    new: vec![],
+                    known_visible: vec![],
+                });
+            }
+            req => {
+                let events = apps.simulate_events_for_requests(vec![req]);
+                for event in events {
+                    reactor.handle_event(event);
+                }
+            }
+        }
+    }
+    apps.simulate_until_quiet(&mut reactor);
+
+    assert_eq!(
+        reactor.layout_manager.layout_engine.calculate_layout(
+            space,
+            full_screen,
+            &reactor.config.settings.layout.gaps,
+            0.0,
+            crate::common::config::HorizontalPlacement::Top,
+            crate::common::config::VerticalPlacement::Right,
+        ),
+        modified
+    );
+}
+
+#[test]
+fn menu_open_state_is_cleared_when_owner_deactivates() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (event_tap_tx, mut event_tap_rx) = actor::channel();
+    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
+
+    reactor.handle_event(Event::MenuOpened(1));
+    let disable = event_tap_rx.try_recv().expect("menu-open should update event tap").1;
+    assert!(matches!(
+        disable,
+        crate::actor::event_tap::Request::SetFocusFollowsMouseEnabled(false)
+    ));
+    assert_eq!(reactor.menu_manager.menu_state, MenuState::Open(1));
+
+    reactor.handle_event(Event::ApplicationDeactivated(1));
+    let enable = event_tap_rx
+        .try_recv()
+        .expect("app deactivation should re-enable focus-follows-mouse")
+        .1;
+    assert!(matches!(
+        enable,
+        crate::actor::event_tap::Request::SetFocusFollowsMouseEnabled(true)
+    ));
+    assert_eq!(reactor.menu_manager.menu_state, MenuState::Closed);
+}
+
+#[test]
+fn stale_menu_open_state_is_cleared_when_other_app_activates() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (event_tap_tx, mut event_tap_rx) = actor::channel();
+    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
+
+    reactor.handle_event(Event::MenuOpened(1));
+    let _ = event_tap_rx.try_recv().expect("menu-open should update event tap");
+    assert_eq!(reactor.menu_manager.menu_state, MenuState::Open(1));
+
+    reactor.handle_event(Event::ApplicationGloballyActivated(2));
+    let enable = event_tap_rx
+        .try_recv()
+        .expect("activation of another app should clear stale menu state")
+        .1;
+    assert!(matches!(
+        enable,
+        crate::actor::event_tap::Request::SetFocusFollowsMouseEnabled(true)
+    ));
+    assert_eq!(reactor.menu_manager.menu_state, MenuState::Closed);
+}
+
+#[test]
+fn reset_menu_state_restores_focus_follows_mouse() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (event_tap_tx, mut event_tap_rx) = actor::channel();
+    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
+
+    // The matching menu-closed notification never arrives.
+    reactor.handle_event(Event::MenuOpened(1));
+    let _ = event_tap_rx.try_recv().expect("menu-open should update event tap");
+    let state = reactor.query_menu_state();
+    assert!(state.open);
+    assert_eq!(state.owner_pid, Some(1));
+
+    reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::ResetMenuState)));
+    let enable = event_tap_rx
+        .try_recv()
+        .expect("resetting the menu state should re-enable focus-follows-mouse")
+        .1;
+    assert!(matches!(
+        enable,
+        crate::actor::event_tap::Request::SetFocusFollowsMouseEnabled(true)
+    ));
+    assert_eq!(reactor.menu_manager.menu_state, MenuState::Closed);
+    assert!(!reactor.query_menu_state().open);
+}
+
+#[test]
+fn mouse_over_during_layout_command_does_not_steal_focus() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+
+    reactor.handle_events(apps.make_app_with_opts(
+        1,
+        make_windows(2),
+        Some(WindowId::new(1, 1)),
+        true,
+        true,
+    ));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+
+    reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::ToggleOrientation)));
+    while raise_manager_rx.try_recv().is_ok() {}
+
+    // The frames requested by the command have not been reported back yet.
+    reactor.handle_event(Event::MouseMovedOverWindow(
+        WindowServerId::new(2),
+        CGPoint::new(750., 500.),
+    ));
+    assert!(
+        raise_manager_rx.try_recv().is_err(),
+        "hover during layout application should not raise"
+    );
+
+    apps.simulate_until_quiet(&mut reactor);
+    reactor.handle_event(Event::MouseMovedOverWindow(
+        WindowServerId::new(2),
+        CGPoint::new(750., 500.),
+    ));
+    assert!(
+        raise_manager_rx.try_recv().is_ok(),
+        "hover after the layout settles should raise"
+    );
+}
+
+#[test]
+fn mouse_over_inside_exclusion_zone_does_not_raise() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app_with_opts(
+        1,
+        make_windows(2),
+        Some(WindowId::new(1, 1)),
+        true,
+        true,
+    ));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+    while raise_manager_rx.try_recv().is_ok() {}
+
+    // A strip along the bottom of the display, where a dock would sit.
+    reactor.config.settings.focus_follows_mouse_exclusion_zones =
+        vec![crate::common::config::FocusExclusionZone {
+            display: Some("test-display-0".into()),
+            x: 0.,
+            y: 950.,
+            width: 1000.,
+            height: 50.,
+        }];
+
+    reactor.handle_event(Event::MouseMovedOverWindow(
+        WindowServerId::new(2),
+        CGPoint::new(750., 980.),
+    ));
+    assert!(
+        raise_manager_rx.try_recv().is_err(),
+        "hover inside an exclusion zone should not raise"
+    );
+
+    reactor.handle_event(Event::MouseMovedOverWindow(
+        WindowServerId::new(2),
+        CGPoint::new(750., 500.),
+    ));
+    assert!(
+        raise_manager_rx.try_recv().is_ok(),
+        "hover outside the exclusion zone should raise"
+    );
+}
+
+#[test]
+fn mouse_over_app_excluded_from_focus_follows_mouse_does_not_raise() {
+    use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings};
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![AppWorkspaceRule {
+        app_id: Some("com.testapp2".into()),
+        disable_focus_follows_mouse: true,
+        ..Default::default()
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app_with_opts(
+        1,
+        make_windows(2),
+        Some(WindowId::new(1, 1)),
+        true,
+        true,
+    ));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    reactor.handle_events(apps.make_app(2, vec![make_window(3)]));
+    apps.simulate_until_quiet(&mut reactor);
+    while raise_manager_rx.try_recv().is_ok() {}
+
+    reactor.handle_event(Event::MouseMovedOverWindow(
+        WindowServerId::new(3),
+        CGPoint::new(850., 500.),
+    ));
+    assert!(
+        raise_manager_rx.try_recv().is_err(),
+        "hovering an excluded app's window should not raise it"
+    );
+
+    reactor.handle_event(Event::MouseMovedOverWindow(
+        WindowServerId::new(2),
+        CGPoint::new(500., 500.),
+    ));
+    assert!(
+        raise_manager_rx.try_recv().is_ok(),
+        "other apps still follow the mouse"
+    );
+}
+
+#[test]
+fn floating_always_above_reraises_floating_windows_after_relayout() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    reactor.config.settings.floating_always_above = true;
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+
+    let floating = WindowId::new(1, 3);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(3), Some(floating), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::ToggleWindowFloating,
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+    while raise_manager_rx.try_recv().is_ok() {}
+
+    reactor.handle_event(Event::Command(Command::Layout(LayoutCommand::ToggleOrientation)));
+    let mut floating_raises = Vec::new();
+    while let Ok((_, msg)) = raise_manager_rx.try_recv() {
+        if let raise_manager::Event::RaiseRequest(RaiseRequest {
+            raise_windows,
+            focus_window: None,
+            focus_quiet: Quiet::Yes,
+            ..
+        }) = msg
+        {
+            floating_raises.push(raise_windows);
+        }
+    }
+    assert_eq!(floating_raises, vec![vec![vec![floating]]]);
+}
+
+#[test]
+fn cycle_floating_preset_steps_through_presets_and_wraps() {
+    use crate::common::config::FloatingPreset;
+    use crate::layout_engine::Anchor;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let preset = |width_frac, height_frac, anchor| FloatingPreset {
+        width_frac,
+        height_frac,
+        anchor,
+    };
+    reactor.config.settings.floating_presets = vec![
+        preset(0.8, 0.8, Anchor::Center),
+        preset(0.5, 0.5, Anchor::Center),
+        preset(0.5, 0.5, Anchor::TopRight),
+    ];
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+
+    let wid = WindowId::new(1, 1);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(2), Some(wid), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+
+    // Tiled windows stay where the layout put them.
+    let tiled_frame = reactor.window_manager.windows[&wid].frame_monotonic;
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::CycleFloatingPreset,
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(reactor.window_manager.windows[&wid].frame_monotonic.same_as(tiled_frame));
+    assert!(reactor.window_manager.floating_preset_index.is_empty());
+
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::ToggleWindowFloating,
+    )));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let expected = [
+        CGRect::new(CGPoint::new(100., 100.), CGSize::new(800., 800.)),
+        CGRect::new(CGPoint::new(250., 250.), CGSize::new(500., 500.)),
+        CGRect::new(CGPoint::new(500., 0.), CGSize::new(500., 500.)),
+        CGRect::new(CGPoint::new(100., 100.), CGSize::new(800., 800.)),
+    ];
+    for frame in expected {
+        reactor.handle_event(Event::Command(Command::Layout(
+            LayoutCommand::CycleFloatingPreset,
+        )));
+        apps.simulate_until_quiet(&mut reactor);
+        let actual = reactor.window_manager.windows[&wid].frame_monotonic;
+        assert!(actual.same_as(frame), "expected {frame:?}, got {actual:?}");
+    }
+}
+
+#[test]
+fn floating_stacking_order_survives_workspace_round_trip() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    reactor.config.settings.preserve_floating_stacking_order = true;
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+
+    let overlapping_dialog = |idx| {
+        let mut window = make_window(idx);
+        window.is_standard = false;
+        window.ax_role = Some("AXWindow".into());
+        window.ax_subrole = Some("AXDialog".into());
+        window.frame = CGRect::new(
+            CGPoint::new(100. + 20. * idx as f64, 100.),
+            CGSize::new(400., 300.),
+        );
+        window
+    };
+    reactor.handle_events(apps.make_app(1, (1..=3).map(overlapping_dialog).collect()));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let [bottom, middle, top] = [2, 3, 1].map(|idx| WindowId::new(1, idx));
+    for wid in [bottom, middle, top] {
+        assert!(reactor.layout_manager.layout_engine.is_window_floating(wid));
+        reactor.send_layout_event(LayoutEvent::WindowFocused(space, wid));
+    }
+
+    let mut switch_to = |reactor: &mut Reactor, apps: &mut Apps, index| {
+        while raise_manager_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Layout(
+            LayoutCommand::SwitchToWorkspace(index),
+        )));
+        apps.simulate_until_quiet(reactor);
+        std::iter::from_fn(|| raise_manager_rx.try_recv().ok())
+            .filter_map(|(_, msg)| match msg {
+                raise_manager::Event::RaiseRequest(RaiseRequest {
+                    raise_windows,
+                    focus_window: None,
+                    ..
+                }) if raise_windows.len() == 1 && raise_windows[0].len() == 1 => {
+                    Some(raise_windows[0][0])
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert!(switch_to(&mut reactor, &mut apps, 1).is_empty());
+    assert_eq!(switch_to(&mut reactor, &mut apps, 0), vec![bottom, middle, top]);
+}
+
+#[test]
+fn switching_to_empty_workspace_warps_to_display_center_when_configured() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (event_tap_tx, mut event_tap_rx) = actor::channel();
+    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let mut switch_warps = |reactor: &mut Reactor, index| {
+        while event_tap_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Layout(
+            LayoutCommand::SwitchToWorkspace(index),
+        )));
+        apps.simulate_until_quiet(reactor);
+        std::iter::from_fn(|| event_tap_rx.try_recv().ok())
+            .filter_map(|(_, request)| match request {
+                crate::actor::event_tap::Request::Warp(point) => Some(point),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(switch_warps(&mut reactor, 1), vec![]);
+    switch_warps(&mut reactor, 0);
+
+    reactor.config.settings.empty_workspace_focus = EmptyWorkspaceFocus::WarpToCenter;
+    assert_eq!(switch_warps(&mut reactor, 1), vec![CGPoint::new(500., 500.)]);
+}
+
+#[test]
+fn set_layout_mode_switches_active_workspace_and_reports_result() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    assert_eq!(
+        reactor.query_layout_mode(Some(space)).map(|data| data.mode),
+        Some(crate::common::config::LayoutMode::Traditional)
+    );
+
+    let result = reactor
+        .set_layout_mode(Some(space), crate::common::config::LayoutMode::Bsp)
+        .expect("active space should accept a layout mode");
+    assert_eq!(result.space_id, space.get());
+    assert_eq!(result.mode, crate::common::config::LayoutMode::Bsp);
+    assert_eq!(
+        reactor.layout_manager.layout_engine.active_layout_mode_at(space),
+        crate::common::config::LayoutMode::Bsp
+    );
+
+    assert!(reactor.query_layout_mode(Some(SpaceId::new(99))).is_none());
+}
+
+#[test]
+fn layout_queries_return_retained_state_for_spaces_off_screen() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let other_space = SpaceId::new(2);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let live = reactor.query_layout_state(space.get()).expect("space is on screen");
+    assert!(!live.stale);
+    let layouts = reactor.query_workspace_layouts(Some(space), None).unwrap();
+    assert!(!layouts.is_empty() && layouts.iter().all(|ws| !ws.stale));
+
+    reactor.handle_event(Event::SpaceChanged(vec![Some(other_space)]));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let retained = reactor.query_layout_state(space.get()).expect("space was managed before");
+    assert!(retained.stale);
+    assert_eq!(retained.mode, live.mode);
+    assert_eq!(retained.focused_window, None);
+    let layouts = reactor.query_workspace_layouts(Some(space), None).unwrap();
+    assert!(!layouts.is_empty() && layouts.iter().all(|ws| ws.stale));
+
+    assert!(reactor.query_layout_state(99).is_none());
+    assert!(reactor.query_workspace_layouts(Some(SpaceId::new(99)), None).is_none());
+}
+
+#[test]
+fn deserialize_restores_a_serialized_snapshot() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(3)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let [first, second, third] = [1, 2, 3].map(|idx| WindowId::new(1, idx));
+    let workspace_of = |reactor: &Reactor, wid| {
+        reactor
+            .layout_manager
+            .layout_engine
+            .virtual_workspace_manager()
+            .workspace_for_window(space, wid)
+    };
+    let original_workspace = workspace_of(&reactor, third);
+    let state = reactor.serialize_state().unwrap();
+
+    reactor.handle_event(Event::Command(Command::Layout(
+        LayoutCommand::MoveWindowToWorkspace {
+            workspace: 1,
+            window_id: Some(3),
+            bundle_id: None,
+            title: None,
+            target_index: None,
+        },
+    )));
+    reactor.handle_event(Event::WindowDestroyed(second));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_ne!(workspace_of(&reactor, third), original_workspace);
+
+    assert!(reactor.deserialize_state("not json").is_err());
+    assert!(reactor.deserialize_state(r#"{"spaces": []}"#).is_err());
+    assert!(reactor.deserialize_state(r#"{"layout_engine_ron": "(broken"}"#).is_err());
+    assert_ne!(workspace_of(&reactor, third), original_workspace);
+
+    let result = reactor.deserialize_state(&state).unwrap();
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(result.restored_windows, 2);
+    assert_eq!(result.unmatched_windows, vec![second]);
+    assert_eq!(result.added_windows, 0);
+    assert_eq!(workspace_of(&reactor, third), original_workspace);
+    assert_eq!(workspace_of(&reactor, second), None);
+    assert_eq!(
+        reactor.layout_manager.layout_engine.windows_in_active_workspace(space),
+        vec![first, third]
+    );
+}
+
+#[test]
+fn window_queries_include_minimized_windows_only_when_asked() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let minimized = WindowId::new(1, 2);
+    let active_workspace = reactor.layout_manager.layout_engine.active_workspace(space).unwrap();
+    reactor.handle_event(Event::WindowMinimized(minimized));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let ids = |windows: &[WindowData]| windows.iter().map(|w| w.id).collect::<Vec<_>>();
+    assert_eq!(ids(&reactor.query_windows(Some(space), false)), vec![
+        WindowId::new(1, 1)
+    ]);
+    let windows = reactor.query_windows(Some(space), true);
+    assert_eq!(ids(&windows), vec![WindowId::new(1, 1), minimized]);
+    assert!(!windows[0].info.is_minimized);
+    assert!(windows[1].info.is_minimized);
+
+    let active = |workspaces: Vec<WorkspaceData>| {
+        workspaces
+            .into_iter()
+            .find(|ws| ws.id == format!("{:?}", active_workspace))
+            .unwrap()
+    };
+    let workspace = active(reactor.query_workspaces(Some(space), false));
+    assert_eq!(ids(&workspace.windows), vec![WindowId::new(1, 1)]);
+    let workspace = active(reactor.query_workspaces(Some(space), true));
+    assert_eq!(ids(&workspace.windows), vec![WindowId::new(1, 1), minimized]);
+    assert!(workspace.windows[1].info.is_minimized);
+
+    reactor.handle_event(Event::WindowDeminiaturized(minimized));
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(reactor.window_manager.minimized_window_workspaces.is_empty());
+    let mut windows = ids(&reactor.query_windows(Some(space), false));
+    windows.sort();
+    assert_eq!(windows, vec![WindowId::new(1, 1), minimized]);
+}
+
+#[test]
+fn retile_space_moves_drifted_windows_back_into_their_tiles() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    // The app moved the window without rift hearing about the final frame.
+    let drifted = WindowId::new(1, 1);
+    let tile = reactor.window_manager.windows[&drifted].frame_monotonic;
+    let mut elsewhere = tile;
+    elsewhere.origin.y += 120.;
+    apps.windows.get_mut(&drifted).unwrap().frame = elsewhere;
+    reactor.window_manager.windows.get_mut(&drifted).unwrap().reported_frame = elsewhere;
+
+    let result = reactor.retile_space(Some(space)).expect("space is active");
+    assert_eq!(result.space_id, space.get());
+    assert_eq!(result.windows, 2);
     let requests = apps.requests();
-    for request in requests {
-        match request {
-            Request::GetVisibleWindows => {
-                // Simulate the login screen condition: No windows are
-                // considered visible by the accessibility API, but they are
-                // from the window server API in the event above.
-                reactor.handle_event(Event::WindowsDiscovered {
-                    pid: 1,
-                    new: vec![],
-                    known_visible: vec![],
-                });
-            }
-            req => {
-                let events = apps.simulate_events_for_requests(vec![req]);
-                for event in events {
-                    reactor.handle_event(event);
+    assert!(requests.iter().any(|request| matches!(
+        request,
+        Request::SetWindowFrame(wid, frame, _, _) if *wid == drifted && *frame == tile
+    )));
+    assert!(!requests.iter().any(|request| matches!(
+        request,
+        Request::SetWindowFrame(wid, _, _, _) if *wid != drifted
+    )));
+
+    assert!(reactor.retile_space(Some(SpaceId::new(99))).is_none());
+}
+
+#[test]
+fn window_transaction_query_reports_tx_record() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![full_screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let wsid = WindowServerId::new(1);
+    let data = reactor
+        .query_window_transaction(wsid)
+        .expect("laid out window should have a tx record");
+    let last_sent = reactor.transaction_manager.get_last_sent_txid(wsid);
+    assert_eq!(data.window_id, Some(WindowId::new(1, 1)));
+    assert_eq!(data.last_sent_txid, last_sent);
+    assert_eq!(data.last_seen_txid, Some(last_sent));
+    assert_eq!(
+        data.pending_target,
+        reactor.transaction_manager.get_target_frame(wsid)
+    );
+
+    assert!(reactor.query_window_transaction(WindowServerId::new(999)).is_none());
+}
+
+#[test]
+fn app_rule_opacity_is_applied_and_follows_title_changes() {
+    use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings};
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![AppWorkspaceRule {
+        app_id: Some("com.testapp1".into()),
+        workspace: None,
+        floating: false,
+        manage: true,
+        app_name: None,
+        title_regex: None,
+        title_substring: Some("translucent".into()),
+        ax_role: None,
+        ax_subrole: None,
+        display: None,
+        confine_to_display: false,
+        opacity: Some(0.9),
+        ..Default::default()
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.virtual_workspaces.reapply_app_rules_on_title_change = true;
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    let mut windows = make_windows(2);
+    windows[0].title = "translucent".into();
+    reactor.handle_events(apps.make_app(1, windows));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let translucent = WindowId::new(1, 1);
+    let plain = WindowId::new(1, 2);
+    assert_eq!(reactor.window_manager.windows[&translucent].opacity, Some(0.9));
+    assert_eq!(reactor.window_manager.windows[&plain].opacity, None);
+
+    reactor.handle_event(Event::WindowTitleChanged(translucent, "renamed".into()));
+    reactor.handle_event(Event::WindowTitleChanged(plain, "translucent too".into()));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(reactor.window_manager.windows[&translucent].opacity, None);
+    assert_eq!(reactor.window_manager.windows[&plain].opacity, Some(0.9));
+}
+
+#[test]
+fn refused_opacity_is_not_retried_for_the_app() {
+    use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings};
+    use crate::sys::window_server;
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![AppWorkspaceRule {
+        app_id: Some("com.testapp1".into()),
+        workspace: None,
+        floating: false,
+        manage: true,
+        app_name: None,
+        title_regex: None,
+        title_substring: None,
+        ax_role: None,
+        ax_subrole: None,
+        display: None,
+        confine_to_display: false,
+        opacity: Some(0.9),
+        ..Default::default()
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.virtual_workspaces.reapply_app_rules_on_title_change = true;
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    window_server::set_test_window_alpha_error(Some(objc2_core_graphics::CGError::IllegalArgument));
+    let calls_before = window_server::test_window_alpha_calls();
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    assert_eq!(window_server::test_window_alpha_calls() - calls_before, 1);
+    assert!(reactor.app_manager.opacity_refused_apps.contains(&1));
+    assert_eq!(
+        reactor.window_manager.windows[&WindowId::new(1, 1)].opacity,
+        None
+    );
+
+    reactor.handle_event(Event::WindowTitleChanged(WindowId::new(1, 2), "renamed".into()));
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(window_server::test_window_alpha_calls() - calls_before, 1);
+    window_server::set_test_window_alpha_error(None);
+
+    reactor.handle_event(Event::ApplicationThreadTerminated(1));
+    assert!(reactor.app_manager.opacity_refused_apps.is_empty());
+}
+
+#[test]
+fn app_rule_constraints_override_inferred_ones_and_follow_title_changes() {
+    use crate::common::config::{
+        AppWorkspaceRule, VirtualWorkspaceSettings, WindowConstraintOverrides,
+    };
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![AppWorkspaceRule {
+        app_id: Some("com.testapp1".into()),
+        title_substring: Some("Preview".into()),
+        constraints: Some(WindowConstraintOverrides {
+            max_width: Some(0.),
+            min_height: Some(200.),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    let mut windows = make_windows(1);
+    windows[0].title = "Preview".into();
+    windows[0].max_size = Some(CGSize::new(400., 300.));
+    reactor.handle_events(apps.make_app(1, windows));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let wid = WindowId::new(1, 1);
+    let constraints =
+        |reactor: &Reactor| reactor.layout_manager.layout_engine.window_layout_constraints(wid);
+    // The rule lifts the reported maximum width, raises the minimum height and
+    // leaves the reported maximum height alone.
+    let overridden = constraints(&reactor).unwrap();
+    assert_eq!(
+        (
+            overridden.max_width,
+            overridden.max_height,
+            overridden.min_height
+        ),
+        (0., 300., 200.)
+    );
+
+    // Title changes re-apply the override even without reapply_app_rules_on_title_change.
+    reactor.handle_event(Event::WindowTitleChanged(wid, "Document".into()));
+    let inferred = constraints(&reactor).unwrap();
+    assert_eq!(
+        (inferred.max_width, inferred.max_height, inferred.min_height),
+        (400., 300., 0.)
+    );
+}
+
+#[test]
+fn reapply_app_rules_command_applies_edited_rules_to_existing_windows() {
+    use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings};
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    let mut windows = make_windows(3);
+    windows[0].title = "palette".into();
+    windows[1].title = "scratch".into();
+    reactor.handle_events(apps.make_app(1, windows));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let palette = WindowId::new(1, 1);
+    let scratch = WindowId::new(1, 2);
+    let editor = WindowId::new(1, 3);
+    assert!(!reactor.layout_manager.layout_engine.is_window_floating(palette));
+
+    let rule = |title: &str, floating: bool, manage: bool| AppWorkspaceRule {
+        app_id: Some("com.testapp1".into()),
+        workspace: None,
+        floating,
+        manage,
+        app_name: None,
+        title_regex: None,
+        title_substring: Some(title.into()),
+        ax_role: None,
+        ax_subrole: None,
+        display: None,
+        confine_to_display: false,
+        opacity: None,
+        ..Default::default()
+    };
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![rule("palette", true, true), rule("scratch", false, false)];
+    reactor
+        .layout_manager
+        .layout_engine
+        .update_virtual_workspace_settings(&settings);
+
+    let changed = reactor.reapply_app_rules();
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(changed.changed_windows, 2);
+    assert!(reactor.layout_manager.layout_engine.is_window_floating(palette));
+    assert!(reactor.window_manager.windows[&scratch].ignore_app_rule);
+    let tiled = reactor
+        .layout_manager
+        .layout_engine
+        .windows_in_active_workspace(SpaceId::new(1));
+    assert!(!tiled.contains(&scratch));
+    assert!(tiled.contains(&editor));
+
+    // Dropping the rules tiles the floated window again and hands the unmanaged
+    // one back to the layout.
+    reactor
+        .layout_manager
+        .layout_engine
+        .update_virtual_workspace_settings(&VirtualWorkspaceSettings::default());
+    assert_eq!(reactor.reapply_app_rules().changed_windows, 2);
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(!reactor.layout_manager.layout_engine.is_window_floating(palette));
+    assert!(!reactor.window_manager.windows[&scratch].ignore_app_rule);
+    assert!(
+        reactor
+            .layout_manager
+            .layout_engine
+            .windows_in_active_workspace(SpaceId::new(1))
+            .contains(&scratch)
+    );
+    assert_eq!(reactor.reapply_app_rules().changed_windows, 0);
+}
+
+#[test]
+fn windows_that_ignore_moves_are_floated_in_place() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    let stuck = WindowId::new(1, 1);
+    let origin = apps.windows[&stuck].frame.origin;
+    apps.windows.get_mut(&stuck).unwrap().position_locked = true;
+    apps.simulate_until_quiet(&mut reactor);
+
+    let state = &reactor.window_manager.windows[&stuck];
+    assert!(state.position_locked);
+    assert!(state.frame_monotonic.origin.same_as(origin));
+    assert!(reactor.layout_manager.layout_engine.is_window_floating(stuck));
+    assert!(apps.windows[&stuck].frame.origin.same_as(origin));
+
+    let _ = reactor.update_layout_or_warn(false, false);
+    let moved = apps.requests().into_iter().any(|request| match request {
+        Request::SetWindowFrame(wid, ..) | Request::SetWindowPos(wid, ..) => wid == stuck,
+        Request::SetBatchWindowFrame(frames, _) => frames.iter().any(|&(wid, _)| wid == stuck),
+        _ => false,
+    });
+    assert!(!moved, "locked window should not be moved again");
+}
+
+#[test]
+fn a_window_that_refuses_one_move_is_not_locked() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    let stuck = WindowId::new(1, 1);
+    apps.windows.get_mut(&stuck).unwrap().position_locked = true;
+
+    // Let the window ignore the first move request only.
+    for _ in 0..10 {
+        if reactor.window_manager.windows[&stuck].move_refusals.is_some() {
+            break;
+        }
+        for event in apps.simulate_events() {
+            reactor.handle_event(event);
+        }
+    }
+    let state = &reactor.window_manager.windows[&stuck];
+    assert!(state.move_refusals.is_some());
+    assert!(!state.position_locked);
+
+    apps.windows.get_mut(&stuck).unwrap().position_locked = false;
+    apps.simulate_until_quiet(&mut reactor);
+    let state = &reactor.window_manager.windows[&stuck];
+    assert!(!state.position_locked);
+    assert!(state.move_refusals.is_none());
+    assert!(!reactor.layout_manager.layout_engine.is_window_floating(stuck));
+    assert!(apps.windows[&stuck].frame.same_as(state.frame_monotonic));
+}
+
+#[test]
+fn edge_resize_keeps_opposite_edge_anchored() {
+    use crate::common::config::{AppWorkspaceRule, VirtualWorkspaceSettings};
+
+    let mut settings = VirtualWorkspaceSettings::default();
+    settings.app_rules = vec![AppWorkspaceRule {
+        app_id: Some("com.testapp1".into()),
+        workspace: None,
+        floating: true,
+        manage: true,
+        app_name: None,
+        title_regex: None,
+        title_substring: None,
+        ax_role: None,
+        ax_subrole: None,
+        display: None,
+        confine_to_display: false,
+        opacity: None,
+        ..Default::default()
+    }];
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &settings,
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![full_screen],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(1)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let wid = WindowId::new(1, 1);
+    let start = reactor.window_manager.windows[&wid].frame_monotonic;
+    let mut resize = |reactor: &mut Reactor, cmd: ReactorCommand| {
+        reactor.handle_event(Event::Command(Command::Reactor(cmd)));
+        apps.simulate_until_quiet(reactor);
+        apps.windows[&wid].frame
+    };
+
+    resize(&mut reactor, ReactorCommand::BeginEdgeResize {
+        window_server_id: WindowServerId::new(1),
+        edge: Edge::Left,
+    });
+    resize(&mut reactor, ReactorCommand::EdgeResizeBy { delta: 30.0 });
+    let frame = resize(&mut reactor, ReactorCommand::EdgeResizeBy { delta: 20.0 });
+    assert_eq!(frame.size.width, start.size.width + 50.0);
+    assert_eq!(frame.max().x, start.max().x);
+    assert_eq!(frame.origin.y, start.origin.y);
+
+    resize(&mut reactor, ReactorCommand::EndEdgeResize);
+    let after_end = resize(&mut reactor, ReactorCommand::EdgeResizeBy { delta: 10.0 });
+    assert_eq!(after_end, frame);
+}
+
+#[test]
+fn it_retains_windows_without_server_ids_after_login_visibility_failure() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+
+    let window = WindowInfo {
+        is_standard: true,
+        is_root: true,
+        is_minimized: false,
+        is_resizable: true,
+        min_size: None,
+        max_size: None,
+        title: "NoServerId".to_string(),
+        frame: CGRect::new(CGPoint::new(50., 50.), CGSize::new(400., 400.)),
+        sys_id: None,
+        bundle_id: None,
+        path: None,
+        ax_role: None,
+        ax_subrole: None,
+    };
+
+    reactor.handle_events(apps.make_app_with_opts(
+        1,
+        vec![window],
+        Some(WindowId::new(1, 1)),
+        true,
+        false,
+    ));
+    apps.simulate_until_quiet(&mut reactor);
+
+    reactor.handle_event(Event::SpaceChanged(vec![None]));
+
+    // Simulate a native fullscreen transition: space temporarily becomes a fullscreen
+    // space id (reactor suppresses it to None), then returns to the original space.
+    let fullscreen_space = SpaceId::new(0x400000000 + space.get());
+    reactor.handle_event(Event::SpaceChanged(vec![Some(fullscreen_space)]));
+
+    reactor.handle_event(Event::SpaceChanged(vec![Some(space)]));
+
+    loop {
+        let requests = apps.requests();
+        if requests.is_empty() {
+            break;
+        }
+
+        let mut other_requests = Vec::new();
+        for request in requests {
+            match request {
+                Request::GetVisibleWindows => {
+                    reactor.handle_event(Event::WindowsDiscovered {
+                        pid: 1,
+                        new: vec![],
+                        known_visible: vec![],
+                    });
                 }
+                other => other_requests.push(other),
+            }
+        }
+
+        if !other_requests.is_empty() {
+            let events = apps.simulate_events_for_requests(other_requests);
+            for event in events {
+                reactor.handle_event(event);
             }
         }
     }
+}
+
+#[test]
+fn display_index_selector_uses_physical_left_to_right_order() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let right = CGRect::new(CGPoint::new(200000., 0.), CGSize::new(1000., 1000.));
+    let left = CGRect::new(CGPoint::new(100000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![right, left],
+        vec![Some(SpaceId::new(1)), Some(SpaceId::new(2))],
+        vec![],
+    ));
+
+    let selected = reactor
+        .screen_for_selector(&DisplaySelector::Index(0), None)
+        .expect("expected display index 0 to resolve");
+
+    assert_eq!(selected.frame, left);
+}
+
+#[test]
+fn display_direction_selector_resolves_by_geometry_in_l_shaped_arrangement() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    // A wide main display, a portrait display to its right that reaches further
+    // down, and a laptop below the main display:
+    //
+    //   +-----------+----+
+    //   |   main    |    |
+    //   +--------+--+ tall
+    //   | laptop |  |    |
+    //   +--------+  +----+
+    let main = CGRect::new(CGPoint::new(0., 0.), CGSize::new(2560., 1440.));
+    let tall = CGRect::new(CGPoint::new(2560., 0.), CGSize::new(1080., 1920.));
+    let laptop = CGRect::new(CGPoint::new(0., 1440.), CGSize::new(1920., 1080.));
+    reactor.handle_event(screen_params_event(
+        vec![tall, laptop, main],
+        vec![
+            Some(SpaceId::new(1)),
+            Some(SpaceId::new(2)),
+            Some(SpaceId::new(3)),
+        ],
+        vec![],
+    ));
+
+    let resolve = |reactor: &Reactor, from: CGRect, direction| {
+        reactor
+            .screen_for_selector(&DisplaySelector::Direction(direction), Some(from.mid()))
+            .map(|s| s.frame)
+    };
+
+    // The tall display's edge is closer to the laptop's center, but it is only
+    // diagonally up from it.
+    assert_eq!(resolve(&reactor, laptop, Direction::Up), Ok(main));
+    assert_eq!(resolve(&reactor, main, Direction::Down), Ok(laptop));
+    assert_eq!(resolve(&reactor, tall, Direction::Left), Ok(main));
+    assert_eq!(resolve(&reactor, main, Direction::Right), Ok(tall));
+    assert_eq!(resolve(&reactor, laptop, Direction::Right), Ok(tall));
+    assert_eq!(
+        resolve(&reactor, main, Direction::Up),
+        Err(DisplaySelectorError::NoDisplayInDirection(Direction::Up))
+    );
+}
+
+#[test]
+fn display_index_selector_rejects_past_the_end_unless_wrapping() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let middle = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    let right = CGRect::new(CGPoint::new(2000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![left, middle, right],
+        vec![
+            Some(SpaceId::new(1)),
+            Some(SpaceId::new(2)),
+            Some(SpaceId::new(3)),
+        ],
+        vec![],
+    ));
+
+    let frame_at = |reactor: &Reactor, index| {
+        reactor
+            .screen_for_selector(&DisplaySelector::Index(index), None)
+            .map(|s| s.frame)
+    };
+
+    assert_eq!(frame_at(&reactor, 0), Ok(left));
+    assert_eq!(frame_at(&reactor, 2), Ok(right));
+    let err = frame_at(&reactor, 3).unwrap_err();
+    assert_eq!(err, DisplaySelectorError::IndexOutOfRange { index: 3, count: 3 });
+    assert_eq!(err.code(), "display_index_out_of_range");
+
+    reactor.config.settings.display_index_wraps = true;
+    assert_eq!(frame_at(&reactor, 0), Ok(left));
+    assert_eq!(frame_at(&reactor, 2), Ok(right));
+    assert_eq!(frame_at(&reactor, 3), Ok(left));
+    assert_eq!(frame_at(&reactor, 7), Ok(middle));
+}
+
+#[test]
+fn focus_display_does_not_warp_when_cursor_warp_is_disabled() {
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (event_tap_tx, mut event_tap_rx) = actor::channel();
+    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
+    let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let right = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![left, right],
+        vec![Some(SpaceId::new(1)), Some(SpaceId::new(2))],
+        vec![],
+    ));
+
+    let mut focus_display_warped = |reactor: &mut Reactor| {
+        while event_tap_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::FocusDisplay(
+            DisplaySelector::Index(1),
+        ))));
+        std::iter::from_fn(|| event_tap_rx.try_recv().ok())
+            .any(|(_, request)| matches!(request, crate::actor::event_tap::Request::Warp(_)))
+    };
+
+    reactor.config.settings.disable_cursor_warp = true;
+    assert!(!focus_display_warped(&mut reactor));
+
+    reactor.config.settings.disable_cursor_warp = false;
+    reactor.config.settings.disable_cursor_warp_displays = vec!["test-display-1".into()];
+    assert!(!focus_display_warped(&mut reactor));
+
+    reactor.config.settings.disable_cursor_warp_displays.clear();
+    assert!(focus_display_warped(&mut reactor));
+}
+
+#[test]
+fn focus_largest_window_picks_biggest_frame_then_most_recent() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(3)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let set_size = |reactor: &mut Reactor, idx: u32, width: f64, height: f64| {
+        let window = reactor.window_manager.windows.get_mut(&WindowId::new(1, idx)).unwrap();
+        window.frame_monotonic.size = CGSize::new(width, height);
+    };
+    set_size(&mut reactor, 1, 100., 100.);
+    set_size(&mut reactor, 2, 400., 300.);
+    set_size(&mut reactor, 3, 200., 200.);
+
+    let mut focus_largest = |reactor: &mut Reactor| {
+        while raise_manager_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Layout(
+            LayoutCommand::FocusLargestWindow,
+        )));
+        std::iter::from_fn(|| raise_manager_rx.try_recv().ok()).find_map(|(_, msg)| match msg {
+            raise_manager::Event::RaiseRequest(RaiseRequest { focus_window, .. }) => {
+                focus_window.map(|(wid, _)| wid)
+            }
+            _ => None,
+        })
+    };
+    assert_eq!(focus_largest(&mut reactor), Some(WindowId::new(1, 2)));
+
+    // Equal areas go to whichever window was focused last.
+    set_size(&mut reactor, 3, 300., 400.);
+    assert_eq!(focus_largest(&mut reactor), Some(WindowId::new(1, 2)));
+    reactor.send_layout_event(LayoutEvent::WindowFocused(space, WindowId::new(1, 3)));
+    assert_eq!(focus_largest(&mut reactor), Some(WindowId::new(1, 3)));
+}
+
+#[test]
+fn focus_spatial_visits_windows_in_reading_order() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    let start = WindowId::new(1, 2);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(4), Some(start), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
     apps.simulate_until_quiet(&mut reactor);
 
+    // A 2x2 grid whose window ids don't follow their positions; the top-right
+    // window sits slightly lower than its row neighbour.
+    let grid = [
+        (1, CGPoint::new(500., 500.)),
+        (2, CGPoint::new(0., 0.)),
+        (3, CGPoint::new(0., 500.)),
+        (4, CGPoint::new(500., 4.)),
+    ];
+    let mut focus_spatial = |reactor: &mut Reactor, command: LayoutCommand| {
+        for (idx, origin) in grid {
+            let window = reactor.window_manager.windows.get_mut(&WindowId::new(1, idx)).unwrap();
+            window.frame_monotonic = CGRect::new(origin, CGSize::new(500., 496.));
+        }
+        while raise_manager_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Layout(command)));
+        let focused = std::iter::from_fn(|| raise_manager_rx.try_recv().ok()).find_map(
+            |(_, msg)| match msg {
+                raise_manager::Event::RaiseRequest(RaiseRequest { focus_window, .. }) => {
+                    focus_window.map(|(wid, _)| wid)
+                }
+                _ => None,
+            },
+        );
+        reactor.handle_event(Event::ApplicationMainWindowChanged(1, focused, Quiet::No));
+        focused.map(|wid| wid.idx.get())
+    };
+
+    let visited: Vec<_> = (0..4)
+        .map(|_| focus_spatial(&mut reactor, LayoutCommand::FocusNextSpatial))
+        .collect();
+    assert_eq!(visited, vec![Some(4), Some(3), Some(1), Some(2)]);
     assert_eq!(
-        reactor.layout_manager.layout_engine.calculate_layout(
-            space,
-            full_screen,
-            &reactor.config.settings.layout.gaps,
-            0.0,
-            crate::common::config::HorizontalPlacement::Top,
-            crate::common::config::VerticalPlacement::Right,
-        ),
-        modified
+        focus_spatial(&mut reactor, LayoutCommand::FocusPrevSpatial),
+        Some(1)
+    );
+    assert_eq!(
+        focus_spatial(&mut reactor, LayoutCommand::FocusPrevSpatial),
+        Some(3)
+    );
+}
+
+#[test]
+fn event_log_keeps_newest_commands_and_events_with_responses() {
+    use crate::model::server::EventLogKind;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(reactor.query_event_log(None).is_none());
+
+    let mut config = reactor.config.clone();
+    config.settings.event_log_size = 3;
+    reactor.handle_event(Event::ConfigUpdated(config));
+
+    let command = |cmd| Event::Command(Command::Layout(cmd));
+    reactor.handle_event(command(LayoutCommand::FocusNextWindow));
+    // Handled by the reactor, which then reports the focus change as an event.
+    reactor.handle_event(command(LayoutCommand::FocusLargestWindow));
+
+    let log = reactor.query_event_log(None).unwrap();
+    let kinds: Vec<_> = log.iter().map(|entry| entry.kind).collect();
+    assert_eq!(kinds, vec![
+        EventLogKind::Command,
+        EventLogKind::Command,
+        EventLogKind::Event
+    ]);
+    assert_eq!(log[0].description, "FocusNextWindow");
+    assert!(log[0].response.as_ref().unwrap().focus_window.is_some());
+    assert_eq!(log[1].description, "FocusLargestWindow");
+    assert!(log[1].response.is_none());
+    assert!(log[2].description.starts_with("WindowFocused"));
+
+    reactor.handle_event(command(LayoutCommand::FocusNextWindow));
+    let log = reactor.query_event_log(None).unwrap();
+    assert_eq!(log.len(), 3);
+    assert_eq!(log[0].description, "FocusLargestWindow");
+    let newest = reactor.query_event_log(Some(1)).unwrap();
+    assert_eq!(newest.len(), 1);
+    assert_eq!(newest[0].description, "FocusNextWindow");
+}
+
+#[test]
+fn window_title_visibility_is_toggled_and_cleared_on_destroy() {
+    use crate::actor::stack_line;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (stack_line_tx, mut stack_line_rx) = actor::channel();
+    reactor.communication_manager.stack_line_tx = Some(stack_line_tx);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    let wid = WindowId::new(1, 1);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(2), Some(wid), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let mut title_events = || {
+        std::iter::from_fn(|| stack_line_rx.try_recv().ok())
+            .filter_map(|(_, event)| match event {
+                stack_line::Event::WindowTitleVisibilityChanged { window_id, visible } => {
+                    Some((window_id, visible))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    title_events();
+
+    let toggle = || Event::Command(Command::Layout(LayoutCommand::ToggleWindowTitleVisible));
+    reactor.handle_event(toggle());
+    assert!(reactor.window_manager.hidden_stack_line_titles.contains(&wid));
+    assert_eq!(title_events(), vec![(wid, false)]);
+
+    reactor.handle_event(toggle());
+    assert!(reactor.window_manager.hidden_stack_line_titles.is_empty());
+    assert_eq!(title_events(), vec![(wid, true)]);
+
+    reactor.handle_event(toggle());
+    title_events();
+    reactor.handle_event(Event::WindowDestroyed(wid));
+    assert!(reactor.window_manager.hidden_stack_line_titles.is_empty());
+    assert_eq!(title_events(), vec![(wid, true)]);
+}
+
+#[test]
+fn toggle_space_activated_focuses_first_window_on_activate() {
+    use crate::common::config::SpaceActivationFocus;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+    reactor.config.settings.space_activation_focus = SpaceActivationFocus::FocusFirstWindow;
+
+    let mut toggle_focus_request = |reactor: &mut Reactor| {
+        while raise_manager_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Reactor(
+            ReactorCommand::ToggleSpaceActivated,
+        )));
+        std::iter::from_fn(|| raise_manager_rx.try_recv().ok()).find_map(|(_, msg)| match msg {
+            raise_manager::Event::RaiseRequest(RaiseRequest { focus_window, .. }) => focus_window,
+            _ => None,
+        })
+    };
+
+    assert_eq!(toggle_focus_request(&mut reactor), None);
+    assert!(!reactor.is_space_active(space));
+
+    let focused = toggle_focus_request(&mut reactor).map(|(wid, _)| wid);
+    assert!(reactor.is_space_active(space));
+    assert!(
+        focused.is_some_and(|wid| wid.pid == 1),
+        "expected a window of the activated space to be focused, got {focused:?}"
     );
+
+    reactor.config.settings.space_activation_focus = SpaceActivationFocus::None;
+    toggle_focus_request(&mut reactor);
+    assert_eq!(toggle_focus_request(&mut reactor), None);
 }
 
 #[test]
-fn menu_open_state_is_cleared_when_owner_deactivates() {
+fn focus_window_on_inactive_space_activates_it_when_configured() {
+    use crate::common::config::InactiveSpaceFocus;
+
+    let mut apps = Apps::new();
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
         &crate::common::config::VirtualWorkspaceSettings::default(),
         &crate::common::config::LayoutSettings::default(),
         None,
     ));
-    let (event_tap_tx, mut event_tap_rx) = actor::channel();
-    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
-
-    reactor.handle_event(Event::MenuOpened(1));
-    let disable = event_tap_rx.try_recv().expect("menu-open should update event tap").1;
-    assert!(matches!(
-        disable,
-        crate::actor::event_tap::Request::SetFocusFollowsMouseEnabled(false)
+    let (raise_manager_tx, mut raise_manager_rx) = actor::channel();
+    reactor.communication_manager.raise_manager_tx = raise_manager_tx;
+    let space = SpaceId::new(1);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(space)],
+        vec![],
     ));
-    assert_eq!(reactor.menu_manager.menu_state, MenuState::Open(1));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+    reactor.handle_event(Event::Command(Command::Reactor(
+        ReactorCommand::ToggleSpaceActivated,
+    )));
+    assert!(!reactor.is_space_active(space));
+
+    let target = WindowId::new(1, 2);
+    let mut focus_request = |reactor: &mut Reactor| {
+        while raise_manager_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::FocusWindow {
+            window_id: target,
+            window_server_id: None,
+        })));
+        std::iter::from_fn(|| raise_manager_rx.try_recv().ok()).find_map(|(_, msg)| match msg {
+            raise_manager::Event::RaiseRequest(RaiseRequest { focus_window, .. }) => focus_window,
+            _ => None,
+        })
+    };
 
-    reactor.handle_event(Event::ApplicationDeactivated(1));
-    let enable = event_tap_rx
-        .try_recv()
-        .expect("app deactivation should re-enable focus-follows-mouse")
-        .1;
-    assert!(matches!(
-        enable,
-        crate::actor::event_tap::Request::SetFocusFollowsMouseEnabled(true)
-    ));
-    assert_eq!(reactor.menu_manager.menu_state, MenuState::Closed);
+    assert_eq!(focus_request(&mut reactor), None);
+    assert!(!reactor.is_space_active(space));
+
+    reactor.config.settings.inactive_space_focus = InactiveSpaceFocus::Activate;
+    assert_eq!(focus_request(&mut reactor).map(|(wid, _)| wid), Some(target));
+    assert!(reactor.is_space_active(space));
 }
 
 #[test]
-fn stale_menu_open_state_is_cleared_when_other_app_activates() {
+fn evacuate_display_moves_windows_to_primary_keeping_workspace_indices() {
+    let mut apps = Apps::new();
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
         &crate::common::config::VirtualWorkspaceSettings::default(),
         &crate::common::config::LayoutSettings::default(),
         None,
     ));
-    let (event_tap_tx, mut event_tap_rx) = actor::channel();
-    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
+    let primary = SpaceId::new(1);
+    let secondary = SpaceId::new(2);
+    let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let right = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![left, right],
+        vec![Some(primary), Some(secondary)],
+        vec![],
+    ));
+    let mut windows = make_windows(3);
+    for window in &mut windows {
+        window.frame.origin = CGPoint::new(1100., 100.);
+    }
+    reactor.handle_events(apps.make_app(1, windows));
+    apps.simulate_until_quiet(&mut reactor);
 
-    reactor.handle_event(Event::MenuOpened(1));
-    let _ = event_tap_rx.try_recv().expect("menu-open should update event tap");
-    assert_eq!(reactor.menu_manager.menu_state, MenuState::Open(1));
+    let workspace_index = |reactor: &mut Reactor, space: SpaceId, wid: WindowId| {
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+        let ws_id = vwm.workspace_for_window(space, wid)?;
+        vwm.list_workspaces(space).iter().position(|(id, _)| *id == ws_id)
+    };
+    let parked = WindowId::new(1, 3);
+    {
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager_mut();
+        let second_workspace = vwm.list_workspaces(secondary)[1].0;
+        assert!(vwm.assign_window_to_workspace(secondary, parked, second_workspace));
+    }
 
-    reactor.handle_event(Event::ApplicationGloballyActivated(2));
-    let enable = event_tap_rx
-        .try_recv()
-        .expect("activation of another app should clear stale menu state")
-        .1;
-    assert!(matches!(
-        enable,
-        crate::actor::event_tap::Request::SetFocusFollowsMouseEnabled(true)
+    // The primary display is the default destination, so evacuating it is a no-op.
+    let moved = CommandEventHandler::handle_command_reactor_evacuate_display(
+        &mut reactor,
+        &DisplaySelector::Index(0),
+        None,
+    );
+    assert_eq!(moved, 0);
+    assert_eq!(workspace_index(&mut reactor, secondary, parked), Some(1));
+
+    let moved = CommandEventHandler::handle_command_reactor_evacuate_display(
+        &mut reactor,
+        &DisplaySelector::Index(1),
+        None,
+    );
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(moved, 3);
+
+    for idx in 1..=3 {
+        assert_eq!(
+            workspace_index(&mut reactor, secondary, WindowId::new(1, idx)),
+            None
+        );
+    }
+    assert_eq!(
+        workspace_index(&mut reactor, primary, WindowId::new(1, 1)),
+        Some(0)
+    );
+    assert_eq!(
+        workspace_index(&mut reactor, primary, WindowId::new(1, 2)),
+        Some(0)
+    );
+    assert_eq!(workspace_index(&mut reactor, primary, parked), Some(1));
+    assert!(apps.windows[&WindowId::new(1, 1)].frame.origin.x < 1000.);
+
+    // Moving windows in bulk must not make each one the focus of its new workspace.
+    let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+    for (ws_id, _) in vwm.list_workspaces(primary) {
+        assert_eq!(vwm.last_focused_window(primary, ws_id), None);
+    }
+}
+
+#[test]
+fn move_window_to_display_resolves_direction_from_explicit_origin() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
     ));
-    assert_eq!(reactor.menu_manager.menu_state, MenuState::Closed);
+    let spaces = [SpaceId::new(1), SpaceId::new(2), SpaceId::new(3)];
+    let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let middle = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    let right = CGRect::new(CGPoint::new(2000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![left, middle, right],
+        spaces.iter().copied().map(Some).collect(),
+        vec![],
+    ));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let mut move_right = |reactor: &mut Reactor, idx, origin| {
+        reactor.handle_event(Event::Command(Command::Reactor(
+            ReactorCommand::MoveWindowToDisplay {
+                selector: DisplaySelector::Direction(Direction::Right),
+                window_id: Some(idx),
+                origin,
+            },
+        )));
+        apps.simulate_until_quiet(reactor);
+    };
+    let space_of = |reactor: &Reactor, wid| {
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+        spaces.into_iter().find(|&space| vwm.workspace_for_window(space, wid).is_some())
+    };
+
+    // Both windows start on the left display; "right" normally means the middle
+    // one, but resolved from the middle display it means the right one.
+    move_right(&mut reactor, 1, Some(DisplaySelector::Index(1)));
+    move_right(&mut reactor, 2, None);
+    assert_eq!(space_of(&reactor, WindowId::new(1, 1)), Some(spaces[2]));
+    assert_eq!(space_of(&reactor, WindowId::new(1, 2)), Some(spaces[1]));
 }
 
 #[test]
-fn it_retains_windows_without_server_ids_after_login_visibility_failure() {
+fn nudge_window_floats_tiled_window_and_keeps_it_on_its_display() {
     let mut apps = Apps::new();
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
         &crate::common::config::VirtualWorkspaceSettings::default(),
@@ -516,89 +3538,217 @@ fn it_retains_windows_without_server_ids_after_login_visibility_failure() {
     let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
     reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
 
-    let window = WindowInfo {
-        is_standard: true,
-        is_root: true,
-        is_minimized: false,
-        is_resizable: true,
-        min_size: None,
-        max_size: None,
-        title: "NoServerId".to_string(),
-        frame: CGRect::new(CGPoint::new(50., 50.), CGSize::new(400., 400.)),
-        sys_id: None,
-        bundle_id: None,
-        path: None,
-        ax_role: None,
-        ax_subrole: None,
+    let wid = WindowId::new(1, 1);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(2), Some(wid), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+    let tiled_frame = reactor.window_manager.windows[&wid].frame_monotonic;
+
+    let nudge = |reactor: &mut Reactor, apps: &mut Apps, dx, dy| {
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::NudgeWindow {
+            dx,
+            dy,
+        })));
+        apps.simulate_until_quiet(reactor);
+        apps.windows[&wid].frame
     };
 
-    reactor.handle_events(apps.make_app_with_opts(
-        1,
-        vec![window],
-        Some(WindowId::new(1, 1)),
-        true,
-        false,
+    let mut expected = tiled_frame;
+    expected.origin.x += 30.;
+    expected.origin.y += 20.;
+    assert!(nudge(&mut reactor, &mut apps, 30., 20.).same_as(expected));
+    assert!(reactor.layout_manager.layout_engine.is_window_floating(wid));
+    assert!(reactor.window_manager.windows[&wid].frame_monotonic.same_as(expected));
+    assert_eq!(
+        apps.windows[&WindowId::new(1, 2)].frame,
+        full_screen,
+        "the remaining tiled window should take the whole display"
+    );
+
+    // Only 40 points of the window stay on screen however far it is pushed.
+    expected.origin.x = 40. - expected.size.width;
+    expected.origin.y = 960.;
+    assert!(nudge(&mut reactor, &mut apps, -5000., 5000.).same_as(expected));
+}
+
+#[test]
+fn balance_displays_evens_out_window_counts() {
+    use crate::common::config::DisplayBalance;
+    use crate::sys::geometry::CGRectExt;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let right = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(3000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![left, right],
+        vec![Some(SpaceId::new(1)), Some(SpaceId::new(2))],
+        vec![],
     ));
+    let mut windows = make_windows(4);
+    for window in &mut windows {
+        window.frame.origin = CGPoint::new(1100., 100.);
+    }
+    reactor.handle_events(apps.make_app(1, windows));
     apps.simulate_until_quiet(&mut reactor);
 
-    reactor.handle_event(Event::SpaceChanged(vec![None]));
+    let (before, after) =
+        CommandEventHandler::handle_command_reactor_balance_displays(&mut reactor);
+    apps.simulate_until_quiet(&mut reactor);
+    assert_eq!(before, vec![0, 4]);
+    assert_eq!(after, vec![2, 2]);
+    let on_left = (1..=4)
+        .filter(|&idx| left.contains(apps.windows[&WindowId::new(1, idx)].frame.mid()))
+        .count();
+    assert_eq!(on_left, 2);
+
+    // Weighted by area the right display is three times the size of the left one.
+    reactor.config.settings.display_balance = DisplayBalance::Area;
+    let (before, after) =
+        CommandEventHandler::handle_command_reactor_balance_displays(&mut reactor);
+    assert_eq!(before, vec![2, 2]);
+    assert_eq!(after, vec![1, 3]);
+}
 
-    // Simulate a native fullscreen transition: space temporarily becomes a fullscreen
-    // space id (reactor suppresses it to None), then returns to the original space.
-    let fullscreen_space = SpaceId::new(0x400000000 + space.get());
-    reactor.handle_event(Event::SpaceChanged(vec![Some(fullscreen_space)]));
+#[test]
+fn unplugged_display_windows_move_to_primary_and_return_on_reconnect() {
+    use crate::common::config::DisplayGravity;
+    use crate::sys::geometry::CGRectExt;
 
-    reactor.handle_event(Event::SpaceChanged(vec![Some(space)]));
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.display_gravity = DisplayGravity::Primary;
+    let primary = SpaceId::new(1);
+    let secondary = SpaceId::new(2);
+    let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let right = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(
+        vec![left, right],
+        vec![Some(primary), Some(secondary)],
+        vec![],
+    ));
+    let mut windows = make_windows(2);
+    for window in &mut windows {
+        window.frame.origin = CGPoint::new(1100., 100.);
+    }
+    reactor.handle_events(apps.make_app(1, windows));
+    apps.simulate_until_quiet(&mut reactor);
 
-    loop {
-        let requests = apps.requests();
-        if requests.is_empty() {
-            break;
-        }
+    let space_of = |reactor: &Reactor, wid: WindowId| {
+        let vwm = reactor.layout_manager.layout_engine.virtual_workspace_manager();
+        [primary, secondary]
+            .into_iter()
+            .find(|space| vwm.workspace_for_window(*space, wid).is_some())
+    };
+    let windows = [WindowId::new(1, 1), WindowId::new(1, 2)];
+    for wid in windows {
+        assert_eq!(space_of(&reactor, wid), Some(secondary));
+    }
 
-        let mut other_requests = Vec::new();
-        for request in requests {
-            match request {
-                Request::GetVisibleWindows => {
-                    reactor.handle_event(Event::WindowsDiscovered {
-                        pid: 1,
-                        new: vec![],
-                        known_visible: vec![],
-                    });
-                }
-                other => other_requests.push(other),
-            }
-        }
+    reactor.handle_event(screen_params_event(vec![left], vec![Some(primary)], vec![]));
+    apps.simulate_until_quiet(&mut reactor);
+    for wid in windows {
+        assert_eq!(space_of(&reactor, wid), Some(primary));
+        assert!(left.contains(apps.windows[&wid].frame.mid()));
+    }
 
-        if !other_requests.is_empty() {
-            let events = apps.simulate_events_for_requests(other_requests);
-            for event in events {
-                reactor.handle_event(event);
-            }
-        }
+    reactor.handle_event(screen_params_event(
+        vec![left, right],
+        vec![Some(primary), Some(secondary)],
+        vec![],
+    ));
+    apps.simulate_until_quiet(&mut reactor);
+    for wid in windows {
+        assert_eq!(space_of(&reactor, wid), Some(secondary));
     }
+    assert!(reactor.space_manager.displaced_windows.is_empty());
 }
 
 #[test]
-fn display_index_selector_uses_physical_left_to_right_order() {
+fn cycle_displays_advances_in_index_order_skipping_inactive_spaces() {
+    use crate::sys::geometry::CGRectExt;
+
     let mut reactor = Reactor::new_for_test(LayoutEngine::new(
         &crate::common::config::VirtualWorkspaceSettings::default(),
         &crate::common::config::LayoutSettings::default(),
         None,
     ));
-    let right = CGRect::new(CGPoint::new(200000., 0.), CGSize::new(1000., 1000.));
-    let left = CGRect::new(CGPoint::new(100000., 0.), CGSize::new(1000., 1000.));
-    reactor.handle_event(screen_params_event(
-        vec![right, left],
-        vec![Some(SpaceId::new(1)), Some(SpaceId::new(2))],
-        vec![],
+    let (event_tap_tx, mut event_tap_rx) = actor::channel();
+    reactor.communication_manager.event_tap_tx = Some(event_tap_tx);
+    let left = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    let middle = CGRect::new(CGPoint::new(1000., 0.), CGSize::new(1000., 1000.));
+    let right = CGRect::new(CGPoint::new(2000., 0.), CGSize::new(1000., 1000.));
+    let spaces = (1..=3).map(|id| Some(SpaceId::new(id))).collect();
+    reactor.handle_event(screen_params_event(vec![middle, right, left], spaces, vec![]));
+    reactor.set_active_spaces(&[Some(SpaceId::new(1)), None, Some(SpaceId::new(3))]);
+    reactor.space_manager.last_cycled_display = Some(0);
+
+    let mut cycle = |reactor: &mut Reactor| {
+        while event_tap_rx.try_recv().is_ok() {}
+        reactor.handle_event(Event::Command(Command::Reactor(ReactorCommand::CycleDisplays {
+            move_mouse: true,
+        })));
+        std::iter::from_fn(|| event_tap_rx.try_recv().ok()).find_map(|(_, request)| match request {
+            crate::actor::event_tap::Request::Warp(point) => Some(point),
+            _ => None,
+        })
+    };
+
+    // Index order is physical (left to right); the right display's space is inactive.
+    assert_eq!(cycle(&mut reactor), Some(middle.mid()));
+    assert_eq!(reactor.space_manager.last_cycled_display, Some(1));
+    assert_eq!(cycle(&mut reactor), Some(left.mid()));
+    assert_eq!(reactor.space_manager.last_cycled_display, Some(0));
+    assert_eq!(cycle(&mut reactor), Some(middle.mid()));
+}
+
+#[test]
+fn reprobe_all_constraints_clears_constraints_and_refreshes_apps() {
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &crate::common::config::VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
     ));
+    let space = SpaceId::new(1);
+    let full_screen = CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.));
+    reactor.handle_event(screen_params_event(vec![full_screen], vec![Some(space)], vec![]));
+    reactor.handle_events(apps.make_app(1, make_windows(2)));
+    apps.simulate_until_quiet(&mut reactor);
+    let _ = apps.requests();
 
-    let selected = reactor
-        .screen_for_selector(&DisplaySelector::Index(0), None)
-        .expect("expected display index 0 to resolve");
+    let wid = WindowId::new(1, 1);
+    assert!(reactor.layout_manager.layout_engine.window_layout_constraints(wid).is_some());
 
-    assert_eq!(selected.frame, left);
+    reactor.handle_event(Event::Command(Command::Reactor(
+        ReactorCommand::ReprobeAllConstraints,
+    )));
+    assert!(reactor.layout_manager.layout_engine.window_layout_constraints(wid).is_none());
+    let requests = apps.requests();
+    assert!(
+        requests.iter().any(|request| matches!(request, Request::GetVisibleWindows)),
+        "apps should be asked to re-report their windows"
+    );
+
+    // Windows on a space rift isn't managing aren't re-read, so they keep theirs.
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(reactor.layout_manager.layout_engine.window_layout_constraints(wid).is_some());
+    reactor.handle_event(Event::Command(Command::Reactor(
+        ReactorCommand::ToggleSpaceActivated,
+    )));
+    assert!(!reactor.is_space_active(space));
+    reactor.handle_event(Event::Command(Command::Reactor(
+        ReactorCommand::ReprobeAllConstraints,
+    )));
+    assert!(reactor.layout_manager.layout_engine.window_layout_constraints(wid).is_some());
 }
 
 #[test]
@@ -705,3 +3855,51 @@ fn topology_relayout_pending_when_space_ids_change_for_same_displays() {
         "Space-id churn on unchanged displays should trigger topology relayout"
     );
 }
+
+#[test]
+fn focus_border_follows_the_focused_window_and_hides_on_destroy() {
+    use crate::actor::focus_border;
+
+    let mut apps = Apps::new();
+    let mut reactor = Reactor::new_for_test(LayoutEngine::new(
+        &VirtualWorkspaceSettings::default(),
+        &crate::common::config::LayoutSettings::default(),
+        None,
+    ));
+    reactor.config.settings.ui.focus_border.enabled = true;
+    let (focus_border_tx, mut focus_border_rx) = actor::channel();
+    reactor.communication_manager.focus_border_tx = Some(focus_border_tx);
+    reactor.handle_event(screen_params_event(
+        vec![CGRect::new(CGPoint::new(0., 0.), CGSize::new(1000., 1000.))],
+        vec![Some(SpaceId::new(1))],
+        vec![],
+    ));
+    let wid = WindowId::new(1, 1);
+    reactor.handle_events(apps.make_app_with_opts(1, make_windows(2), Some(wid), true, true));
+    reactor.handle_event(Event::ApplicationGloballyActivated(1));
+    apps.simulate_until_quiet(&mut reactor);
+
+    let mut last_border_event = || {
+        std::iter::from_fn(|| focus_border_rx.try_recv().ok())
+            .map(|(_, event)| event)
+            .last()
+    };
+    let frame_of = |reactor: &Reactor, wid| reactor.window_manager.windows[&wid].frame_monotonic;
+
+    let Some(focus_border::Event::Show { frame }) = last_border_event() else {
+        panic!("expected the focus border to be shown");
+    };
+    assert!(frame.same_as(frame_of(&reactor, wid)));
+
+    reactor.handle_event(Event::WindowDestroyed(WindowId::new(1, 2)));
+    apps.simulate_until_quiet(&mut reactor);
+    let Some(focus_border::Event::Show { frame }) = last_border_event() else {
+        panic!("expected the focus border to follow the resized window");
+    };
+    assert!(frame.same_as(frame_of(&reactor, wid)));
+
+    reactor.handle_event(Event::WindowDestroyed(wid));
+    apps.simulate_until_quiet(&mut reactor);
+    assert!(matches!(last_border_event(), Some(focus_border::Event::Hide)));
+    assert_eq!(reactor.communication_manager.focus_border_frame, None);
+}
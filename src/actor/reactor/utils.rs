@@ -8,14 +8,15 @@ use crate::sys::window_server::{WindowServerId, WindowServerInfo, window_is_stic
 /// A window is manageable if:
 /// - It is not minimized
 /// - Its layer is 0 (if info available)
-/// - It is not sticky
+/// - It is not sticky, per its window server tags or the spaces it is on
 /// - Its level is normal (if available)
-/// - It is AX standard and AX root
+/// - It is AX root and either AX standard or of an auto-float subrole (e.g. a dialog)
 pub fn compute_window_manageability(
     window_server_id: Option<WindowServerId>,
     is_minimized: bool,
     is_ax_standard: bool,
     is_ax_root: bool,
+    is_auto_float: bool,
     window_server_info: &HashMap<WindowServerId, WindowServerInfo>,
 ) -> bool {
     if is_minimized {
@@ -24,7 +25,7 @@ pub fn compute_window_manageability(
 
     if let Some(wsid) = window_server_id {
         if let Some(info) = window_server_info.get(&wsid) {
-            if info.layer != 0 {
+            if info.layer != 0 || info.sticky {
                 return false;
             }
         }
@@ -38,5 +39,5 @@ pub fn compute_window_manageability(
             }
         }
     }
-    is_ax_standard && is_ax_root
+    (is_ax_standard || is_auto_float) && is_ax_root
 }
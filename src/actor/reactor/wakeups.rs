@@ -0,0 +1,133 @@
+use std::time::Instant;
+
+use super::Event;
+use crate::actor::{self, reactor};
+use crate::sys::timer::Timer;
+
+/// Deadlines the reactor can ask to be woken up for. Each has its own one-shot
+/// timer, so nothing ticks while there is nothing to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wakeup {
+    /// The next frame poll for apps listed in `settings.frame_poll` is due.
+    FramePoll,
+    /// A pending manageability change has had time to settle.
+    Manageability,
+    /// A held external move's grace period has run out.
+    ExternalMoves,
+}
+
+impl Wakeup {
+    fn event(self) -> Event {
+        match self {
+            Wakeup::FramePoll => Event::PollWindowFrames,
+            Wakeup::Manageability => Event::SettleManageability,
+            Wakeup::ExternalMoves => Event::SettleExternalMoves,
+        }
+    }
+}
+
+pub type Sender = actor::Sender<(Wakeup, Instant)>;
+type Receiver = actor::Receiver<(Wakeup, Instant)>;
+
+/// Tracks the deadline each timer is armed for, so the timer task only hears
+/// about changes.
+#[derive(Default)]
+pub struct WakeupScheduler {
+    tx: Option<Sender>,
+    frame_poll: Option<Instant>,
+    manageability: Option<Instant>,
+    external_moves: Option<Instant>,
+}
+
+impl WakeupScheduler {
+    pub fn new(tx: Sender) -> Self {
+        Self {
+            tx: Some(tx),
+            ..Default::default()
+        }
+    }
+
+    /// Arm `kind`'s timer for `at`. Passing `None` only forgets the deadline;
+    /// a timer that still fires finds nothing to do.
+    pub fn schedule(&mut self, kind: Wakeup, at: Option<Instant>) {
+        let slot = self.slot(kind);
+        if *slot == at {
+            return;
+        }
+        *slot = at;
+        if let (Some(tx), Some(at)) = (&self.tx, at) {
+            tx.send((kind, at));
+        }
+    }
+
+    /// Note that `kind`'s timer fired and is no longer armed.
+    pub fn fired(&mut self, kind: Wakeup) { *self.slot(kind) = None; }
+
+    fn slot(&mut self, kind: Wakeup) -> &mut Option<Instant> {
+        match kind {
+            Wakeup::FramePoll => &mut self.frame_poll,
+            Wakeup::Manageability => &mut self.manageability,
+            Wakeup::ExternalMoves => &mut self.external_moves,
+        }
+    }
+}
+
+/// Run the timers, sending the reactor an event whenever one fires.
+pub async fn run(mut requests: Receiver, events_tx: reactor::Sender) {
+    let mut frame_poll = Timer::manual();
+    let mut manageability = Timer::manual();
+    let mut external_moves = Timer::manual();
+
+    loop {
+        tokio::select! {
+            request = requests.recv() => {
+                let Some((_span, (kind, at))) = request else {
+                    break;
+                };
+                let timer = match kind {
+                    Wakeup::FramePoll => &frame_poll,
+                    Wakeup::Manageability => &manageability,
+                    Wakeup::ExternalMoves => &external_moves,
+                };
+                timer.set_next_fire(at.saturating_duration_since(Instant::now()));
+            }
+            Some(()) = frame_poll.next() => events_tx.send(Wakeup::FramePoll.event()),
+            Some(()) = manageability.next() => events_tx.send(Wakeup::Manageability.event()),
+            Some(()) = external_moves.next() => events_tx.send(Wakeup::ExternalMoves.event()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn only_changed_deadlines_are_sent() {
+        let (tx, mut rx) = actor::channel();
+        let mut scheduler = WakeupScheduler::new(tx);
+        let at = Instant::now() + Duration::from_millis(300);
+
+        scheduler.schedule(Wakeup::Manageability, Some(at));
+        scheduler.schedule(Wakeup::Manageability, Some(at));
+        assert!(matches!(rx.try_recv(), Ok((_, (Wakeup::Manageability, sent))) if sent == at));
+        assert!(rx.try_recv().is_err());
+
+        // Forgetting a deadline doesn't need the timer task.
+        scheduler.schedule(Wakeup::Manageability, None);
+        assert!(rx.try_recv().is_err());
+
+        // Once a timer fired, the same deadline has to be armed again.
+        scheduler.schedule(Wakeup::FramePoll, Some(at));
+        scheduler.fired(Wakeup::FramePoll);
+        scheduler.schedule(Wakeup::FramePoll, Some(at));
+        assert_eq!(
+            std::iter::from_fn(|| rx.try_recv().ok())
+                .map(|(_, (kind, _))| kind)
+                .collect::<Vec<_>>(),
+            vec![Wakeup::FramePoll, Wakeup::FramePoll]
+        );
+    }
+}
@@ -9,7 +9,7 @@ use tracing::instrument;
 use crate::actor::app::WindowId;
 use crate::actor::reactor::{Command, ReactorCommand};
 use crate::actor::{self, reactor};
-use crate::common::collections::HashMap;
+use crate::common::collections::{HashMap, HashSet};
 use crate::common::config::{Config, HorizontalPlacement, VerticalPlacement};
 use crate::layout_engine::LayoutKind;
 use crate::model::tree::NodeId;
@@ -25,6 +25,10 @@ pub struct GroupInfo {
     pub total_count: usize,
     pub selected_index: usize,
     pub window_ids: Vec<WindowId>,
+    /// Titles of `window_ids`, in the same order
+    pub window_titles: Vec<String>,
+    /// The group is collapsed and `frame` is its whole title strip.
+    pub is_collapsed: bool,
 }
 
 #[derive(Debug)]
@@ -39,6 +43,11 @@ pub enum Event {
     ConfigUpdated(Config),
     MouseDown(CGPoint),
     MouseMoved(CGPoint),
+    /// The user's title preference for a window in the stack line
+    WindowTitleVisibilityChanged {
+        window_id: WindowId,
+        visible: bool,
+    },
 }
 
 pub struct StackLine {
@@ -52,6 +61,7 @@ pub struct StackLine {
     coordinate_converter: CoordinateConverter,
     group_sigs_by_space: HashMap<SpaceId, Vec<GroupSig>>,
     cursor_over_indicator: bool,
+    hidden_titles: HashSet<WindowId>,
 }
 
 pub type Sender = actor::Sender<Event>;
@@ -74,6 +84,7 @@ impl StackLine {
             coordinate_converter,
             group_sigs_by_space: HashMap::default(),
             cursor_over_indicator: false,
+            hidden_titles: HashSet::default(),
         }
     }
 
@@ -99,6 +110,7 @@ impl StackLine {
                     | Event::ScreenParametersChanged(_)
                     | Event::MouseDown(_)
                     | Event::MouseMoved(_)
+                    | Event::WindowTitleVisibilityChanged { .. }
             )
         {
             return;
@@ -129,6 +141,9 @@ impl StackLine {
             Event::MouseMoved(point) => {
                 self.handle_mouse_moved(point);
             }
+            Event::WindowTitleVisibilityChanged { window_id, visible } => {
+                self.handle_window_title_visibility_changed(window_id, visible);
+            }
         }
     }
 
@@ -139,8 +154,7 @@ impl StackLine {
         groups: Vec<GroupInfo>,
         space_has_fullscreen: bool,
     ) {
-        let active: crate::common::collections::HashSet<SpaceId> =
-            active_space_ids.iter().copied().collect();
+        let active: HashSet<SpaceId> = active_space_ids.iter().copied().collect();
 
         self.indicators.retain(|_node_id, indicator| match indicator.space_id() {
             Some(indicator_space_id) if !active.contains(&indicator_space_id) => {
@@ -234,6 +248,32 @@ impl StackLine {
         tracing::debug!("Updated stack line configuration");
     }
 
+    fn handle_window_title_visibility_changed(&mut self, window_id: WindowId, visible: bool) {
+        let changed = if visible {
+            self.hidden_titles.remove(&window_id)
+        } else {
+            self.hidden_titles.insert(window_id)
+        };
+        if !changed || !self.is_enabled() {
+            return;
+        }
+
+        let config = self.indicator_config();
+        for (node_id, indicator) in &self.indicators {
+            let Some(mut group_data) = indicator.group_data() else {
+                continue;
+            };
+            if !group_data.window_ids.contains(&window_id) {
+                continue;
+            }
+            group_data.title_visible =
+                title_visibility(&group_data.window_ids, &self.hidden_titles);
+            if let Err(err) = indicator.update(config, group_data) {
+                tracing::warn!(?err, ?node_id, "failed to update stack line title visibility");
+            }
+        }
+    }
+
     fn handle_mouse_down(&mut self, screen_point: CGPoint) {
         if !self.is_enabled() {
             return;
@@ -342,17 +382,23 @@ impl StackLine {
             group_kind,
             total_count: group.total_count,
             selected_index: group.selected_index,
+            title_visible: title_visibility(&group.window_ids, &self.hidden_titles),
             window_ids: group.window_ids,
+            window_titles: group.window_titles,
         };
 
-        let indicator_frame = Self::calculate_indicator_frame(
-            group.frame,
-            group_kind,
-            config.bar_thickness,
-            config.horizontal_placement,
-            config.vertical_placement,
-            config.spacing,
-        );
+        let indicator_frame = if group.is_collapsed {
+            group.frame
+        } else {
+            Self::calculate_indicator_frame(
+                group.frame,
+                group_kind,
+                config.bar_thickness,
+                config.horizontal_placement,
+                config.vertical_placement,
+                config.spacing,
+            )
+        };
 
         let node_id = group.node_id;
 
@@ -450,6 +496,8 @@ struct GroupSig {
     total: usize,
     selected_index: usize,
     window_ids: Vec<WindowId>,
+    window_titles: Vec<String>,
+    is_collapsed: bool,
 }
 
 impl GroupSig {
@@ -465,10 +513,16 @@ impl GroupSig {
             total: g.total_count,
             selected_index: g.selected_index,
             window_ids: g.window_ids.clone(),
+            window_titles: g.window_titles.clone(),
+            is_collapsed: g.is_collapsed,
         }
     }
 }
 
+fn title_visibility(window_ids: &[WindowId], hidden: &HashSet<WindowId>) -> Vec<bool> {
+    window_ids.iter().map(|wid| !hidden.contains(wid)).collect()
+}
+
 fn hit_margins(frame: CGRect, thickness: f64) -> (f64, f64) {
     let base = (thickness * 0.25).clamp(1.0, 5.0);
     let target_short = 14.0;
@@ -506,6 +560,39 @@ mod tests {
         assert_eq!(LayoutKind::Horizontal.is_group(), false);
     }
 
+    #[test]
+    fn test_title_visibility_follows_window_order() {
+        let windows = [
+            WindowId::new(1, 1),
+            WindowId::new(1, 2),
+            WindowId::new(2, 1),
+        ];
+        let hidden: HashSet<WindowId> = [WindowId::new(1, 2)].into_iter().collect();
+        assert_eq!(title_visibility(&windows, &hidden), vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_visible_title_skips_hidden_and_untitled_windows() {
+        let window_ids = vec![
+            WindowId::new(1, 1),
+            WindowId::new(1, 2),
+            WindowId::new(2, 1),
+        ];
+        let hidden: HashSet<WindowId> = [WindowId::new(1, 2)].into_iter().collect();
+        let group_data = GroupDisplayData {
+            group_kind: GroupKind::Horizontal,
+            total_count: 3,
+            selected_index: 0,
+            title_visible: title_visibility(&window_ids, &hidden),
+            window_ids,
+            window_titles: vec!["Inbox".into(), "Draft".into(), String::new()],
+        };
+        assert_eq!(group_data.visible_title(0), Some("Inbox"));
+        assert_eq!(group_data.visible_title(1), None);
+        assert_eq!(group_data.visible_title(2), None);
+        assert_eq!(group_data.visible_title(3), None);
+    }
+
     #[test]
     fn test_calculate_indicator_frame() {
         let group_frame = CGRect::new(CGPoint::new(100.0, 200.0), CGSize::new(400.0, 300.0));
@@ -343,6 +343,9 @@ impl WmController {
                         layout::LayoutCommand::MoveWindowToWorkspace {
                             workspace: workspace_index,
                             window_id: None,
+                            bundle_id: None,
+                            title: None,
+                            target_index: None,
                         },
                     )));
                 } else {
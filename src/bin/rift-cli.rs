@@ -4,7 +4,7 @@ use std::process::{self};
 use clap::{Parser, Subcommand};
 use rift_wm::actor::app::WindowId;
 use rift_wm::actor::reactor::{self, DisplaySelector};
-use rift_wm::common::config::LayoutMode;
+use rift_wm::common::config::{LayoutMode, NewWindowInsert};
 use rift_wm::ipc::{RiftCommand, RiftMachClient, RiftRequest, RiftResponse};
 use rift_wm::layout_engine as layout;
 use rift_wm::sys::window_server::WindowServerId;
@@ -62,16 +62,24 @@ enum QueryCommands {
     Workspaces {
         #[arg(long)]
         space_id: Option<u64>,
+        /// Also list minimized windows, under the workspace they were minimized from
+        #[arg(long)]
+        include_minimized: bool,
     },
     /// List windows (optionally filtered by space)
     Windows {
         #[arg(long)]
         space_id: Option<u64>,
+        /// Also list minimized windows
+        #[arg(long)]
+        include_minimized: bool,
     },
     /// List connected displays
     Displays,
     /// Get information about a specific window
     Window { window_id: String },
+    /// Get the frame transaction state for a window (last sent/seen txid, pending target)
+    WindowTransaction { window_server_id: u32 },
     /// List running applications
     Applications,
     /// Get layout state for a space
@@ -83,8 +91,35 @@ enum QueryCommands {
         #[arg(long)]
         workspace_id: Option<usize>,
     },
+    /// Get the layout mode of the active workspace (optionally for a specific MacOS space)
+    LayoutMode {
+        #[arg(long)]
+        space_id: Option<u64>,
+    },
+    /// Get the split orientation pending for the next window (see `layout split-direction`)
+    PendingSplit {
+        #[arg(long)]
+        space_id: Option<u64>,
+    },
     /// Get performance metrics
-    Metrics,
+    Metrics {
+        /// Print Prometheus text exposition format instead of JSON
+        #[arg(long)]
+        prometheus: bool,
+    },
+    /// Get Rift's Mission Control state (inactive, active or transitioning)
+    MissionControl,
+    /// Get whether Rift thinks an app's menu is open (focus-follows-mouse is paused while it is)
+    MenuState,
+    /// Get the most recent layout events and commands (needs settings.event_log_size)
+    EventLog {
+        /// Only show this many of the newest entries
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Get the config with defaults filled in and which settings differ from them
+    /// (by value, so a setting written out with its default value doesn't count)
+    ConfigDiffFromDefaults,
 }
 
 #[derive(Subcommand)]
@@ -125,10 +160,39 @@ enum ExecuteCommands {
     Debug,
     /// Serialize and print runtime state
     Serialize,
+    /// Restore workspaces and layouts from the output of `serialize`
+    /// Example:
+    ///   rift-cli execute deserialize --file state.json
+    Deserialize {
+        /// File holding the serialized state, or `-` to read it from stdin
+        #[arg(long)]
+        file: String,
+    },
     /// Toggle whether the current space is managed by rift
     ToggleSpaceActivated,
+    /// Forget inferred window size constraints and re-read them from the apps
+    ReprobeAllConstraints,
+    /// Lay out one space's tiled windows again (the focused display's by default)
+    RetileSpace {
+        #[arg(long)]
+        space_id: Option<u64>,
+    },
+    /// Run app rules again for every window on an active space, e.g. after editing them
+    ReapplyAppRules,
+    /// Mark every menu closed, e.g. when focus-follows-mouse stays off after a menu closed
+    ResetMenuState,
     /// Show timing metrics
     ShowTiming,
+    /// Time only one in every `rate` events of the given kind (0 disables timing for it)
+    SetMetricsSampling {
+        /// Event kind, e.g. `mouse_moved` or `window_frame_changed`
+        #[arg(long)]
+        event: String,
+        #[arg(long)]
+        rate: u32,
+    },
+    /// Clear all per-event sampling rates so every event is timed again
+    ResetMetricsSampling,
 }
 
 #[derive(Subcommand)]
@@ -137,12 +201,28 @@ enum WindowCommands {
     Next,
     /// Focus the previous window
     Prev,
+    /// Focus the next window, including floating windows
+    FocusNext,
+    /// Focus the previous window, including floating windows
+    FocusPrev,
+    /// Focus the window taking up the most screen area in the active workspace
+    FocusLargest,
+    /// Focus the next window in reading order (left to right, then top to bottom)
+    FocusNextSpatial,
+    /// Focus the previous window in reading order
+    FocusPrevSpatial,
     /// Move focus in a direction
     Focus {
         direction: String, // up, down, left, right
     },
     /// Toggle window floating state
     ToggleFloat,
+    /// Tile every floating window in the active workspace
+    TileAllFloating,
+    /// Float the windows the last tile-all-floating tiled again, at their old frames
+    FloatAllTiled,
+    /// Toggle whether the window follows you as you switch workspaces on its display
+    ToggleSticky,
     /// Toggle fullscreen mode (fills the whole screen, ignores outer gaps)
     ToggleFullscreen,
     /// Toggle fullscreen within configured outer gaps (respects outer gaps / fills tiling area)
@@ -158,12 +238,70 @@ enum WindowCommands {
     ///   rift-cli execute window resize-by --amount 0.05    # grow by 5%
     ///   rift-cli execute window resize-by --amount -0.10   # shrink by 10%
     ResizeBy { amount: f64 },
+    /// Grow the focused tiled window in a direction until it meets another window or the
+    /// workspace edge
+    GrowToFill {
+        direction: String, // up, down, left, right
+    },
+    /// Size the focused floating window to fractions of its display and place it at an anchor.
+    /// Tiled windows are left alone.
+    /// Examples:
+    ///   rift-cli execute window resize-to-fraction --width 0.5 --height 1.0
+    ///   rift-cli execute window resize-to-fraction --width 0.5 --height 0.5 --anchor top-right
+    ResizeToFraction {
+        #[arg(long)]
+        width: f64,
+        #[arg(long)]
+        height: f64,
+        /// center, top, bottom, left, right, top-left, top-right, bottom-left or bottom-right
+        #[arg(long, default_value = "center")]
+        anchor: String,
+    },
+    /// Size and place the focused floating window by the next of the configured
+    /// `floating_presets`. Tiled windows are left alone.
+    CycleFloatingPreset,
+    /// Tag the focused window with a mark; the mark is moved off any other window
+    Mark { mark: String },
+    /// Focus the window with a mark, switching workspace if needed
+    FocusMark { mark: String },
     /// Close a window by window server identifier
     Close {
         /// Window Id (window server id or idx from window id)
         #[arg(long)]
         window_id: String,
     },
+    /// Start resizing a floating window from one edge; the opposite edge stays fixed
+    BeginEdgeResize {
+        /// Window server id of the floating window
+        #[arg(long)]
+        window_id: String,
+        /// Edge to move: left, right, top, or bottom
+        #[arg(long)]
+        edge: String,
+    },
+    /// Move the grabbed edge by a number of points (positive grows the window)
+    EdgeResizeBy {
+        #[arg(allow_hyphen_values = true)]
+        delta: f64,
+    },
+    /// Finish the current edge resize
+    EndEdgeResize,
+    /// Move the focused window by a number of points, floating it if it is tiled
+    Nudge {
+        #[arg(long, allow_hyphen_values = true, default_value_t = 0.0)]
+        dx: f64,
+        #[arg(long, allow_hyphen_values = true, default_value_t = 0.0)]
+        dy: f64,
+    },
+    /// Hide an app's windows (like Cmd-H), or bring them back to their workspaces
+    ToggleAppHidden {
+        /// Bundle identifier of the app (e.g. com.apple.Safari)
+        #[arg(long)]
+        bundle_id: Option<String>,
+        /// Process id of the app
+        #[arg(long)]
+        pid: Option<i32>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -178,11 +316,38 @@ enum WorkspaceCommands {
     MoveWindow {
         workspace_id: usize,
         window_id: Option<u32>,
+        /// Move a window of this app instead; waits briefly for it if it hasn't opened yet
+        #[arg(long)]
+        bundle_id: Option<String>,
+        /// Only match windows whose title contains this
+        #[arg(long)]
+        title: Option<String>,
+        /// Position in the target workspace's window order (0-based, clamped)
+        #[arg(long)]
+        target_index: Option<usize>,
+    },
+    /// Move current window to the next workspace
+    MoveWindowNext {
+        /// Also switch to the target workspace
+        #[arg(long)]
+        follow: bool,
+    },
+    /// Move current window to the previous workspace
+    MoveWindowPrev {
+        /// Also switch to the target workspace
+        #[arg(long)]
+        follow: bool,
     },
     /// Create a new workspace
     Create,
     /// Switch to the last workspace
     Last,
+    /// Move current window to the last workspace
+    SendToLast {
+        /// Also switch to the target workspace
+        #[arg(long)]
+        follow: bool,
+    },
     /// Set layout mode for a workspace (or active workspace when omitted)
     SetLayout {
         /// Workspace index (0-based). Defaults to active workspace if omitted.
@@ -191,6 +356,47 @@ enum WorkspaceCommands {
         /// Layout mode: traditional, bsp, stack, master_stack, scrolling
         mode: String,
     },
+    /// Switch the active workspace to the next layout mode in `layout.cycle_modes`
+    CycleLayout,
+    /// Rename a workspace (or the active workspace when omitted)
+    Rename {
+        /// Workspace index (0-based). Defaults to active workspace if omitted.
+        #[arg(long)]
+        workspace_id: Option<usize>,
+        name: String,
+    },
+    /// Switch to the workspace holding a window of an app and focus it;
+    /// repeat to cycle through the app's windows on other workspaces
+    FocusApp {
+        /// Bundle identifier of the app (e.g. com.apple.Safari)
+        #[arg(long)]
+        bundle_id: Option<String>,
+        /// Process id of the app
+        #[arg(long)]
+        pid: Option<i32>,
+    },
+    /// Switch to the workspace holding the app's window that most recently asked
+    /// for attention and focus it
+    FocusAppUrgent {
+        /// Bundle identifier of the app (e.g. com.tinyspeck.slackmacgap)
+        #[arg(long)]
+        bundle_id: String,
+    },
+    /// Spread the current display's windows evenly across its workspaces
+    Distribute,
+    /// Decide whether focus cycling wraps in the active workspace; omit --wrap to
+    /// use `layout.focus_cycle_wrap` again
+    SetFocusWrap {
+        #[arg(long)]
+        wrap: Option<bool>,
+    },
+    /// Set where new windows go in the active workspace; omit --insert to use
+    /// `layout.new_window_insert` again
+    SetInsertPosition {
+        /// after_focused, end, beginning, or as_master
+        #[arg(long)]
+        insert: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -201,10 +407,29 @@ enum LayoutCommands {
     Descend,
     /// Move the selected node in a direction
     MoveNode { direction: String },
+    /// Move the selected window into the neighboring container in a direction
+    MoveIntoContainer { direction: String },
+    /// Rearrange the focused window's container into a grid of rows and columns
+    Grid {
+        #[arg(long)]
+        rows: usize,
+        #[arg(long)]
+        cols: usize,
+    },
     /// Join the selected window with neighbor in a direction
     JoinWindow { direction: String },
+    /// Split the focused window horizontally or vertically when the next window opens
+    SplitDirection { orientation: String },
+    /// Keep the workspace's window sizes while windows open and close (traditional layout only)
+    Freeze,
+    /// Stop keeping the workspace's window sizes
+    Unfreeze,
     /// Toggle stacked state for the selected container
     ToggleStack,
+    /// Collapse the focused stacked container to its title strip, or expand it back
+    ToggleCollapsed,
+    /// Hide or show the focused window's title in the stack line (drawn when its thickness is at least 12)
+    ToggleTitle,
     /// Global orientation toggle that works consistently across layout modes (and between splits/stacks)
     ToggleOrientation,
     /// Unjoin previously joined windows
@@ -219,12 +444,22 @@ enum LayoutCommands {
     PromoteToMaster,
     /// Swap the first master with the first stack window (master/stack layout only)
     SwapMasterStack,
+    /// Swap the focused window with the master window (first window outside master/stack)
+    SwapWithMaster,
     /// Swap two windows by window id (`WindowId { pid: ..., idx: ... }`)
     SwapWindows { a: String, b: String },
     /// Scroll the strip by a normalized delta (scrolling layout only)
     ScrollStrip { delta: f64 },
     /// Snap the strip to the nearest column boundary (scrolling layout only)
     SnapStrip,
+    /// Scroll the strip one page to the left (scrolling layout only)
+    ScrollPageLeft,
+    /// Scroll the strip one page to the right (scrolling layout only)
+    ScrollPageRight,
+    /// Scroll the strip to its first column (scrolling layout only)
+    ScrollToStart,
+    /// Scroll the strip to its last full page (scrolling layout only)
+    ScrollToEnd,
     /// Toggle centering of the selected column in scrolling layout.
     /// If invoked again on the same selection, centering is removed.
     CenterSelection,
@@ -326,6 +561,12 @@ enum DisplayCommands {
         #[arg(long)]
         uuid: Option<String>,
     },
+    /// Focus the next display in index order, wrapping around and skipping inactive ones.
+    Cycle {
+        /// Also move the mouse cursor to the display.
+        #[arg(long)]
+        move_mouse: bool,
+    },
     /// Move mouse cursor to a display by index (0-based)
     MoveMouseToIndex {
         /// Display index (0-based)
@@ -350,19 +591,45 @@ enum DisplayCommands {
         /// Optional window id (window idx); defaults to the focused window if omitted.
         #[arg(long)]
         window_id: Option<u32>,
+        /// Resolve --direction from this display index instead of the window's display.
+        #[arg(long)]
+        from_index: Option<usize>,
+        /// Resolve --direction from this display UUID instead of the window's display.
+        #[arg(long)]
+        from_uuid: Option<String>,
+    },
+    /// Move every window off a display (e.g. before unplugging it), keeping workspace indices.
+    Evacuate {
+        /// Direction relative to the current display (left, right, up, down).
+        #[arg(long)]
+        direction: Option<String>,
+        /// Display index (0-based).
+        #[arg(long)]
+        index: Option<usize>,
+        /// Display UUID.
+        #[arg(long)]
+        uuid: Option<String>,
+        /// Destination display index (0-based); defaults to the primary display.
+        #[arg(long)]
+        to_index: Option<usize>,
+        /// Destination display UUID; defaults to the primary display.
+        #[arg(long)]
+        to_uuid: Option<String>,
     },
+    /// Move windows between displays so each active workspace holds its share of them.
+    Balance,
 }
 
 #[derive(Subcommand)]
 enum SubscribeCommands {
     /// Subscribe to Mach IPC events
     Mach {
-        /// Event to subscribe to (workspace_changed, windows_changed, window_title_changed, stacks_changed, *)
+        /// Event to subscribe to (see `subscribe list-events`), or * for all of them
         event: String,
     },
     /// Subscribe to events via CLI command execution
     Cli {
-        /// Event to subscribe to (workspace_changed, windows_changed, window_title_changed, stacks_changed, *)
+        /// Event to subscribe to (see `subscribe list-events`), or * for all of them
         #[arg(long)]
         event: String,
         /// Command to execute when event occurs
@@ -384,6 +651,8 @@ enum SubscribeCommands {
     },
     /// List current CLI subscriptions
     ListCli,
+    /// List the events that can be subscribed to
+    ListEvents,
 }
 
 fn main() {
@@ -427,7 +696,12 @@ fn main() {
     // Send request and handle response.
     match client.send_request(&request) {
         Ok(resp) => match resp {
-            RiftResponse::Success { data } => {
+            RiftResponse::Success { data: Value::String(text) }
+                if matches!(request, RiftRequest::GetMetricsPrometheus) =>
+            {
+                print!("{}", text);
+            }
+            RiftResponse::Success { data } | RiftResponse::SuccessWithWarnings { data, .. } => {
                 if let Err(e) = write_json(
                     &data,
                     std::env::var("RIFT_CLI_PRETTY").map(|v| v != "0").unwrap_or(false),
@@ -470,16 +744,30 @@ fn build_request(command: Commands) -> Result<RiftRequest, String> {
 
 fn build_query_request(query: QueryCommands) -> Result<RiftRequest, String> {
     match query {
-        QueryCommands::Workspaces { space_id } => Ok(RiftRequest::GetWorkspaces { space_id }),
-        QueryCommands::Windows { space_id } => Ok(RiftRequest::GetWindows { space_id }),
+        QueryCommands::Workspaces { space_id, include_minimized } => {
+            Ok(RiftRequest::GetWorkspaces { space_id, include_minimized })
+        }
+        QueryCommands::Windows { space_id, include_minimized } => {
+            Ok(RiftRequest::GetWindows { space_id, include_minimized })
+        }
         QueryCommands::Displays => Ok(RiftRequest::GetDisplays),
         QueryCommands::Window { window_id } => Ok(RiftRequest::GetWindowInfo { window_id }),
+        QueryCommands::WindowTransaction { window_server_id } => {
+            Ok(RiftRequest::GetWindowTransaction { window_server_id })
+        }
         QueryCommands::Applications => Ok(RiftRequest::GetApplications),
         QueryCommands::Layout { space_id } => Ok(RiftRequest::GetLayoutState { space_id }),
         QueryCommands::WorkspaceLayout { space_id, workspace_id } => {
             Ok(RiftRequest::GetWorkspaceLayouts { space_id, workspace_id })
         }
-        QueryCommands::Metrics => Ok(RiftRequest::GetMetrics),
+        QueryCommands::LayoutMode { space_id } => Ok(RiftRequest::GetLayoutMode { space_id }),
+        QueryCommands::PendingSplit { space_id } => Ok(RiftRequest::GetPendingSplit { space_id }),
+        QueryCommands::Metrics { prometheus: false } => Ok(RiftRequest::GetMetrics),
+        QueryCommands::Metrics { prometheus: true } => Ok(RiftRequest::GetMetricsPrometheus),
+        QueryCommands::MissionControl => Ok(RiftRequest::GetMissionControlState),
+        QueryCommands::MenuState => Ok(RiftRequest::GetMenuState),
+        QueryCommands::EventLog { limit } => Ok(RiftRequest::GetEventLog { limit }),
+        QueryCommands::ConfigDiffFromDefaults => Ok(RiftRequest::GetConfigDiffFromDefaults),
     }
 }
 
@@ -492,6 +780,7 @@ fn build_subscribe_request(sub: SubscribeCommands) -> Result<RiftRequest, String
         SubscribeCommands::UnsubMach { event } => Ok(RiftRequest::Unsubscribe { event }),
         SubscribeCommands::UnsubCli { event } => Ok(RiftRequest::UnsubscribeCli { event }),
         SubscribeCommands::ListCli => Ok(RiftRequest::ListCliSubscriptions),
+        SubscribeCommands::ListEvents => Ok(RiftRequest::GetSubscribableEvents),
     }
 }
 
@@ -514,12 +803,37 @@ fn build_execute_request(execute: ExecuteCommands) -> Result<RiftRequest, String
         ExecuteCommands::Serialize => {
             RiftCommand::Reactor(reactor::Command::Reactor(reactor::ReactorCommand::Serialize))
         }
+        ExecuteCommands::Deserialize { file } => {
+            let state = if file == "-" {
+                std::io::read_to_string(std::io::stdin())
+            } else {
+                std::fs::read_to_string(&file)
+            }
+            .map_err(|e| format!("Could not read {file}: {e}"))?;
+            return Ok(RiftRequest::DeserializeState { state });
+        }
         ExecuteCommands::ToggleSpaceActivated => RiftCommand::Reactor(reactor::Command::Reactor(
             reactor::ReactorCommand::ToggleSpaceActivated,
         )),
+        ExecuteCommands::ReprobeAllConstraints => RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::ReprobeAllConstraints,
+        )),
+        ExecuteCommands::RetileSpace { space_id } => {
+            return Ok(RiftRequest::RetileSpace { space_id });
+        }
+        ExecuteCommands::ReapplyAppRules => return Ok(RiftRequest::ReapplyAppRules),
+        ExecuteCommands::ResetMenuState => return Ok(RiftRequest::ResetMenuState),
         ExecuteCommands::ShowTiming => RiftCommand::Reactor(reactor::Command::Metrics(
             rift_wm::common::log::MetricsCommand::ShowTiming,
         )),
+        ExecuteCommands::SetMetricsSampling { event, rate } => {
+            RiftCommand::Reactor(reactor::Command::Metrics(
+                rift_wm::common::log::MetricsCommand::SetSampling { event, rate },
+            ))
+        }
+        ExecuteCommands::ResetMetricsSampling => RiftCommand::Reactor(reactor::Command::Metrics(
+            rift_wm::common::log::MetricsCommand::ResetSampling,
+        )),
     };
 
     if let RiftCommand::Config(rift_wm::common::config::ConfigCommand::GetConfig) = &rift_command {
@@ -555,12 +869,36 @@ fn map_window_command(cmd: WindowCommands) -> Result<RiftCommand, String> {
     match cmd {
         WindowCommands::Next => Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::NextWindow))),
         WindowCommands::Prev => Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::PrevWindow))),
+        WindowCommands::FocusNext => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::FocusNextWindow,
+        ))),
+        WindowCommands::FocusPrev => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::FocusPrevWindow,
+        ))),
+        WindowCommands::FocusLargest => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::FocusLargestWindow,
+        ))),
+        WindowCommands::FocusNextSpatial => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::FocusNextSpatial,
+        ))),
+        WindowCommands::FocusPrevSpatial => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::FocusPrevSpatial,
+        ))),
         WindowCommands::Focus { direction } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::MoveFocus(direction.into()),
         ))),
         WindowCommands::ToggleFloat => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ToggleWindowFloating,
         ))),
+        WindowCommands::TileAllFloating => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::TileAllFloating,
+        ))),
+        WindowCommands::FloatAllTiled => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::FloatAllTiled)))
+        }
+        WindowCommands::ToggleSticky => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::ToggleSticky)))
+        }
         WindowCommands::ToggleFullscreen => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ToggleFullscreen,
         ))),
@@ -576,12 +914,71 @@ fn map_window_command(cmd: WindowCommands) -> Result<RiftCommand, String> {
         WindowCommands::ResizeBy { amount } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ResizeWindowBy { amount },
         ))),
+        WindowCommands::GrowToFill { direction } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::GrowToFill(direction.into())),
+        )),
+        WindowCommands::ResizeToFraction { width, height, anchor } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::ResizeFloatingToFraction {
+                width_frac: width,
+                height_frac: height,
+                anchor: parse_anchor(&anchor)?,
+            }),
+        )),
+        WindowCommands::CycleFloatingPreset => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::CycleFloatingPreset,
+        ))),
+        WindowCommands::Mark { mark } => {
+            if mark.is_empty() {
+                return Err("mark must not be empty".to_string());
+            }
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::MarkWindow(
+                mark,
+            ))))
+        }
+        WindowCommands::FocusMark { mark } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::FocusMark(mark),
+        ))),
         WindowCommands::Close { window_id } => {
             let wsid = parse_window_server_id(&window_id)?;
             Ok(RiftCommand::Reactor(reactor::Command::Reactor(
                 reactor::ReactorCommand::CloseWindow { window_server_id: Some(wsid) },
             )))
         }
+        WindowCommands::BeginEdgeResize { window_id, edge } => {
+            let window_server_id = parse_window_server_id(&window_id)?;
+            let edge = match edge.to_lowercase().as_str() {
+                "left" => reactor::Edge::Left,
+                "right" => reactor::Edge::Right,
+                "top" => reactor::Edge::Top,
+                "bottom" => reactor::Edge::Bottom,
+                other => {
+                    return Err(format!(
+                        "Invalid edge '{}', expected left, right, top or bottom",
+                        other
+                    ));
+                }
+            };
+            Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+                reactor::ReactorCommand::BeginEdgeResize { window_server_id, edge },
+            )))
+        }
+        WindowCommands::EdgeResizeBy { delta } => Ok(RiftCommand::Reactor(
+            reactor::Command::Reactor(reactor::ReactorCommand::EdgeResizeBy { delta }),
+        )),
+        WindowCommands::EndEdgeResize => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::EndEdgeResize,
+        ))),
+        WindowCommands::Nudge { dx, dy } => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::NudgeWindow { dx, dy },
+        ))),
+        WindowCommands::ToggleAppHidden { bundle_id, pid } => {
+            if bundle_id.is_none() && pid.is_none() {
+                return Err("toggle-app-hidden requires --bundle-id or --pid".to_string());
+            }
+            Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+                reactor::ReactorCommand::ToggleAppHidden { bundle_id, pid },
+            )))
+        }
     }
 }
 
@@ -635,11 +1032,26 @@ fn map_workspace_command(cmd: WorkspaceCommands) -> Result<RiftCommand, String>
         WorkspaceCommands::Switch { workspace_id } => Ok(RiftCommand::Reactor(
             reactor::Command::Layout(LC::SwitchToWorkspace(workspace_id)),
         )),
-        WorkspaceCommands::MoveWindow { workspace_id, window_id } => Ok(RiftCommand::Reactor(
-            reactor::Command::Layout(LC::MoveWindowToWorkspace {
+        WorkspaceCommands::MoveWindow {
+            workspace_id,
+            window_id,
+            bundle_id,
+            title,
+            target_index,
+        } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::MoveWindowToWorkspace {
                 workspace: workspace_id,
                 window_id,
-            }),
+                bundle_id,
+                title,
+                target_index,
+            },
+        ))),
+        WorkspaceCommands::MoveWindowNext { follow } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::MoveWindowToNextWorkspace { follow }),
+        )),
+        WorkspaceCommands::MoveWindowPrev { follow } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::MoveWindowToPrevWorkspace { follow }),
         )),
         WorkspaceCommands::Create => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::CreateWorkspace,
@@ -647,12 +1059,62 @@ fn map_workspace_command(cmd: WorkspaceCommands) -> Result<RiftCommand, String>
         WorkspaceCommands::Last => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::SwitchToLastWorkspace,
         ))),
+        WorkspaceCommands::SendToLast { follow } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::SendToLastWorkspace { follow }),
+        )),
         WorkspaceCommands::SetLayout { workspace_id, mode } => {
             let mode = parse_layout_mode(&mode)?;
             Ok(RiftCommand::Reactor(reactor::Command::Layout(
                 LC::SetWorkspaceLayout { workspace: workspace_id, mode },
             )))
         }
+        WorkspaceCommands::CycleLayout => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::CycleLayoutMode,
+        ))),
+        WorkspaceCommands::Rename { workspace_id, name } => {
+            if name.trim().is_empty() {
+                return Err("workspace name must not be empty".to_string());
+            }
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(
+                LC::RenameWorkspace { index: workspace_id, name },
+            )))
+        }
+        WorkspaceCommands::FocusApp { bundle_id, pid } => {
+            if bundle_id.is_none() && pid.is_none() {
+                return Err("focus-app requires --bundle-id or --pid".to_string());
+            }
+            Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+                reactor::ReactorCommand::FocusAppWorkspace { bundle_id, pid },
+            )))
+        }
+        WorkspaceCommands::FocusAppUrgent { bundle_id } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::FocusAppUrgent { bundle_id }),
+        )),
+        WorkspaceCommands::Distribute => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::DistributeWindows,
+        ))),
+        WorkspaceCommands::SetFocusWrap { wrap } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::SetWorkspaceFocusWrap { wrap }),
+        )),
+        WorkspaceCommands::SetInsertPosition { insert } => {
+            let insert = insert.as_deref().map(parse_new_window_insert).transpose()?;
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(
+                LC::SetWorkspaceInsertPosition { insert },
+            )))
+        }
+    }
+}
+
+fn parse_new_window_insert(value: &str) -> Result<NewWindowInsert, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "after_focused" => Ok(NewWindowInsert::AfterFocused),
+        "end" => Ok(NewWindowInsert::End),
+        "beginning" => Ok(NewWindowInsert::Beginning),
+        "as_master" => Ok(NewWindowInsert::AsMaster),
+        other => Err(format!(
+            "Invalid insert position '{}'; must be after_focused, end, beginning, or as_master",
+            other
+        )),
     }
 }
 
@@ -664,12 +1126,36 @@ fn map_layout_command(cmd: LayoutCommands) -> Result<RiftCommand, String> {
         LayoutCommands::MoveNode { direction } => Ok(RiftCommand::Reactor(
             reactor::Command::Layout(LC::MoveNode(direction.into())),
         )),
+        LayoutCommands::MoveIntoContainer { direction } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::MoveWindowIntoContainer(direction.into())),
+        )),
+        LayoutCommands::Grid { rows, cols } => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::GridArrange {
+                rows,
+                cols,
+            })))
+        }
         LayoutCommands::JoinWindow { direction } => Ok(RiftCommand::Reactor(
             reactor::Command::Layout(LC::JoinWindow(direction.into())),
         )),
+        LayoutCommands::SplitDirection { orientation } => Ok(RiftCommand::Reactor(
+            reactor::Command::Layout(LC::SetSplitDirection(parse_orientation(&orientation)?)),
+        )),
+        LayoutCommands::Freeze => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::FreezeLayout)))
+        }
+        LayoutCommands::Unfreeze => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::UnfreezeLayout,
+        ))),
         LayoutCommands::ToggleStack => {
             Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::ToggleStack)))
         }
+        LayoutCommands::ToggleCollapsed => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::ToggleContainerCollapsed,
+        ))),
+        LayoutCommands::ToggleTitle => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::ToggleWindowTitleVisible,
+        ))),
         LayoutCommands::ToggleOrientation => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::ToggleOrientation,
         ))),
@@ -691,6 +1177,9 @@ fn map_layout_command(cmd: LayoutCommands) -> Result<RiftCommand, String> {
         LayoutCommands::SwapMasterStack => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::SwapMasterStack,
         ))),
+        LayoutCommands::SwapWithMaster => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::SwapWithMaster,
+        ))),
         LayoutCommands::SwapWindows { a, b } => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::SwapWindows(parse_window_id(&a)?, parse_window_id(&b)?),
         ))),
@@ -702,6 +1191,18 @@ fn map_layout_command(cmd: LayoutCommands) -> Result<RiftCommand, String> {
         LayoutCommands::SnapStrip => {
             Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::SnapStrip)))
         }
+        LayoutCommands::ScrollPageLeft => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::ScrollPageLeft,
+        ))),
+        LayoutCommands::ScrollPageRight => Ok(RiftCommand::Reactor(reactor::Command::Layout(
+            LC::ScrollPageRight,
+        ))),
+        LayoutCommands::ScrollToStart => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::ScrollToStart)))
+        }
+        LayoutCommands::ScrollToEnd => {
+            Ok(RiftCommand::Reactor(reactor::Command::Layout(LC::ScrollToEnd)))
+        }
         LayoutCommands::CenterSelection => Ok(RiftCommand::Reactor(reactor::Command::Layout(
             LC::CenterSelection,
         ))),
@@ -814,6 +1315,9 @@ fn map_display_command(cmd: DisplayCommands) -> Result<RiftCommand, String> {
                 reactor::ReactorCommand::FocusDisplay(selector),
             )))
         }
+        DisplayCommands::Cycle { move_mouse } => Ok(RiftCommand::Reactor(
+            reactor::Command::Reactor(reactor::ReactorCommand::CycleDisplays { move_mouse }),
+        )),
         DisplayCommands::MoveMouseToIndex { index } => {
             Ok(RiftCommand::Reactor(reactor::Command::Reactor(
                 reactor::ReactorCommand::MoveMouseToDisplay(DisplaySelector::Index(index)),
@@ -829,11 +1333,49 @@ fn map_display_command(cmd: DisplayCommands) -> Result<RiftCommand, String> {
             index,
             uuid,
             window_id,
-        } => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
-            reactor::ReactorCommand::MoveWindowToDisplay {
-                selector: build_display_selector(direction, index, uuid)?,
-                window_id,
-            },
+            from_index,
+            from_uuid,
+        } => {
+            let origin = match (from_index, from_uuid) {
+                (Some(_), Some(_)) => {
+                    return Err("use at most one of --from-index or --from-uuid".to_string());
+                }
+                (Some(index), None) => Some(DisplaySelector::Index(index)),
+                (None, Some(uuid)) => Some(DisplaySelector::Uuid(uuid)),
+                (None, None) => None,
+            };
+            Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+                reactor::ReactorCommand::MoveWindowToDisplay {
+                    selector: build_display_selector(direction, index, uuid)?,
+                    window_id,
+                    origin,
+                },
+            )))
+        }
+        DisplayCommands::Evacuate {
+            direction,
+            index,
+            uuid,
+            to_index,
+            to_uuid,
+        } => {
+            let target = match (to_index, to_uuid) {
+                (Some(_), Some(_)) => {
+                    return Err("use at most one of --to-index or --to-uuid".to_string());
+                }
+                (Some(index), None) => Some(DisplaySelector::Index(index)),
+                (None, Some(uuid)) => Some(DisplaySelector::Uuid(uuid)),
+                (None, None) => None,
+            };
+            Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+                reactor::ReactorCommand::EvacuateDisplay {
+                    selector: build_display_selector(direction, index, uuid)?,
+                    target,
+                },
+            )))
+        }
+        DisplayCommands::Balance => Ok(RiftCommand::Reactor(reactor::Command::Reactor(
+            reactor::ReactorCommand::BalanceDisplays,
         ))),
     }
 }
@@ -876,6 +1418,35 @@ fn parse_focus_direction(value: &str) -> Result<layout::Direction, String> {
     }
 }
 
+fn parse_orientation(value: &str) -> Result<layout::Orientation, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "horizontal" => Ok(layout::Orientation::Horizontal),
+        "vertical" => Ok(layout::Orientation::Vertical),
+        other => Err(format!(
+            "Invalid orientation '{}'; must be horizontal or vertical",
+            other
+        )),
+    }
+}
+
+fn parse_anchor(value: &str) -> Result<layout::Anchor, String> {
+    match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+        "center" => Ok(layout::Anchor::Center),
+        "top" => Ok(layout::Anchor::Top),
+        "bottom" => Ok(layout::Anchor::Bottom),
+        "left" => Ok(layout::Anchor::Left),
+        "right" => Ok(layout::Anchor::Right),
+        "top-left" => Ok(layout::Anchor::TopLeft),
+        "top-right" => Ok(layout::Anchor::TopRight),
+        "bottom-left" => Ok(layout::Anchor::BottomLeft),
+        "bottom-right" => Ok(layout::Anchor::BottomRight),
+        other => Err(format!(
+            "Invalid anchor '{}'; must be center, top, bottom, left, right, top-left, top-right, bottom-left, or bottom-right",
+            other
+        )),
+    }
+}
+
 fn write_json(value: &Value, pretty: bool) -> Result<(), String> {
     let stdout = io::stdout();
     let mut handle = stdout.lock();
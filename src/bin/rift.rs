@@ -8,6 +8,7 @@ use objc2_application_services::AXUIElement;
 use rift_wm::actor::config::ConfigActor;
 use rift_wm::actor::config_watcher::ConfigWatcher;
 use rift_wm::actor::event_tap::EventTap;
+use rift_wm::actor::focus_border::FocusBorder;
 use rift_wm::actor::menu_bar::Menu;
 use rift_wm::actor::mission_control::MissionControlActor;
 use rift_wm::actor::mission_control_observer::NativeMissionControl;
@@ -17,7 +18,7 @@ use rift_wm::actor::reactor::{self, Reactor};
 use rift_wm::actor::stack_line::StackLine;
 use rift_wm::actor::window_notify as window_notify_actor;
 use rift_wm::actor::wm_controller::{self, WmController};
-use rift_wm::common::config::{Config, config_file, restore_file};
+use rift_wm::common::config::{Config, config_file};
 use rift_wm::common::log;
 use rift_wm::common::util::execute_startup_commands;
 use rift_wm::ipc;
@@ -55,7 +56,7 @@ struct Cli {
     #[arg(long)]
     validate: bool,
 
-    /// Deprecated no-op flag retained for CLI compatibility.
+    /// Restore the layout written by `save_and_exit`.
     #[arg(long)]
     restore: bool,
 
@@ -151,14 +152,34 @@ Enable it in System Settings > Desktop & Dock (Mission Control) and restart Rift
 
     let (broadcast_tx, broadcast_rx) = rift_wm::actor::channel();
 
-    let layout = LayoutEngine::new(
-        &config.virtual_workspaces,
-        &config.settings.layout,
-        Some(broadcast_tx.clone()),
-    );
+    let new_layout = || {
+        LayoutEngine::new(
+            &config.virtual_workspaces,
+            &config.settings.layout,
+            Some(broadcast_tx.clone()),
+        )
+    };
+    let restore_file = config.settings.restore_file_path();
+    let layout = if opt.restore && restore_file.exists() {
+        match LayoutEngine::load(
+            restore_file.clone(),
+            &config.virtual_workspaces,
+            &config.settings.layout,
+            Some(broadcast_tx.clone()),
+        ) {
+            Ok(layout) => layout,
+            Err(e) => {
+                eprintln!("Could not restore layout from {}: {e}", restore_file.display());
+                new_layout()
+            }
+        }
+    } else {
+        new_layout()
+    };
     let (event_tap_tx, event_tap_rx) = rift_wm::actor::channel();
     let (menu_tx, menu_rx) = rift_wm::actor::channel();
     let (stack_line_tx, stack_line_rx) = rift_wm::actor::channel();
+    let (focus_border_tx, focus_border_rx) = rift_wm::actor::channel();
     let (wnd_tx, wnd_rx) = rift_wm::actor::channel();
     let window_tx_store = WindowTxStore::new();
     let reactor = Reactor::spawn(
@@ -169,6 +190,7 @@ Enable it in System Settings > Desktop & Dock (Mission Control) and restart Rift
         broadcast_tx.clone(),
         menu_tx.clone(),
         stack_line_tx.clone(),
+        focus_border_tx,
         Some((wnd_tx.clone(), window_tx_store.clone())),
         opt.one,
     );
@@ -221,7 +243,7 @@ Enable it in System Settings > Desktop & Dock (Mission Control) and restart Rift
     });
 
     let wm_config = wm_controller::Config {
-        restore_file: restore_file(),
+        restore_file: config.settings.restore_file_path(),
         config: config.clone(),
     };
     let (mc_tx, mc_rx) = rift_wm::actor::channel();
@@ -262,6 +284,7 @@ Enable it in System Settings > Desktop & Dock (Mission Control) and restart Rift
         events_tx.clone(),
         CoordinateConverter::default(),
     );
+    let focus_border = FocusBorder::new(config.clone(), focus_border_rx, mtm);
 
     let mission_control = MissionControlActor::new(config.clone(), mc_rx, reactor.clone(), mtm);
     let mission_control_native = NativeMissionControl::new(events_tx.clone(), mc_native_rx);
@@ -289,6 +312,7 @@ Enable it in System Settings > Desktop & Dock (Mission Control) and restart Rift
             supervise("event_tap", event_tap.run()),
             supervise("menu", menu.run()),
             supervise("stack_line", stack_line.run()),
+            supervise("focus_border", focus_border.run()),
             supervise("window_notify", wn_actor.run()),
             supervise("mc_native", mission_control_native.run()),
             supervise("mission_control", mission_control.run()),
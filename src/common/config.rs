@@ -5,8 +5,9 @@ use anyhow::bail;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::collections::HashMap;
+use super::collections::{BTreeMap, HashMap};
 use crate::actor::wm_controller::WmCommand;
+use crate::layout_engine::Anchor;
 use crate::sys::hotkey::{Hotkey, HotkeySpec};
 
 const MAX_WORKSPACES: usize = 32;
@@ -85,6 +86,29 @@ pub struct VirtualWorkspaceSettings {
     pub app_rules: Vec<AppWorkspaceRule>,
     #[serde(default)]
     pub workspace_rules: Vec<WorkspaceLayoutRule>,
+    #[serde(default)]
+    pub workspace_groups: Vec<WorkspaceGroup>,
+    /// AX subroles of non-standard windows (dialogs, panels) that Rift tracks as
+    /// floating by default instead of ignoring. A matching app rule can assign
+    /// their workspace or unmanage them, but can't make them tile.
+    #[serde(default = "default_auto_float_subroles")]
+    pub auto_float_subroles: Vec<String>,
+}
+
+/// Workspaces on different displays that are switched to together.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceGroup {
+    pub members: Vec<WorkspaceGroupMember>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceGroupMember {
+    /// Display UUID the workspace lives on
+    pub display: String,
+    /// Workspace on that display, by index or name
+    pub workspace: WorkspaceSelector,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -142,6 +166,63 @@ pub struct AppWorkspaceRule {
     /// non-empty string and will be compared against the accessibility subrole
     /// reported by the AX APIs for a window (exact string match).
     pub ax_subrole: Option<String>,
+
+    /// Optional: Display UUID or name that newly created matching windows are moved to.
+    /// `workspace` is then resolved on that display.
+    pub display: Option<String>,
+
+    /// Optional: Move matching windows back to the display they are assigned to when the
+    /// app itself moves them onto another display. User drags are not affected.
+    #[serde(default)]
+    pub confine_to_display: bool,
+
+    /// Optional: Opacity (0.0 to 1.0) applied to matching windows. macOS may
+    /// refuse to change another app's windows; rift then logs a warning once
+    /// and leaves that app's windows as they are.
+    #[serde(default)]
+    pub opacity: Option<f32>,
+
+    /// Optional: Never raise matching windows on hover, even with focus-follows-mouse
+    /// enabled globally.
+    #[serde(default)]
+    pub disable_focus_follows_mouse: bool,
+
+    /// Optional: Size limits that take the place of the ones inferred for matching
+    /// windows, for apps that report them wrongly.
+    #[serde(default)]
+    pub constraints: Option<WindowConstraintOverrides>,
+}
+
+impl Default for AppWorkspaceRule {
+    fn default() -> Self {
+        Self {
+            app_id: None,
+            workspace: None,
+            floating: false,
+            manage: true,
+            app_name: None,
+            title_regex: None,
+            title_substring: None,
+            ax_role: None,
+            ax_subrole: None,
+            display: None,
+            confine_to_display: false,
+            opacity: None,
+            disable_focus_follows_mouse: false,
+            constraints: None,
+        }
+    }
+}
+
+/// Size limits from an app rule. A field left out keeps the value inferred for
+/// the window; 0 removes that limit.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WindowConstraintOverrides {
+    pub min_width: Option<f64>,
+    pub min_height: Option<f64>,
+    pub max_width: Option<f64>,
+    pub max_height: Option<f64>,
 }
 
 impl Default for VirtualWorkspaceSettings {
@@ -157,6 +238,8 @@ impl Default for VirtualWorkspaceSettings {
             reapply_app_rules_on_title_change: false,
             app_rules: Vec::new(),
             workspace_rules: Vec::new(),
+            workspace_groups: Vec::new(),
+            auto_float_subroles: default_auto_float_subroles(),
         }
     }
 }
@@ -278,6 +361,60 @@ impl VirtualWorkspaceSettings {
                     issues.push(format!("Duplicate ax_subrole '{}' in rule {}", ax_sub, index));
                 }
             }
+
+            if let Some(opacity) = rule.opacity {
+                if !(0.0..=1.0).contains(&opacity) {
+                    issues.push(format!(
+                        "App rule {} has opacity {} outside 0.0..=1.0",
+                        index, opacity
+                    ));
+                }
+            }
+
+            if let Some(constraints) = rule.constraints {
+                let limits = [
+                    ("min_width", constraints.min_width),
+                    ("min_height", constraints.min_height),
+                    ("max_width", constraints.max_width),
+                    ("max_height", constraints.max_height),
+                ];
+                for (name, value) in limits {
+                    if let Some(value) = value
+                        && !(value.is_finite() && value >= 0.0)
+                    {
+                        issues.push(format!(
+                            "App rule {} has constraints.{} {} that is not a size",
+                            index, name, value
+                        ));
+                    }
+                }
+                let inverted = |min: Option<f64>, max: Option<f64>| {
+                    min.zip(max).is_some_and(|(min, max)| max > 0.0 && max < min)
+                };
+                if inverted(constraints.min_width, constraints.max_width)
+                    || inverted(constraints.min_height, constraints.max_height)
+                {
+                    issues.push(format!(
+                        "App rule {} has a constraints maximum below its minimum",
+                        index
+                    ));
+                }
+            }
+        }
+
+        for (index, group) in self.workspace_groups.iter().enumerate() {
+            if group.members.len() < 2 {
+                issues.push(format!("Workspace group {} needs at least two members", index));
+            }
+            let mut seen_displays = crate::common::collections::HashSet::default();
+            for member in &group.members {
+                if !seen_displays.insert(&member.display) {
+                    issues.push(format!(
+                        "Workspace group {} lists display '{}' more than once",
+                        index, member.display
+                    ));
+                }
+            }
         }
 
         issues
@@ -304,6 +441,17 @@ pub struct Config {
     pub virtual_workspaces: VirtualWorkspaceSettings,
 }
 
+/// A config with every default filled in, alongside which of its settings
+/// differ from the defaults. This compares values only, so a setting the user
+/// wrote out with its default value reads the same as one left out.
+#[derive(Serialize, Debug, Clone)]
+pub struct ConfigDefaultsDiff {
+    pub config: Value,
+    /// Every setting in `config` by its dotted path (as accepted by `config set`),
+    /// mapped to whether its value differs from the default config.
+    pub differs_from_default: BTreeMap<String, bool>,
+}
+
 unsafe impl Send for Config {}
 unsafe impl Sync for Config {}
 
@@ -320,16 +468,79 @@ pub struct Settings {
     pub animation_easing: AnimationEasing,
     #[serde(default = "yes")]
     pub default_disable: bool,
+    /// Where focus and the cursor go after `toggle_space_activated`
+    #[serde(default)]
+    pub space_activation_focus: SpaceActivationFocus,
+    /// What `focus_window` does with a window on a space Rift isn't managing
+    #[serde(default)]
+    pub inactive_space_focus: InactiveSpaceFocus,
     #[serde(default = "yes")]
     pub mouse_follows_focus: bool,
     #[serde(default = "yes")]
     pub mouse_hides_on_focus: bool,
+    /// Never warp the cursor, on any display
+    #[serde(default = "no")]
+    pub disable_cursor_warp: bool,
+    /// Display UUIDs on which the cursor is never warped
+    #[serde(default)]
+    pub disable_cursor_warp_displays: Vec<String>,
+    /// Wrap out-of-range display indices around instead of rejecting them
+    #[serde(default = "no")]
+    pub display_index_wraps: bool,
     #[serde(default = "yes")]
     pub focus_follows_mouse: bool,
     /// Hotkey that disables focus-follows-mouse while held.
     /// Accepts either a full hotkey (e.g. "Ctrl + A") or a modifier-only spec (e.g. "Ctrl")
     #[serde(default)]
     pub focus_follows_mouse_disable_hotkey: Option<HotkeySpec>,
+    /// Screen areas where hovering a window never focuses or raises it
+    #[serde(default)]
+    pub focus_follows_mouse_exclusion_zones: Vec<FocusExclusionZone>,
+    /// Modifier (e.g. "Ctrl" or "RightAlt") that toggles the focused window's
+    /// floating state when pressed and released twice in quick succession
+    #[serde(default)]
+    pub double_tap_float_modifier: Option<HotkeySpec>,
+    /// Longest gap between, and duration of, the taps of `double_tap_float_modifier`
+    #[serde(default = "default_double_tap_interval_ms")]
+    pub double_tap_interval_ms: u64,
+    /// Suppress focus-follows-mouse while windows are still moving after a layout command
+    #[serde(default = "no")]
+    pub focus_lock_during_layout: bool,
+    /// Keep floating windows in the active workspace raised above tiled windows
+    #[serde(default = "no")]
+    pub floating_always_above: bool,
+    /// Restore the front-to-back order of a workspace's floating windows when
+    /// switching back to it
+    #[serde(default = "no")]
+    pub preserve_floating_stacking_order: bool,
+    /// Sizes and positions `cycle_floating_preset` steps the focused floating
+    /// window through, in order
+    #[serde(default = "default_floating_presets")]
+    pub floating_presets: Vec<FloatingPreset>,
+    /// What happens when something other than rift moves a tiled window
+    /// without resizing it
+    #[serde(default)]
+    pub tiled_window_external_move: ExternalMoveBehavior,
+    /// How long, in milliseconds, a window moved by something other than rift
+    /// must stay put before rift reassigns its space or re-tiles it. 0 acts
+    /// on every move immediately
+    #[serde(default)]
+    pub external_move_grace_ms: u64,
+    /// What happens to focus after switching to a workspace with no windows
+    #[serde(default)]
+    pub empty_workspace_focus: EmptyWorkspaceFocus,
+    /// Which display workspace-switch commands act on
+    #[serde(default)]
+    pub workspace_switch_target: WorkspaceSwitchTarget,
+    /// Where windows go when their display is disconnected
+    #[serde(default)]
+    pub display_gravity: DisplayGravity,
+    /// Move windows back when the display they were moved off reconnects
+    #[serde(default = "yes")]
+    pub restore_windows_on_display_return: bool,
+    /// How `balance_displays` decides each display's share of windows
+    #[serde(default)]
+    pub display_balance: DisplayBalance,
     /// Apps that should not trigger automatic workspace switching when activated.
     /// List of bundle identifiers (e.g., "com.apple.Spotlight") that often
     /// inappropriately steal focus and shouldn't cause workspace switches.
@@ -346,6 +557,10 @@ pub struct Settings {
     #[serde(default)]
     pub window_snapping: WindowSnappingSettings,
 
+    /// Periodically re-read window frames for apps whose AX notifications are unreliable
+    #[serde(default)]
+    pub frame_poll: FramePollSettings,
+
     /// Commands to run on startup (e.g., for subscribing to events)
     #[serde(default)]
     pub run_on_start: Vec<String>,
@@ -354,6 +569,94 @@ pub struct Settings {
     /// Enable hot-reloading of the config file when it changes
     #[serde(default = "yes")]
     pub hot_reload: bool,
+
+    /// Where `save_and_exit` writes the layout and `rift --restore` reads it;
+    /// defaults to `~/.rift/layout.ron`
+    #[serde(default)]
+    pub restore_file: Option<PathBuf>,
+    /// How many of the most recent layout events and commands to keep for
+    /// `rift-cli query event-log`. 0 turns the log off
+    #[serde(default)]
+    pub event_log_size: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyWorkspaceFocus {
+    /// Leave focus with whatever window had it
+    #[default]
+    Keep,
+    /// Clear the key window so no app keeps keyboard focus
+    Defocus,
+    /// Move the cursor to the center of the display
+    WarpToCenter,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceSwitchTarget {
+    /// The display under the cursor, falling back to the focused window's display
+    #[default]
+    Auto,
+    /// The focused window's display, even when the cursor is on another one,
+    /// falling back to the display under the cursor
+    Focused,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayGravity {
+    /// Keep the windows with the disconnected display's layout
+    #[default]
+    Off,
+    /// Move them to the primary display
+    Primary,
+    /// Move them to the display closest to the disconnected one
+    Nearest,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SpaceActivationFocus {
+    /// Leave focus and the cursor where they are
+    #[default]
+    None,
+    /// Focus the first window of a newly activated space, or a window on
+    /// another active space after deactivating one
+    FocusFirstWindow,
+    /// Like `FocusFirstWindow`, but warp the cursor to the display center instead
+    WarpCursor,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InactiveSpaceFocus {
+    /// Leave the window unfocused
+    #[default]
+    Ignore,
+    /// Activate the window's space, then focus the window
+    Activate,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalMoveBehavior {
+    /// Put the window straight back into its tile
+    #[default]
+    SnapBack,
+    /// Swap the window with the tiled window under its new center, or snap it
+    /// back if there is none
+    Retile,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayBalance {
+    /// Give every display the same number of windows
+    #[default]
+    Even,
+    /// Give each display a share proportional to its area
+    Area,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default, Copy)]
@@ -394,6 +697,8 @@ pub struct UiSettings {
     pub stack_line: StackLineSettings,
     #[serde(default)]
     pub mission_control: MissionControlSettings,
+    #[serde(default)]
+    pub focus_border: FocusBorderSettings,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -440,13 +745,69 @@ impl Default for GestureSettings {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 #[serde(deny_unknown_fields)]
 pub struct WindowSnappingSettings {
     #[serde(default = "default_drag_swap_fraction")]
     pub drag_swap_fraction: f64,
+    /// Swap a tiled window with the tile it is dropped on even when that tile
+    /// is on another display, instead of moving it into that display's layout.
+    #[serde(default = "no")]
+    pub across_displays: bool,
+    /// Distance (in points) a window must move from where it was grabbed
+    /// before the move is treated as a drag
+    #[serde(default = "default_drag_deadzone")]
+    pub drag_deadzone: f64,
+}
+
+impl Default for WindowSnappingSettings {
+    fn default() -> Self {
+        Self {
+            // A config without a `window_snapping` table has always ended up
+            // with 0.0 here, so keep that rather than the 0.3 used when the
+            // table is present.
+            drag_swap_fraction: 0.0,
+            across_displays: false,
+            drag_deadzone: default_drag_deadzone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FramePollSettings {
+    /// Bundle identifiers of apps to poll; polling is disabled while this is empty
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Time between polls, in milliseconds (at least 250)
+    #[serde(default = "default_frame_poll_interval_ms")]
+    pub interval_ms: u64,
+    /// Minimum difference (in points) between the polled and the tracked frame
+    /// before the polled frame is treated as a move/resize
+    #[serde(default = "default_frame_poll_threshold")]
+    pub threshold: f64,
+}
+
+impl Default for FramePollSettings {
+    fn default() -> Self {
+        Self {
+            apps: Vec::new(),
+            interval_ms: default_frame_poll_interval_ms(),
+            threshold: default_frame_poll_threshold(),
+        }
+    }
+}
+
+impl FramePollSettings {
+    pub fn is_enabled_for(&self, bundle_id: Option<&str>) -> bool {
+        bundle_id.is_some_and(|id| self.apps.iter().any(|app| app == id))
+    }
 }
 
+fn default_frame_poll_interval_ms() -> u64 { 1000 }
+
+fn default_frame_poll_threshold() -> f64 { 2.0 }
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum MenuBarDisplayMode {
@@ -512,12 +873,49 @@ pub struct MissionControlSettings {
     pub fade_enabled: bool,
     #[serde(default = "default_mission_control_fade_duration_ms")]
     pub fade_duration_ms: f64,
+    /// Scale the overlay in/out slightly while it appears and disappears
+    #[serde(default = "no")]
+    pub scale_enabled: bool,
+}
+
+/// Border drawn around the focused window
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FocusBorderSettings {
+    #[serde(default = "no")]
+    pub enabled: bool,
+    /// Width of the border (in points)
+    #[serde(default = "default_focus_border_thickness")]
+    pub thickness: f64,
+    /// Border color as `[r, g, b, a]`, each between 0.0 and 1.0
+    #[serde(default = "default_focus_border_color")]
+    pub color: [f64; 4],
+    /// Display UUIDs on which the border is never drawn
+    #[serde(default)]
+    pub disabled_displays: Vec<String>,
+}
+
+impl Default for FocusBorderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            thickness: default_focus_border_thickness(),
+            color: default_focus_border_color(),
+            disabled_displays: Vec::new(),
+        }
+    }
 }
 
+fn default_focus_border_thickness() -> f64 { 2.0 }
+
+fn default_focus_border_color() -> [f64; 4] { [0.0, 0.5, 1.0, 0.9] }
+
 fn default_mission_control_fade_duration_ms() -> f64 { 180.0 }
 
 fn default_drag_swap_fraction() -> f64 { 0.3 }
 
+fn default_drag_deadzone() -> f64 { 4.0 }
+
 fn default_master_stack_ratio() -> f64 { 0.6 }
 
 fn default_master_stack_count() -> usize { 1 }
@@ -548,7 +946,7 @@ impl StackLineSettings {
     pub fn thickness(&self) -> f64 { if self.enabled { self.thickness } else { 0.0 } }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct LayoutSettings {
     /// Layout mode: "traditional", "bsp", "stack", "master_stack", or "scrolling"
@@ -566,6 +964,54 @@ pub struct LayoutSettings {
     /// Scrolling layout configuration (niri-style columns)
     #[serde(default)]
     pub scrolling: ScrollingLayoutSettings,
+    /// Leave floating windows out of `focus_next_window` / `focus_prev_window` cycling
+    #[serde(default)]
+    pub focus_cycle_skip_floating: bool,
+    /// Wrap window focus cycling around from the last window to the first and back;
+    /// `false` stops at either end
+    #[serde(default = "yes")]
+    pub focus_cycle_wrap: bool,
+    /// Where newly added windows are inserted in the workspace layout
+    #[serde(default)]
+    pub new_window_insert: NewWindowInsert,
+    /// Drop all gaps while a workspace has a single tiled window
+    #[serde(default)]
+    pub smart_gaps: bool,
+    /// Even out window sizes in a workspace whenever a window is added to or removed from it
+    #[serde(default)]
+    pub auto_balance: bool,
+    /// Layout modes `cycle_layout_mode` steps through, in order; every mode when unset
+    #[serde(default)]
+    pub cycle_modes: Option<Vec<LayoutMode>>,
+    /// What to do with a window that is wider or taller than its display
+    #[serde(default)]
+    pub oversized_windows: OversizedWindowPolicy,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NewWindowInsert {
+    /// Next to the focused window; master_stack keeps using `new_window_placement`
+    #[default]
+    AfterFocused,
+    /// After every other window in the layout
+    End,
+    /// Before every other window; in master_stack, at the top of the stack area
+    Beginning,
+    /// In the first (master) slot, pushing the other windows down
+    AsMaster,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OversizedWindowPolicy {
+    /// Leave the frame where the layout put it
+    #[default]
+    Ignore,
+    /// Shrink the window to the display, but never below its minimum size
+    ShrinkToFit,
+    /// Keep the size and center the window so the display clips it evenly
+    Center,
 }
 
 /// Layout mode enum
@@ -585,6 +1031,16 @@ pub enum LayoutMode {
     Scrolling,
 }
 
+impl LayoutMode {
+    pub const ALL: [LayoutMode; 5] = [
+        LayoutMode::Traditional,
+        LayoutMode::Bsp,
+        LayoutMode::Stack,
+        LayoutMode::MasterStack,
+        LayoutMode::Scrolling,
+    ];
+}
+
 impl ToString for LayoutMode {
     fn to_string(&self) -> String {
         match self {
@@ -678,7 +1134,9 @@ pub struct MasterStackSettings {
     /// Which side the master area occupies
     #[serde(default)]
     pub master_side: MasterStackSide,
-    /// Where new windows are inserted when the master area is already full
+    /// Where new windows are inserted when the master area is already full.
+    /// `Master` makes the new window the master and demotes the old one to
+    /// the top of the stack; `Stack` leaves the master alone.
     #[serde(default = "default_master_stack_new_window_placement")]
     pub new_window_placement: MasterStackNewWindowPlacement,
 }
@@ -686,8 +1144,11 @@ pub struct MasterStackSettings {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum MasterStackNewWindowPlacement {
+    /// The new window becomes master
     Master,
+    /// The new window joins the top of the stack
     Stack,
+    /// Whichever of the two holds the focused window
     Focused,
 }
 
@@ -772,6 +1233,9 @@ pub struct GapSettings {
     /// Display-specific gap overrides keyed by display UUID
     #[serde(default)]
     pub per_display: HashMap<String, GapOverride>,
+    /// Layout-mode-specific gap overrides, applied on top of any display override
+    #[serde(default)]
+    pub per_mode: ModeGapOverrides,
 }
 
 /// Outer gap configuration (space between windows and screen edges)
@@ -816,6 +1280,34 @@ pub struct GapOverride {
     pub inner: Option<InnerGaps>,
 }
 
+/// Overrides for gaps on a per-layout-mode basis
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ModeGapOverrides {
+    #[serde(default)]
+    pub traditional: Option<GapOverride>,
+    #[serde(default)]
+    pub bsp: Option<GapOverride>,
+    #[serde(default)]
+    pub stack: Option<GapOverride>,
+    #[serde(default)]
+    pub master_stack: Option<GapOverride>,
+    #[serde(default)]
+    pub scrolling: Option<GapOverride>,
+}
+
+impl ModeGapOverrides {
+    pub fn get(&self, mode: LayoutMode) -> Option<&GapOverride> {
+        match mode {
+            LayoutMode::Traditional => self.traditional.as_ref(),
+            LayoutMode::Bsp => self.bsp.as_ref(),
+            LayoutMode::Stack => self.stack.as_ref(),
+            LayoutMode::MasterStack => self.master_stack.as_ref(),
+            LayoutMode::Scrolling => self.scrolling.as_ref(),
+        }
+    }
+}
+
 impl Default for StackSettings {
     fn default() -> Self {
         Self {
@@ -836,7 +1328,34 @@ impl Default for MasterStackSettings {
     }
 }
 
+/// A rectangle, relative to a display's top-left corner, that focus-follows-mouse ignores.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FocusExclusionZone {
+    /// Display UUID the zone applies to; omit to apply it on every display
+    #[serde(default)]
+    pub display: Option<String>,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A floating window size, as fractions of its display, and where to place it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct FloatingPreset {
+    pub width_frac: f64,
+    pub height_frac: f64,
+    #[serde(default)]
+    pub anchor: Anchor,
+}
+
 impl Settings {
+    pub fn restore_file_path(&self) -> PathBuf {
+        self.restore_file.clone().unwrap_or_else(restore_file)
+    }
+
     pub fn validate(&self) -> Vec<String> {
         let mut issues = Vec::new();
 
@@ -856,6 +1375,39 @@ impl Settings {
 
         issues.extend(self.layout.validate());
 
+        for (idx, preset) in self.floating_presets.iter().enumerate() {
+            let in_range = |frac: f64| frac > 0.0 && frac <= 1.0;
+            if !in_range(preset.width_frac) || !in_range(preset.height_frac) {
+                issues.push(format!(
+                    "floating_presets[{}] fractions must be in (0, 1], got {}x{}",
+                    idx, preset.width_frac, preset.height_frac
+                ));
+            }
+        }
+
+        for (idx, zone) in self.focus_follows_mouse_exclusion_zones.iter().enumerate() {
+            if zone.width <= 0.0 || zone.height <= 0.0 {
+                issues.push(format!(
+                    "focus_follows_mouse_exclusion_zones[{}] must have a positive size, got {}x{}",
+                    idx, zone.width, zone.height
+                ));
+            }
+        }
+
+        let focus_border = &self.ui.focus_border;
+        if focus_border.thickness <= 0.0 {
+            issues.push(format!(
+                "ui.focus_border.thickness must be positive, got {}",
+                focus_border.thickness
+            ));
+        }
+        if focus_border.color.iter().any(|c| !(0.0..=1.0).contains(c)) {
+            issues.push(format!(
+                "ui.focus_border.color components must be between 0.0 and 1.0, got {:?}",
+                focus_border.color
+            ));
+        }
+
         if self.gestures.swipe_vertical_tolerance < 0.0 {
             issues.push(format!(
                 "gestures.swipe_vertical_tolerance must be non-negative, got {}",
@@ -867,6 +1419,25 @@ impl Settings {
     }
 }
 
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            mode: LayoutMode::default(),
+            stack: StackSettings::default(),
+            master_stack: MasterStackSettings::default(),
+            gaps: GapSettings::default(),
+            scrolling: ScrollingLayoutSettings::default(),
+            focus_cycle_skip_floating: false,
+            focus_cycle_wrap: true,
+            new_window_insert: NewWindowInsert::default(),
+            smart_gaps: false,
+            auto_balance: false,
+            cycle_modes: None,
+            oversized_windows: OversizedWindowPolicy::default(),
+        }
+    }
+}
+
 impl LayoutSettings {
     pub fn validate(&self) -> Vec<String> {
         let mut issues = Vec::new();
@@ -879,8 +1450,20 @@ impl LayoutSettings {
 
         issues.extend(self.scrolling.validate());
 
+        if self.cycle_modes.as_ref().is_some_and(|modes| modes.is_empty()) {
+            issues.push("layout.cycle_modes must list at least one layout mode".to_string());
+        }
+
         issues
     }
+
+    /// The layout modes `cycle_layout_mode` visits, in order.
+    pub fn cycle_modes(&self) -> &[LayoutMode] {
+        match &self.cycle_modes {
+            Some(modes) if !modes.is_empty() => modes,
+            _ => &LayoutMode::ALL,
+        }
+    }
 }
 
 impl ScrollingLayoutSettings {
@@ -992,6 +1575,23 @@ impl GapSettings {
             }
         }
 
+        for mode in LayoutMode::ALL {
+            let Some(overrides) = self.per_mode.get(mode) else {
+                continue;
+            };
+            let name = mode.to_string();
+            if let Some(outer) = &overrides.outer {
+                for issue in outer.validate() {
+                    issues.push(format!("per_mode.{name} {issue}"));
+                }
+            }
+            if let Some(inner) = &overrides.inner {
+                for issue in inner.validate() {
+                    issues.push(format!("per_mode.{name} {issue}"));
+                }
+            }
+        }
+
         issues
     }
 
@@ -1000,6 +1600,7 @@ impl GapSettings {
             outer: self.outer.clone(),
             inner: self.inner.clone(),
             per_display: HashMap::default(),
+            per_mode: self.per_mode.clone(),
         };
         if let Some(uuid) = display_uuid {
             if let Some(overrides) = self.per_display.get(uuid) {
@@ -1013,6 +1614,20 @@ impl GapSettings {
         }
         resolved
     }
+
+    /// These gaps with the `per_mode` override for `mode` applied, or `None`
+    /// when that mode has no override.
+    pub fn for_mode(&self, mode: LayoutMode) -> Option<GapSettings> {
+        let overrides = self.per_mode.get(mode)?;
+        let mut resolved = self.clone();
+        if let Some(outer_override) = &overrides.outer {
+            resolved.outer = outer_override.clone();
+        }
+        if let Some(inner_override) = &overrides.inner {
+            resolved.inner = inner_override.clone();
+        }
+        Some(resolved)
+    }
 }
 
 impl OuterGaps {
@@ -1085,11 +1700,41 @@ fn default_animation_duration() -> f64 { 0.3 }
 
 fn default_animation_fps() -> f64 { 100.0 }
 
+fn default_double_tap_interval_ms() -> u64 { 300 }
+
+fn default_floating_presets() -> Vec<FloatingPreset> {
+    vec![
+        FloatingPreset {
+            width_frac: 0.8,
+            height_frac: 0.8,
+            anchor: Anchor::Center,
+        },
+        FloatingPreset {
+            width_frac: 0.5,
+            height_frac: 0.5,
+            anchor: Anchor::Center,
+        },
+        FloatingPreset {
+            width_frac: 0.5,
+            height_frac: 0.5,
+            anchor: Anchor::TopRight,
+        },
+    ]
+}
+
 #[allow(dead_code)]
 fn no() -> bool { false }
 
 fn default_workspace_count() -> usize { 4 }
 
+fn default_auto_float_subroles() -> Vec<String> {
+    vec![
+        "AXDialog".to_string(),
+        "AXSystemDialog".to_string(),
+        "AXFloatingWindow".to_string(),
+    ]
+}
+
 fn default_workspace_names() -> Vec<String> {
     vec![
         "Main".to_string(),
@@ -1109,6 +1754,31 @@ fn default_overscroll_threshold() -> f64 { 0.625 }
 fn default_stack_line_spacing() -> f64 { 1.0 }
 fn default_stack_line_thickness() -> f64 { 20.0 }
 
+/// Tables are walked down to their leaves; anything else, including lists like
+/// the key bindings, counts as a single setting.
+fn collect_non_default(
+    value: &Value,
+    default: Option<&Value>,
+    path: String,
+    out: &mut BTreeMap<String, bool>,
+) {
+    match value {
+        Value::Object(table) if !table.is_empty() => {
+            for (key, child) in table {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect_non_default(child, default.and_then(|d| d.get(key)), child_path, out);
+            }
+        }
+        _ => {
+            out.insert(path, default != Some(value));
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum HapticPattern {
@@ -1153,6 +1823,19 @@ impl Config {
         Ok(())
     }
 
+    /// Resolve the config for display, marking the settings that differ from
+    /// [`Config::default`].
+    pub fn diff_from_defaults(&self) -> anyhow::Result<ConfigDefaultsDiff> {
+        let config = serde_json::to_value(self)?;
+        let defaults = serde_json::to_value(Config::default())?;
+        let mut non_default = BTreeMap::new();
+        collect_non_default(&config, Some(&defaults), String::new(), &mut non_default);
+        Ok(ConfigDefaultsDiff {
+            config,
+            differs_from_default: non_default,
+        })
+    }
+
     /// Validates the entire configuration and returns a list of issues found.
     pub fn validate(&self) -> Vec<String> {
         let mut issues = Vec::new();
@@ -1468,6 +2151,26 @@ mod tests {
         assert!(!cfg.keys.is_empty());
     }
 
+    #[test]
+    fn test_config_diff_marks_settings_changed_from_defaults() {
+        let mut cfg = Config::default();
+        let diff = cfg.diff_from_defaults().unwrap();
+        assert!(!diff.differs_from_default.is_empty());
+        assert!(diff.differs_from_default.values().all(|changed| !changed));
+
+        cfg.settings.animate = !cfg.settings.animate;
+        let diff = cfg.diff_from_defaults().unwrap();
+        assert!(diff.differs_from_default["settings.animate"]);
+        assert!(!diff.differs_from_default["settings.animation_fps"]);
+        let changed: Vec<_> =
+            diff.differs_from_default.iter().filter(|(_, changed)| **changed).collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(
+            diff.config["settings"]["animate"],
+            Value::Bool(cfg.settings.animate)
+        );
+    }
+
     #[test]
     fn test_levenshtein_suggests() {
         let err =
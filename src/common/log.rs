@@ -1,5 +1,7 @@
+use std::sync::LazyLock;
 use std::time::{Duration, Instant};
 
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
@@ -7,6 +9,8 @@ use tracing_subscriber::{EnvFilter, Layer, Registry};
 use tracing_timing::{Histogram, group};
 use tracing_tree::time::UtcDateTime;
 
+use crate::common::collections::HashMap;
+
 pub fn init_logging() {
     tracing_subscriber::registry()
         .with(tree_layer())
@@ -37,11 +41,59 @@ fn timing_layer() -> TimingLayer {
 #[serde(rename_all = "snake_case")]
 pub enum MetricsCommand {
     ShowTiming,
+    /// Time only one in every `rate` events of the given kind (e.g.
+    /// `window_frame_changed`). A rate of 0 stops timing them; 1 times all.
+    SetSampling {
+        event: String,
+        rate: u32,
+    },
+    /// Time every event again.
+    ResetSampling,
 }
 
 pub fn handle_command(command: MetricsCommand) {
     match command {
         MetricsCommand::ShowTiming => show_timing(),
+        MetricsCommand::SetSampling { event, rate } => SAMPLER.lock().set_rate(event, rate),
+        MetricsCommand::ResetSampling => SAMPLER.lock().reset(),
+    }
+}
+
+static SAMPLER: LazyLock<Mutex<EventSampler>> = LazyLock::new(Default::default);
+
+/// Whether this occurrence of an event of kind `event` should be timed.
+pub fn should_time_event(event: &str) -> bool { SAMPLER.lock().should_sample(event) }
+
+/// Per-kind sampling rates for event timing. Kinds without a rate are always
+/// sampled.
+#[derive(Debug, Default)]
+pub struct EventSampler {
+    kinds: HashMap<String, SampledKind>,
+}
+
+#[derive(Debug, Default)]
+struct SampledKind {
+    rate: u32,
+    seen: u32,
+}
+
+impl EventSampler {
+    pub fn set_rate(&mut self, event: String, rate: u32) {
+        self.kinds.insert(event, SampledKind { rate, seen: 0 });
+    }
+
+    pub fn reset(&mut self) { self.kinds.clear(); }
+
+    pub fn should_sample(&mut self, event: &str) -> bool {
+        let Some(kind) = self.kinds.get_mut(event) else {
+            return true;
+        };
+        if kind.rate == 0 {
+            return false;
+        }
+        let sample = kind.seen % kind.rate == 0;
+        kind.seen = (kind.seen + 1) % kind.rate;
+        sample
     }
 }
 
@@ -79,3 +131,23 @@ pub fn trace_misc<T>(desc: &str, f: impl FnOnce() -> T) -> T {
     tracing::trace!(time = ?(end - start), "{desc}");
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling_rate_times_one_in_n_events() {
+        let mut sampler = EventSampler::default();
+        sampler.set_rate("window_frame_changed".into(), 4);
+        sampler.set_rate("mouse_moved_over_window".into(), 0);
+
+        let sampled = (0..100).filter(|_| sampler.should_sample("window_frame_changed")).count();
+        assert_eq!(sampled, 25);
+        assert!(!sampler.should_sample("mouse_moved_over_window"));
+        assert!(sampler.should_sample("window_created"));
+
+        sampler.reset();
+        assert!((0..10).all(|_| sampler.should_sample("window_frame_changed")));
+    }
+}
@@ -1,3 +1,7 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
 use tracing::{error, trace};
 
 pub fn parse_command(command: &str) -> Vec<String> {
@@ -98,3 +102,63 @@ pub fn execute_startup_commands(commands: &[String]) {
         });
     }
 }
+
+/// Replace `path` with `contents` by writing a sibling temp file and renaming it
+/// over the original, so a crash mid-write never leaves a truncated file behind.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let Some(file_name) = path.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        ));
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let written = File::create(&tmp_path).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    if let Err(err) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err);
+    }
+    fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupted_atomic_write_keeps_previous_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("layout.ron");
+
+        write_atomic(&path, b"(good: true)").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "(good: true)");
+
+        let err = write_atomic_with(&path, |file| {
+            file.write_all(b"(good: fa")?;
+            Err(io::Error::other("interrupted"))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "interrupted");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "(good: true)");
+        assert!(!dir.path().join("nested").join("layout.ron.tmp").exists());
+
+        write_atomic(&path, b"(good: again)").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "(good: again)");
+    }
+}
@@ -2,7 +2,7 @@ use std::ffi::{CStr, c_char};
 use std::time::Duration;
 
 use r#continue::continuation;
-use tracing::{error, info, trace};
+use tracing::{error, info, trace, warn};
 
 pub mod cli_exec;
 pub mod protocol;
@@ -257,10 +257,17 @@ impl MachHandler {
                 let data = state.list_cli_subscriptions();
                 RiftResponse::Success { data }
             }
-
-            RiftRequest::GetWorkspaces { space_id } => {
-                let workspaces =
-                    self.reactor.query_workspaces(space_id.map(crate::sys::screen::SpaceId::new));
+            RiftRequest::GetSubscribableEvents => RiftResponse::Success {
+                data: serde_json::json!({
+                    "events": crate::actor::broadcast::subscribable_events(),
+                }),
+            },
+
+            RiftRequest::GetWorkspaces { space_id, include_minimized } => {
+                let workspaces = self.reactor.query_workspaces(
+                    space_id.map(crate::sys::screen::SpaceId::new),
+                    include_minimized,
+                );
                 RiftResponse::Success {
                     data: serde_json::to_value(workspaces).unwrap(),
                 }
@@ -273,53 +280,180 @@ impl MachHandler {
                 }
             }
 
-            RiftRequest::GetWindows { space_id } => {
+            RiftRequest::GetWindows { space_id, include_minimized } => {
                 let space_id = space_id.map(|id| crate::sys::screen::SpaceId::new(id));
 
-                let windows = self.reactor.query_windows(space_id);
+                let windows = self.reactor.query_windows(space_id, include_minimized);
                 RiftResponse::Success {
                     data: serde_json::to_value(windows).unwrap(),
                 }
             }
 
             RiftRequest::GetWindowInfo { window_id } => {
-                let window_id = match crate::actor::app::WindowId::from_debug_string(&window_id) {
-                    Some(wid) => wid,
-                    None => {
-                        error!("Invalid window_id format: {}", window_id);
-                        return RiftResponse::Error {
-                            error: serde_json::json!({ "message": "Invalid window_id format", "window_id": window_id }),
-                        };
-                    }
+                let Some(wid) = crate::actor::app::WindowId::parse(&window_id) else {
+                    error!("Invalid window_id format: {}", window_id);
+                    return RiftResponse::Error {
+                        error: serde_json::json!({
+                            "message": "Invalid window_id format, expected \"WindowId { pid: <pid>, idx: <idx> }\" or \"<pid>:<idx>\"",
+                            "window_id": window_id,
+                        }),
+                    };
                 };
 
-                match self.reactor.query_window_info(window_id) {
+                match self.reactor.query_window_info(wid) {
                     Some(window) => RiftResponse::Success {
                         data: serde_json::to_value(window).unwrap(),
                     },
                     None => RiftResponse::Error {
-                        error: serde_json::json!({ "message": "Window not found" }),
+                        error: serde_json::json!({ "message": "Window not found", "window_id": window_id }),
                     },
                 }
             }
 
+            RiftRequest::GetWindowTransaction { window_server_id } => {
+                let transaction = self.reactor.query_window_transaction(
+                    crate::sys::window_server::WindowServerId::new(window_server_id),
+                );
+                RiftResponse::Success {
+                    data: serde_json::to_value(transaction).unwrap(),
+                }
+            }
+
             RiftRequest::GetLayoutState { space_id } => {
                 match self.reactor.query_layout_state(space_id) {
                     Some(layout_state) => RiftResponse::Success {
                         data: serde_json::to_value(layout_state).unwrap(),
                     },
                     None => RiftResponse::Error {
-                        error: serde_json::json!({ "message": "Space not found or inactive" }),
+                        error: serde_json::json!({ "message": "Space not found", "space_id": space_id }),
                     },
                 }
             }
             RiftRequest::GetWorkspaceLayouts { space_id, workspace_id } => {
-                let workspace_layouts = self.reactor.query_workspace_layouts(
+                match self.reactor.query_workspace_layouts(
                     space_id.map(crate::sys::screen::SpaceId::new),
                     workspace_id,
-                );
-                RiftResponse::Success {
-                    data: serde_json::to_value(workspace_layouts).unwrap(),
+                ) {
+                    Some(workspace_layouts) => RiftResponse::Success {
+                        data: serde_json::to_value(workspace_layouts).unwrap(),
+                    },
+                    None => RiftResponse::Error {
+                        error: serde_json::json!({ "message": "Space not found", "space_id": space_id }),
+                    },
+                }
+            }
+
+            RiftRequest::GetLayoutMode { space_id } => {
+                match self.reactor.query_layout_mode(space_id.map(crate::sys::screen::SpaceId::new))
+                {
+                    Some(layout_mode) => RiftResponse::Success {
+                        data: serde_json::to_value(layout_mode).unwrap(),
+                    },
+                    None => RiftResponse::Error {
+                        error: serde_json::json!({ "message": "Space not found or has no active workspace" }),
+                    },
+                }
+            }
+
+            RiftRequest::GetPendingSplit { space_id } => {
+                match self
+                    .reactor
+                    .query_pending_split(space_id.map(crate::sys::screen::SpaceId::new))
+                {
+                    Some(pending_split) => RiftResponse::Success {
+                        data: serde_json::to_value(pending_split).unwrap(),
+                    },
+                    None => RiftResponse::Error {
+                        error: serde_json::json!({ "message": "Space not found or has no active workspace" }),
+                    },
+                }
+            }
+
+            RiftRequest::RetileSpace { space_id } => {
+                match self.reactor.retile_space(space_id.map(crate::sys::screen::SpaceId::new)) {
+                    Some(result) => RiftResponse::Success {
+                        data: serde_json::to_value(result).unwrap(),
+                    },
+                    None => RiftResponse::Error {
+                        error: serde_json::json!({ "message": "Space not found or inactive" }),
+                    },
+                }
+            }
+
+            RiftRequest::ReapplyAppRules => match self.reactor.reapply_app_rules() {
+                Some(result) => RiftResponse::Success {
+                    data: serde_json::to_value(result).unwrap(),
+                },
+                None => RiftResponse::Error {
+                    error: serde_json::json!({ "message": "Failed to reapply app rules" }),
+                },
+            },
+
+            RiftRequest::GetMenuState => match self.reactor.query_menu_state() {
+                Some(state) => RiftResponse::Success {
+                    data: serde_json::to_value(state).unwrap(),
+                },
+                None => RiftResponse::Error {
+                    error: serde_json::json!({ "message": "Failed to query menu state" }),
+                },
+            },
+
+            RiftRequest::ResetMenuState => match self.reactor.reset_menu_state() {
+                Some(state) => RiftResponse::Success {
+                    data: serde_json::to_value(state).unwrap(),
+                },
+                None => RiftResponse::Error {
+                    error: serde_json::json!({ "message": "Failed to reset menu state" }),
+                },
+            },
+
+            RiftRequest::GetEventLog { limit } => match self.reactor.query_event_log(limit) {
+                Some(entries) => RiftResponse::Success {
+                    data: serde_json::to_value(entries).unwrap(),
+                },
+                None => RiftResponse::Error {
+                    error: serde_json::json!({
+                        "message": "Event log is off; set settings.event_log_size to enable it"
+                    }),
+                },
+            },
+
+            RiftRequest::DeserializeState { state } => {
+                match self.reactor.deserialize_state(state) {
+                    Ok(result) => RiftResponse::Success {
+                        data: serde_json::to_value(result).unwrap(),
+                    },
+                    Err(message) => RiftResponse::Error {
+                        error: serde_json::json!({ "message": message }),
+                    },
+                }
+            }
+
+            RiftRequest::SetLayoutMode { space_id, mode } => {
+                let layout_mode = match serde_json::from_value::<crate::common::config::LayoutMode>(
+                    serde_json::Value::String(mode.clone()),
+                ) {
+                    Ok(layout_mode) => layout_mode,
+                    Err(_) => {
+                        return RiftResponse::Error {
+                            error: serde_json::json!({
+                                "message": "Invalid layout mode; must be traditional, bsp, stack, master_stack, or scrolling",
+                                "mode": mode,
+                            }),
+                        };
+                    }
+                };
+
+                match self
+                    .reactor
+                    .set_layout_mode(space_id.map(crate::sys::screen::SpaceId::new), layout_mode)
+                {
+                    Some(result) => RiftResponse::Success {
+                        data: serde_json::to_value(result).unwrap(),
+                    },
+                    None => RiftResponse::Error {
+                        error: serde_json::json!({ "message": "Space not found or has no active workspace" }),
+                    },
                 }
             }
 
@@ -335,6 +469,22 @@ impl MachHandler {
                 RiftResponse::Success { data: metrics }
             }
 
+            RiftRequest::GetMetricsPrometheus => match self.reactor.query_metrics_prometheus() {
+                Some(text) => RiftResponse::Success {
+                    data: serde_json::Value::String(text),
+                },
+                None => RiftResponse::Error {
+                    error: serde_json::json!({ "message": "reactor is not running" }),
+                },
+            },
+
+            RiftRequest::GetMissionControlState => {
+                let state = self.reactor.query_mission_control_state();
+                RiftResponse::Success {
+                    data: serde_json::json!({ "state": state }),
+                }
+            }
+
             RiftRequest::GetConfig => {
                 match self.perform_config_query(|tx| config_actor::Event::QueryConfig(tx)) {
                     Ok(config) => match serde_json::to_value(&config) {
@@ -355,6 +505,32 @@ impl MachHandler {
                 }
             }
 
+            RiftRequest::GetConfigDiffFromDefaults => {
+                match self.perform_config_query(|tx| config_actor::Event::QueryConfig(tx)) {
+                    Ok(config) => match config.diff_from_defaults().map(serde_json::to_value) {
+                        Ok(Ok(value)) => RiftResponse::Success { data: value },
+                        Ok(Err(e)) => {
+                            error!("Failed to serialize config diff: {}", e);
+                            RiftResponse::Error {
+                                error: serde_json::json!({ "message": "Failed to serialize config diff", "details": format!("{}", e) }),
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to diff config against defaults: {}", e);
+                            RiftResponse::Error {
+                                error: serde_json::json!({ "message": "Failed to diff config against defaults", "details": format!("{}", e) }),
+                            }
+                        }
+                    },
+                    Err(e) => {
+                        error!("{}", e);
+                        RiftResponse::Error {
+                            error: serde_json::json!({ "message": "Failed to get config response", "details": format!("{}", e) }),
+                        }
+                    }
+                }
+            }
+
             RiftRequest::ExecuteCommand { command, args } => {
                 match serde_json::from_str::<RiftCommand>(&command) {
                     Ok(RiftCommand::Config(_)) => {
@@ -456,8 +632,8 @@ unsafe extern "C" fn handle_mach_request_c(
 
     let client_port = unsafe { (*original_msg).msgh_remote_port };
 
-    let request: RiftRequest = match serde_json::from_str(message_str) {
-        Ok(req) => req,
+    let (request, warnings) = match protocol::parse_request(message_str) {
+        Ok(parsed) => parsed,
         Err(e) => {
             error!("Failed to parse request: {}", e);
             let error_response = RiftResponse::Error {
@@ -468,7 +644,11 @@ unsafe extern "C" fn handle_mach_request_c(
         }
     };
 
-    let response = handler.handle_request(request, client_port);
+    if !warnings.is_empty() {
+        warn!(?warnings, "Request fields were ignored or defaulted");
+    }
+
+    let response = handler.handle_request(request, client_port).with_warnings(warnings);
     send_response(original_msg, &response);
 }
 
@@ -117,6 +117,77 @@ impl CliExecutor for DefaultCliExecutor {
                     env_vars.insert("RIFT_DISPLAY_UUID".into(), display_uuid.clone());
                 }
             }
+            BroadcastEvent::WorkspaceEmpty {
+                workspace_id,
+                workspace_name,
+                space_id,
+                display_uuid,
+            }
+            | BroadcastEvent::WorkspaceNonempty {
+                workspace_id,
+                workspace_name,
+                space_id,
+                display_uuid,
+            } => {
+                let event_type = if matches!(event, BroadcastEvent::WorkspaceEmpty { .. }) {
+                    "workspace_empty"
+                } else {
+                    "workspace_nonempty"
+                };
+                env_vars.insert("RIFT_EVENT_TYPE".into(), event_type.into());
+                env_vars.insert("RIFT_WORKSPACE_ID".into(), workspace_id.to_string());
+                env_vars.insert("RIFT_WORKSPACE_NAME".into(), workspace_name.clone());
+                env_vars.insert("RIFT_SPACE_ID".into(), space_id.to_string());
+                if let Some(display_uuid) = display_uuid.as_ref() {
+                    env_vars.insert("RIFT_DISPLAY_UUID".into(), display_uuid.clone());
+                }
+            }
+            BroadcastEvent::WorkspaceRenamed {
+                space_id,
+                workspace_id,
+                workspace_index,
+                previous_name,
+                workspace_name,
+                display_uuid,
+            } => {
+                env_vars.insert("RIFT_EVENT_TYPE".into(), "workspace_renamed".into());
+                env_vars.insert("RIFT_WORKSPACE_ID".into(), workspace_id.to_string());
+                env_vars.insert("RIFT_WORKSPACE_NAME".into(), workspace_name.clone());
+                env_vars.insert("RIFT_PREVIOUS_WORKSPACE_NAME".into(), previous_name.clone());
+                if let Some(workspace_index) = workspace_index {
+                    env_vars.insert("RIFT_WORKSPACE_INDEX".into(), workspace_index.to_string());
+                }
+                env_vars.insert("RIFT_SPACE_ID".into(), space_id.to_string());
+                if let Some(display_uuid) = display_uuid.as_ref() {
+                    env_vars.insert("RIFT_DISPLAY_UUID".into(), display_uuid.clone());
+                }
+            }
+            BroadcastEvent::WindowManageabilityChanged {
+                window_id,
+                is_manageable,
+                reason,
+            } => {
+                env_vars.insert("RIFT_EVENT_TYPE".into(), "window_manageability_changed".into());
+                env_vars.insert("RIFT_WINDOW_ID".into(), window_id.to_debug_string());
+                env_vars.insert("RIFT_WINDOW_MANAGEABLE".into(), is_manageable.to_string());
+                env_vars.insert("RIFT_MANAGEABILITY_REASON".into(), reason.as_str().into());
+            }
+            BroadcastEvent::AppLaunched { pid, bundle_id, app_name }
+            | BroadcastEvent::AppTerminated { pid, bundle_id, app_name } => {
+                let event_type = if matches!(event, BroadcastEvent::AppLaunched { .. }) {
+                    "app_launched"
+                } else {
+                    "app_terminated"
+                };
+                env_vars.insert("RIFT_EVENT_TYPE".into(), event_type.into());
+                env_vars.insert("RIFT_APP_PID".into(), pid.to_string());
+                if let Some(bundle_id) = bundle_id.as_ref() {
+                    env_vars.insert("RIFT_APP_BUNDLE_ID".into(), bundle_id.clone());
+                }
+                if let Some(app_name) = app_name.as_ref() {
+                    env_vars.insert("RIFT_APP_NAME".into(), app_name.clone());
+                }
+            }
         }
 
         let event_json = match serde_json::to_string(event) {
@@ -7,14 +7,23 @@ use serde_json::Value;
 pub enum RiftRequest {
     GetWorkspaces {
         space_id: Option<u64>,
+        /// Also list minimized windows, under the workspace they were minimized from.
+        #[serde(default)]
+        include_minimized: bool,
     },
     GetDisplays,
     GetWindows {
         space_id: Option<u64>,
+        /// Also list minimized windows that were on the space.
+        #[serde(default)]
+        include_minimized: bool,
     },
     GetWindowInfo {
         window_id: String,
     },
+    GetWindowTransaction {
+        window_server_id: u32,
+    },
     GetLayoutState {
         space_id: u64,
     },
@@ -22,11 +31,53 @@ pub enum RiftRequest {
         space_id: Option<u64>,
         workspace_id: Option<usize>,
     },
+    GetLayoutMode {
+        space_id: Option<u64>,
+    },
+    SetLayoutMode {
+        space_id: Option<u64>,
+        mode: String,
+    },
+    /// Get the split orientation set by `set_split_direction` for the next window.
+    GetPendingSplit {
+        space_id: Option<u64>,
+    },
+    /// Lay out one space's tiled windows again; replies with how many there were.
+    RetileSpace {
+        space_id: Option<u64>,
+    },
+    /// Run app rules again for every window on an active space; replies with how
+    /// many changed.
+    ReapplyAppRules,
+    /// Whether Rift thinks an app's menu is open, which holds off focus-follows-mouse.
+    GetMenuState,
+    /// Mark every menu closed again; replies with the state that was cleared.
+    ResetMenuState,
+    /// The most recent layout events and commands with their responses, oldest
+    /// first; `limit` keeps only the newest entries. Needs `settings.event_log_size`.
+    GetEventLog {
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    /// Restore the output of the `serialize` command; replies with the windows
+    /// that couldn't be matched.
+    DeserializeState {
+        state: String,
+    },
     GetApplications,
     GetMetrics,
+    /// The `GetMetrics` numbers, plus event and layout command counts and
+    /// handler latencies, as Prometheus text exposition format in a string.
+    GetMetricsPrometheus,
+    GetMissionControlState,
     GetConfig,
+    /// The config with every default filled in, plus which settings differ
+    /// from the defaults. Settings are compared by value, not by whether the
+    /// config file mentions them.
+    GetConfigDiffFromDefaults,
     ExecuteCommand {
         command: String,
+        #[serde(default)]
         args: Vec<String>,
     },
     Subscribe {
@@ -38,20 +89,91 @@ pub enum RiftRequest {
     SubscribeCli {
         event: String,
         command: String,
+        #[serde(default)]
         args: Vec<String>,
     },
     UnsubscribeCli {
         event: String,
     },
     ListCliSubscriptions,
+    /// List the event names accepted by `Subscribe`, with a description and the
+    /// payload fields of each.
+    GetSubscribableEvents,
 }
 
 #[non_exhaustive]
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum RiftResponse {
-    Success { data: Value },
-    Error { error: Value },
+    /// A success for a request that was only partly understood. Listed first so
+    /// it is not read back as a plain `Success`.
+    SuccessWithWarnings {
+        data: Value,
+        warnings: Vec<RequestWarning>,
+    },
+    Success {
+        data: Value,
+    },
+    Error {
+        error: Value,
+    },
+}
+
+impl RiftResponse {
+    /// Attach the warnings from `parse_request` to a successful response.
+    pub fn with_warnings(self, warnings: Vec<RequestWarning>) -> Self {
+        match self {
+            RiftResponse::Success { data } if !warnings.is_empty() => {
+                RiftResponse::SuccessWithWarnings { data, warnings }
+            }
+            other => other,
+        }
+    }
+}
+
+/// A field of a request that was accepted but not taken as sent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RequestWarning {
+    /// The field is not part of the request (e.g. sent by a newer client) and was ignored.
+    UnknownField { field: String },
+    /// The field was missing and its default was used.
+    DefaultedField { field: String },
+}
+
+/// Parse a request, tolerating unknown fields and missing optional ones. Both are
+/// reported as warnings instead of failing the request; an unknown request or a
+/// missing required field is still an error.
+pub fn parse_request(json: &str) -> serde_json::Result<(RiftRequest, Vec<RequestWarning>)> {
+    let sent: Value = serde_json::from_str(json)?;
+    let request = RiftRequest::deserialize(&sent)?;
+
+    // Serializing the parsed request back gives its full set of fields, so
+    // comparing against what was sent shows what was dropped or filled in.
+    let parsed = serde_json::to_value(&request)?;
+    let mut warnings = Vec::new();
+    if let (Some(sent), Some(parsed)) = (request_fields(&sent), request_fields(&parsed)) {
+        warnings.extend(
+            sent.keys()
+                .filter(|field| !parsed.contains_key(*field))
+                .map(|field| RequestWarning::UnknownField { field: field.clone() }),
+        );
+        warnings.extend(
+            parsed
+                .keys()
+                .filter(|field| !sent.contains_key(*field))
+                .map(|field| RequestWarning::DefaultedField { field: field.clone() }),
+        );
+    }
+    Ok((request, warnings))
+}
+
+fn request_fields(request: &Value) -> Option<&serde_json::Map<String, Value>> {
+    let mut variants = request.as_object()?.values();
+    match (variants.next(), variants.next()) {
+        (Some(fields), None) => fields.as_object(),
+        _ => None,
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -59,3 +181,73 @@ pub enum RiftCommand {
     Reactor(crate::actor::reactor::Command),
     Config(crate::common::config::ConfigCommand),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_request_ignores_unknown_fields_with_a_warning() {
+        let (request, warnings) =
+            parse_request(r#"{"get_windows": {"space_id": 3, "include_hidden": true}}"#).unwrap();
+        assert!(matches!(request, RiftRequest::GetWindows {
+            space_id: Some(3),
+            ..
+        }));
+        assert_eq!(warnings, vec![
+            RequestWarning::UnknownField {
+                field: "include_hidden".to_string()
+            },
+            RequestWarning::DefaultedField {
+                field: "include_minimized".to_string()
+            },
+        ]);
+
+        let (_, warnings) =
+            parse_request(r#"{"get_windows": {"space_id": 3, "include_minimized": false}}"#)
+                .unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn parse_request_defaults_missing_optional_fields() {
+        let (request, warnings) =
+            parse_request(r#"{"execute_command": {"command": "\"save_and_exit\""}}"#).unwrap();
+        assert!(matches!(
+            request,
+            RiftRequest::ExecuteCommand { ref args, .. } if args.is_empty()
+        ));
+        assert_eq!(warnings, vec![RequestWarning::DefaultedField {
+            field: "args".to_string()
+        }]);
+
+        let (request, warnings) =
+            parse_request(r#"{"get_workspaces": {"include_minimized": true}}"#).unwrap();
+        assert!(matches!(request, RiftRequest::GetWorkspaces {
+            space_id: None,
+            include_minimized: true
+        }));
+        assert_eq!(warnings, vec![RequestWarning::DefaultedField {
+            field: "space_id".to_string()
+        }]);
+
+        // Required fields are still required.
+        assert!(parse_request(r#"{"get_window_info": {}}"#).is_err());
+        assert!(parse_request(r#"{"get_window_info": {"extra": 1}}"#).is_err());
+    }
+
+    #[test]
+    fn warnings_survive_a_response_round_trip() {
+        let warnings = vec![RequestWarning::UnknownField { field: "extra".to_string() }];
+        let response =
+            RiftResponse::Success { data: serde_json::json!(1) }.with_warnings(warnings.clone());
+        let json = serde_json::to_string(&response).unwrap();
+        match serde_json::from_str::<RiftResponse>(&json).unwrap() {
+            RiftResponse::SuccessWithWarnings { data, warnings: read } => {
+                assert_eq!(data, serde_json::json!(1));
+                assert_eq!(read, warnings);
+            }
+            other => panic!("unexpected response {other:?}"),
+        }
+    }
+}
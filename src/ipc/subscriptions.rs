@@ -2,6 +2,7 @@ use std::ffi::CString;
 use std::os::raw::c_char;
 use std::sync::Arc;
 use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crossbeam_channel::{Sender, TrySendError, bounded};
 use dashmap::DashMap;
@@ -10,7 +11,7 @@ use parking_lot::{Mutex, RwLock};
 use serde_json::Value;
 use tracing::{debug, error, info, warn};
 
-use crate::actor::broadcast::BroadcastEvent;
+use crate::actor::broadcast::{BroadcastEvent, WILDCARD_EVENT, is_subscribable_event};
 use crate::common::collections::{HashMap, HashSet};
 use crate::sys::mach::{mach_release_send_right, mach_retain_send_right, mach_try_send_message};
 
@@ -20,6 +21,10 @@ pub type ClientPort = u32;
 pub struct CliSubscription {
     pub command: String,
     pub args: Vec<String>,
+    /// Number of events this subscription has been dispatched for.
+    pub fire_count: u64,
+    /// Milliseconds since the Unix epoch of the most recent dispatch.
+    pub last_fired_ms: Option<u64>,
 }
 
 pub struct ServerState {
@@ -65,6 +70,9 @@ impl ServerState {
 
     pub fn subscribe_client(&self, client_port: ClientPort, event: String) {
         info!("Client {} subscribing to event: {}", client_port, event);
+        if !is_subscribable_event(&event) {
+            warn!("Client {} subscribed to unknown event '{}'", client_port, event);
+        }
         let mut added = false;
         let mut should_retain_send_right = false;
 
@@ -135,8 +143,16 @@ impl ServerState {
             "CLI subscribing to event '{}' with command: {} {:?}",
             event, command, args
         );
+        if !is_subscribable_event(&event) {
+            warn!("CLI subscribed to unknown event '{}'", event);
+        }
 
-        let subscription = CliSubscription { command, args };
+        let subscription = CliSubscription {
+            command,
+            args,
+            fire_count: 0,
+            last_fired_ms: None,
+        };
 
         let mut guard = self.cli_subscriptions.lock();
         let list = guard.entry(event.clone()).or_insert_with(Vec::new);
@@ -167,6 +183,8 @@ impl ServerState {
                     "event": event,
                     "command": s.command,
                     "args": s.args,
+                    "fire_count": s.fire_count,
+                    "last_fired_ms": s.last_fired_ms,
                 }));
             }
         }
@@ -182,18 +200,13 @@ impl ServerState {
     }
 
     fn forward_event_to_subscribers(&self, event: BroadcastEvent) {
-        let event_name = match &event {
-            BroadcastEvent::WorkspaceChanged { .. } => "workspace_changed",
-            BroadcastEvent::WindowsChanged { .. } => "windows_changed",
-            BroadcastEvent::WindowTitleChanged { .. } => "window_title_changed",
-            BroadcastEvent::StacksChanged { .. } => "stacks_changed",
-        };
+        let event_name = event.name();
 
         let mut targets: HashSet<ClientPort> = HashSet::default();
         if let Some(clients) = self.subscriptions_by_event.get(event_name) {
             targets.extend(clients.iter().copied());
         }
-        if let Some(clients) = self.subscriptions_by_event.get("*") {
+        if let Some(clients) = self.subscriptions_by_event.get(WILDCARD_EVENT) {
             targets.extend(clients.iter().copied());
         }
 
@@ -230,22 +243,23 @@ impl ServerState {
     }
 
     fn forward_event_to_cli_subscribers(&self, event: BroadcastEvent) {
-        let event_name = match &event {
-            BroadcastEvent::WorkspaceChanged { .. } => "workspace_changed",
-            BroadcastEvent::WindowsChanged { .. } => "windows_changed",
-            BroadcastEvent::WindowTitleChanged { .. } => "window_title_changed",
-            BroadcastEvent::StacksChanged { .. } => "stacks_changed",
-        };
+        let event_name = event.name();
 
-        // Collect relevant subscriptions without full HashMap clone
+        // Collect relevant subscriptions without full HashMap clone, recording
+        // the dispatch on each one while the lock is held.
+        let now_ms =
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
         let mut relevant: Vec<CliSubscription> = Vec::new();
         {
-            let guard = self.cli_subscriptions.lock();
-            if let Some(list) = guard.get(event_name) {
-                relevant.extend(list.iter().cloned());
-            }
-            if let Some(list) = guard.get("*") {
-                relevant.extend(list.iter().cloned());
+            let mut guard = self.cli_subscriptions.lock();
+            for key in [event_name, WILDCARD_EVENT] {
+                if let Some(list) = guard.get_mut(key) {
+                    for subscription in list.iter_mut() {
+                        subscription.fire_count += 1;
+                        subscription.last_fired_ms = Some(now_ms);
+                        relevant.push(subscription.clone());
+                    }
+                }
             }
         }
 
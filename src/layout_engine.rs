@@ -7,7 +7,7 @@ mod workspaces;
 
 pub use engine::{EventResponse, LayoutCommand, LayoutEngine, LayoutEvent};
 pub(crate) use floating::FloatingManager;
-pub use graph::{Direction, LayoutKind, Orientation};
+pub use graph::{Anchor, Direction, LayoutKind, Orientation};
 pub(crate) use systems::LayoutId;
 pub use systems::{
     BspLayoutSystem, LayoutSystem, LayoutSystemKind, MasterStackLayoutSystem,
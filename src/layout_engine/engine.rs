@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::path::PathBuf;
 
@@ -5,16 +6,25 @@ use objc2_core_foundation::{CGPoint, CGRect, CGSize};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
-use super::{Direction, FloatingManager, LayoutId, LayoutSystemKind, WorkspaceLayouts};
+use super::{
+    Anchor, Direction, FloatingManager, LayoutId, LayoutKind, LayoutSystemKind, Orientation,
+    WorkspaceLayouts,
+};
 use crate::actor::app::{AppInfo, WindowId, pid_t};
 use crate::actor::broadcast::{BroadcastEvent, BroadcastSender};
 use crate::common::collections::{HashMap, HashSet};
-use crate::common::config::{LayoutMode, LayoutSettings, VirtualWorkspaceSettings};
+use crate::common::config::{
+    LayoutMode, LayoutSettings, NewWindowInsert, OversizedWindowPolicy, VirtualWorkspaceSettings,
+};
+use crate::common::util::write_atomic;
 use crate::layout_engine::LayoutSystem;
 use crate::layout_engine::systems::WindowLayoutConstraints;
+use crate::layout_engine::utils::compute_tiling_area;
 use crate::model::virtual_workspace::{
-    AppRuleAssignment, AppRuleResult, VirtualWorkspace, VirtualWorkspaceId, VirtualWorkspaceManager,
+    AppRuleAssignment, AppRuleResult, VirtualWorkspace, VirtualWorkspaceId,
+    VirtualWorkspaceManager, WorkspaceError,
 };
+use crate::sys::app::WindowInfo;
 use crate::sys::screen::SpaceId;
 
 #[derive(Debug, Clone)]
@@ -25,25 +35,81 @@ pub struct GroupContainerInfo {
     pub total_count: usize,
     pub selected_index: usize,
     pub window_ids: Vec<crate::actor::app::WindowId>,
+    pub is_collapsed: bool,
 }
 
 #[non_exhaustive]
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, strum_macros::IntoStaticStr)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum LayoutCommand {
     NextWindow,
     PrevWindow,
+    /// Cycle focus through every window in the active workspace: tiled windows in
+    /// layout order, followed by floating windows unless `focus_cycle_skip_floating` is set.
+    FocusNextWindow,
+    FocusPrevWindow,
+    /// Focus the manageable window with the largest on-screen area in the active
+    /// workspace, preferring the most recently focused one on a tie.
+    FocusLargestWindow,
+    /// Cycle focus through the manageable windows in the active workspace in reading
+    /// order of their on-screen frames: left to right, then top to bottom, wrapping.
+    FocusNextSpatial,
+    FocusPrevSpatial,
+    /// Tag the focused window with a mark, moving the mark off any other window.
+    MarkWindow(String),
+    /// Focus the window carrying the mark, switching workspace if needed.
+    FocusMark(String),
+    /// Focus the app's window that most recently asked for attention, switching
+    /// workspace if needed. A window asks for attention when an unread count like
+    /// "(3)" appears or rises in its title while it isn't focused.
+    FocusAppUrgent {
+        bundle_id: String,
+    },
     MoveFocus(#[serde(rename = "direction")] Direction),
     Ascend,
     Descend,
     MoveNode(Direction),
+    /// Move the focused window into the neighboring container in the given direction
+    /// as a child of it, rather than swapping with it (traditional layout only).
+    MoveWindowIntoContainer(Direction),
+    /// Rearrange the children of the focused window's container into a grid of
+    /// `rows` by `cols` nested splits, filled row by row; any children past the last
+    /// cell are stacked in it (traditional layout only).
+    GridArrange {
+        rows: usize,
+        cols: usize,
+    },
+    /// Split the focused window in the given orientation when the next window is
+    /// added to its workspace, instead of placing it by the usual rules
+    /// (traditional layout only).
+    SetSplitDirection(Orientation),
+    /// Pin the active workspace's window sizes: until `UnfreezeLayout`, new windows
+    /// are appended without resizing the others relative to each other, a closed
+    /// window's space goes to its neighbor, and auto-balance leaves it alone.
+    FreezeLayout,
+    UnfreezeLayout,
 
     JoinWindow(Direction),
     ToggleStack,
+    /// Collapse the focused stacked container down to its title strip, or expand it
+    /// back (traditional layout only).
+    ToggleContainerCollapsed,
+    /// Hide or show the focused window's title in the stack line.
+    ToggleWindowTitleVisible,
     ToggleOrientation,
     UnjoinWindows,
     ToggleFocusFloating,
     ToggleWindowFloating,
+    /// Tile every floating window in the active workspace, placed as
+    /// `new_window_insert` says. Their floating frames are kept for `FloatAllTiled`.
+    TileAllFloating,
+    /// Float the windows the last `TileAllFloating` tiled in the active workspace
+    /// again, back at their old frames.
+    FloatAllTiled,
+    /// Keep the focused window on whichever workspace its display is showing,
+    /// following it through workspace switches.
+    ToggleSticky,
     ToggleFullscreen,
     ToggleFullscreenWithinGaps,
 
@@ -52,6 +118,21 @@ pub enum LayoutCommand {
     ResizeWindowBy {
         amount: f64,
     },
+    /// Grow the focused tiled window toward a direction until it meets
+    /// another window or the edge of the workspace, taking up any empty space
+    /// in between.
+    GrowToFill(Direction),
+    /// Size the focused floating window to fractions of its display and place
+    /// it at `anchor`. Does nothing for tiled windows.
+    ResizeFloatingToFraction {
+        width_frac: f64,
+        height_frac: f64,
+        #[serde(default)]
+        anchor: Anchor,
+    },
+    /// Size and place the focused floating window by the next of the
+    /// `floating_presets` setting. Does nothing for tiled windows.
+    CycleFloatingPreset,
 
     /// Scroll the strip by a normalized delta (scaled by column step width)
     ScrollStrip {
@@ -59,6 +140,12 @@ pub enum LayoutCommand {
     },
     /// Snap the strip to the nearest column boundary
     SnapStrip,
+    /// Scroll the strip by a full viewport width
+    ScrollPageLeft,
+    ScrollPageRight,
+    /// Scroll the strip to its first column / last full page
+    ScrollToStart,
+    ScrollToEnd,
     /// Toggle centering for the selected column without changing alignment settings.
     /// The center override is cleared when focus moves to a different window.
     CenterSelection,
@@ -69,13 +156,48 @@ pub enum LayoutCommand {
     MoveWindowToWorkspace {
         workspace: usize,
         window_id: Option<u32>,
+        /// Pick the window by app instead of id. If no such window exists yet,
+        /// the move is held until one is created.
+        #[serde(default)]
+        bundle_id: Option<String>,
+        /// Only pick windows whose title contains this.
+        #[serde(default)]
+        title: Option<String>,
+        /// Position for the window in the destination workspace's window order,
+        /// clamped to the last slot. Defaults to the usual insertion point.
+        #[serde(default)]
+        target_index: Option<usize>,
+    },
+    /// Move the focused window to the adjacent workspace, wrapping like
+    /// `NextWorkspace`/`PrevWorkspace`. With `follow`, also switch to it.
+    MoveWindowToNextWorkspace {
+        #[serde(default)]
+        follow: bool,
+    },
+    MoveWindowToPrevWorkspace {
+        #[serde(default)]
+        follow: bool,
+    },
+    /// Move the focused window to the workspace `SwitchToLastWorkspace` would go
+    /// to on its display. With `follow`, also switch to it.
+    SendToLastWorkspace {
+        #[serde(default)]
+        follow: bool,
     },
     SetWorkspaceLayout {
         workspace: Option<usize>,
         mode: LayoutMode,
     },
+    /// Switch the active workspace to the layout mode after its current one in
+    /// `layout.cycle_modes`, wrapping around.
+    CycleLayoutMode,
     CreateWorkspace,
     SwitchToLastWorkspace,
+    /// Rename the workspace at `index` (or the active one) on the focused display.
+    RenameWorkspace {
+        index: Option<usize>,
+        name: String,
+    },
 
     SwapWindows(crate::actor::app::WindowId, crate::actor::app::WindowId),
 
@@ -87,6 +209,21 @@ pub enum LayoutCommand {
     },
     PromoteToMaster,
     SwapMasterStack,
+    /// Swap the focused window with the master window (the first window in
+    /// traversal order outside of master_stack).
+    SwapWithMaster,
+    /// Decide for the active workspace whether focus cycling wraps around,
+    /// overriding `focus_cycle_wrap`. Leaving `wrap` out clears the override.
+    SetWorkspaceFocusWrap {
+        #[serde(default)]
+        wrap: Option<bool>,
+    },
+    /// Set where new windows go in the active workspace, overriding
+    /// `new_window_insert`. Leaving `insert` out clears the override.
+    SetWorkspaceInsertPosition {
+        #[serde(default)]
+        insert: Option<NewWindowInsert>,
+    },
 }
 
 #[non_exhaustive]
@@ -129,6 +266,14 @@ pub struct EventResponse {
     pub boundary_hit: Option<Direction>,
 }
 
+/// Layout settings replaced for a single workspace by `SetWorkspaceFocusWrap` and
+/// `SetWorkspaceInsertPosition`; unset fields fall back to the global settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct WorkspaceOverrides {
+    focus_wrap: Option<bool>,
+    new_window_insert: Option<NewWindowInsert>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct LayoutEngine {
     workspace_layouts: WorkspaceLayouts,
@@ -146,6 +291,20 @@ pub struct LayoutEngine {
     space_display_map: HashMap<SpaceId, Option<String>>,
     #[serde(skip)]
     display_last_space: HashMap<String, SpaceId>,
+    #[serde(skip)]
+    occupied_workspaces: HashSet<VirtualWorkspaceId>,
+    #[serde(skip)]
+    pending_splits: HashMap<VirtualWorkspaceId, (WindowId, Orientation)>,
+    #[serde(skip)]
+    pending_balance: HashSet<VirtualWorkspaceId>,
+    #[serde(skip)]
+    frozen_workspaces: HashSet<VirtualWorkspaceId>,
+    #[serde(skip)]
+    workspace_overrides: HashMap<VirtualWorkspaceId, WorkspaceOverrides>,
+    #[serde(skip)]
+    tiled_from_floating: HashMap<VirtualWorkspaceId, Vec<WindowId>>,
+    #[serde(skip)]
+    sticky_windows: HashSet<WindowId>,
 }
 
 impl LayoutEngine {
@@ -215,6 +374,9 @@ impl LayoutEngine {
         }
 
         window_order.retain(|wid| !self.floating.is_floating(*wid));
+        if mode != LayoutMode::Traditional {
+            self.frozen_workspaces.remove(&workspace_id);
+        }
 
         let Some(workspace) = self.virtual_workspace_manager.workspaces.get_mut(workspace_id)
         else {
@@ -304,6 +466,7 @@ impl LayoutEngine {
         let Some((ws_id, layout_id)) = self.workspace_and_layout(space) else {
             return Vec::new();
         };
+        let gaps = self.gaps_for_layout(ws_id, layout_id, gaps);
         let stack_offset = self.layout_settings.stack.stack_offset;
         match self.workspace_tree(ws_id) {
             LayoutSystemKind::Traditional(s) => {
@@ -312,7 +475,7 @@ impl LayoutEngine {
                         layout_id,
                         screen,
                         stack_offset,
-                        gaps,
+                        &gaps,
                         stack_line_thickness,
                         stack_line_horiz,
                         stack_line_vert,
@@ -322,7 +485,7 @@ impl LayoutEngine {
                         layout_id,
                         screen,
                         stack_offset,
-                        gaps,
+                        &gaps,
                         stack_line_thickness,
                         stack_line_horiz,
                         stack_line_vert,
@@ -335,7 +498,7 @@ impl LayoutEngine {
                         layout_id,
                         screen,
                         stack_offset,
-                        gaps,
+                        &gaps,
                         stack_line_thickness,
                         stack_line_horiz,
                         stack_line_vert,
@@ -345,7 +508,7 @@ impl LayoutEngine {
                         layout_id,
                         screen,
                         stack_offset,
-                        gaps,
+                        &gaps,
                         stack_line_thickness,
                         stack_line_horiz,
                         stack_line_vert,
@@ -358,7 +521,7 @@ impl LayoutEngine {
                         layout_id,
                         screen,
                         stack_offset,
-                        gaps,
+                        &gaps,
                         stack_line_thickness,
                         stack_line_horiz,
                         stack_line_vert,
@@ -368,7 +531,7 @@ impl LayoutEngine {
                         layout_id,
                         screen,
                         stack_offset,
-                        gaps,
+                        &gaps,
                         stack_line_thickness,
                         stack_line_horiz,
                         stack_line_vert,
@@ -413,11 +576,15 @@ impl LayoutEngine {
             for (index, (workspace_id, name)) in workspaces.iter().enumerate() {
                 let desired_mode =
                     self.virtual_workspace_manager.desired_layout_mode_for_workspace(index, name);
-                let current_mode = self
-                    .virtual_workspace_manager
-                    .workspace_info(space, *workspace_id)
-                    .map(|ws| ws.layout_mode())
-                    .unwrap_or_default();
+                let Some(workspace) =
+                    self.virtual_workspace_manager.workspace_info(space, *workspace_id)
+                else {
+                    continue;
+                };
+                if workspace.layout_mode_overridden {
+                    continue;
+                }
+                let current_mode = workspace.layout_mode();
                 if current_mode != desired_mode {
                     let _ = self.switch_workspace_layout_mode(space, *workspace_id, desired_mode);
                 }
@@ -439,20 +606,136 @@ impl LayoutEngine {
         }
     }
 
+    /// The window the next window added to the active workspace on `space` will
+    /// be split against, and the orientation, as set by `SetSplitDirection`.
+    pub fn pending_split(&self, space: SpaceId) -> Option<(WindowId, Orientation)> {
+        let ws_id = self.virtual_workspace_manager.active_workspace(space)?;
+        self.pending_splits.get(&ws_id).copied()
+    }
+
+    /// Consume the workspace's split hint by selecting the window it was set on and
+    /// nesting it in a container of that orientation, so the window added next
+    /// lands beside it. Returns whether a split was made.
+    fn take_pending_split(&mut self, ws_id: VirtualWorkspaceId, layout: LayoutId) -> bool {
+        let Some((target, orientation)) = self.pending_splits.remove(&ws_id) else {
+            return false;
+        };
+        let tree = self.workspace_tree_mut(ws_id);
+        if !matches!(tree, LayoutSystemKind::Traditional(_)) || !tree.select_window(layout, target)
+        {
+            return false;
+        }
+        tree.split_selection(layout, LayoutKind::from(orientation));
+        true
+    }
+
+    /// With `auto_balance`, remember that `ws_id` gained or lost a tiled window.
+    fn mark_for_auto_balance(&mut self, ws_id: VirtualWorkspaceId) {
+        if self.layout_settings.auto_balance && !self.frozen_workspaces.contains(&ws_id) {
+            self.pending_balance.insert(ws_id);
+        }
+    }
+
+    /// Whether focus cycling wraps around in the active workspace on `space`.
+    pub fn focus_wraps(&self, space: SpaceId) -> bool {
+        match self.virtual_workspace_manager.active_workspace(space) {
+            Some(ws_id) => self.focus_wraps_in(ws_id),
+            None => self.layout_settings.focus_cycle_wrap,
+        }
+    }
+
+    fn focus_wraps_in(&self, ws_id: VirtualWorkspaceId) -> bool {
+        self.workspace_overrides
+            .get(&ws_id)
+            .and_then(|overrides| overrides.focus_wrap)
+            .unwrap_or(self.layout_settings.focus_cycle_wrap)
+    }
+
+    fn new_window_insert_in(&self, ws_id: VirtualWorkspaceId) -> NewWindowInsert {
+        self.workspace_overrides
+            .get(&ws_id)
+            .and_then(|overrides| overrides.new_window_insert)
+            .unwrap_or(self.layout_settings.new_window_insert)
+    }
+
+    fn update_workspace_overrides(
+        &mut self,
+        ws_id: VirtualWorkspaceId,
+        update: impl FnOnce(&mut WorkspaceOverrides),
+    ) {
+        let overrides = self.workspace_overrides.entry(ws_id).or_default();
+        update(overrides);
+        if *overrides == WorkspaceOverrides::default() {
+            self.workspace_overrides.remove(&ws_id);
+        }
+    }
+
+    /// Whether `FreezeLayout` is in effect for the active workspace on `space`.
+    pub fn is_layout_frozen(&self, space: SpaceId) -> bool {
+        self.virtual_workspace_manager
+            .active_workspace(space)
+            .is_some_and(|ws_id| self.is_workspace_frozen(ws_id))
+    }
+
+    pub fn is_workspace_frozen(&self, ws_id: VirtualWorkspaceId) -> bool {
+        self.frozen_workspaces.contains(&ws_id)
+    }
+
+    /// Add a window to a frozen workspace without disturbing the existing sizes.
+    /// Only traditional workspaces can be frozen.
+    fn add_window_to_frozen_layout(
+        &mut self,
+        ws_id: VirtualWorkspaceId,
+        layout: LayoutId,
+        wid: WindowId,
+    ) {
+        match self.workspace_tree_mut(ws_id) {
+            LayoutSystemKind::Traditional(tree) => tree.append_window_keeping_ratios(layout, wid),
+            tree => tree.add_window_after_selection(layout, wid),
+        }
+    }
+
+    /// Remove a window, giving its space to a neighbor if the workspace is frozen.
+    fn remove_window_from_workspace(&mut self, ws_id: VirtualWorkspaceId, wid: WindowId) {
+        let frozen = self.frozen_workspaces.contains(&ws_id);
+        match self.workspace_tree_mut(ws_id) {
+            LayoutSystemKind::Traditional(tree) if frozen => tree.remove_window_into_neighbor(wid),
+            tree => tree.remove_window(wid),
+        }
+    }
+
+    /// Balance every workspace that gained or lost a tiled window since the last
+    /// call. The reactor holds this back while a window is being dragged.
+    pub fn apply_pending_auto_balance(&mut self) {
+        if self.pending_balance.is_empty() {
+            return;
+        }
+        let pending = std::mem::take(&mut self.pending_balance);
+        for (ws_id, layout) in self.workspace_layouts.active_layouts_with_workspace() {
+            if pending.contains(&ws_id) {
+                self.workspace_tree_mut(ws_id).balance(layout);
+            }
+        }
+    }
+
     pub fn active_layout_mode_at(&self, space: SpaceId) -> crate::common::config::LayoutMode {
         if let Some(ws_id) = self.virtual_workspace_manager.active_workspace(space) {
-            match self.workspace_tree(ws_id) {
-                LayoutSystemKind::Traditional(_) => crate::common::config::LayoutMode::Traditional,
-                LayoutSystemKind::Bsp(_) => crate::common::config::LayoutMode::Bsp,
-                LayoutSystemKind::Stack(_) => crate::common::config::LayoutMode::Stack,
-                LayoutSystemKind::MasterStack(_) => crate::common::config::LayoutMode::MasterStack,
-                LayoutSystemKind::Scrolling(_) => crate::common::config::LayoutMode::Scrolling,
-            }
+            self.layout_mode_of(ws_id)
         } else {
             crate::common::config::LayoutMode::default()
         }
     }
 
+    fn layout_mode_of(&self, ws_id: VirtualWorkspaceId) -> crate::common::config::LayoutMode {
+        match self.workspace_tree(ws_id) {
+            LayoutSystemKind::Traditional(_) => crate::common::config::LayoutMode::Traditional,
+            LayoutSystemKind::Bsp(_) => crate::common::config::LayoutMode::Bsp,
+            LayoutSystemKind::Stack(_) => crate::common::config::LayoutMode::Stack,
+            LayoutSystemKind::MasterStack(_) => crate::common::config::LayoutMode::MasterStack,
+            LayoutSystemKind::Scrolling(_) => crate::common::config::LayoutMode::Scrolling,
+        }
+    }
+
     pub fn layout_specific_animate_settings(&self, space: SpaceId) -> Option<bool> {
         if let Some(ws_id) = self.virtual_workspace_manager.active_workspace(space) {
             match self.workspace_tree(ws_id) {
@@ -472,6 +755,14 @@ impl LayoutEngine {
             .collect()
     }
 
+    /// Floating windows in the active workspace of `space`, bottom to top.
+    pub fn floating_windows_in_stacking_order(&self, space: SpaceId) -> Vec<WindowId> {
+        let mut windows = self.active_floating_windows_in_workspace(space);
+        windows.sort_unstable();
+        self.floating.sort_by_stacking_order(&mut windows);
+        windows
+    }
+
     fn refocus_workspace(
         &mut self,
         space: SpaceId,
@@ -834,14 +1125,25 @@ impl LayoutEngine {
         let ws_ids = self.virtual_workspace_manager.workspaces_for_window(wid);
         if !ws_ids.is_empty() {
             for ws_id in ws_ids {
-                self.workspace_tree_mut(ws_id).remove_window(wid);
+                self.remove_window_from_workspace(ws_id, wid);
+                self.mark_for_auto_balance(ws_id);
             }
         } else {
             // Fallback: search all workspaces
+            let holding: Vec<_> = self
+                .workspace_layouts
+                .active_layouts_with_workspace()
+                .into_iter()
+                .filter(|&(ws_id, layout)| self.workspace_tree(ws_id).contains_window(layout, wid))
+                .map(|(ws_id, _)| ws_id)
+                .collect();
             let ws_ids: Vec<_> = self.virtual_workspace_manager.workspaces.keys().collect();
             for ws_id in ws_ids {
                 self.workspace_tree_mut(ws_id).remove_window(wid);
             }
+            for ws_id in holding {
+                self.mark_for_auto_balance(ws_id);
+            }
         }
 
         if preserve_floating {
@@ -859,6 +1161,10 @@ impl LayoutEngine {
             self.focused_window = None;
         }
         self.window_layout_constraints.remove(&wid);
+        if !preserve_floating {
+            self.sticky_windows.remove(&wid);
+        }
+        self.pending_splits.retain(|_, (target, _)| *target != wid);
 
         if let Some(space) = affected_space {
             self.broadcast_windows_changed(space);
@@ -984,11 +1290,67 @@ impl LayoutEngine {
             broadcast_tx,
             space_display_map: HashMap::default(),
             display_last_space: HashMap::default(),
+            occupied_workspaces: HashSet::default(),
+            pending_splits: HashMap::default(),
+            pending_balance: HashSet::default(),
+            frozen_workspaces: HashSet::default(),
+            workspace_overrides: HashMap::default(),
+            tiled_from_floating: HashMap::default(),
+            sticky_windows: HashSet::default(),
         }
     }
 
     pub fn debug_tree(&self, space: SpaceId) { self.debug_tree_desc(space, "", false); }
 
+    /// Moves a window that was just inserted after the selection into the slot
+    /// requested by `new_window_insert` or the workspace's override of it, keeping
+    /// it selected.
+    fn apply_new_window_insert(
+        &mut self,
+        ws_id: VirtualWorkspaceId,
+        layout: LayoutId,
+        wid: WindowId,
+    ) {
+        let target = match self.new_window_insert_in(ws_id) {
+            NewWindowInsert::AfterFocused => return,
+            NewWindowInsert::End => usize::MAX,
+            NewWindowInsert::AsMaster => 0,
+            NewWindowInsert::Beginning => match self.workspace_tree(ws_id) {
+                LayoutSystemKind::MasterStack(_) => self.layout_settings.master_stack.master_count,
+                _ => 0,
+            },
+        };
+
+        self.move_window_to_position(ws_id, layout, wid, target);
+    }
+
+    /// Moves `wid` to `target` in the layout's window order, clamped to the last
+    /// slot, and selects it.
+    fn move_window_to_position(
+        &mut self,
+        ws_id: VirtualWorkspaceId,
+        layout: LayoutId,
+        wid: WindowId,
+        target: usize,
+    ) {
+        let tree = self.workspace_tree_mut(ws_id);
+        let order = tree.visible_windows_in_layout(layout);
+        let Some(mut pos) = order.iter().position(|&w| w == wid) else {
+            return;
+        };
+        let target = target.min(order.len() - 1);
+        // Adjacent swaps keep the relative order of every other window.
+        while pos < target {
+            tree.swap_windows(layout, wid, order[pos + 1]);
+            pos += 1;
+        }
+        while pos > target {
+            tree.swap_windows(layout, wid, order[pos - 1]);
+            pos -= 1;
+        }
+        tree.select_window(layout, wid);
+    }
+
     pub fn debug_tree_desc(&self, space: SpaceId, desc: &'static str, print: bool) {
         if let Some(workspace_id) = self.virtual_workspace_manager.active_workspace(space) {
             if let Some(layout) = self.workspace_layouts.active(space, workspace_id) {
@@ -1049,24 +1411,31 @@ impl LayoutEngine {
                     max_size,
                 ) in windows_with_titles
                 {
-                    self.window_layout_constraints.insert(
-                        wid,
-                        WindowLayoutConstraints {
-                            is_resizable,
-                            locked_width: size_hint.width,
-                            locked_height: size_hint.height,
-                            min_width: min_size.map_or(0.0, |s| s.width),
-                            min_height: min_size.map_or(0.0, |s| s.height),
-                            max_width: max_size.map_or(0.0, |s| s.width),
-                            max_height: max_size.map_or(0.0, |s| s.height),
-                        }
-                        .normalized(),
-                    );
-
                     let title_ref = title_opt.as_deref();
                     let ax_role_ref = ax_role_opt.as_deref();
                     let ax_subrole_ref = ax_subrole_opt.as_deref();
 
+                    let mut constraints = WindowLayoutConstraints {
+                        is_resizable,
+                        locked_width: size_hint.width,
+                        locked_height: size_hint.height,
+                        min_width: min_size.map_or(0.0, |s| s.width),
+                        min_height: min_size.map_or(0.0, |s| s.height),
+                        max_width: max_size.map_or(0.0, |s| s.width),
+                        max_height: max_size.map_or(0.0, |s| s.height),
+                    }
+                    .normalized();
+                    if let Some(overrides) = self.virtual_workspace_manager.app_rule_constraints(
+                        app_bundle_id,
+                        app_name,
+                        title_ref,
+                        ax_role_ref,
+                        ax_subrole_ref,
+                    ) {
+                        constraints = constraints.with_overrides(&overrides);
+                    }
+                    self.window_layout_constraints.insert(wid, constraints);
+
                     let was_floating = self.floating.is_floating(wid);
                     let assignment = match self
                         .virtual_workspace_manager
@@ -1151,7 +1520,42 @@ impl LayoutEngine {
                         }
                     }
 
+                    let added: Vec<WindowId> = desired
+                        .iter()
+                        .copied()
+                        .filter(|&wid| !self.workspace_tree(ws_id).contains_window(layout, wid))
+                        .collect();
+                    let stale: Vec<WindowId> = self
+                        .workspace_tree(ws_id)
+                        .visible_windows_in_layout(layout)
+                        .into_iter()
+                        .filter(|wid| wid.pid == pid && !desired.contains(wid))
+                        .collect();
+                    if self.frozen_workspaces.contains(&ws_id) {
+                        for wid in stale {
+                            self.remove_window_from_workspace(ws_id, wid);
+                        }
+                        for &wid in &added {
+                            self.add_window_to_frozen_layout(ws_id, layout, wid);
+                        }
+                        self.workspace_tree_mut(ws_id).set_windows_for_app(layout, pid, desired);
+                        continue;
+                    }
+                    // The first new window takes the workspace's split hint, if any.
+                    let split_window =
+                        added.first().copied().filter(|_| self.take_pending_split(ws_id, layout));
+                    if let Some(wid) = split_window {
+                        self.workspace_tree_mut(ws_id).add_window_after_selection(layout, wid);
+                    }
                     self.workspace_tree_mut(ws_id).set_windows_for_app(layout, pid, desired);
+                    if !added.is_empty() || !stale.is_empty() {
+                        self.mark_for_auto_balance(ws_id);
+                    }
+                    for wid in added {
+                        if Some(wid) != split_window {
+                            self.apply_new_window_insert(ws_id, layout, wid);
+                        }
+                    }
                 }
 
                 self.broadcast_windows_changed(space);
@@ -1164,6 +1568,7 @@ impl LayoutEngine {
                 }
                 self.floating.remove_all_for_pid(pid);
                 self.window_layout_constraints.retain(|wid, _| wid.pid != pid);
+                self.sticky_windows.retain(|wid| wid.pid != pid);
 
                 self.virtual_workspace_manager.remove_windows_for_app(pid);
                 self.virtual_workspace_manager.remove_app_floating_positions(pid);
@@ -1194,8 +1599,17 @@ impl LayoutEngine {
                     self.workspace_layouts.active(space, assigned_workspace)
                 {
                     if !self.workspace_tree(assigned_workspace).contains_window(layout, wid) {
-                        self.workspace_tree_mut(assigned_workspace)
-                            .add_window_after_selection(layout, wid);
+                        if self.frozen_workspaces.contains(&assigned_workspace) {
+                            self.add_window_to_frozen_layout(assigned_workspace, layout, wid);
+                        } else {
+                            let split = self.take_pending_split(assigned_workspace, layout);
+                            self.workspace_tree_mut(assigned_workspace)
+                                .add_window_after_selection(layout, wid);
+                            if !split {
+                                self.apply_new_window_insert(assigned_workspace, layout, wid);
+                            }
+                            self.mark_for_auto_balance(assigned_workspace);
+                        }
                     }
                 } else {
                     warn!(
@@ -1242,8 +1656,9 @@ impl LayoutEngine {
                         );
                         continue;
                     };
-                    let gaps =
+                    let display_gaps =
                         self.layout_settings.gaps.effective_for_display(display_uuid.as_deref());
+                    let gaps = self.gaps_for_layout(ws_id, layout, &display_gaps).into_owned();
                     self.workspace_tree_mut(ws_id).on_window_resized(
                         layout,
                         wid,
@@ -1395,6 +1810,14 @@ impl LayoutEngine {
 
                 EventResponse::default()
             }
+            LayoutCommand::ToggleSticky => {
+                if let Some(wid) = self.focused_window {
+                    if !self.sticky_windows.remove(&wid) {
+                        self.sticky_windows.insert(wid);
+                    }
+                }
+                EventResponse::default()
+            }
             LayoutCommand::NextWindow | LayoutCommand::PrevWindow => {
                 let forward = matches!(command, LayoutCommand::NextWindow);
                 let windows = if is_floating {
@@ -1406,6 +1829,14 @@ impl LayoutEngine {
                     )
                 };
                 if let Some(idx) = windows.iter().position(|&w| Some(w) == self.focused_window) {
+                    let at_end = if forward {
+                        idx + 1 == windows.len()
+                    } else {
+                        idx == 0
+                    };
+                    if at_end && !self.focus_wraps_in(workspace_id) {
+                        return EventResponse::default();
+                    }
                     let next = if forward {
                         (idx + 1) % windows.len()
                     } else {
@@ -1422,6 +1853,41 @@ impl LayoutEngine {
                     EventResponse::default()
                 }
             }
+            LayoutCommand::FocusNextWindow | LayoutCommand::FocusPrevWindow => {
+                let forward = matches!(command, LayoutCommand::FocusNextWindow);
+                let mut windows = self.filter_active_workspace_windows(
+                    space,
+                    self.workspace_tree(workspace_id).visible_windows_in_layout(layout),
+                );
+                if !self.layout_settings.focus_cycle_skip_floating {
+                    windows.extend(self.active_floating_windows_in_workspace(space));
+                }
+                if windows.is_empty() {
+                    return EventResponse::default();
+                }
+                let current = windows.iter().position(|&w| Some(w) == self.focused_window);
+                let at_end = match current {
+                    Some(idx) if forward => idx + 1 == windows.len(),
+                    Some(idx) => idx == 0,
+                    None => false,
+                };
+                if at_end && !self.focus_wraps_in(workspace_id) {
+                    return EventResponse::default();
+                }
+                let next = match current {
+                    Some(idx) if forward => (idx + 1) % windows.len(),
+                    Some(idx) => (idx + windows.len() - 1) % windows.len(),
+                    None if forward => 0,
+                    None => windows.len() - 1,
+                };
+                let response = EventResponse {
+                    focus_window: Some(windows[next]),
+                    raise_windows: vec![windows[next]],
+                    boundary_hit: None,
+                };
+                self.apply_focus_response(space, workspace_id, layout, &response);
+                response
+            }
             LayoutCommand::MoveFocus(direction) => {
                 debug!(
                     "MoveFocus command received, direction: {:?}, is_floating: {}",
@@ -1509,9 +1975,24 @@ impl LayoutEngine {
             | LayoutCommand::PrevWorkspace(_)
             | LayoutCommand::SwitchToWorkspace(_)
             | LayoutCommand::MoveWindowToWorkspace { .. }
+            | LayoutCommand::MoveWindowToNextWorkspace { .. }
+            | LayoutCommand::MoveWindowToPrevWorkspace { .. }
+            | LayoutCommand::SendToLastWorkspace { .. }
             | LayoutCommand::SetWorkspaceLayout { .. }
+            | LayoutCommand::CycleLayoutMode
             | LayoutCommand::CreateWorkspace
-            | LayoutCommand::SwitchToLastWorkspace => EventResponse::default(),
+            | LayoutCommand::SwitchToLastWorkspace
+            | LayoutCommand::RenameWorkspace { .. }
+            | LayoutCommand::ResizeFloatingToFraction { .. }
+            | LayoutCommand::GrowToFill(_)
+            | LayoutCommand::CycleFloatingPreset
+            | LayoutCommand::FocusLargestWindow
+            | LayoutCommand::FocusNextSpatial
+            | LayoutCommand::FocusPrevSpatial
+            | LayoutCommand::MarkWindow(_)
+            | LayoutCommand::FocusMark(_)
+            | LayoutCommand::FocusAppUrgent { .. }
+            | LayoutCommand::ToggleWindowTitleVisible => EventResponse::default(),
             LayoutCommand::JoinWindow(direction) => {
                 self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
                 self.workspace_tree_mut(workspace_id)
@@ -1524,6 +2005,50 @@ impl LayoutEngine {
                     self.layout_settings.stack.default_orientation;
                 self.toggle_stack_for_workspace(workspace_id, layout, default_orientation)
             }
+            LayoutCommand::MoveWindowIntoContainer(direction) => {
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                if let LayoutSystemKind::Traditional(s) = self.workspace_tree_mut(workspace_id) {
+                    s.move_selection_into_container(layout, direction);
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::GridArrange { rows, cols } => {
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                if let LayoutSystemKind::Traditional(s) = self.workspace_tree_mut(workspace_id) {
+                    s.arrange_selection_container_in_grid(layout, rows, cols);
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::SetSplitDirection(orientation) => {
+                let tree = self.workspace_tree(workspace_id);
+                if matches!(tree, LayoutSystemKind::Traditional(_))
+                    && let Some(target) = tree.selected_window(layout)
+                {
+                    self.pending_splits.insert(workspace_id, (target, orientation));
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::FreezeLayout => {
+                let tree = self.workspace_tree(workspace_id);
+                if !matches!(tree, LayoutSystemKind::Traditional(_)) {
+                    debug!(?workspace_id, "Only traditional layouts can be frozen");
+                    return EventResponse::default();
+                }
+                self.frozen_workspaces.insert(workspace_id);
+                self.pending_balance.remove(&workspace_id);
+                EventResponse::default()
+            }
+            LayoutCommand::UnfreezeLayout => {
+                self.frozen_workspaces.remove(&workspace_id);
+                EventResponse::default()
+            }
+            LayoutCommand::ToggleContainerCollapsed => {
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                if let LayoutSystemKind::Traditional(s) = self.workspace_tree_mut(workspace_id) {
+                    s.toggle_collapse_of_selected_stack(layout);
+                }
+                EventResponse::default()
+            }
             LayoutCommand::UnjoinWindows => {
                 self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
                 self.workspace_tree_mut(workspace_id).unjoin_selection(layout);
@@ -1609,6 +2134,73 @@ impl LayoutEngine {
                 }
                 EventResponse::default()
             }
+            LayoutCommand::SwapWithMaster => {
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                match self.workspace_tree_mut(workspace_id) {
+                    LayoutSystemKind::MasterStack(s) => s.swap_with_master(layout),
+                    tree => {
+                        let selected = tree.selected_window(layout);
+                        let first = tree.visible_windows_in_layout(layout).first().copied();
+                        if let (Some(selected), Some(first)) = (selected, first) {
+                            if selected != first && tree.swap_windows(layout, selected, first) {
+                                let _ = tree.select_window(layout, selected);
+                            }
+                        }
+                    }
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::TileAllFloating => {
+                let floating = self.active_floating_windows_in_workspace(space);
+                if floating.is_empty() {
+                    return EventResponse::default();
+                }
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                for &wid in &floating {
+                    self.floating.remove_active(space, wid.pid, wid);
+                    self.floating.remove_floating(wid);
+                    if self.frozen_workspaces.contains(&workspace_id) {
+                        self.add_window_to_frozen_layout(workspace_id, layout, wid);
+                    } else {
+                        self.workspace_tree_mut(workspace_id)
+                            .add_window_after_selection(layout, wid);
+                        self.apply_new_window_insert(workspace_id, layout, wid);
+                    }
+                }
+                self.floating.set_last_focus(None);
+                if let Some(focused) = self.focused_window {
+                    let _ = self.workspace_tree_mut(workspace_id).select_window(layout, focused);
+                }
+                self.mark_for_auto_balance(workspace_id);
+                self.tiled_from_floating.insert(workspace_id, floating);
+                EventResponse::default()
+            }
+            LayoutCommand::FloatAllTiled => {
+                let Some(windows) = self.tiled_from_floating.remove(&workspace_id) else {
+                    return EventResponse::default();
+                };
+                self.workspace_layouts.mark_last_saved(space, workspace_id, layout);
+                for wid in windows {
+                    if self.floating.is_floating(wid)
+                        || !self.workspace_tree(workspace_id).contains_window(layout, wid)
+                    {
+                        continue;
+                    }
+                    self.remove_window_from_workspace(workspace_id, wid);
+                    self.floating.add_floating(wid);
+                    self.floating.add_active(space, wid.pid, wid);
+                }
+                self.mark_for_auto_balance(workspace_id);
+                EventResponse::default()
+            }
+            LayoutCommand::SetWorkspaceFocusWrap { wrap } => {
+                self.update_workspace_overrides(workspace_id, |o| o.focus_wrap = wrap);
+                EventResponse::default()
+            }
+            LayoutCommand::SetWorkspaceInsertPosition { insert } => {
+                self.update_workspace_overrides(workspace_id, |o| o.new_window_insert = insert);
+                EventResponse::default()
+            }
             LayoutCommand::ScrollStrip { delta } => {
                 let mut resp = EventResponse::default();
                 if let LayoutSystemKind::Scrolling(system) = self.workspace_tree_mut(workspace_id) {
@@ -1622,6 +2214,24 @@ impl LayoutEngine {
                 }
                 EventResponse::default()
             }
+            LayoutCommand::ScrollPageLeft | LayoutCommand::ScrollPageRight => {
+                let pages = if matches!(command, LayoutCommand::ScrollPageRight) {
+                    1.0
+                } else {
+                    -1.0
+                };
+                if let LayoutSystemKind::Scrolling(system) = self.workspace_tree_mut(workspace_id) {
+                    system.scroll_by_pages(layout, pages);
+                }
+                EventResponse::default()
+            }
+            LayoutCommand::ScrollToStart | LayoutCommand::ScrollToEnd => {
+                let to_end = matches!(command, LayoutCommand::ScrollToEnd);
+                if let LayoutSystemKind::Scrolling(system) = self.workspace_tree_mut(workspace_id) {
+                    system.scroll_to_edge(layout, to_end);
+                }
+                EventResponse::default()
+            }
             LayoutCommand::CenterSelection => {
                 if let LayoutSystemKind::Scrolling(system) = self.workspace_tree_mut(workspace_id) {
                     system.center_selected_column(layout);
@@ -1631,6 +2241,26 @@ impl LayoutEngine {
         }
     }
 
+    /// Gaps to lay out `layout` with: `gaps` with the override for the
+    /// workspace's layout mode, if any. With `smart_gaps`, a workspace holding a
+    /// single tiled window drops both outer and inner gaps.
+    fn gaps_for_layout<'a>(
+        &self,
+        workspace_id: crate::model::VirtualWorkspaceId,
+        layout: LayoutId,
+        gaps: &'a crate::common::config::GapSettings,
+    ) -> Cow<'a, crate::common::config::GapSettings> {
+        if self.layout_settings.smart_gaps
+            && self.workspace_tree(workspace_id).visible_windows_in_layout(layout).len() == 1
+        {
+            Cow::Owned(crate::common::config::GapSettings::default())
+        } else if let Some(mode_gaps) = gaps.for_mode(self.layout_mode_of(workspace_id)) {
+            Cow::Owned(mode_gaps)
+        } else {
+            Cow::Borrowed(gaps)
+        }
+    }
+
     pub fn calculate_layout(
         &mut self,
         space: SpaceId,
@@ -1643,12 +2273,13 @@ impl LayoutEngine {
         let Some((ws_id, layout)) = self.workspace_and_layout(space) else {
             return Vec::new();
         };
+        let gaps = self.gaps_for_layout(ws_id, layout, gaps);
         self.workspace_tree(ws_id).calculate_layout(
             layout,
             screen,
             self.layout_settings.stack.stack_offset,
             &self.window_layout_constraints,
-            gaps,
+            &gaps,
             stack_line_thickness,
             stack_line_horiz,
             stack_line_vert,
@@ -1727,12 +2358,13 @@ impl LayoutEngine {
 
         if let Some(active_workspace_id) = self.virtual_workspace_manager.active_workspace(space) {
             if let Some(layout) = self.workspace_layouts.active(space, active_workspace_id) {
+                let gaps = self.gaps_for_layout(active_workspace_id, layout, gaps);
                 let tiled_positions = self.workspace_tree(active_workspace_id).calculate_layout(
                     layout,
                     screen,
                     self.layout_settings.stack.stack_offset,
                     &self.window_layout_constraints,
-                    gaps,
+                    &gaps,
                     stack_line_thickness,
                     stack_line_horiz,
                     stack_line_vert,
@@ -1782,6 +2414,12 @@ impl LayoutEngine {
             }
         }
 
+        if self.layout_settings.oversized_windows != OversizedWindowPolicy::Ignore {
+            for (wid, rect) in positions.iter_mut() {
+                *rect = self.fit_oversized_frame(*wid, *rect, screen);
+            }
+        }
+
         let hidden_windows = self.virtual_workspace_manager.windows_in_inactive_workspaces(space);
         for wid in hidden_windows {
             let original_frame = get_window_frame(wid);
@@ -1822,6 +2460,43 @@ impl LayoutEngine {
         positions.into_iter().collect()
     }
 
+    /// Apply `oversized_windows` to a frame that is wider or taller than `screen`.
+    fn fit_oversized_frame(&self, wid: WindowId, frame: CGRect, screen: CGRect) -> CGRect {
+        let policy = self.layout_settings.oversized_windows;
+        let constraints = self.window_layout_constraints.get(&wid).copied().unwrap_or_default();
+        let fit_axis = |origin: f64, len: f64, start: f64, available: f64, horizontal: bool| {
+            if len <= available {
+                return (origin, len);
+            }
+            let len = match policy {
+                OversizedWindowPolicy::Ignore => return (origin, len),
+                OversizedWindowPolicy::ShrinkToFit => {
+                    let floor = constraints
+                        .fixed_for_axis(horizontal)
+                        .unwrap_or(constraints.min_for_axis(horizontal));
+                    available.max(floor).min(len)
+                }
+                OversizedWindowPolicy::Center => len,
+            };
+            (start + (available - len) / 2.0, len)
+        };
+        let (x, width) = fit_axis(
+            frame.origin.x,
+            frame.size.width,
+            screen.origin.x,
+            screen.size.width,
+            true,
+        );
+        let (y, height) = fit_axis(
+            frame.origin.y,
+            frame.size.height,
+            screen.origin.y,
+            screen.size.height,
+            false,
+        );
+        CGRect::new(CGPoint::new(x, y), CGSize::new(width, height))
+    }
+
     pub fn collect_group_containers_in_selection_path(
         &mut self,
         space: SpaceId,
@@ -1882,12 +2557,13 @@ impl LayoutEngine {
         let mut positions = HashMap::default();
 
         if let Some(layout) = self.workspace_layouts.active(space, workspace_id) {
+            let gaps = self.gaps_for_layout(workspace_id, layout, gaps);
             let tiled_positions = self.workspace_tree(workspace_id).calculate_layout(
                 layout,
                 screen,
                 self.layout_settings.stack.stack_offset,
                 &self.window_layout_constraints,
-                gaps,
+                &gaps,
                 stack_line_thickness,
                 stack_line_horiz,
                 stack_line_vert,
@@ -1942,38 +2618,280 @@ impl LayoutEngine {
         }
     }
 
-    pub fn load(_path: PathBuf) -> anyhow::Result<Self> {
-        Ok(Self::new(&VirtualWorkspaceSettings::default(), &LayoutSettings::default(), None))
+    /// Reads a layout written by [`Self::save`], using the given settings rather
+    /// than the ones it was saved with.
+    pub fn load(
+        path: PathBuf,
+        virtual_workspace_config: &VirtualWorkspaceSettings,
+        layout_settings: &LayoutSettings,
+        broadcast_tx: Option<BroadcastSender>,
+    ) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(&path)?;
+        let mut engine = Self::new(virtual_workspace_config, layout_settings, broadcast_tx);
+        engine.import_state(&contents)?;
+        Ok(engine)
     }
 
-    pub fn save(&self, _path: PathBuf) -> std::io::Result<()> { Ok(()) }
+    pub fn save(&self, path: PathBuf) -> std::io::Result<()> {
+        let contents = ron::ser::to_string(&self).map_err(std::io::Error::other)?;
+        write_atomic(&path, contents.as_bytes())
+    }
 
     pub fn serialize_to_string(&self) -> String { ron::ser::to_string(&self).unwrap() }
 
+    /// Replaces workspaces, layouts and floating state with a snapshot from
+    /// [`Self::serialize_to_string`], keeping the current settings. Windows in the
+    /// snapshot are taken as they are; dropping ones that no longer exist is up
+    /// to the caller. Nothing changes if the snapshot doesn't parse.
+    pub fn import_state(&mut self, snapshot: &str) -> Result<(), ron::error::SpannedError> {
+        let imported: LayoutEngine = ron::de::from_str(snapshot)?;
+        self.workspace_layouts = imported.workspace_layouts;
+        self.floating = imported.floating;
+        self.virtual_workspace_manager.import_state(imported.virtual_workspace_manager);
+        self.occupied_workspaces.clear();
+        self.pending_splits.clear();
+        self.pending_balance.clear();
+        self.frozen_workspaces.clear();
+        self.workspace_overrides.clear();
+        self.tiled_from_floating.clear();
+        let settings = self.layout_settings.clone();
+        self.set_layout_settings(&settings);
+        Ok(())
+    }
+
     #[cfg(test)]
     pub(crate) fn selected_window(&mut self, space: SpaceId) -> Option<WindowId> {
         let (ws_id, layout) = self.workspace_and_layout(space)?;
         self.workspace_tree(ws_id).selected_window(layout)
     }
 
-    pub fn handle_virtual_workspace_command(
+    /// Move `window` from workspace `from` to `to` on `space`, keeping it tiled or
+    /// floating as it was. Returns false (leaving the window in place) if the
+    /// assignment is rejected.
+    fn transfer_window_to_workspace(
         &mut self,
         space: SpaceId,
-        command: &LayoutCommand,
-    ) -> EventResponse {
-        match command {
-            LayoutCommand::NextWorkspace(skip_empty) => {
-                if let Some(current_workspace) =
-                    self.virtual_workspace_manager.active_workspace(space)
+        window: WindowId,
+        from: VirtualWorkspaceId,
+        to: VirtualWorkspaceId,
+    ) -> bool {
+        let is_floating = self.floating.is_floating(window);
+        if is_floating {
+            self.floating.remove_active_for_window(window);
+        } else {
+            self.remove_window_from_all_tiling_trees(window);
+        }
+
+        if !self.virtual_workspace_manager.assign_window_to_workspace(space, window, to) {
+            if is_floating {
+                self.floating.add_active(space, window.pid, window);
+            } else if let Some(prev_layout) = self.workspace_layouts.active(space, from) {
+                self.workspace_tree_mut(from).add_window_after_selection(prev_layout, window);
+            }
+            return false;
+        }
+
+        if is_floating {
+            if self.virtual_workspace_manager.active_workspace(space) == Some(to) {
+                self.floating.add_active(space, window.pid, window);
+            }
+        } else if let Some(target_layout) = self.workspace_layouts.active(space, to) {
+            self.workspace_tree_mut(to).add_window_after_selection(target_layout, window);
+        }
+        true
+    }
+
+    /// Move `window` to the workspace at `workspace_index` on `space`, at
+    /// `target_index` in its window order if given. Returns false if either the
+    /// workspace or the window's current one is unknown.
+    pub fn move_window_to_workspace_index(
+        &mut self,
+        space: SpaceId,
+        window: WindowId,
+        workspace_index: usize,
+        target_index: Option<usize>,
+    ) -> bool {
+        let workspaces = self.virtual_workspace_manager.list_workspaces(space);
+        let Some(&(to, _)) = workspaces.get(workspace_index) else {
+            return false;
+        };
+        let Some(from) = self.virtual_workspace_manager.workspace_for_window(space, window) else {
+            return false;
+        };
+        if from == to {
+            return true;
+        }
+        if !self.transfer_window_to_workspace(space, window, from, to) {
+            return false;
+        }
+        if let Some(index) = target_index {
+            self.move_transferred_window_to_index(space, to, window, index);
+        }
+        self.broadcast_windows_changed(space);
+        true
+    }
+
+    /// Reorder a window that `transfer_window_to_workspace` just tiled into `to`.
+    /// Floating windows have no place in the order and are left alone.
+    fn move_transferred_window_to_index(
+        &mut self,
+        space: SpaceId,
+        to: VirtualWorkspaceId,
+        window: WindowId,
+        index: usize,
+    ) {
+        if let Some(layout) = self.workspace_layouts.active(space, to) {
+            self.move_window_to_position(to, layout, window, index);
+        }
+    }
+
+    /// Spread the windows of `space` round-robin over all of its workspaces, leaving
+    /// the windows in `keep` where they are. Returns the resulting window count of
+    /// each workspace, in workspace order.
+    pub fn distribute_windows(
+        &mut self,
+        space: SpaceId,
+        keep: &HashSet<WindowId>,
+    ) -> (Vec<usize>, EventResponse) {
+        let workspaces: Vec<VirtualWorkspaceId> = self
+            .virtual_workspace_manager_mut()
+            .list_workspaces(space)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        if workspaces.is_empty() {
+            return (Vec::new(), EventResponse::default());
+        }
+
+        let movable: Vec<(WindowId, VirtualWorkspaceId)> = workspaces
+            .iter()
+            .flat_map(|&ws| {
+                self.virtual_workspace_manager
+                    .workspace_windows(space, ws)
+                    .into_iter()
+                    .map(move |wid| (wid, ws))
+            })
+            .filter(|(wid, _)| !keep.contains(wid))
+            .collect();
+        for (i, (wid, from)) in movable.into_iter().enumerate() {
+            let to = workspaces[i % workspaces.len()];
+            if to != from {
+                self.transfer_window_to_workspace(space, wid, from, to);
+            }
+        }
+
+        let mut response = EventResponse::default();
+        let active = self.virtual_workspace_manager.active_workspace(space);
+        if let Some(focused) = self.focused_window
+            && self
+                .virtual_workspace_manager
+                .workspace_for_window(space, focused)
+                .is_some_and(|ws| Some(ws) != active)
+        {
+            self.focused_window = None;
+            response.focus_window = self.windows_in_active_workspace(space).first().copied();
+        }
+        self.broadcast_windows_changed(space);
+
+        let counts = workspaces
+            .iter()
+            .map(|&ws| self.virtual_workspace_manager.workspace_windows(space, ws).len())
+            .collect();
+        (counts, response)
+    }
+
+    fn move_focused_window_to_adjacent_workspace(
+        &mut self,
+        space: SpaceId,
+        forward: bool,
+        follow: bool,
+    ) -> EventResponse {
+        let Some(window) = self.focused_window else {
+            return EventResponse::default();
+        };
+        let op_space = self.space_with_window(window).unwrap_or(space);
+        let Some(current) = self.virtual_workspace_manager.workspace_for_window(op_space, window)
+        else {
+            return EventResponse::default();
+        };
+        let target = if forward {
+            self.virtual_workspace_manager.next_workspace(op_space, current, None)
+        } else {
+            self.virtual_workspace_manager.prev_workspace(op_space, current, None)
+        };
+        self.move_focused_window_to(op_space, window, current, target, follow)
+    }
+
+    fn send_focused_window_to_last_workspace(
+        &mut self,
+        space: SpaceId,
+        follow: bool,
+    ) -> EventResponse {
+        let Some(window) = self.focused_window else {
+            return EventResponse::default();
+        };
+        let op_space = self.space_with_window(window).unwrap_or(space);
+        let Some(current) = self.virtual_workspace_manager.workspace_for_window(op_space, window)
+        else {
+            return EventResponse::default();
+        };
+        let target = self.virtual_workspace_manager.last_workspace(op_space);
+        self.move_focused_window_to(op_space, window, current, target, follow)
+    }
+
+    fn move_focused_window_to(
+        &mut self,
+        op_space: SpaceId,
+        window: WindowId,
+        current: VirtualWorkspaceId,
+        target: Option<VirtualWorkspaceId>,
+        follow: bool,
+    ) -> EventResponse {
+        let Some(target) = target.filter(|target| *target != current) else {
+            return EventResponse::default();
+        };
+        let workspaces = self.virtual_workspace_manager.list_workspaces(op_space);
+        let Some(index) = workspaces.iter().position(|(id, _)| *id == target) else {
+            return EventResponse::default();
+        };
+
+        let response = self.handle_virtual_workspace_command(
+            op_space,
+            &LayoutCommand::MoveWindowToWorkspace {
+                workspace: index,
+                window_id: None,
+                bundle_id: None,
+                title: None,
+                target_index: None,
+            },
+        );
+        if !follow
+            || self.virtual_workspace_manager.workspace_for_window(op_space, window) != Some(target)
+        {
+            return response;
+        }
+
+        self.virtual_workspace_manager
+            .set_last_focused_window(op_space, target, Some(window));
+        self.handle_virtual_workspace_command(op_space, &LayoutCommand::SwitchToWorkspace(index))
+    }
+
+    pub fn handle_virtual_workspace_command(
+        &mut self,
+        space: SpaceId,
+        command: &LayoutCommand,
+    ) -> EventResponse {
+        match command {
+            LayoutCommand::NextWorkspace(skip_empty) => {
+                if let Some(current_workspace) =
+                    self.virtual_workspace_manager.active_workspace(space)
                 {
                     if let Some(next_workspace) = self.virtual_workspace_manager.next_workspace(
                         space,
                         current_workspace,
                         *skip_empty,
                     ) {
-                        self.virtual_workspace_manager.set_active_workspace(space, next_workspace);
-
-                        self.update_active_floating_windows(space);
+                        self.activate_workspace(space, next_workspace);
 
                         self.broadcast_workspace_changed(space);
                         self.broadcast_windows_changed(space);
@@ -1992,9 +2910,7 @@ impl LayoutEngine {
                         current_workspace,
                         *skip_empty,
                     ) {
-                        self.virtual_workspace_manager.set_active_workspace(space, prev_workspace);
-
-                        self.update_active_floating_windows(space);
+                        self.activate_workspace(space, prev_workspace);
 
                         self.broadcast_workspace_changed(space);
                         self.broadcast_windows_changed(space);
@@ -2016,9 +2932,7 @@ impl LayoutEngine {
                             if let Some(last_workspace) =
                                 self.virtual_workspace_manager.last_workspace(space)
                             {
-                                self.virtual_workspace_manager
-                                    .set_active_workspace(space, last_workspace);
-                                self.update_active_floating_windows(space);
+                                self.activate_workspace(space, last_workspace);
                                 self.broadcast_workspace_changed(space);
                                 self.broadcast_windows_changed(space);
                                 return self.refocus_workspace(space, last_workspace);
@@ -2026,9 +2940,7 @@ impl LayoutEngine {
                         }
                         return EventResponse::default();
                     }
-                    self.virtual_workspace_manager.set_active_workspace(space, workspace_id);
-
-                    self.update_active_floating_windows(space);
+                    self.activate_workspace(space, workspace_id);
 
                     self.broadcast_workspace_changed(space);
                     self.broadcast_windows_changed(space);
@@ -2040,6 +2952,8 @@ impl LayoutEngine {
             LayoutCommand::MoveWindowToWorkspace {
                 workspace: workspace_index,
                 window_id: maybe_id,
+                target_index,
+                ..
             } => {
                 let focused_window = if let Some(spec_u32) = maybe_id {
                     match self.virtual_workspace_manager.find_window_by_idx(space, *spec_u32) {
@@ -2076,46 +2990,26 @@ impl LayoutEngine {
                     return EventResponse::default();
                 }
 
-                let is_floating = self.floating.is_floating(focused_window);
-
-                if is_floating {
-                    self.floating.remove_active_for_window(focused_window);
-                } else {
-                    self.remove_window_from_all_tiling_trees(focused_window);
-                }
-
-                let assigned = self.virtual_workspace_manager.assign_window_to_workspace(
+                if !self.transfer_window_to_workspace(
                     op_space,
                     focused_window,
+                    current_workspace_id,
                     target_workspace_id,
-                );
-                if !assigned {
-                    if is_floating {
-                        self.floating.add_active(op_space, focused_window.pid, focused_window);
-                    } else if let Some(prev_layout) =
-                        self.workspace_layouts.active(op_space, current_workspace_id)
-                    {
-                        self.workspace_tree_mut(current_workspace_id)
-                            .add_window_after_selection(prev_layout, focused_window);
-                    }
+                ) {
                     return EventResponse::default();
                 }
-
-                if !is_floating {
-                    if let Some(target_layout) =
-                        self.workspace_layouts.active(op_space, target_workspace_id)
-                    {
-                        self.workspace_tree_mut(target_workspace_id)
-                            .add_window_after_selection(target_layout, focused_window);
-                    }
+                if let Some(index) = target_index {
+                    self.move_transferred_window_to_index(
+                        op_space,
+                        target_workspace_id,
+                        focused_window,
+                        *index,
+                    );
                 }
 
                 let active_workspace = self.virtual_workspace_manager.active_workspace(op_space);
 
                 if Some(target_workspace_id) == active_workspace {
-                    if is_floating {
-                        self.floating.add_active(op_space, focused_window.pid, focused_window);
-                    }
                     return EventResponse {
                         focus_window: Some(focused_window),
                         raise_windows: vec![],
@@ -2149,6 +3043,15 @@ impl LayoutEngine {
                 self.broadcast_windows_changed(op_space);
                 EventResponse::default()
             }
+            LayoutCommand::MoveWindowToNextWorkspace { follow } => {
+                self.move_focused_window_to_adjacent_workspace(space, true, *follow)
+            }
+            LayoutCommand::MoveWindowToPrevWorkspace { follow } => {
+                self.move_focused_window_to_adjacent_workspace(space, false, *follow)
+            }
+            LayoutCommand::SendToLastWorkspace { follow } => {
+                self.send_focused_window_to_last_workspace(space, *follow)
+            }
             LayoutCommand::CreateWorkspace => {
                 match self.virtual_workspace_manager.create_workspace(space, None) {
                     Ok(_workspace_id) => {
@@ -2160,11 +3063,33 @@ impl LayoutEngine {
                 }
                 EventResponse::default()
             }
+            LayoutCommand::RenameWorkspace { index, name } => {
+                let Some(workspace_id) = self.workspace_id_for_index(space, *index) else {
+                    let err = WorkspaceError::InvalidWorkspaceIndex(index.unwrap_or_default());
+                    warn!(
+                        code = err.code(),
+                        ?index,
+                        "Rename workspace ignored: no such workspace"
+                    );
+                    return EventResponse::default();
+                };
+                match self.virtual_workspace_manager.rename_workspace(
+                    space,
+                    workspace_id,
+                    name.clone(),
+                ) {
+                    Ok(previous_name) => {
+                        self.broadcast_workspace_renamed(space, workspace_id, previous_name);
+                    }
+                    Err(err) => {
+                        warn!(code = err.code(), ?err, "Failed to rename workspace");
+                    }
+                }
+                EventResponse::default()
+            }
             LayoutCommand::SwitchToLastWorkspace => {
                 if let Some(last_workspace) = self.virtual_workspace_manager.last_workspace(space) {
-                    self.virtual_workspace_manager.set_active_workspace(space, last_workspace);
-
-                    self.update_active_floating_windows(space);
+                    self.activate_workspace(space, last_workspace);
 
                     self.broadcast_workspace_changed(space);
                     self.broadcast_windows_changed(space);
@@ -2177,6 +3102,9 @@ impl LayoutEngine {
                 let Some(workspace_id) = self.workspace_id_for_index(space, *workspace) else {
                     return EventResponse::default();
                 };
+                if let Some(ws) = self.virtual_workspace_manager.workspaces.get_mut(workspace_id) {
+                    ws.layout_mode_overridden = true;
+                }
 
                 if !self.switch_workspace_layout_mode(space, workspace_id, *mode) {
                     return EventResponse::default();
@@ -2202,6 +3130,22 @@ impl LayoutEngine {
                     boundary_hit: None,
                 }
             }
+            LayoutCommand::CycleLayoutMode => {
+                let modes = self.layout_settings.cycle_modes().to_vec();
+                let current = self.active_layout_mode_at(space);
+                // A mode outside the set (e.g. from a workspace rule) re-enters it at the start.
+                let next = match modes.iter().position(|&mode| mode == current) {
+                    Some(idx) => modes[(idx + 1) % modes.len()],
+                    None => modes[0],
+                };
+                if next == current {
+                    return EventResponse::default();
+                }
+                self.handle_virtual_workspace_command(space, &LayoutCommand::SetWorkspaceLayout {
+                    workspace: None,
+                    mode: next,
+                })
+            }
             _ => EventResponse::default(),
         }
     }
@@ -2254,6 +3198,25 @@ impl LayoutEngine {
         target_space: SpaceId,
         target_screen_size: CGSize,
         window_id: WindowId,
+    ) -> EventResponse {
+        self.move_window_to_space_workspace(
+            source_space,
+            target_space,
+            target_screen_size,
+            window_id,
+            None,
+        )
+    }
+
+    /// Like [`Self::move_window_to_space`], but lands the window on the target space's
+    /// workspace at `target_workspace_index` instead of its active one, when it exists.
+    pub fn move_window_to_space_workspace(
+        &mut self,
+        source_space: SpaceId,
+        target_space: SpaceId,
+        target_screen_size: CGSize,
+        window_id: WindowId,
+        target_workspace_index: Option<usize>,
     ) -> EventResponse {
         if source_space == target_space {
             return EventResponse {
@@ -2263,6 +3226,99 @@ impl LayoutEngine {
             };
         }
 
+        let Some((source_workspace_id, target_workspace_id)) = self
+            .transfer_window_to_space_workspace(
+                source_space,
+                target_space,
+                target_screen_size,
+                window_id,
+                target_workspace_index,
+            )
+        else {
+            return EventResponse::default();
+        };
+
+        if self.focused_window == Some(window_id) {
+            self.focused_window = None;
+        }
+
+        if let Some(active_ws) = self.virtual_workspace_manager.active_workspace(source_space) {
+            if active_ws == source_workspace_id {
+                self.virtual_workspace_manager.set_last_focused_window(
+                    source_space,
+                    source_workspace_id,
+                    None,
+                );
+            }
+        }
+
+        self.virtual_workspace_manager.set_last_focused_window(
+            target_space,
+            target_workspace_id,
+            Some(window_id),
+        );
+        self.focused_window = Some(window_id);
+
+        self.broadcast_windows_changed(source_space);
+        self.broadcast_windows_changed(target_space);
+
+        EventResponse {
+            raise_windows: vec![window_id],
+            focus_window: Some(window_id),
+            boundary_hit: None,
+        }
+    }
+
+    /// Like [`Self::move_window_to_space_workspace`], but leaves focus alone, for
+    /// moving many windows at once. Returns whether the window moved.
+    pub fn relocate_window_to_space_workspace(
+        &mut self,
+        source_space: SpaceId,
+        target_space: SpaceId,
+        target_screen_size: CGSize,
+        window_id: WindowId,
+        target_workspace_index: Option<usize>,
+    ) -> bool {
+        if source_space == target_space {
+            return false;
+        }
+        let Some((source_workspace_id, _)) = self.transfer_window_to_space_workspace(
+            source_space,
+            target_space,
+            target_screen_size,
+            window_id,
+            target_workspace_index,
+        ) else {
+            return false;
+        };
+
+        if self
+            .virtual_workspace_manager
+            .last_focused_window(source_space, source_workspace_id)
+            == Some(window_id)
+        {
+            self.virtual_workspace_manager.set_last_focused_window(
+                source_space,
+                source_workspace_id,
+                None,
+            );
+        }
+
+        self.broadcast_windows_changed(source_space);
+        self.broadcast_windows_changed(target_space);
+        true
+    }
+
+    /// Takes `window_id` out of its workspace and layout and adds it to the target
+    /// space's workspace. Returns the source and target workspaces when it moved.
+    fn transfer_window_to_space_workspace(
+        &mut self,
+        source_space: SpaceId,
+        target_space: SpaceId,
+        target_screen_size: CGSize,
+        window_id: WindowId,
+        target_workspace_index: Option<usize>,
+    ) -> Option<(VirtualWorkspaceId, VirtualWorkspaceId)> {
         let _ = self.virtual_workspace_manager.list_workspaces(source_space);
         let _ = self.virtual_workspace_manager.list_workspaces(target_space);
 
@@ -2271,11 +3327,16 @@ impl LayoutEngine {
             .workspace_for_window(source_space, window_id)
             .or_else(|| self.virtual_workspace_manager.workspace_for_window_any(window_id));
 
-        let Some(source_workspace_id) = source_workspace else {
-            return EventResponse::default();
-        };
+        let source_workspace_id = source_workspace?;
 
-        let mut target_workspace_id = self.virtual_workspace_manager.active_workspace(target_space);
+        let mut target_workspace_id = target_workspace_index
+            .and_then(|index| {
+                self.virtual_workspace_manager
+                    .list_workspaces(target_space)
+                    .get(index)
+                    .map(|(id, _)| *id)
+            })
+            .or_else(|| self.virtual_workspace_manager.active_workspace(target_space));
         if target_workspace_id.is_none() {
             if let Some((id, _)) =
                 self.virtual_workspace_manager.list_workspaces(target_space).first()
@@ -2285,9 +3346,7 @@ impl LayoutEngine {
             }
         }
 
-        let Some(target_workspace_id) = target_workspace_id else {
-            return EventResponse::default();
-        };
+        let target_workspace_id = target_workspace_id?;
 
         let was_floating = self.floating.is_floating(window_id);
 
@@ -2312,7 +3371,7 @@ impl LayoutEngine {
                 self.workspace_tree_mut(source_workspace_id)
                     .add_window_after_selection(src_layout, window_id);
             }
-            return EventResponse::default();
+            return None;
         }
 
         {
@@ -2338,37 +3397,7 @@ impl LayoutEngine {
                 .add_window_after_selection(target_layout, window_id);
         }
 
-        if self.focused_window == Some(window_id) {
-            self.focused_window = None;
-        }
-
-        if let Some(active_ws) = self.virtual_workspace_manager.active_workspace(source_space) {
-            if active_ws == source_workspace_id {
-                self.virtual_workspace_manager.set_last_focused_window(
-                    source_space,
-                    source_workspace_id,
-                    None,
-                );
-            }
-        }
-
-        self.virtual_workspace_manager.set_last_focused_window(
-            target_space,
-            target_workspace_id,
-            Some(window_id),
-        );
-        self.focused_window = Some(window_id);
-
-        if source_space != target_space {
-            self.broadcast_windows_changed(source_space);
-        }
-        self.broadcast_windows_changed(target_space);
-
-        EventResponse {
-            raise_windows: vec![window_id],
-            focus_window: Some(window_id),
-            boundary_hit: None,
-        }
+        Some((source_workspace_id, target_workspace_id))
     }
 
     pub fn workspace_name(
@@ -2389,25 +3418,295 @@ impl LayoutEngine {
         self.virtual_workspace_manager.get_stats()
     }
 
+    pub fn is_window_sticky(&self, window_id: WindowId) -> bool {
+        self.sticky_windows.contains(&window_id)
+    }
+
     pub fn is_window_floating(&self, window_id: WindowId) -> bool {
         self.floating.is_floating(window_id)
     }
 
-    fn update_active_floating_windows(&mut self, space: SpaceId) {
-        let windows_in_workspace =
-            self.virtual_workspace_manager.windows_in_active_workspace(space);
-        self.floating.rebuild_active_for_workspace(space, windows_in_workspace);
+    /// Whether the engine holds any state for the window: a workspace, floating
+    /// state, or size constraints.
+    pub fn tracks_window(&self, window_id: WindowId) -> bool {
+        self.virtual_workspace_manager.workspace_for_window_any(window_id).is_some()
+            || self.floating.is_floating(window_id)
+            || self.window_layout_constraints.contains_key(&window_id)
     }
 
-    pub fn store_floating_window_positions(
-        &mut self,
-        space: SpaceId,
+    pub fn window_layout_constraints(
+        &self,
+        window_id: WindowId,
+    ) -> Option<WindowLayoutConstraints> {
+        self.window_layout_constraints.get(&window_id).copied()
+    }
+
+    pub fn clear_window_layout_constraints(&mut self, windows: &[WindowId]) {
+        for wid in windows {
+            self.window_layout_constraints.remove(wid);
+        }
+    }
+
+    /// Works out a tracked window's size limits again from the ones it reports
+    /// and the app rule it now matches, e.g. after its title changed. Returns
+    /// whether they changed.
+    pub fn refresh_window_constraints(
+        &mut self,
+        wid: WindowId,
+        info: &WindowInfo,
+        app_info: Option<&AppInfo>,
+    ) -> bool {
+        let Some(&current) = self.window_layout_constraints.get(&wid) else {
+            return false;
+        };
+        let mut constraints = WindowLayoutConstraints {
+            min_width: info.min_size.map_or(0.0, |s| s.width),
+            min_height: info.min_size.map_or(0.0, |s| s.height),
+            max_width: info.max_size.map_or(0.0, |s| s.width),
+            max_height: info.max_size.map_or(0.0, |s| s.height),
+            ..current
+        }
+        .normalized();
+        if let Some(overrides) = self.virtual_workspace_manager.app_rule_constraints(
+            app_info.and_then(|info| info.bundle_id.as_deref()),
+            app_info.and_then(|info| info.localized_name.as_deref()),
+            Some(info.title.as_str()),
+            info.ax_role.as_deref(),
+            info.ax_subrole.as_deref(),
+        ) {
+            constraints = constraints.with_overrides(&overrides);
+        }
+        if constraints == current {
+            return false;
+        }
+        self.window_layout_constraints.insert(wid, constraints);
+        true
+    }
+
+    /// Sizes floating window `wid` to fractions of `bounds`, within its size
+    /// constraints, places it at `anchor` and stores that as its floating
+    /// position. Returns the new frame, or `None` if the window is not floating.
+    pub fn resize_floating_to_fraction(
+        &mut self,
+        space: SpaceId,
+        wid: WindowId,
+        bounds: CGRect,
+        width_frac: f64,
+        height_frac: f64,
+        anchor: Anchor,
+    ) -> Option<CGRect> {
+        if !self.floating.is_floating(wid) {
+            return None;
+        }
+        let workspace_id = self.virtual_workspace_manager.workspace_for_window(space, wid)?;
+        let constraints = self.window_layout_constraints(wid).unwrap_or_default();
+        let fit = |frac: f64, extent: f64, locked: f64, min: f64, max: f64| {
+            if !constraints.is_resizable && locked > 0.0 {
+                return locked;
+            }
+            let size = extent * frac.clamp(0.0, 1.0);
+            let size = if max > 0.0 { size.min(max) } else { size };
+            size.max(min).max(1.0)
+        };
+        let size = CGSize::new(
+            fit(
+                width_frac,
+                bounds.size.width,
+                constraints.locked_width,
+                constraints.min_width,
+                constraints.max_width,
+            ),
+            fit(
+                height_frac,
+                bounds.size.height,
+                constraints.locked_height,
+                constraints.min_height,
+                constraints.max_height,
+            ),
+        );
+        let frame = anchor.place(bounds, size);
+        self.virtual_workspace_manager
+            .store_floating_position(space, workspace_id, wid, frame);
+        Some(frame)
+    }
+
+    /// Grows the focused tiled window toward `direction` up to the nearest tiled
+    /// window in its way, or the tiling area of `screen`, by resizing the split
+    /// it borders. Free space is measured on the frames the tree lays out, so a
+    /// window that hasn't caught up with the layout yet doesn't count as a gap.
+    /// Returns whether the layout changed.
+    pub fn grow_to_fill(
+        &mut self,
+        space: SpaceId,
+        screen: CGRect,
+        direction: Direction,
+        stack_line_thickness: f64,
+        stack_line_horiz: crate::common::config::HorizontalPlacement,
+        stack_line_vert: crate::common::config::VerticalPlacement,
+    ) -> bool {
+        // Signed coordinate of the side of `rect` facing `direction`, so that
+        // "further along `direction`" is always larger.
+        fn edge(rect: CGRect, direction: Direction) -> f64 {
+            match direction {
+                Direction::Left => -rect.min().x,
+                Direction::Right => rect.max().x,
+                Direction::Up => -rect.min().y,
+                Direction::Down => rect.max().y,
+            }
+        }
+        const FILL_EPSILON: f64 = 1.0;
+
+        let Some((ws_id, layout)) = self.workspace_and_layout(space) else {
+            return false;
+        };
+        let Some(wid) = self.focused_window.filter(|wid| !self.floating.is_floating(*wid)) else {
+            return false;
+        };
+        if !self.workspace_tree(ws_id).contains_window(layout, wid) {
+            return false;
+        }
+        let display_uuid = self.display_uuid_for_space(space);
+        let display_gaps = self.layout_settings.gaps.effective_for_display(display_uuid.as_deref());
+        let frames: HashMap<WindowId, CGRect> = self
+            .calculate_layout(
+                space,
+                screen,
+                &display_gaps,
+                stack_line_thickness,
+                stack_line_horiz,
+                stack_line_vert,
+            )
+            .into_iter()
+            .collect();
+        let Some(&frame) = frames.get(&wid) else {
+            return false;
+        };
+        let gaps = self.gaps_for_layout(ws_id, layout, &display_gaps);
+
+        let horizontal = direction.orientation() == Orientation::Horizontal;
+        let inner_gap = if horizontal {
+            gaps.inner.horizontal
+        } else {
+            gaps.inner.vertical
+        };
+        let in_the_way = |other: &CGRect| {
+            let overlaps = if horizontal {
+                other.min().y < frame.max().y && other.max().y > frame.min().y
+            } else {
+                other.min().x < frame.max().x && other.max().x > frame.min().x
+            };
+            overlaps && -edge(*other, direction.opposite()) >= edge(frame, direction) - FILL_EPSILON
+        };
+        let limit = frames
+            .iter()
+            .filter(|(other, rect)| **other != wid && in_the_way(rect))
+            .map(|(_, rect)| -edge(*rect, direction.opposite()) - inner_gap)
+            .fold(edge(compute_tiling_area(screen, &gaps), direction), f64::min);
+        let growth = limit - edge(frame, direction);
+        if growth < FILL_EPSILON {
+            return false;
+        }
+
+        let mut new_frame = frame;
+        match direction {
+            Direction::Left => {
+                new_frame.origin.x -= growth;
+                new_frame.size.width += growth;
+            }
+            Direction::Right => new_frame.size.width += growth,
+            Direction::Up => {
+                new_frame.origin.y -= growth;
+                new_frame.size.height += growth;
+            }
+            Direction::Down => new_frame.size.height += growth,
+        }
+        self.workspace_tree_mut(ws_id)
+            .on_window_resized(layout, wid, frame, new_frame, screen, &gaps);
+        self.workspace_layouts.mark_last_saved(space, ws_id, layout);
+        true
+    }
+
+    /// Floats a tiled window and pins its floating position to `frame`.
+    pub fn float_window_at(&mut self, space: SpaceId, wid: WindowId, frame: CGRect) {
+        let Some(workspace_id) = self.virtual_workspace_manager.workspace_for_window(space, wid)
+        else {
+            return;
+        };
+        if !self.floating.is_floating(wid) {
+            self.workspace_tree_mut(workspace_id).remove_window(wid);
+            self.floating.add_floating(wid);
+            self.floating.add_active(space, wid.pid, wid);
+        }
+        self.virtual_workspace_manager
+            .store_floating_position(space, workspace_id, wid, frame);
+    }
+
+    /// Position of a tiled window in its workspace's layout order.
+    pub fn window_index_in_workspace(
+        &self,
+        space: SpaceId,
+        workspace_id: VirtualWorkspaceId,
+        window_id: WindowId,
+    ) -> Option<usize> {
+        let layout = self.workspace_layouts.active(space, workspace_id)?;
+        self.workspace_tree(workspace_id)
+            .visible_windows_in_layout(layout)
+            .iter()
+            .position(|&wid| wid == window_id)
+    }
+
+    /// Make `workspace` the active one on `space`, bringing along the sticky
+    /// windows of the workspace being left.
+    fn activate_workspace(&mut self, space: SpaceId, workspace: VirtualWorkspaceId) {
+        let previous = self.virtual_workspace_manager.active_workspace(space);
+        self.virtual_workspace_manager.set_active_workspace(space, workspace);
+        if let Some(previous) = previous.filter(|previous| *previous != workspace) {
+            let mut sticky: Vec<WindowId> = self
+                .sticky_windows
+                .iter()
+                .copied()
+                .filter(|wid| {
+                    self.virtual_workspace_manager.workspace_for_window(space, *wid)
+                        == Some(previous)
+                })
+                .collect();
+            sticky.sort();
+            for wid in sticky {
+                self.transfer_window_to_workspace(space, wid, previous, workspace);
+            }
+        }
+        self.update_active_floating_windows(space);
+    }
+
+    fn update_active_floating_windows(&mut self, space: SpaceId) {
+        let windows_in_workspace =
+            self.virtual_workspace_manager.windows_in_active_workspace(space);
+        self.floating.rebuild_active_for_workspace(space, windows_in_workspace);
+    }
+
+    pub fn store_floating_window_positions(
+        &mut self,
+        space: SpaceId,
         floating_positions: &[(WindowId, CGRect)],
     ) {
         self.virtual_workspace_manager
             .store_current_floating_positions(space, floating_positions);
     }
 
+    pub fn store_floating_stacking_order(&mut self, space: SpaceId) {
+        let order = self.floating_windows_in_stacking_order(space);
+        self.virtual_workspace_manager.store_floating_stacking_order(space, order);
+    }
+
+    /// The saved stacking order of the active workspace on `space`, limited to windows
+    /// that are still floating there.
+    pub fn take_floating_stacking_order(&mut self, space: SpaceId) -> Vec<WindowId> {
+        let current = self.floating_windows_in_stacking_order(space);
+        let mut order = self.virtual_workspace_manager.take_floating_stacking_order(space);
+        order.retain(|wid| current.contains(wid));
+        order
+    }
+
     fn broadcast_workspace_changed(&self, space_id: SpaceId) {
         if let Some(ref broadcast_tx) = self.broadcast_tx {
             if let Some((active_workspace_id, active_workspace_name)) =
@@ -2424,7 +3723,30 @@ impl LayoutEngine {
         }
     }
 
-    fn broadcast_windows_changed(&self, space_id: SpaceId) {
+    fn broadcast_workspace_renamed(
+        &mut self,
+        space_id: SpaceId,
+        workspace_id: VirtualWorkspaceId,
+        previous_name: String,
+    ) {
+        let Some(ref broadcast_tx) = self.broadcast_tx else {
+            return;
+        };
+        let workspaces = self.virtual_workspace_manager.list_workspaces(space_id);
+        let Some(position) = workspaces.iter().position(|(id, _)| *id == workspace_id) else {
+            return;
+        };
+        let _ = broadcast_tx.send(BroadcastEvent::WorkspaceRenamed {
+            space_id,
+            workspace_id,
+            workspace_index: Some(position as u64),
+            previous_name,
+            workspace_name: workspaces[position].1.clone(),
+            display_uuid: self.display_uuid_for_space(space_id),
+        });
+    }
+
+    fn broadcast_windows_changed(&mut self, space_id: SpaceId) {
         if let Some(ref broadcast_tx) = self.broadcast_tx {
             if let Some((workspace_id, workspace_name)) =
                 self.active_workspace_id_and_name(space_id)
@@ -2448,6 +3770,46 @@ impl LayoutEngine {
                 let _ = broadcast_tx.send(event);
             }
         }
+        self.broadcast_workspace_occupancy(space_id);
+    }
+
+    /// Emits `WorkspaceEmpty`/`WorkspaceNonempty` for every workspace on the space
+    /// whose window count crossed zero since the last check. Floating windows count
+    /// towards occupancy the same as tiled ones.
+    fn broadcast_workspace_occupancy(&mut self, space_id: SpaceId) {
+        let display_uuid = self.display_uuid_for_space(space_id);
+        for (workspace_id, workspace_name) in
+            self.virtual_workspace_manager.list_workspaces(space_id)
+        {
+            let occupied = self
+                .virtual_workspace_manager
+                .workspace_info(space_id, workspace_id)
+                .is_some_and(|ws| ws.window_count() > 0);
+            let event = if occupied {
+                if !self.occupied_workspaces.insert(workspace_id) {
+                    continue;
+                }
+                BroadcastEvent::WorkspaceNonempty {
+                    space_id,
+                    workspace_id,
+                    workspace_name,
+                    display_uuid: display_uuid.clone(),
+                }
+            } else {
+                if !self.occupied_workspaces.remove(&workspace_id) {
+                    continue;
+                }
+                BroadcastEvent::WorkspaceEmpty {
+                    space_id,
+                    workspace_id,
+                    workspace_name,
+                    display_uuid: display_uuid.clone(),
+                }
+            };
+            if let Some(ref broadcast_tx) = self.broadcast_tx {
+                let _ = broadcast_tx.send(event);
+            }
+        }
     }
 
     pub fn debug_log_workspace_stats(&self) {
@@ -2510,8 +3872,8 @@ mod tests {
     use super::*;
     use crate::common::collections::HashMap;
     use crate::common::config::{
-        LayoutMode, LayoutSettings, VirtualWorkspaceSettings, WorkspaceLayoutRule,
-        WorkspaceSelector,
+        LayoutMode, LayoutSettings, MasterStackNewWindowPlacement, VirtualWorkspaceSettings,
+        WorkspaceLayoutRule, WorkspaceSelector,
     };
 
     fn test_engine() -> LayoutEngine {
@@ -2582,6 +3944,376 @@ mod tests {
         );
     }
 
+    #[test]
+    fn focus_next_window_cycles_tiled_then_floating() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(60);
+        let pid: pid_t = 600;
+        let tiled_a = WindowId::new(pid, 1);
+        let tiled_b = WindowId::new(pid, 2);
+        let floating = WindowId::new(pid, 3);
+        let size = CGSize::new(400.0, 400.0);
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            pid,
+            vec![
+                (tiled_a, None, None, None, true, size, None, None),
+                (tiled_b, None, None, None, true, size, None, None),
+                (floating, None, None, None, true, size, None, None),
+            ],
+            None,
+        ));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, floating));
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::ToggleWindowFloating,
+        );
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, tiled_a));
+
+        let cycle = |engine: &mut LayoutEngine, forward: bool| {
+            let command = if forward {
+                LayoutCommand::FocusNextWindow
+            } else {
+                LayoutCommand::FocusPrevWindow
+            };
+            engine
+                .handle_command(Some(space), &[space], &HashMap::default(), command)
+                .focus_window
+        };
+        assert_eq!(cycle(&mut engine, true), Some(tiled_b));
+        assert_eq!(cycle(&mut engine, true), Some(floating));
+        assert_eq!(cycle(&mut engine, true), Some(tiled_a));
+        assert_eq!(cycle(&mut engine, false), Some(floating));
+
+        engine.layout_settings.focus_cycle_skip_floating = true;
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, tiled_b));
+        assert_eq!(cycle(&mut engine, true), Some(tiled_a));
+    }
+
+    #[test]
+    fn tile_all_floating_tiles_floating_windows_and_float_all_tiled_restores_them() {
+        use crate::common::config::GapSettings;
+
+        let mut engine = test_engine();
+        let space = SpaceId::new(62);
+        let w = |idx| WindowId::new(620, idx);
+        let size = CGSize::new(400.0, 400.0);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1920.0, 1080.0));
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            620,
+            (1..=3).map(|idx| (w(idx), None, None, None, true, size, None, None)).collect(),
+            None,
+        ));
+        let command = |engine: &mut LayoutEngine, cmd| {
+            let _ = engine.handle_command(Some(space), &[space], &HashMap::default(), cmd);
+        };
+        for idx in [2, 3] {
+            let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(idx)));
+            command(&mut engine, LayoutCommand::ToggleWindowFloating);
+        }
+        let floating_frames = [
+            (w(2), CGRect::new(CGPoint::new(100.0, 100.0), size)),
+            (w(3), CGRect::new(CGPoint::new(700.0, 300.0), size)),
+        ];
+        engine.store_floating_window_positions(space, &floating_frames);
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(1)));
+
+        let tiled = |engine: &LayoutEngine| {
+            let (ws_id, layout) = engine.workspace_and_layout(space).unwrap();
+            engine.workspace_tree(ws_id).visible_windows_in_layout(layout)
+        };
+        let frames = |engine: &mut LayoutEngine| -> HashMap<WindowId, CGRect> {
+            engine
+                .calculate_layout(
+                    space,
+                    screen,
+                    &GapSettings::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                )
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(tiled(&engine), vec![w(1)]);
+
+        command(&mut engine, LayoutCommand::TileAllFloating);
+        assert_eq!(tiled(&engine).len(), 3);
+        assert!(!engine.is_window_floating(w(2)) && !engine.is_window_floating(w(3)));
+        assert!(frames(&mut engine).values().all(|frame| frame.size.height == 1080.0));
+
+        command(&mut engine, LayoutCommand::FloatAllTiled);
+        assert_eq!(tiled(&engine), vec![w(1)]);
+        let restored = frames(&mut engine);
+        for (wid, frame) in floating_frames {
+            assert!(engine.is_window_floating(wid));
+            assert_eq!(restored[&wid], frame);
+        }
+    }
+
+    #[test]
+    fn resize_floating_to_fraction_places_window_at_anchor() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(61);
+        let pid: pid_t = 610;
+        let tiled = WindowId::new(pid, 1);
+        let floating = WindowId::new(pid, 2);
+        let size = CGSize::new(400.0, 400.0);
+        let bounds = CGRect::new(CGPoint::new(100.0, 50.0), CGSize::new(1000.0, 800.0));
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            pid,
+            vec![
+                (tiled, None, None, None, true, size, None, None),
+                (floating, None, None, None, true, size, None, None),
+            ],
+            None,
+        ));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, floating));
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::ToggleWindowFloating,
+        );
+
+        let rect = |x, y, w, h| CGRect::new(CGPoint::new(x, y), CGSize::new(w, h));
+        let mut resize = |wid, w, h, anchor| {
+            engine.resize_floating_to_fraction(space, wid, bounds, w, h, anchor)
+        };
+        assert_eq!(
+            resize(floating, 0.5, 0.5, Anchor::Center),
+            Some(rect(350.0, 250.0, 500.0, 400.0))
+        );
+        assert_eq!(
+            resize(floating, 0.25, 1.0, Anchor::TopLeft),
+            Some(rect(100.0, 50.0, 250.0, 800.0))
+        );
+        assert_eq!(
+            resize(floating, 0.5, 2.0, Anchor::Right),
+            Some(rect(600.0, 50.0, 500.0, 800.0))
+        );
+        assert_eq!(resize(tiled, 0.5, 0.5, Anchor::Center), None);
+    }
+
+    fn engine_with_focused_windows(space: SpaceId, pid: pid_t) -> LayoutEngine {
+        let mut engine = test_engine();
+        let size = CGSize::new(400.0, 400.0);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            pid,
+            vec![
+                (WindowId::new(pid, 1), None, None, None, true, size, None, None),
+                (WindowId::new(pid, 2), None, None, None, true, size, None, None),
+            ],
+            None,
+        ));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, WindowId::new(pid, 1)));
+        engine
+    }
+
+    #[test]
+    fn move_window_to_next_workspace_without_follow_keeps_workspace() {
+        let space = SpaceId::new(70);
+        let mut engine = engine_with_focused_windows(space, 700);
+        let moved = WindowId::new(700, 1);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+
+        let command = LayoutCommand::MoveWindowToNextWorkspace { follow: false };
+        let response = engine.handle_virtual_workspace_command(space, &command);
+
+        let vwm = engine.virtual_workspace_manager();
+        assert_eq!(vwm.workspace_for_window(space, moved), Some(workspaces[1].0));
+        assert_eq!(vwm.active_workspace(space), Some(workspaces[0].0));
+        assert_eq!(response.focus_window, Some(WindowId::new(700, 2)));
+    }
+
+    #[test]
+    fn move_window_to_prev_workspace_with_follow_wraps_and_switches() {
+        let space = SpaceId::new(71);
+        let mut engine = engine_with_focused_windows(space, 710);
+        let moved = WindowId::new(710, 1);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        let last = workspaces.last().unwrap().0;
+
+        let command = LayoutCommand::MoveWindowToPrevWorkspace { follow: true };
+        let response = engine.handle_virtual_workspace_command(space, &command);
+
+        let vwm = engine.virtual_workspace_manager();
+        assert_eq!(vwm.workspace_for_window(space, moved), Some(last));
+        assert_eq!(vwm.active_workspace(space), Some(last));
+        assert_eq!(response.focus_window, Some(moved));
+    }
+
+    #[test]
+    fn send_to_last_workspace_moves_window_to_previously_active_workspace() {
+        let space = SpaceId::new(73);
+        let mut engine = engine_with_focused_windows(space, 730);
+        let moved = WindowId::new(730, 1);
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        let (first, third) = (workspaces[0].0, workspaces[2].0);
+
+        for index in [2, 0] {
+            let switch = LayoutCommand::SwitchToWorkspace(index);
+            let _ = engine.handle_virtual_workspace_command(space, &switch);
+        }
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, moved));
+        let vwm = engine.virtual_workspace_manager();
+        assert_eq!(vwm.last_workspace(space), Some(third));
+
+        let command = LayoutCommand::SendToLastWorkspace { follow: false };
+        let _ = engine.handle_virtual_workspace_command(space, &command);
+        let vwm = engine.virtual_workspace_manager();
+        assert_eq!(vwm.workspace_for_window(space, moved), Some(third));
+        assert_eq!(vwm.active_workspace(space), Some(first));
+
+        let other = WindowId::new(730, 2);
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, other));
+        let command = LayoutCommand::SendToLastWorkspace { follow: true };
+        let response = engine.handle_virtual_workspace_command(space, &command);
+        let vwm = engine.virtual_workspace_manager();
+        assert_eq!(vwm.workspace_for_window(space, other), Some(third));
+        assert_eq!(vwm.active_workspace(space), Some(third));
+        assert_eq!(vwm.last_workspace(space), Some(first));
+        assert_eq!(response.focus_window, Some(other));
+    }
+
+    #[test]
+    fn distribute_windows_round_robins_and_keeps_pinned_windows() {
+        let space = SpaceId::new(72);
+        let pid = 720;
+        let mut engine = test_engine();
+        let size = CGSize::new(400.0, 400.0);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let windows: Vec<_> = (1..=6).map(|idx| WindowId::new(pid, idx)).collect();
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            pid,
+            windows
+                .iter()
+                .map(|&wid| (wid, None, None, None, true, size, None, None))
+                .collect(),
+            None,
+        ));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, windows[1]));
+        let workspaces = engine.virtual_workspace_manager_mut().list_workspaces(space);
+        assert_eq!(workspaces.len(), 4);
+        let pinned = windows[5];
+
+        let keep: HashSet<WindowId> = [pinned].into_iter().collect();
+        let (counts, response) = engine.distribute_windows(space, &keep);
+
+        assert_eq!(counts, vec![3, 1, 1, 1]);
+        let vwm = engine.virtual_workspace_manager();
+        for (i, &wid) in windows[..5].iter().enumerate() {
+            assert_eq!(vwm.workspace_for_window(space, wid), Some(workspaces[i % 4].0));
+        }
+        assert_eq!(vwm.workspace_for_window(space, pinned), Some(workspaces[0].0));
+        assert_eq!(vwm.active_workspace(space), Some(workspaces[0].0));
+        // The focused window moved away, so focus falls back to the active workspace.
+        let refocused = response.focus_window.expect("focus should move to the active workspace");
+        assert_eq!(vwm.workspace_for_window(space, refocused), Some(workspaces[0].0));
+    }
+
+    #[test]
+    fn rename_workspace_rejects_empty_and_duplicate_names() {
+        let (broadcast_tx, mut broadcast_rx) = crate::actor::channel();
+        let mut engine = LayoutEngine::new(
+            &VirtualWorkspaceSettings::default(),
+            &LayoutSettings::default(),
+            Some(broadcast_tx),
+        );
+        let space = SpaceId::new(62);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        while broadcast_rx.try_recv().is_ok() {}
+
+        let rename = |engine: &mut LayoutEngine, index: Option<usize>, name: &str| {
+            let command = LayoutCommand::RenameWorkspace { index, name: name.to_string() };
+            let _ = engine.handle_virtual_workspace_command(space, &command);
+            engine.virtual_workspace_manager_mut().list_workspaces(space)
+        };
+        let original = engine.virtual_workspace_manager_mut().list_workspaces(space);
+
+        let renamed = rename(&mut engine, Some(1), "  web ");
+        assert_eq!(renamed[1].1, "web");
+        match broadcast_rx.try_recv() {
+            Ok((
+                _,
+                BroadcastEvent::WorkspaceRenamed {
+                    workspace_id,
+                    workspace_index,
+                    previous_name,
+                    workspace_name,
+                    ..
+                },
+            )) => {
+                assert_eq!(workspace_id, original[1].0);
+                assert_eq!(workspace_index, Some(1));
+                assert_eq!(previous_name, original[1].1);
+                assert_eq!(workspace_name, "web");
+            }
+            other => panic!("expected a workspace_renamed event, got {other:?}"),
+        }
+
+        assert_eq!(rename(&mut engine, Some(0), "web"), renamed);
+        assert_eq!(rename(&mut engine, None, "   "), renamed);
+        assert!(broadcast_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn workspace_occupancy_events_fire_on_zero_crossings() {
+        let (broadcast_tx, mut broadcast_rx) = crate::actor::channel();
+        let mut engine = LayoutEngine::new(
+            &VirtualWorkspaceSettings::default(),
+            &LayoutSettings::default(),
+            Some(broadcast_tx),
+        );
+        let space = SpaceId::new(61);
+        let pid: pid_t = 610;
+        let first = WindowId::new(pid, 1);
+        let second = WindowId::new(pid, 2);
+        let size = CGSize::new(400.0, 400.0);
+
+        let occupancy_events = |rx: &mut crate::actor::Receiver<BroadcastEvent>| {
+            let mut events = Vec::new();
+            while let Ok((_, event)) = rx.try_recv() {
+                match event {
+                    BroadcastEvent::WorkspaceEmpty { .. } => events.push("empty"),
+                    BroadcastEvent::WorkspaceNonempty { .. } => events.push("nonempty"),
+                    _ => {}
+                }
+            }
+            events
+        };
+
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            pid,
+            vec![
+                (first, None, None, None, true, size, None, None),
+                (second, None, None, None, true, size, None, None),
+            ],
+            None,
+        ));
+        assert_eq!(occupancy_events(&mut broadcast_rx), vec!["nonempty"]);
+
+        let _ = engine.handle_event(LayoutEvent::WindowRemoved(first));
+        assert!(occupancy_events(&mut broadcast_rx).is_empty());
+
+        let _ = engine.handle_event(LayoutEvent::WindowRemoved(second));
+        assert_eq!(occupancy_events(&mut broadcast_rx), vec!["empty"]);
+    }
+
     #[test]
     fn move_focus_to_uninitialized_adjacent_space_does_not_panic() {
         let mut engine = test_engine();
@@ -2611,7 +4343,105 @@ mod tests {
     }
 
     #[test]
-    fn update_virtual_workspace_settings_reapplies_workspace_rules() {
+    fn workspaces_keep_their_own_layout_modes_across_switches() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(8);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+
+        let set_layout = LayoutCommand::SetWorkspaceLayout {
+            workspace: Some(1),
+            mode: LayoutMode::Scrolling,
+        };
+        let _ = engine.handle_virtual_workspace_command(space, &set_layout);
+        assert_eq!(engine.active_layout_mode_at(space), LayoutMode::Traditional);
+
+        let switch_to = |engine: &mut LayoutEngine, index| {
+            let _ = engine
+                .handle_virtual_workspace_command(space, &LayoutCommand::SwitchToWorkspace(index));
+            engine.active_layout_mode_at(space)
+        };
+        assert_eq!(switch_to(&mut engine, 1), LayoutMode::Scrolling);
+        assert_eq!(switch_to(&mut engine, 0), LayoutMode::Traditional);
+
+        // A config reload without matching rules must not undo the explicit choice.
+        engine.update_virtual_workspace_settings(&VirtualWorkspaceSettings::default());
+        assert_eq!(switch_to(&mut engine, 1), LayoutMode::Scrolling);
+
+        let mut restored: LayoutEngine = ron::de::from_str(&engine.serialize_to_string()).unwrap();
+        assert_eq!(restored.active_layout_mode_at(space), LayoutMode::Scrolling);
+        assert_eq!(switch_to(&mut restored, 0), LayoutMode::Traditional);
+    }
+
+    #[test]
+    fn load_reads_back_a_saved_layout() {
+        let mut engine = test_engine();
+        let space = SpaceId::new(10);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let set_layout = LayoutCommand::SetWorkspaceLayout {
+            workspace: None,
+            mode: LayoutMode::Scrolling,
+        };
+        let _ = engine.handle_virtual_workspace_command(space, &set_layout);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("layout.ron");
+        engine.save(path.clone()).unwrap();
+        let loaded = LayoutEngine::load(
+            path,
+            &VirtualWorkspaceSettings::default(),
+            &LayoutSettings::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(loaded.active_layout_mode_at(space), LayoutMode::Scrolling);
+
+        let missing = dir.path().join("missing.ron");
+        assert!(
+            LayoutEngine::load(
+                missing,
+                &VirtualWorkspaceSettings::default(),
+                &LayoutSettings::default(),
+                None,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn cycle_layout_mode_only_visits_allowed_modes() {
+        let settings = LayoutSettings {
+            cycle_modes: Some(vec![LayoutMode::Traditional, LayoutMode::Scrolling]),
+            ..Default::default()
+        };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let space = SpaceId::new(9);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+
+        let cycle = |engine: &mut LayoutEngine| {
+            let _ = engine.handle_virtual_workspace_command(space, &LayoutCommand::CycleLayoutMode);
+            engine.active_layout_mode_at(space)
+        };
+        assert_eq!(cycle(&mut engine), LayoutMode::Scrolling);
+        assert_eq!(cycle(&mut engine), LayoutMode::Traditional);
+        assert_eq!(cycle(&mut engine), LayoutMode::Scrolling);
+
+        let set_bsp = LayoutCommand::SetWorkspaceLayout {
+            workspace: None,
+            mode: LayoutMode::Bsp,
+        };
+        let _ = engine.handle_virtual_workspace_command(space, &set_bsp);
+        assert_eq!(cycle(&mut engine), LayoutMode::Traditional);
+
+        assert!(LayoutSettings::default().validate().is_empty());
+        let empty = LayoutSettings {
+            cycle_modes: Some(vec![]),
+            ..Default::default()
+        };
+        assert_eq!(empty.validate().len(), 1);
+    }
+
+    #[test]
+    fn update_virtual_workspace_settings_reapplies_workspace_rules() {
         let mut engine = test_engine();
         let space = SpaceId::new(7);
         let workspace_list = engine.virtual_workspace_manager_mut().list_workspaces(space);
@@ -2800,4 +4630,896 @@ mod tests {
         assert!(locked_frame.origin.x + locked_frame.size.width <= max_x);
         assert!(locked_frame.origin.y + locked_frame.size.height <= max_y);
     }
+
+    #[test]
+    fn smart_gaps_drop_gaps_for_a_lone_window() {
+        use crate::common::config::{GapSettings, InnerGaps, OuterGaps};
+
+        let space = SpaceId::new(82);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let settings = LayoutSettings {
+            smart_gaps: true,
+            ..Default::default()
+        };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let gaps = GapSettings {
+            outer: OuterGaps {
+                top: 10.0,
+                left: 10.0,
+                bottom: 10.0,
+                right: 10.0,
+            },
+            inner: InnerGaps { horizontal: 8.0, vertical: 8.0 },
+            ..Default::default()
+        };
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(820, 1)));
+
+        let frames = |engine: &mut LayoutEngine| {
+            engine.calculate_layout(
+                space,
+                screen,
+                &gaps,
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+        };
+        assert_eq!(frames(&mut engine), vec![(WindowId::new(820, 1), screen)]);
+
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(820, 2)));
+        let tiled = frames(&mut engine);
+        assert_eq!(tiled.len(), 2);
+        for (_, frame) in tiled {
+            assert_eq!(frame.origin.y, 10.0);
+            assert_eq!(frame.size.height, 780.0);
+        }
+    }
+
+    #[test]
+    fn scrolling_workspace_uses_its_mode_gaps() {
+        use crate::common::config::{GapOverride, GapSettings, ModeGapOverrides, OuterGaps};
+
+        let space = SpaceId::new(85);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let outer = |gap: f64| OuterGaps {
+            top: gap,
+            left: gap,
+            bottom: gap,
+            right: gap,
+        };
+        let gaps = GapSettings {
+            outer: outer(20.0),
+            per_mode: ModeGapOverrides {
+                scrolling: Some(GapOverride {
+                    outer: Some(outer(0.0)),
+                    inner: None,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = test_engine();
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(850, 1)));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(850, 2)));
+        let top_edges = |engine: &mut LayoutEngine| -> Vec<f64> {
+            engine
+                .calculate_layout(space, screen, &gaps, 0.0, Default::default(), Default::default())
+                .into_iter()
+                .map(|(_, frame)| frame.origin.y)
+                .collect()
+        };
+
+        // Traditional has no override, so it keeps the global outer gap.
+        assert_eq!(top_edges(&mut engine), vec![20.0, 20.0]);
+
+        let _ =
+            engine.handle_virtual_workspace_command(space, &LayoutCommand::SetWorkspaceLayout {
+                workspace: None,
+                mode: LayoutMode::Scrolling,
+            });
+        assert_eq!(top_edges(&mut engine), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn group_containers_use_their_mode_gaps() {
+        use crate::common::config::{GapOverride, GapSettings, ModeGapOverrides, OuterGaps};
+
+        let space = SpaceId::new(86);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let outer = |gap: f64| OuterGaps {
+            top: gap,
+            left: gap,
+            bottom: gap,
+            right: gap,
+        };
+        let gaps = GapSettings {
+            outer: outer(20.0),
+            per_mode: ModeGapOverrides {
+                traditional: Some(GapOverride {
+                    outer: Some(outer(0.0)),
+                    inner: None,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut engine = test_engine();
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(860, 1)));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(860, 2)));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, WindowId::new(860, 2)));
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::ToggleStack,
+        );
+
+        let groups = engine.collect_group_containers(
+            space,
+            screen,
+            &gaps,
+            0.0,
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].frame.origin, CGPoint::new(0.0, 0.0));
+
+        let groups = engine.collect_group_containers_in_selection_path(
+            space,
+            screen,
+            &gaps,
+            0.0,
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].frame.origin, CGPoint::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn set_split_direction_splits_focused_window_for_next_window() {
+        use crate::common::config::GapSettings;
+
+        let space = SpaceId::new(83);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let mut engine = test_engine();
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(830, 1)));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(830, 2)));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, WindowId::new(830, 2)));
+
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::SetSplitDirection(Orientation::Vertical),
+        );
+        assert_eq!(
+            engine.pending_split(space),
+            Some((WindowId::new(830, 2), Orientation::Vertical))
+        );
+
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(830, 3)));
+        assert_eq!(engine.pending_split(space), None);
+
+        let frames: HashMap<WindowId, CGRect> = engine
+            .calculate_layout(
+                space,
+                screen,
+                &GapSettings::default(),
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+            .into_iter()
+            .collect();
+        let first = frames[&WindowId::new(830, 1)];
+        let focused = frames[&WindowId::new(830, 2)];
+        let added = frames[&WindowId::new(830, 3)];
+        assert_eq!(first.size.height, screen.size.height);
+        assert_eq!(focused.origin.x, added.origin.x);
+        assert_eq!(focused.size.width, added.size.width);
+        assert!(focused.max().y <= added.origin.y);
+    }
+
+    #[test]
+    fn split_hint_applies_to_its_window_when_discovered_window_arrives() {
+        use crate::common::config::GapSettings;
+
+        let space = SpaceId::new(87);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let pid: pid_t = 870;
+        let w = |idx| WindowId::new(pid, idx);
+        let size = CGSize::new(400.0, 400.0);
+        let on_screen = |engine: &mut LayoutEngine, windows: &[WindowId]| {
+            let windows = windows
+                .iter()
+                .map(|&wid| (wid, None, None, None, true, size, None, None))
+                .collect();
+            let event = LayoutEvent::WindowsOnScreenUpdated(space, pid, windows, None);
+            let _ = engine.handle_event(event);
+        };
+        let mut engine = test_engine();
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        on_screen(&mut engine, &[w(1), w(2)]);
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(2)));
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::SetSplitDirection(Orientation::Vertical),
+        );
+        // Focus moving on doesn't change which window the hint splits.
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(1)));
+
+        on_screen(&mut engine, &[w(1), w(2), w(3)]);
+        assert_eq!(engine.pending_split(space), None);
+
+        let frames: HashMap<WindowId, CGRect> = engine
+            .calculate_layout(
+                space,
+                screen,
+                &GapSettings::default(),
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+            .into_iter()
+            .collect();
+        assert_eq!(frames[&w(1)].size.height, screen.size.height);
+        assert_eq!(frames[&w(2)].origin.x, frames[&w(3)].origin.x);
+        assert!(frames[&w(2)].max().y <= frames[&w(3)].origin.y);
+
+        // A hint whose window closes is dropped.
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(1)));
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::SetSplitDirection(Orientation::Horizontal),
+        );
+        assert_eq!(
+            engine.pending_split(space),
+            Some((w(1), Orientation::Horizontal))
+        );
+        let _ = engine.handle_event(LayoutEvent::WindowRemoved(w(1)));
+        assert_eq!(engine.pending_split(space), None);
+    }
+
+    #[test]
+    fn auto_balance_gives_an_added_window_an_equal_share() {
+        use crate::common::config::GapSettings;
+
+        let space = SpaceId::new(84);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(900.0, 600.0));
+        let settings = LayoutSettings {
+            auto_balance: true,
+            ..Default::default()
+        };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(840, 1)));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(840, 2)));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, WindowId::new(840, 1)));
+        engine.apply_pending_auto_balance();
+        for _ in 0..4 {
+            let _ = engine.handle_command(
+                Some(space),
+                &[space],
+                &HashMap::default(),
+                LayoutCommand::ResizeWindowGrow,
+            );
+        }
+
+        let widths = |engine: &mut LayoutEngine| -> Vec<f64> {
+            engine
+                .calculate_layout(
+                    space,
+                    screen,
+                    &GapSettings::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                )
+                .into_iter()
+                .map(|(_, frame)| frame.size.width)
+                .collect()
+        };
+        assert!(widths(&mut engine)[0] > 450.0);
+
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(840, 3)));
+        engine.apply_pending_auto_balance();
+        let widths = widths(&mut engine);
+        assert_eq!(widths.len(), 3);
+        for width in widths {
+            assert!((width - 300.0).abs() < 1.0, "expected equal thirds, got {width}");
+        }
+    }
+
+    #[test]
+    fn auto_balance_evens_out_a_layout_the_app_update_dropped_a_window_from() {
+        use crate::common::config::GapSettings;
+
+        let space = SpaceId::new(88);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(900.0, 600.0));
+        let pid: pid_t = 880;
+        let w = |idx| WindowId::new(pid, idx);
+        let size = CGSize::new(400.0, 400.0);
+        let on_screen = |engine: &mut LayoutEngine, windows: &[WindowId]| {
+            let windows = windows
+                .iter()
+                .map(|&wid| (wid, None, None, None, true, size, None, None))
+                .collect();
+            let event = LayoutEvent::WindowsOnScreenUpdated(space, pid, windows, None);
+            let _ = engine.handle_event(event);
+        };
+        let settings = LayoutSettings {
+            auto_balance: true,
+            ..Default::default()
+        };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        on_screen(&mut engine, &[w(1), w(2), w(3)]);
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(1)));
+        engine.apply_pending_auto_balance();
+        for _ in 0..4 {
+            let _ = engine.handle_command(
+                Some(space),
+                &[space],
+                &HashMap::default(),
+                LayoutCommand::ResizeWindowGrow,
+            );
+        }
+
+        // Once the window belongs to another workspace, the next update takes it out of
+        // this layout without a WindowRemoved.
+        let vwm = engine.virtual_workspace_manager_mut();
+        let other = vwm.list_workspaces(space)[1].0;
+        assert!(vwm.assign_window_to_workspace(space, w(3), other));
+        on_screen(&mut engine, &[w(1), w(2), w(3)]);
+        engine.apply_pending_auto_balance();
+
+        let widths: Vec<f64> = engine
+            .calculate_layout(
+                space,
+                screen,
+                &GapSettings::default(),
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+            .into_iter()
+            .map(|(_, frame)| frame.size.width)
+            .collect();
+        assert_eq!(widths.len(), 2);
+        for width in widths {
+            assert!((width - 450.0).abs() < 1.0, "expected equal halves, got {width}");
+        }
+    }
+
+    #[test]
+    fn frozen_layout_keeps_existing_ratios_when_windows_come_and_go() {
+        use crate::common::config::GapSettings;
+
+        let space = SpaceId::new(85);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(900.0, 600.0));
+        let settings = LayoutSettings {
+            auto_balance: true,
+            ..Default::default()
+        };
+        let w = |idx| WindowId::new(850, idx);
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, w(1)));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, w(2)));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(1)));
+        engine.apply_pending_auto_balance();
+        let command = |engine: &mut LayoutEngine, cmd| {
+            let _ = engine.handle_command(Some(space), &[space], &HashMap::default(), cmd);
+        };
+        for _ in 0..4 {
+            command(&mut engine, LayoutCommand::ResizeWindowGrow);
+        }
+        command(&mut engine, LayoutCommand::FreezeLayout);
+        assert!(engine.is_layout_frozen(space));
+
+        let widths = |engine: &mut LayoutEngine| -> HashMap<WindowId, f64> {
+            engine
+                .calculate_layout(
+                    space,
+                    screen,
+                    &GapSettings::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                )
+                .into_iter()
+                .map(|(wid, frame)| (wid, frame.size.width))
+                .collect()
+        };
+        let before = widths(&mut engine);
+        let ratio = before[&w(1)] / before[&w(2)];
+        assert!(ratio > 1.0);
+
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, w(3)));
+        engine.apply_pending_auto_balance();
+        let after = widths(&mut engine);
+        assert_eq!(after.len(), 3);
+        assert!((after[&w(1)] / after[&w(2)] - ratio).abs() < 0.01);
+        assert!((after[&w(3)] - 300.0).abs() < 1.0);
+
+        // Closing the new window hands its space to its neighbor only.
+        let _ = engine.handle_event(LayoutEvent::WindowRemoved(w(3)));
+        engine.apply_pending_auto_balance();
+        let closed = widths(&mut engine);
+        assert!((closed[&w(1)] - after[&w(1)]).abs() < 1.0);
+        assert!((closed[&w(2)] - (after[&w(2)] + after[&w(3)])).abs() < 1.0);
+
+        command(&mut engine, LayoutCommand::UnfreezeLayout);
+        assert!(!engine.is_layout_frozen(space));
+    }
+
+    #[test]
+    fn freeze_layout_is_limited_to_traditional_workspaces() {
+        let space = SpaceId::new(86);
+        let mut engine = test_engine();
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(900.0, 600.0)));
+        let command = |engine: &mut LayoutEngine, cmd| {
+            let _ = engine.handle_command(Some(space), &[space], &HashMap::default(), cmd);
+        };
+        let set_mode = |engine: &mut LayoutEngine, mode| {
+            let set_layout = LayoutCommand::SetWorkspaceLayout { workspace: None, mode };
+            let _ = engine.handle_virtual_workspace_command(space, &set_layout);
+        };
+
+        set_mode(&mut engine, LayoutMode::Bsp);
+        command(&mut engine, LayoutCommand::FreezeLayout);
+        assert!(!engine.is_layout_frozen(space));
+
+        set_mode(&mut engine, LayoutMode::Traditional);
+        command(&mut engine, LayoutCommand::FreezeLayout);
+        assert!(engine.is_layout_frozen(space));
+
+        // Leaving traditional drops the freeze rather than carrying it over.
+        set_mode(&mut engine, LayoutMode::MasterStack);
+        assert!(!engine.is_layout_frozen(space));
+    }
+
+    #[test]
+    fn move_window_to_workspace_places_window_at_target_index() {
+        let space = SpaceId::new(81);
+        let mut engine = test_engine();
+        let w = |idx| WindowId::new(810, idx);
+        let size = CGSize::new(400.0, 400.0);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            810,
+            (1..=4).map(|idx| (w(idx), None, None, None, true, size, None, None)).collect(),
+            None,
+        ));
+        for idx in [3, 4] {
+            assert!(engine.move_window_to_workspace_index(space, w(idx), 1, None));
+        }
+        let target = engine.virtual_workspace_manager.list_workspaces(space)[1].0;
+        let order = |engine: &LayoutEngine| {
+            let layout = engine.workspace_layouts.active(space, target).unwrap();
+            engine.workspace_tree(target).visible_windows_in_layout(layout)
+        };
+        assert_eq!(order(&engine), vec![w(3), w(4)]);
+
+        let move_to = |engine: &mut LayoutEngine, idx, target_index| {
+            let _ = engine.handle_virtual_workspace_command(
+                space,
+                &LayoutCommand::MoveWindowToWorkspace {
+                    workspace: 1,
+                    window_id: Some(idx),
+                    bundle_id: None,
+                    title: None,
+                    target_index: Some(target_index),
+                },
+            );
+        };
+        move_to(&mut engine, 2, 1);
+        assert_eq!(order(&engine), vec![w(3), w(2), w(4)]);
+
+        // Out-of-range indices land in the last slot.
+        move_to(&mut engine, 1, 99);
+        assert_eq!(order(&engine), vec![w(3), w(2), w(4), w(1)]);
+    }
+
+    #[test]
+    fn sticky_window_follows_workspace_switches() {
+        let space = SpaceId::new(82);
+        let mut engine = test_engine();
+        let sticky = WindowId::new(820, 1);
+        let other = WindowId::new(820, 2);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        for wid in [sticky, other] {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, wid));
+        }
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, sticky));
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::ToggleSticky,
+        );
+        assert!(engine.is_window_sticky(sticky));
+
+        let workspaces: Vec<_> = engine
+            .virtual_workspace_manager
+            .list_workspaces(space)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        let workspace_of = |engine: &LayoutEngine, wid| {
+            engine.virtual_workspace_manager.workspace_for_window(space, wid)
+        };
+        let next = LayoutCommand::NextWorkspace(Some(false));
+        let _ = engine.handle_virtual_workspace_command(space, &next);
+        assert_eq!(workspace_of(&engine, sticky), Some(workspaces[1]));
+        assert_eq!(workspace_of(&engine, other), Some(workspaces[0]));
+        let _ =
+            engine.handle_virtual_workspace_command(space, &LayoutCommand::SwitchToWorkspace(2));
+        assert_eq!(workspace_of(&engine, sticky), Some(workspaces[2]));
+        assert_eq!(engine.windows_in_active_workspace(space), vec![sticky]);
+
+        let _ = engine.handle_event(LayoutEvent::WindowRemoved(sticky));
+        assert!(!engine.is_window_sticky(sticky));
+    }
+
+    #[test]
+    fn oversized_floating_window_follows_policy() {
+        use crate::common::config::GapSettings;
+
+        let space = SpaceId::new(83);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1200.0, 800.0));
+        let oversized = CGRect::new(CGPoint::new(-100.0, -50.0), CGSize::new(1600.0, 1000.0));
+        let wid = WindowId::new(830, 1);
+
+        let frame_under = |policy| {
+            let settings = LayoutSettings {
+                oversized_windows: policy,
+                ..Default::default()
+            };
+            let mut engine =
+                LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+            let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+            let min_size = Some(CGSize::new(1300.0, 600.0));
+            let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+                space,
+                830,
+                vec![(wid, None, None, None, true, oversized.size, min_size, None)],
+                None,
+            ));
+            let _ = engine.handle_event(LayoutEvent::WindowFocused(space, wid));
+            let _ = engine.handle_command(
+                Some(space),
+                &[space],
+                &HashMap::default(),
+                LayoutCommand::ToggleWindowFloating,
+            );
+            engine.store_floating_window_positions(space, &[(wid, oversized)]);
+            let positions = engine.calculate_layout_with_virtual_workspaces(
+                space,
+                screen,
+                &GapSettings::default(),
+                0.0,
+                Default::default(),
+                Default::default(),
+                |_| None,
+                &[screen],
+            );
+            positions.into_iter().find(|(w, _)| *w == wid).unwrap().1
+        };
+
+        assert_eq!(frame_under(OversizedWindowPolicy::Ignore), oversized);
+        // The minimum width keeps it wider than the display, so it is centered on that axis.
+        assert_eq!(
+            frame_under(OversizedWindowPolicy::ShrinkToFit),
+            CGRect::new(CGPoint::new(-50.0, 0.0), CGSize::new(1300.0, 800.0))
+        );
+        assert_eq!(
+            frame_under(OversizedWindowPolicy::Center),
+            CGRect::new(CGPoint::new(-200.0, -100.0), oversized.size)
+        );
+    }
+
+    #[test]
+    fn grow_to_fill_takes_space_left_by_closed_neighbor() {
+        use crate::common::config::GapSettings;
+
+        let space = SpaceId::new(84);
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let (a, b) = (WindowId::new(840, 1), WindowId::new(840, 2));
+        let mut engine = test_engine();
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, screen.size));
+        let _ =
+            engine.handle_virtual_workspace_command(space, &LayoutCommand::SetWorkspaceLayout {
+                workspace: None,
+                mode: LayoutMode::Scrolling,
+            });
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, a));
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, b));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, a));
+        let layout = |engine: &mut LayoutEngine| -> HashMap<WindowId, CGRect> {
+            engine
+                .calculate_layout(
+                    space,
+                    screen,
+                    &GapSettings::default(),
+                    0.0,
+                    Default::default(),
+                    Default::default(),
+                )
+                .into_iter()
+                .collect()
+        };
+        let grow = |engine: &mut LayoutEngine, direction: Direction| {
+            engine.grow_to_fill(
+                space,
+                screen,
+                direction,
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+        };
+
+        // The next column sits right against the focused one.
+        assert!(!grow(&mut engine, Direction::Right));
+
+        // Closing it leaves the rest of the screen empty.
+        let _ = engine.handle_event(LayoutEvent::WindowRemoved(b));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, a));
+        let before = layout(&mut engine)[&a];
+        assert!(before.max().x < screen.max().x - 100.0);
+
+        assert!(!grow(&mut engine, Direction::Left));
+        assert!(grow(&mut engine, Direction::Right));
+        let grown = layout(&mut engine)[&a];
+        assert_eq!(grown.origin.x, before.origin.x);
+        assert!(grown.max().x > before.max().x + 100.0);
+    }
+
+    /// Tiles three windows, focuses the middle one, then adds a window from another
+    /// app with `insert`. Returns the layout order before and after the insertion.
+    fn insert_new_window(
+        mode: LayoutMode,
+        insert: NewWindowInsert,
+    ) -> (Vec<WindowId>, Vec<WindowId>) {
+        let space = SpaceId::new(80);
+        let mut settings = LayoutSettings { mode, ..Default::default() };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let size = CGSize::new(400.0, 400.0);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            800,
+            (1..=3)
+                .map(|idx| (WindowId::new(800, idx), None, None, None, true, size, None, None))
+                .collect(),
+            None,
+        ));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, WindowId::new(800, 2)));
+
+        let order = |engine: &LayoutEngine| {
+            let (ws_id, layout) = engine.workspace_and_layout(space).unwrap();
+            engine.workspace_tree(ws_id).visible_windows_in_layout(layout)
+        };
+        let before = order(&engine);
+
+        settings.new_window_insert = insert;
+        engine.set_layout_settings(&settings);
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(801, 1)));
+        (before, order(&engine))
+    }
+
+    #[test]
+    fn new_window_insert_after_focused() {
+        let new = WindowId::new(801, 1);
+        let (before, after) =
+            insert_new_window(LayoutMode::Traditional, NewWindowInsert::AfterFocused);
+        let focused = before.iter().position(|&w| w == WindowId::new(800, 2)).unwrap();
+        assert_eq!(after.iter().position(|&w| w == new), Some(focused + 1));
+    }
+
+    #[test]
+    fn new_window_insert_end() {
+        let (mut before, after) = insert_new_window(LayoutMode::Traditional, NewWindowInsert::End);
+        before.push(WindowId::new(801, 1));
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn new_window_insert_beginning() {
+        let (mut before, after) =
+            insert_new_window(LayoutMode::Traditional, NewWindowInsert::Beginning);
+        before.insert(0, WindowId::new(801, 1));
+        assert_eq!(after, before);
+
+        // master_stack keeps the master and puts the window at the top of the stack.
+        let (mut before, after) =
+            insert_new_window(LayoutMode::MasterStack, NewWindowInsert::Beginning);
+        before.insert(1, WindowId::new(801, 1));
+        assert_eq!(after, before);
+    }
+
+    fn swap_with_master(mode: LayoutMode) -> (Vec<WindowId>, Vec<WindowId>) {
+        let space = SpaceId::new(81);
+        let settings = LayoutSettings { mode, ..Default::default() };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let size = CGSize::new(400.0, 400.0);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            810,
+            (1..=3)
+                .map(|idx| (WindowId::new(810, idx), None, None, None, true, size, None, None))
+                .collect(),
+            None,
+        ));
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, WindowId::new(810, 3)));
+
+        let order = |engine: &LayoutEngine| {
+            let (ws_id, layout) = engine.workspace_and_layout(space).unwrap();
+            engine.workspace_tree(ws_id).visible_windows_in_layout(layout)
+        };
+        let before = order(&engine);
+        let _ = engine.handle_command(
+            Some(space),
+            &[space],
+            &HashMap::default(),
+            LayoutCommand::SwapWithMaster,
+        );
+        (before, order(&engine))
+    }
+
+    #[test]
+    fn swap_with_master_exchanges_focused_and_master() {
+        let focused = WindowId::new(810, 3);
+        for mode in [LayoutMode::MasterStack, LayoutMode::Traditional] {
+            let (before, after) = swap_with_master(mode);
+            let master = before[0];
+            let focused_idx = before.iter().position(|&w| w == focused).unwrap();
+            assert_ne!(master, focused);
+            assert_eq!(after[0], focused, "{mode:?}");
+            assert_eq!(after[focused_idx], master, "{mode:?}");
+        }
+    }
+
+    /// Tiles three windows in master_stack, then adds one more with `placement`.
+    /// Returns the layout order before and after.
+    fn add_master_stack_window(
+        placement: MasterStackNewWindowPlacement,
+    ) -> (Vec<WindowId>, Vec<WindowId>) {
+        let space = SpaceId::new(82);
+        let mut settings = LayoutSettings {
+            mode: LayoutMode::MasterStack,
+            ..Default::default()
+        };
+        settings.master_stack.new_window_placement = placement;
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let size = CGSize::new(400.0, 400.0);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        let _ = engine.handle_event(LayoutEvent::WindowsOnScreenUpdated(
+            space,
+            820,
+            (1..=3)
+                .map(|idx| (WindowId::new(820, idx), None, None, None, true, size, None, None))
+                .collect(),
+            None,
+        ));
+
+        let order = |engine: &LayoutEngine| {
+            let (ws_id, layout) = engine.workspace_and_layout(space).unwrap();
+            engine.workspace_tree(ws_id).visible_windows_in_layout(layout)
+        };
+        let before = order(&engine);
+        let _ = engine.handle_event(LayoutEvent::WindowAdded(space, WindowId::new(821, 1)));
+        (before, order(&engine))
+    }
+
+    #[test]
+    fn master_stack_new_window_placement_decides_the_master() {
+        let new = WindowId::new(821, 1);
+
+        // The new window takes over and the old master heads the stack.
+        let (before, after) = add_master_stack_window(MasterStackNewWindowPlacement::Master);
+        assert_eq!(after[0], new);
+        assert_eq!(after[1], before[0]);
+        assert_eq!(after.len(), before.len() + 1);
+
+        // The master stays put and the new window heads the stack.
+        let (before, after) = add_master_stack_window(MasterStackNewWindowPlacement::Stack);
+        assert_eq!(after[0], before[0]);
+        assert_eq!(after[1], new);
+        assert_eq!(after.len(), before.len() + 1);
+    }
+
+    #[test]
+    fn new_window_insert_as_master() {
+        let (mut before, after) =
+            insert_new_window(LayoutMode::MasterStack, NewWindowInsert::AsMaster);
+        before.insert(0, WindowId::new(801, 1));
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn workspace_insert_position_override_takes_precedence_over_global() {
+        let space = SpaceId::new(86);
+        let settings = LayoutSettings {
+            new_window_insert: NewWindowInsert::End,
+            ..Default::default()
+        };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let w = |idx| WindowId::new(860, idx);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        for idx in 1..=2 {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, w(idx)));
+        }
+        let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(1)));
+        let set_insert = |engine: &mut LayoutEngine, insert| {
+            let _ = engine.handle_command(
+                Some(space),
+                &[space],
+                &HashMap::default(),
+                LayoutCommand::SetWorkspaceInsertPosition { insert },
+            );
+        };
+        let add = |engine: &mut LayoutEngine, idx| {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, w(idx)));
+            let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(1)));
+            let (ws_id, layout) = engine.workspace_and_layout(space).unwrap();
+            engine.workspace_tree(ws_id).visible_windows_in_layout(layout)
+        };
+
+        set_insert(&mut engine, Some(NewWindowInsert::Beginning));
+        assert_eq!(add(&mut engine, 3), vec![w(3), w(1), w(2)]);
+
+        set_insert(&mut engine, None);
+        assert_eq!(add(&mut engine, 4), vec![w(3), w(1), w(2), w(4)]);
+    }
+
+    #[test]
+    fn workspace_focus_wrap_override_takes_precedence_over_global() {
+        let space = SpaceId::new(87);
+        let mut settings = LayoutSettings {
+            focus_cycle_wrap: false,
+            ..Default::default()
+        };
+        let mut engine = LayoutEngine::new(&VirtualWorkspaceSettings::default(), &settings, None);
+        let w = |idx| WindowId::new(870, idx);
+        let _ = engine.handle_event(LayoutEvent::SpaceExposed(space, CGSize::new(1920.0, 1080.0)));
+        for idx in 1..=3 {
+            let _ = engine.handle_event(LayoutEvent::WindowAdded(space, w(idx)));
+        }
+        let command = |engine: &mut LayoutEngine, cmd| {
+            engine.handle_command(Some(space), &[space], &HashMap::default(), cmd)
+        };
+        let set_wrap = |engine: &mut LayoutEngine, wrap| {
+            let _ = command(engine, LayoutCommand::SetWorkspaceFocusWrap { wrap });
+        };
+        let next_from_last = |engine: &mut LayoutEngine| {
+            let _ = engine.handle_event(LayoutEvent::WindowFocused(space, w(3)));
+            command(engine, LayoutCommand::NextWindow).focus_window
+        };
+
+        assert_eq!(next_from_last(&mut engine), None);
+        set_wrap(&mut engine, Some(true));
+        assert!(engine.focus_wraps(space));
+        assert_eq!(next_from_last(&mut engine), Some(w(1)));
+
+        settings.focus_cycle_wrap = true;
+        engine.set_layout_settings(&settings);
+        set_wrap(&mut engine, Some(false));
+        assert_eq!(next_from_last(&mut engine), None);
+        set_wrap(&mut engine, None);
+        assert_eq!(next_from_last(&mut engine), Some(w(1)));
+    }
 }
@@ -10,6 +10,9 @@ pub(crate) struct FloatingManager {
     #[serde(skip)]
     active_floating_windows: HashMap<SpaceId, HashMap<pid_t, HashSet<WindowId>>>,
     last_floating_focus: Option<WindowId>,
+    /// Floating windows ordered bottom to top by when they were last focused.
+    #[serde(skip)]
+    stacking_order: Vec<WindowId>,
 }
 
 impl FloatingManager {
@@ -26,6 +29,7 @@ impl FloatingManager {
     pub(crate) fn remove_floating(&mut self, window_id: WindowId) {
         self.floating_windows.remove(&window_id);
         self.remove_active_entries(window_id);
+        self.stacking_order.retain(|&wid| wid != window_id);
         if self.last_floating_focus == Some(window_id) {
             self.last_floating_focus = None;
         }
@@ -70,12 +74,23 @@ impl FloatingManager {
 
     pub(crate) fn set_last_focus(&mut self, wid: Option<WindowId>) {
         self.last_floating_focus = wid;
+        if let Some(wid) = wid.filter(|&wid| self.is_floating(wid)) {
+            self.stacking_order.retain(|&other| other != wid);
+            self.stacking_order.push(wid);
+        }
+    }
+
+    /// Sorts `windows` bottom to top. Windows that have never been focused keep
+    /// their relative order and go below the ones that have.
+    pub(crate) fn sort_by_stacking_order(&self, windows: &mut [WindowId]) {
+        windows.sort_by_key(|wid| self.stacking_order.iter().position(|other| other == wid));
     }
 
     pub(crate) fn last_focus(&self) -> Option<WindowId> { self.last_floating_focus }
 
     pub(crate) fn remove_all_for_pid(&mut self, pid: pid_t) {
         let _ = self.floating_windows.remove_all_for_pid(pid);
+        self.stacking_order.retain(|wid| wid.pid != pid);
 
         for space_map in self.active_floating_windows.values_mut() {
             space_map.remove(&pid);
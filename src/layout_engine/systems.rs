@@ -4,11 +4,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::actor::app::{WindowId, pid_t};
 use crate::common::collections::HashMap;
+use crate::common::config::WindowConstraintOverrides;
 use crate::layout_engine::{Direction, LayoutKind};
 
 slotmap::new_key_type! { pub struct LayoutId; }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct WindowLayoutConstraints {
     pub is_resizable: bool,
     pub locked_width: f64,
@@ -43,6 +44,18 @@ impl WindowLayoutConstraints {
         }
     }
 
+    /// Replaces the limits an app rule sets, keeping the rest.
+    pub fn with_overrides(self, overrides: &WindowConstraintOverrides) -> Self {
+        Self {
+            min_width: overrides.min_width.unwrap_or(self.min_width),
+            min_height: overrides.min_height.unwrap_or(self.min_height),
+            max_width: overrides.max_width.unwrap_or(self.max_width),
+            max_height: overrides.max_height.unwrap_or(self.max_height),
+            ..self
+        }
+        .normalized()
+    }
+
     pub fn min_for_axis(self, horizontal: bool) -> f64 {
         if horizontal {
             self.min_width
@@ -164,6 +177,7 @@ pub trait LayoutSystem: Serialize + for<'de> Deserialize<'de> {
     fn unjoin_selection(&mut self, _layout: LayoutId);
     fn resize_selection_by(&mut self, layout: LayoutId, amount: f64);
     fn rebalance(&mut self, layout: LayoutId);
+    fn balance(&mut self, layout: LayoutId);
     fn toggle_tile_orientation(&mut self, layout: LayoutId);
 }
 
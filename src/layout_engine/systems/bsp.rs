@@ -266,6 +266,21 @@ impl BspLayoutSystem {
         }
     }
 
+    /// Weight each split by the leaves on either side so every leaf gets the same
+    /// share of the area. Returns the number of leaves under `node`.
+    fn balance_node(&mut self, node: NodeId) -> usize {
+        let children: Vec<_> = node.children(&self.tree.map).collect();
+        let (Some(&first), Some(&second)) = (children.first(), children.get(1)) else {
+            return 1;
+        };
+        let first_leaves = self.balance_node(first);
+        let second_leaves = self.balance_node(second);
+        if let Some(NodeKind::Split { ratio, .. }) = self.kind.get_mut(node) {
+            *ratio = first_leaves as f32 / (first_leaves + second_leaves) as f32;
+        }
+        first_leaves + second_leaves
+    }
+
     fn collect_windows_under(&self, node: NodeId, out: &mut Vec<WindowId>) {
         match self.kind.get(node) {
             Some(NodeKind::Leaf { window, .. }) => {
@@ -1526,6 +1541,12 @@ impl LayoutSystem for BspLayoutSystem {
 
     fn rebalance(&mut self, _layout: LayoutId) {}
 
+    fn balance(&mut self, layout: LayoutId) {
+        if let Some(state) = self.layouts.get(layout) {
+            self.balance_node(state.root);
+        }
+    }
+
     fn toggle_tile_orientation(&mut self, layout: LayoutId) {
         let sel_snapshot = self.selection_of_layout(layout);
 
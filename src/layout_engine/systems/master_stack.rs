@@ -387,6 +387,30 @@ impl MasterStackLayoutSystem {
         }
     }
 
+    /// Swap the selected window with the first master window, or with the top
+    /// of the stack when the master itself is selected. Selection follows the
+    /// selected window.
+    pub fn swap_with_master(&mut self, layout: LayoutId) {
+        let (_root, master, stack) = self.ensure_structure(layout);
+        let Some(selected) = self.inner.selected_window(layout) else {
+            return;
+        };
+        let Some(master_wid) = self.windows_in_container(master).first().copied() else {
+            return;
+        };
+        let other = if master_wid == selected {
+            match self.windows_in_container(stack).first().copied() {
+                Some(wid) => wid,
+                None => return,
+            }
+        } else {
+            master_wid
+        };
+        if self.inner.swap_windows(layout, selected, other) {
+            let _ = self.inner.select_window(layout, selected);
+        }
+    }
+
     pub(crate) fn collect_group_containers_in_selection_path(
         &self,
         layout: LayoutId,
@@ -760,5 +784,10 @@ impl LayoutSystem for MasterStackLayoutSystem {
 
     fn rebalance(&mut self, layout: LayoutId) { self.normalize_layout(layout); }
 
+    fn balance(&mut self, layout: LayoutId) {
+        self.inner.balance(layout);
+        self.normalize_layout(layout);
+    }
+
     fn toggle_tile_orientation(&mut self, layout: LayoutId) { self.normalize_layout(layout); }
 }
@@ -447,6 +447,66 @@ impl ScrollingLayoutSystem {
         state.scroll_offset_px.store(next.to_bits(), Ordering::Relaxed);
     }
 
+    /// Offset range and page size for page navigation. The upper bound is the offset at
+    /// which the end of the last column lines up with the right edge of the viewport.
+    fn page_offset_bounds(&self, state: &LayoutState) -> Option<(f64, f64, f64)> {
+        let screen_width = f64::from_bits(state.last_screen_width.load(Ordering::Relaxed));
+        let gap_x = f64::from_bits(state.last_gap_x.load(Ordering::Relaxed));
+        if screen_width <= 0.0 {
+            return None;
+        }
+        let (widths, starts) = Self::column_widths_and_starts(
+            state,
+            screen_width,
+            gap_x,
+            self.settings.min_column_width_ratio,
+            self.settings.max_column_width_ratio,
+        );
+        let (Some(last_start), Some(last_width)) = (starts.last(), widths.last()) else {
+            return None;
+        };
+        let baseline = if state.center_override_window.is_some() {
+            f64::from_bits(state.last_center_offset_delta_px.load(Ordering::Relaxed))
+        } else {
+            0.0
+        };
+        let last_page = (last_start + last_width - screen_width).clamp(0.0, *last_start);
+        Some((baseline, baseline + last_page, screen_width + gap_x))
+    }
+
+    /// Scroll the strip by whole viewport widths (positive pages scroll right).
+    pub fn scroll_by_pages(&mut self, layout: LayoutId, pages: f64) {
+        let Some(state) = self.layout_state(layout) else {
+            return;
+        };
+        let Some((min_offset, max_offset, page)) = self.page_offset_bounds(state) else {
+            return;
+        };
+        let current = f64::from_bits(state.scroll_offset_px.load(Ordering::Relaxed));
+        // Never page backwards when the strip is already scrolled past the last full page.
+        let (min_offset, max_offset) = if pages > 0.0 {
+            (min_offset, max_offset.max(current))
+        } else {
+            (min_offset.min(current), max_offset)
+        };
+        let next = (current + pages * page).clamp(min_offset, max_offset);
+        state.scroll_offset_px.store(next.to_bits(), Ordering::Relaxed);
+        state.overscroll_accumulation.store(0.0f64.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Scroll the strip to its first column, or to the last full page when `to_end` is set.
+    pub fn scroll_to_edge(&mut self, layout: LayoutId, to_end: bool) {
+        let Some(state) = self.layout_state(layout) else {
+            return;
+        };
+        let Some((min_offset, max_offset, _)) = self.page_offset_bounds(state) else {
+            return;
+        };
+        let next = if to_end { max_offset } else { min_offset };
+        state.scroll_offset_px.store(next.to_bits(), Ordering::Relaxed);
+        state.overscroll_accumulation.store(0.0f64.to_bits(), Ordering::Relaxed);
+    }
+
     pub fn center_selected_column(&mut self, layout: LayoutId) {
         let Some(state) = self.layout_state_mut(layout) else {
             return;
@@ -1334,6 +1394,14 @@ impl LayoutSystem for ScrollingLayoutSystem {
 
     fn rebalance(&mut self, _layout: LayoutId) {}
 
+    fn balance(&mut self, layout: LayoutId) {
+        if let Some(state) = self.layout_state_mut(layout) {
+            for column in &mut state.columns {
+                column.width_offset = 0.0;
+            }
+        }
+    }
+
     fn toggle_tile_orientation(&mut self, _layout: LayoutId) {}
 }
 
@@ -1412,6 +1480,37 @@ mod tests {
         (system, layout, w1, w2)
     }
 
+    #[test]
+    fn paging_past_the_end_clamps_to_last_full_page() {
+        let mut system = ScrollingLayoutSystem::new(&ScrollingLayoutSettings::default());
+        let layout = system.create_layout();
+        let windows: Vec<WindowId> = (1..=4).map(|idx| wid(30, idx)).collect();
+        for window in &windows {
+            system.add_window_after_selection(layout, *window);
+        }
+        let screen = screen(1000.0, 800.0);
+        let gaps = GapSettings::default();
+        let _ = render(&system, layout, screen, &gaps);
+
+        system.scroll_to_edge(layout, false);
+        assert_eq!(scroll_offset(&system, layout), 0.0);
+
+        system.scroll_by_pages(layout, 1.0);
+        assert_eq!(scroll_offset(&system, layout), 1000.0);
+        system.scroll_by_pages(layout, 1.0);
+        system.scroll_by_pages(layout, 1.0);
+        assert_eq!(scroll_offset(&system, layout), 1800.0);
+
+        let frames = render(&system, layout, screen, &gaps);
+        let last = frame_for(&frames, windows[3]);
+        assert_eq!(last.origin.x + last.size.width, screen.size.width);
+
+        system.scroll_by_pages(layout, -5.0);
+        assert_eq!(scroll_offset(&system, layout), 0.0);
+        system.scroll_to_edge(layout, true);
+        assert_eq!(scroll_offset(&system, layout), 1800.0);
+    }
+
     #[test]
     fn respects_min_width_and_min_height_independently() {
         let mut system = ScrollingLayoutSystem::new(&ScrollingLayoutSettings::default());
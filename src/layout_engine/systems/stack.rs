@@ -336,6 +336,8 @@ impl LayoutSystem for StackLayoutSystem {
 
     fn rebalance(&mut self, _layout: LayoutId) {}
 
+    fn balance(&mut self, _layout: LayoutId) {}
+
     fn toggle_tile_orientation(&mut self, layout: LayoutId) {
         self.toggle_root_stack_orientation(layout);
     }
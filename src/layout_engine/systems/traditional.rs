@@ -1002,6 +1002,11 @@ impl LayoutSystem for TraditionalLayoutSystem {
         self.rebalance_node(root)
     }
 
+    fn balance(&mut self, layout: LayoutId) {
+        let root = self.root(layout);
+        self.balance_node(root);
+    }
+
     fn swap_windows(&mut self, layout: LayoutId, a: WindowId, b: WindowId) -> bool {
         let node_a = match self.tree.data.window.node_for(layout, a) {
             Some(n) => n,
@@ -1136,7 +1141,103 @@ impl TraditionalLayoutSystem {
             total_count: children.len(),
             selected_index: ui_selected_index,
             window_ids,
+            is_collapsed: self.tree.data.layout.is_collapsed(node),
+        }
+    }
+
+    /// Collapses the stacked container holding the selection to its title strip, or
+    /// expands it if it is already collapsed. Returns false when the selection is not
+    /// inside a stack.
+    pub(crate) fn toggle_collapse_of_selected_stack(&mut self, layout: LayoutId) -> bool {
+        let selection = self.selection(layout);
+        let Some(container) =
+            selection.ancestors(self.map()).find(|&node| self.layout(node).is_stacked())
+        else {
+            return false;
+        };
+        self.tree.data.layout.toggle_collapsed(container);
+        true
+    }
+
+    /// Moves the selection into the neighboring container in `direction`, making it
+    /// a child of that container instead of swapping places with it. Returns false
+    /// when the neighbor in that direction is a window or there is none.
+    pub(crate) fn move_selection_into_container(
+        &mut self,
+        layout: LayoutId,
+        direction: Direction,
+    ) -> bool {
+        let selection = self.selection(layout);
+        let map = self.map();
+        let Some(target) =
+            selection.ancestors(map).find_map(|node| self.move_over(node, direction))
+        else {
+            return false;
+        };
+        if self.window_at(target).is_some() {
+            return false;
         }
+        match direction {
+            Direction::Right | Direction::Down => match target.first_child(map) {
+                Some(first) => selection.detach(&mut self.tree).insert_before(first),
+                None => selection.detach(&mut self.tree).push_back(target),
+            },
+            Direction::Left | Direction::Up => selection.detach(&mut self.tree).push_back(target),
+        };
+        self.select(selection);
+        true
+    }
+
+    /// Rearranges the children of the container holding the selection into a grid
+    /// of `rows` horizontal rows with up to `cols` cells each, filled in order.
+    /// Children past `rows * cols` are stacked together with the last one in the
+    /// final cell. Returns false when there is nothing to arrange.
+    pub(crate) fn arrange_selection_container_in_grid(
+        &mut self,
+        layout: LayoutId,
+        rows: usize,
+        cols: usize,
+    ) -> bool {
+        let selection = self.selection(layout);
+        let container = selection.parent(self.map()).unwrap_or(selection);
+        let children: Vec<_> = container.children(self.map()).collect();
+        if rows == 0 || cols == 0 || children.len() < 2 {
+            return false;
+        }
+
+        let cell_count = rows.saturating_mul(cols).min(children.len());
+        let (singles, last) = children.split_at(cell_count - 1);
+        let mut cells: Vec<&[NodeId]> = singles.chunks(1).collect();
+        cells.push(last);
+
+        self.set_layout(container, LayoutKind::Vertical);
+        for row_cells in cells.chunks(cols) {
+            let row = if row_cells.len() == 1 {
+                container
+            } else {
+                let row = self.tree.mk_node().push_back(container);
+                self.set_layout(row, LayoutKind::Horizontal);
+                row
+            };
+            for cell in row_cells {
+                let parent = if cell.len() == 1 {
+                    row
+                } else {
+                    let perpendicular = match self.layout(row).orientation() {
+                        Orientation::Horizontal => Orientation::Vertical,
+                        Orientation::Vertical => Orientation::Horizontal,
+                    };
+                    let stack = self.tree.mk_node().push_back(row);
+                    self.set_layout(stack, LayoutKind::stack_with_offset(perpendicular));
+                    stack
+                };
+                for &node in *cell {
+                    node.detach(&mut self.tree).push_back(parent);
+                }
+            }
+        }
+        self.select(selection);
+        true
     }
 
     pub(crate) fn collect_group_containers_in_selection_path(
@@ -1155,6 +1256,7 @@ impl TraditionalLayoutSystem {
         let map = &self.tree.map;
 
         let tiling_area = compute_tiling_area(screen, gaps);
+        let collapsed_strip = collapsed_strip_len(stack_offset, stack_line_thickness);
 
         let mut node = self.root(layout);
         let mut rect = tiling_area;
@@ -1183,6 +1285,10 @@ impl TraditionalLayoutSystem {
                 }
 
                 let is_horizontal = matches!(kind, HorizontalStack);
+                let is_collapsed = self.tree.data.layout.is_collapsed(node);
+                if is_collapsed {
+                    rect = collapsed_strip_rect(rect, is_horizontal, collapsed_strip);
+                }
                 out.push(self.stack_group_container_info(
                     node,
                     kind,
@@ -1191,16 +1297,18 @@ impl TraditionalLayoutSystem {
                     selected_index,
                 ));
 
-                let layout_res = stack_layout_result(
-                    rect,
-                    children.len(),
-                    stack_offset,
-                    is_horizontal,
-                    stack_line_thickness,
-                    stack_line_horiz,
-                    stack_line_vert,
-                );
-                rect = layout_res.get_focused_frame_for_index(selected_index, selected_index);
+                if !is_collapsed {
+                    let layout_res = stack_layout_result(
+                        rect,
+                        children.len(),
+                        stack_offset,
+                        is_horizontal,
+                        stack_line_thickness,
+                        stack_line_horiz,
+                        stack_line_vert,
+                    );
+                    rect = layout_res.get_focused_frame_for_index(selected_index, selected_index);
+                }
 
                 node = local_sel;
                 continue;
@@ -1213,7 +1321,13 @@ impl TraditionalLayoutSystem {
                 .local_selection(map, node)
                 .or_else(|| node.first_child(map))
             {
-                rect = self.calculate_child_frame_in_container(node, next, rect, gaps);
+                rect = self.calculate_child_frame_in_container(
+                    node,
+                    next,
+                    rect,
+                    gaps,
+                    collapsed_strip,
+                );
                 node = next;
                 continue;
             }
@@ -1239,6 +1353,7 @@ impl TraditionalLayoutSystem {
         let map = &self.tree.map;
 
         let tiling_area = compute_tiling_area(screen, gaps);
+        let collapsed_strip = collapsed_strip_len(stack_offset, stack_line_thickness);
 
         let mut stack: Vec<(NodeId, CGRect)> = vec![(self.root(layout), tiling_area)];
 
@@ -1260,6 +1375,21 @@ impl TraditionalLayoutSystem {
                 let selected_index = children.iter().position(|&c| c == local_sel).unwrap_or(0);
 
                 let is_horizontal = matches!(kind, HorizontalStack);
+                if self.tree.data.layout.is_collapsed(node) {
+                    let strip = collapsed_strip_rect(rect, is_horizontal, collapsed_strip);
+                    out.push(self.stack_group_container_info(
+                        node,
+                        kind,
+                        strip,
+                        &children,
+                        selected_index,
+                    ));
+                    for &child in children.iter().rev() {
+                        stack.push((child, strip));
+                    }
+                    continue;
+                }
+
                 out.push(self.stack_group_container_info(
                     node,
                     kind,
@@ -1291,8 +1421,13 @@ impl TraditionalLayoutSystem {
 
             if !children.is_empty() {
                 for &child in children.iter().rev() {
-                    let child_rect =
-                        self.calculate_child_frame_in_container(node, child, rect, gaps);
+                    let child_rect = self.calculate_child_frame_in_container(
+                        node,
+                        child,
+                        rect,
+                        gaps,
+                        collapsed_strip,
+                    );
                     stack.push((child, child_rect));
                 }
             }
@@ -1308,6 +1443,7 @@ impl TraditionalLayoutSystem {
         child_index: usize,
         horizontal: bool,
         gaps: &crate::common::config::GapSettings,
+        collapsed_strip: f64,
     ) -> CGRect {
         use objc2_core_foundation::{CGPoint, CGSize};
 
@@ -1315,7 +1451,14 @@ impl TraditionalLayoutSystem {
             return parent_rect;
         }
 
-        let total: f32 = siblings.iter().map(|&child| self.tree.data.layout.info[child].size).sum();
+        let layout = &self.tree.data.layout;
+        let is_collapsed = |child: NodeId| layout.collapses_along(child, horizontal);
+        let collapsed_count = siblings.iter().filter(|&&child| is_collapsed(child)).count();
+        let total: f32 = siblings
+            .iter()
+            .filter(|&&child| !is_collapsed(child))
+            .map(|&child| layout.info[child].size)
+            .sum();
         let inner_gap = if horizontal {
             gaps.inner.horizontal
         } else {
@@ -1334,6 +1477,14 @@ impl TraditionalLayoutSystem {
         } else {
             (axis_len - total_gap).max(0.0)
         };
+        let shared_axis = (usable_axis - collapsed_strip * collapsed_count as f64).max(0.0);
+        let seg_len_of = |child: NodeId| {
+            if is_collapsed(child) {
+                collapsed_strip
+            } else {
+                shared_axis * f64::from(layout.info[child].size) / f64::from(total)
+            }
+        };
 
         let mut offset = if horizontal {
             parent_rect.origin.x
@@ -1342,17 +1493,13 @@ impl TraditionalLayoutSystem {
         };
 
         for i in 0..child_index {
-            let ratio = f64::from(self.tree.data.layout.info[siblings[i]].size) / f64::from(total);
-            let seg_len = usable_axis * ratio;
-            offset += seg_len;
+            offset += seg_len_of(siblings[i]);
             if i < siblings.len() - 1 {
                 offset += inner_gap;
             }
         }
 
-        let ratio =
-            f64::from(self.tree.data.layout.info[siblings[child_index]].size) / f64::from(total);
-        let seg_len = usable_axis * ratio;
+        let seg_len = seg_len_of(siblings[child_index]);
 
         if horizontal {
             CGRect::new(
@@ -1373,6 +1520,7 @@ impl TraditionalLayoutSystem {
         child_node: NodeId,
         parent_rect: CGRect,
         gaps: &crate::common::config::GapSettings,
+        collapsed_strip: f64,
     ) -> CGRect {
         let parent_kind = self.tree.data.layout.kind(parent_node);
         let map = &self.tree.map;
@@ -1380,12 +1528,22 @@ impl TraditionalLayoutSystem {
         let child_index = siblings.iter().position(|&n| n == child_node).unwrap_or(0);
 
         match parent_kind {
-            crate::layout_engine::LayoutKind::Horizontal => {
-                self.calculate_child_frame_in_axis(parent_rect, &siblings, child_index, true, gaps)
-            }
-            crate::layout_engine::LayoutKind::Vertical => {
-                self.calculate_child_frame_in_axis(parent_rect, &siblings, child_index, false, gaps)
-            }
+            crate::layout_engine::LayoutKind::Horizontal => self.calculate_child_frame_in_axis(
+                parent_rect,
+                &siblings,
+                child_index,
+                true,
+                gaps,
+                collapsed_strip,
+            ),
+            crate::layout_engine::LayoutKind::Vertical => self.calculate_child_frame_in_axis(
+                parent_rect,
+                &siblings,
+                child_index,
+                false,
+                gaps,
+                collapsed_strip,
+            ),
             crate::layout_engine::LayoutKind::HorizontalStack
             | crate::layout_engine::LayoutKind::VerticalStack => parent_rect,
         }
@@ -1444,6 +1602,38 @@ impl TraditionalLayoutSystem {
         node
     }
 
+    /// Add `wid` as the last child of the layout's root and select it, leaving the
+    /// ratios between the existing windows as they are.
+    pub(crate) fn append_window_keeping_ratios(&mut self, layout: LayoutId, wid: WindowId) {
+        let root = self.root(layout);
+        let node = self.add_window_under(layout, root, wid);
+        self.select(node);
+    }
+
+    /// Remove `wid`, handing its share to the sibling just before it (or after it,
+    /// if it is first) so the other siblings keep their ratios to each other.
+    pub(crate) fn remove_window_into_neighbor(&mut self, wid: WindowId) {
+        let nodes: Vec<_> =
+            self.tree.data.window.take_nodes_for(wid).map(|(_, node)| node).collect();
+        for node in nodes {
+            let map = &self.tree.map;
+            let neighbor = node.prev_sibling(map).or_else(|| node.next_sibling(map));
+            if let (Some(parent), Some(neighbor)) = (node.parent(map), neighbor) {
+                let siblings: Vec<_> = parent.children(map).collect();
+                let count = siblings.len() as f32;
+                let info = &mut self.tree.data.layout.info;
+                let share = core::mem::replace(&mut info[node].size, 0.0);
+                info[neighbor].size += share;
+                let scale = (count - 1.0) / info[parent].total;
+                for sibling in siblings {
+                    info[sibling].size *= scale;
+                }
+                info[parent].total = count - 1.0;
+            }
+            node.detach(&mut self.tree).remove();
+        }
+    }
+
     pub(crate) fn window_at(&self, node: NodeId) -> Option<WindowId> {
         self.tree.data.window.at(node)
     }
@@ -1501,6 +1691,28 @@ impl TraditionalLayoutSystem {
         }
     }
 
+    /// Size each child by the tiles under it so every tile gets the same share of
+    /// the area, as in BSP. Returns the number of tiles `node` shows; a stacked or
+    /// tabbed group shows one.
+    fn balance_node(&mut self, node: NodeId) -> usize {
+        let children: Vec<_> = node.children(&self.tree.map).collect();
+        if children.is_empty() {
+            return 1;
+        }
+        let tiles: Vec<usize> = children.iter().map(|&child| self.balance_node(child)).collect();
+        let is_group = self.layout(node).is_group();
+        let count = children.len() as f32;
+        let total: usize = tiles.iter().sum();
+        // Child sizes add up to the child count, so scale the tile counts to match.
+        let scale = count / total as f32;
+        for (child, tiles) in children.into_iter().zip(tiles) {
+            self.tree.data.layout.info[child].size =
+                if is_group { 1.0 } else { tiles as f32 * scale };
+        }
+        self.tree.data.layout.info[node].total = count;
+        if is_group { 1 } else { total }
+    }
+
     pub(crate) fn select(&mut self, selection: NodeId) {
         self.tree.data.selection.select(&self.tree.map, selection)
     }
@@ -2243,6 +2455,9 @@ pub(crate) struct LayoutInfo {
     pub(crate) is_fullscreen: bool,
     #[serde(default)]
     is_fullscreen_within_gaps: bool,
+    /// Stacked container shrunk down to its title strip.
+    #[serde(default)]
+    is_collapsed: bool,
 }
 
 impl Layout {
@@ -2301,6 +2516,7 @@ impl Layout {
         self.info[node].kind = kind;
         if !kind.is_group() {
             self.info[node].last_ungrouped_kind = kind;
+            self.info[node].is_collapsed = false;
         }
     }
 
@@ -2354,6 +2570,25 @@ impl Layout {
         info.is_fullscreen || info.is_fullscreen_within_gaps
     }
 
+    fn toggle_collapsed(&mut self, node: NodeId) -> bool {
+        self.info[node].is_collapsed = !self.info[node].is_collapsed;
+        self.info[node].is_collapsed
+    }
+
+    pub(crate) fn is_collapsed(&self, node: NodeId) -> bool { self.info[node].is_collapsed }
+
+    /// Whether `node` is a collapsed stack that only keeps its strip along this axis.
+    /// Horizontal stacks collapse vertically and vertical stacks horizontally, i.e.
+    /// along the axis their stack line takes space from.
+    fn collapses_along(&self, node: NodeId, horizontal: bool) -> bool {
+        let info = &self.info[node];
+        info.is_collapsed
+            && matches!(
+                (info.kind, horizontal),
+                (LayoutKind::VerticalStack, true) | (LayoutKind::HorizontalStack, false)
+            )
+    }
+
     fn debug(&self, node: NodeId, is_container: bool) -> String {
         let info = &self.info[node];
         if is_container {
@@ -2387,7 +2622,12 @@ impl Layout {
         stack_offset: f64,
         gaps: &crate::common::config::GapSettings,
         horizontal: bool,
+        collapsed_strip: f64,
     ) -> (f64, Option<f64>, Option<f64>, bool) {
+        if self.collapses_along(node, horizontal) {
+            let strip = Some(collapsed_strip);
+            return (collapsed_strip, strip, strip, false);
+        }
         if let Some(wid) = window.at(node) {
             if let Some(c) = constraints.get(&wid).copied() {
                 return self.effective_leaf_axis_constraints(c, horizontal);
@@ -2429,6 +2669,7 @@ impl Layout {
                 stack_offset,
                 gaps,
                 horizontal,
+                collapsed_strip,
             );
             mins.push(min.max(0.0));
             fixed_parts.push(fixed.map(|v| v.max(0.0)));
@@ -2544,6 +2785,33 @@ impl Layout {
                     return;
                 }
                 let is_horizontal = matches!(info.kind, HorizontalStack);
+                if info.is_collapsed {
+                    // Everything in a collapsed stack shares the strip; the stack line
+                    // draws over it, so no reservation is taken out.
+                    let strip = collapsed_strip_rect(
+                        rect,
+                        is_horizontal,
+                        collapsed_strip_len(stack_offset, stack_line_thickness),
+                    );
+                    for &child in &children {
+                        self.apply_with_gaps(
+                            map,
+                            window,
+                            selection,
+                            child,
+                            strip,
+                            screen,
+                            sizes,
+                            stack_offset,
+                            constraints,
+                            gaps,
+                            stack_line_thickness,
+                            stack_line_horiz,
+                            stack_line_vert,
+                        );
+                    }
+                    return;
+                }
                 let focused_idx = children
                     .iter()
                     .position(|&c| self.is_focused_in_subtree(map, window, c))
@@ -2559,6 +2827,7 @@ impl Layout {
                         stack_offset,
                         gaps,
                         is_horizontal,
+                        collapsed_strip_len(stack_offset, stack_line_thickness),
                     );
                     let axis_len = if is_horizontal { rect.size.width } else { rect.size.height };
                     // Stack offset capping exists to preserve required focused size.
@@ -2720,6 +2989,7 @@ impl Layout {
                         stack_offset,
                         gaps,
                         horizontal,
+                        collapsed_strip_len(stack_offset, stack_line_thickness),
                     );
                 AxisConstraints {
                     min,
@@ -2806,6 +3076,25 @@ fn stack_layout_result(
     StackLayoutResult::new(container_rect, child_count, stack_offset, is_horizontal)
 }
 
+/// Length a collapsed stack keeps along its collapsed axis: enough to show the stack
+/// line, or one stack offset when the stack line is disabled.
+fn collapsed_strip_len(stack_offset: f64, stack_line_thickness: f64) -> f64 {
+    if stack_line_thickness > 0.0 {
+        stack_line_thickness
+    } else {
+        stack_offset.max(0.0)
+    }
+}
+
+fn collapsed_strip_rect(mut rect: CGRect, is_horizontal: bool, strip_len: f64) -> CGRect {
+    if is_horizontal {
+        rect.size.height = rect.size.height.min(strip_len);
+    } else {
+        rect.size.width = rect.size.width.min(strip_len);
+    }
+    rect
+}
+
 fn adjust_stack_container_rect(
     mut container_rect: CGRect,
     is_horizontal: bool,
@@ -3184,6 +3473,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn move_selection_into_container_nests_window_in_sibling_split() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        let root = system.root(layout);
+        system.tree.data.layout.set_kind(root, LayoutKind::Horizontal);
+
+        system.add_window_after_selection(layout, w(1));
+        system.add_window_after_selection(layout, w(2));
+        system.split_selection(layout, LayoutKind::Vertical);
+        system.add_window_after_selection(layout, w(3));
+
+        let split = system.selection(layout).parent(system.map()).unwrap();
+        assert_eq!(system.layout(split), LayoutKind::Vertical);
+
+        system.select_window(layout, w(1));
+        assert!(system.move_selection_into_container(layout, Direction::Right));
+
+        let node = system.selection(layout);
+        assert_eq!(system.window_at(node), Some(w(1)));
+        assert_eq!(node.parent(system.map()), Some(split));
+        let children: Vec<_> = split
+            .children(system.map())
+            .filter_map(|child| system.window_at(child))
+            .collect();
+        assert_eq!(children, vec![w(1), w(2), w(3)]);
+
+        // The only neighbor left is another window, so this is a no-op.
+        assert!(!system.move_selection_into_container(layout, Direction::Down));
+        assert_eq!(node.parent(system.map()), Some(split));
+    }
+
+    #[test]
+    fn arrange_selection_container_in_grid_builds_rows_of_splits() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        let root = system.root(layout);
+        system.tree.data.layout.set_kind(root, LayoutKind::Horizontal);
+        for idx in 1..=4 {
+            system.add_window_after_selection(layout, w(idx));
+        }
+        system.select_window(layout, w(2));
+
+        assert!(system.arrange_selection_container_in_grid(layout, 2, 2));
+        assert_eq!(system.window_at(system.selection(layout)), Some(w(2)));
+
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let frames: HashMap<WindowId, CGRect> = system
+            .calculate_layout(
+                layout,
+                screen,
+                40.0,
+                &HashMap::default(),
+                &Default::default(),
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+            .into_iter()
+            .collect();
+        let cell = |x, y| CGRect::new(CGPoint::new(x, y), CGSize::new(500.0, 400.0));
+        assert_eq!(frames[&w(1)], cell(0.0, 0.0));
+        assert_eq!(frames[&w(2)], cell(500.0, 0.0));
+        assert_eq!(frames[&w(3)], cell(0.0, 400.0));
+        assert_eq!(frames[&w(4)], cell(500.0, 400.0));
+    }
+
+    #[test]
+    fn balance_gives_every_window_the_same_share_of_the_area() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        let root = system.root(layout);
+        system.tree.data.layout.set_kind(root, LayoutKind::Horizontal);
+        system.add_window_after_selection(layout, w(1));
+        system.add_window_after_selection(layout, w(2));
+        system.split_selection(layout, LayoutKind::Vertical);
+        system.add_window_after_selection(layout, w(3));
+
+        system.balance(layout);
+
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(900.0, 800.0));
+        let frames: HashMap<WindowId, CGRect> = system
+            .calculate_layout(
+                layout,
+                screen,
+                40.0,
+                &HashMap::default(),
+                &Default::default(),
+                0.0,
+                Default::default(),
+                Default::default(),
+            )
+            .into_iter()
+            .collect();
+        let area = |wid| frames[&wid].size.width * frames[&wid].size.height;
+        assert!((frames[&w(1)].size.width - 300.0).abs() < 1.0);
+        for idx in 2..=3 {
+            assert!((area(w(idx)) - area(w(1))).abs() < 1000.0);
+        }
+    }
+
+    #[test]
+    fn arrange_selection_container_in_grid_accepts_huge_dimensions() {
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        for idx in 1..=3 {
+            system.add_window_after_selection(layout, w(idx));
+        }
+
+        assert!(system.arrange_selection_container_in_grid(layout, usize::MAX, usize::MAX));
+        assert_eq!(system.visible_windows_in_layout(layout).len(), 3);
+    }
+
     #[test]
     fn stacked_locked_windows_do_not_consume_entire_parent_axis() {
         use crate::common::config::StackDefaultOrientation;
@@ -3246,6 +3648,74 @@ mod tests {
         );
     }
 
+    #[test]
+    fn collapsed_stack_reserves_only_its_strip() {
+        use crate::common::config::StackDefaultOrientation;
+
+        let mut system = TraditionalLayoutSystem::default();
+        let layout = system.create_layout();
+        let root = system.root(layout);
+        system.tree.data.layout.set_kind(root, LayoutKind::Vertical);
+
+        let w1 = w(1);
+        let w2 = w(2);
+        let w3 = w(3);
+        system.add_window_after_selection(layout, w1);
+        system.add_window_after_selection(layout, w2);
+        system.add_window_after_selection(layout, w3);
+
+        system.select_window(layout, w1);
+        system.join_selection_with_direction(layout, Direction::Down);
+        let _ = system
+            .apply_stacking_to_parent_of_selection(layout, StackDefaultOrientation::Horizontal);
+        let stack = system.selection(layout).parent(system.map()).unwrap();
+        assert_eq!(system.layout(stack), LayoutKind::HorizontalStack);
+
+        let screen = CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(1000.0, 800.0));
+        let gaps = Default::default();
+        let frames = |system: &TraditionalLayoutSystem| -> HashMap<WindowId, CGRect> {
+            system
+                .calculate_layout(
+                    layout,
+                    screen,
+                    40.0,
+                    &HashMap::default(),
+                    &gaps,
+                    20.0,
+                    Default::default(),
+                    Default::default(),
+                )
+                .into_iter()
+                .collect()
+        };
+
+        assert!(system.toggle_collapse_of_selected_stack(layout));
+        let collapsed = frames(&system);
+        for wid in [w1, w2] {
+            assert_eq!(collapsed[&wid].size.height, 20.0);
+        }
+        assert_eq!(collapsed[&w3].origin.y, 20.0);
+        assert_eq!(collapsed[&w3].size.height, 780.0);
+
+        let groups = system.collect_group_containers(
+            layout,
+            screen,
+            40.0,
+            &gaps,
+            20.0,
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].is_collapsed);
+        assert_eq!(groups[0].frame.size.height, 20.0);
+
+        assert!(system.toggle_collapse_of_selected_stack(layout));
+        let expanded = frames(&system);
+        assert_eq!(expanded[&w3].size.height, 400.0);
+        assert!(expanded[&w1].size.height > 20.0);
+    }
+
     #[test]
     fn focused_locked_child_in_mixed_stack_limits_stack_growth() {
         use crate::common::config::StackDefaultOrientation;
@@ -1,10 +1,16 @@
-use objc2_core_foundation::CGRect;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Instant;
+
+use objc2_core_foundation::{CGPoint, CGRect};
 use serde::{Deserialize, Serialize};
 
 use crate::actor::app::{AppInfo, AppThreadHandle, WindowId, pid_t};
+use crate::actor::reactor::transaction_manager::TransactionId;
 use crate::common::log::MetricsCommand;
 use crate::layout_engine::{Direction, LayoutCommand};
 use crate::sys::app::WindowInfo;
+use crate::sys::geometry::SameAs;
 use crate::sys::screen::SpaceId;
 use crate::sys::window_server::WindowServerId;
 
@@ -23,6 +29,9 @@ pub enum Command {
 #[serde(untagged)]
 pub enum DisplaySelector {
     Direction(Direction),
+    /// Zero-based index into the displays ordered left-to-right, then
+    /// top-to-bottom. Out-of-range indices are rejected unless
+    /// `settings.display_index_wraps` is enabled.
     Index(usize),
     Uuid(String),
 }
@@ -32,6 +41,11 @@ pub enum DisplaySelector {
 pub enum ReactorCommand {
     Debug,
     Serialize,
+    /// Restore workspaces, window assignments and layouts from the output of
+    /// `Serialize`. Windows that no longer exist are left out.
+    Deserialize {
+        state: String,
+    },
     SaveAndExit,
     SwitchSpace(Direction),
     ToggleSpaceActivated,
@@ -50,7 +64,93 @@ pub enum ReactorCommand {
     MoveWindowToDisplay {
         selector: DisplaySelector,
         window_id: Option<u32>,
+        /// Resolve a direction `selector` from the center of this display instead of
+        /// the window's current one. Omitting it keeps the default.
+        #[serde(default)]
+        origin: Option<DisplaySelector>,
+    },
+    /// Drop every inferred window constraint and re-read them from the apps.
+    ReprobeAllConstraints,
+    /// Start resizing a floating window from `edge`; the opposite edge stays put
+    /// until `EndEdgeResize`.
+    BeginEdgeResize {
+        window_server_id: WindowServerId,
+        edge: Edge,
+    },
+    /// Move the grabbed edge by `delta` points; positive values grow the window.
+    EdgeResizeBy {
+        delta: f64,
+    },
+    EndEdgeResize,
+    /// Move the focused window by `dx`/`dy` points, floating it first if it is
+    /// tiled. Part of the window always stays on its display.
+    NudgeWindow {
+        dx: f64,
+        dy: f64,
+    },
+    /// Switch to the workspace holding a window of the given app and focus it.
+    /// Repeated invocations cycle through the app's windows across workspaces.
+    FocusAppWorkspace {
+        bundle_id: Option<String>,
+        pid: Option<pid_t>,
+    },
+    /// Hide the given app like Cmd-H, or unhide it if it was hidden this way.
+    /// Unhidden windows return to the workspaces they were on when hidden.
+    ToggleAppHidden {
+        bundle_id: Option<String>,
+        pid: Option<pid_t>,
+    },
+    /// Focus the next display in index order, wrapping around and skipping displays
+    /// whose space is inactive. Optionally moves the cursor there as well.
+    CycleDisplays {
+        #[serde(default)]
+        move_mouse: bool,
+    },
+    /// Move every window on the selected display's workspaces to `target` (the primary
+    /// display when omitted), keeping each on the workspace with the same index.
+    EvacuateDisplay {
+        selector: DisplaySelector,
+        #[serde(default)]
+        target: Option<DisplaySelector>,
     },
+    /// Spread the windows of the current display round-robin across its workspaces.
+    /// Windows pinned to a workspace by an app rule, and sticky windows, stay put.
+    DistributeWindows,
+    /// Move windows between displays so each display's active workspace holds its
+    /// share of them, as set by `display_balance`. Pinned, sticky and position-locked
+    /// windows stay put.
+    BalanceDisplays,
+    /// Lay out the tiled windows of one space (the focused display's when omitted)
+    /// again from scratch, leaving other displays alone.
+    RetileSpace {
+        #[serde(default)]
+        space_id: Option<SpaceId>,
+    },
+    /// Run app rules again for every window on an active space, so edited rules
+    /// apply without restarting or reopening windows.
+    ReapplyAppRules,
+    /// Forget any menu Rift thinks is open, for when a menu-closed notification
+    /// was missed and focus-follows-mouse stays suppressed.
+    ResetMenuState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+#[derive(Debug, Clone)]
+pub struct EdgeResizeSession {
+    pub(crate) window: WindowId,
+    pub(crate) window_server_id: WindowServerId,
+    pub(crate) edge: Edge,
+    /// Coordinate of the edge opposite `edge`, held fixed for the whole session.
+    pub(crate) anchor: f64,
+    pub(crate) frame: CGRect,
 }
 
 #[derive(Debug, Clone)]
@@ -91,13 +191,31 @@ pub enum DragState {
     },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum MissionControlState {
     Inactive,
     Active,
     Transitioning,
 }
 
+/// The reactor's `MissionControlState`, mirrored into an atomic so query handles
+/// can read it without a round trip through the reactor queue.
+#[derive(Debug, Clone, Default)]
+pub struct SharedMissionControlState(Arc<AtomicU8>);
+
+impl SharedMissionControlState {
+    pub fn get(&self) -> MissionControlState {
+        match self.0.load(Ordering::Acquire) {
+            1 => MissionControlState::Active,
+            2 => MissionControlState::Transitioning,
+            _ => MissionControlState::Inactive,
+        }
+    }
+
+    pub fn set(&self, state: MissionControlState) { self.0.store(state as u8, Ordering::Release); }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MenuState {
     Closed,
@@ -148,22 +266,67 @@ pub(crate) struct WindowState {
     /// This value only updates monotonically with respect to writes; in other
     /// words, we only accept reads when we know they come after the last write.
     pub(crate) frame_monotonic: CGRect,
+    /// The last frame the window itself reported. Unlike `frame_monotonic`,
+    /// this never includes writes that haven't been confirmed.
+    pub(crate) reported_frame: CGRect,
+    /// Set once the window is seen ignoring our move requests. We stop moving
+    /// such windows and float them where they are.
+    pub(crate) position_locked: bool,
+    /// Where the window stayed while ignoring our latest move requests, and how
+    /// many in a row it ignored there.
+    pub(crate) move_refusals: Option<(CGPoint, u32)>,
     pub(crate) is_manageable: bool,
     pub(crate) ignore_app_rule: bool,
+    /// Opacity set by the window's app rule. Other opacity changes should be
+    /// relative to this baseline rather than replace it.
+    pub(crate) opacity: Option<f32>,
+    /// Transaction id carried by the most recent frame change event for this window.
+    pub(crate) last_seen_txid: Option<TransactionId>,
+    /// When a `WindowCreated` event introduced the window; unset for windows
+    /// found while discovering an app's existing windows.
+    pub(crate) created_at: Option<Instant>,
 }
 
 impl From<WindowInfo> for WindowState {
     fn from(info: WindowInfo) -> WindowState {
         WindowState {
             frame_monotonic: info.frame,
+            reported_frame: info.frame,
+            position_locked: false,
+            move_refusals: None,
             info,
             is_manageable: false,
             ignore_app_rule: false,
+            opacity: None,
+            last_seen_txid: None,
+            created_at: None,
         }
     }
 }
 
 impl WindowState {
+    /// Refused moves in a row, all at the same origin, before the window is
+    /// locked in place. A single refusal is often just the size being applied
+    /// ahead of the position.
+    pub(crate) const MOVE_REFUSALS_BEFORE_LOCK: u32 = 3;
+
+    /// Records a move the window ignored, staying at `origin`. Returns whether
+    /// it should now be locked in place.
+    pub(crate) fn note_refused_move(&mut self, origin: CGPoint) -> bool {
+        let count = match self.move_refusals {
+            Some((at, count)) if at.same_as(origin) => count + 1,
+            _ => 1,
+        };
+        self.move_refusals = Some((origin, count));
+        count >= Self::MOVE_REFUSALS_BEFORE_LOCK
+    }
+
+    /// Lets rift move the window again, e.g. after the user moved it themselves.
+    pub(crate) fn unlock_position(&mut self) {
+        self.position_locked = false;
+        self.move_refusals = None;
+    }
+
     pub(crate) fn is_effectively_manageable(&self) -> bool {
         self.is_manageable && !self.ignore_app_rule
     }
@@ -197,3 +360,24 @@ pub enum ReactorError {
         #[from] tokio::sync::mpsc::error::SendError<crate::actor::raise_manager::Event>,
     ),
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DisplaySelectorError {
+    #[error("Display index {index} is out of range ({count} displays)")]
+    IndexOutOfRange { index: usize, count: usize },
+    #[error("No display with UUID {0}")]
+    UnknownUuid(String),
+    #[error("No display in direction {0:?}")]
+    NoDisplayInDirection(Direction),
+}
+
+impl DisplaySelectorError {
+    /// Stable identifier for logs and scripts that should not depend on the message text.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DisplaySelectorError::IndexOutOfRange { .. } => "display_index_out_of_range",
+            DisplaySelectorError::UnknownUuid(_) => "display_unknown_uuid",
+            DisplaySelectorError::NoDisplayInDirection(_) => "display_not_in_direction",
+        }
+    }
+}
@@ -1,13 +1,21 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
 use serde::de::Deserializer;
 use serde::ser::Serializer;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
 use crate::actor::app::{WindowId, pid_t};
+use crate::actor::reactor::transaction_manager::TransactionId;
+use crate::common::config::LayoutMode;
+use crate::layout_engine::systems::WindowLayoutConstraints;
+use crate::layout_engine::{EventResponse, Orientation};
 use crate::sys::app::WindowInfo;
 use crate::sys::geometry::CGRectDef;
 use crate::sys::screen::{ScreenId, ScreenInfo, SpaceId};
 use crate::sys::window_server::WindowServerId;
+use crate::ui::common::RenderFailureStats;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceData {
@@ -15,6 +23,9 @@ pub struct WorkspaceData {
     pub index: usize,
     pub name: String,
     pub layout_mode: String,
+    /// Whether `freeze_layout` is keeping the workspace's window sizes.
+    #[serde(default)]
+    pub is_frozen: bool,
     pub is_active: bool,
     pub window_count: usize,
     pub windows: Vec<WindowData>,
@@ -27,6 +38,220 @@ pub struct WorkspaceLayoutData {
     pub name: String,
     pub layout_mode: String,
     pub is_active: bool,
+    /// The space isn't currently managed on a display, so this is the layout
+    /// retained from when it last was
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutModeData {
+    pub space_id: u64,
+    pub workspace_id: String,
+    pub mode: LayoutMode,
+}
+
+/// `GetPendingSplit` response: the split the next window added to the active
+/// workspace will get, if `SetSplitDirection` was used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSplitData {
+    pub space_id: u64,
+    pub workspace_id: String,
+    /// The window that was focused when the hint was set, which the next window
+    /// is placed beside
+    pub window_id: Option<WindowId>,
+    pub orientation: Option<Orientation>,
+}
+
+/// `RetileSpace` response: how many tiled windows were laid out again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetileSpaceData {
+    pub space_id: u64,
+    pub windows: usize,
+}
+
+/// `GetMenuState` / `ResetMenuState` response. Rift tracks the one app whose menu
+/// is open rather than a nesting depth, and holds off focus-follows-mouse while
+/// it is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuStateData {
+    pub open: bool,
+    pub owner_pid: Option<pid_t>,
+}
+
+/// One `GetEventLog` entry: a layout event or layout command the reactor handled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLogEntryData {
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub kind: EventLogKind,
+    /// The event or command as it appears in debug logs
+    pub description: String,
+    /// What the layout engine asked for in return; `None` for commands the
+    /// reactor handles without it
+    pub response: Option<EventResponse>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventLogKind {
+    Event,
+    Command,
+}
+
+/// `GetMetrics` response. `GetMetricsPrometheus` renders the same numbers with
+/// [`MetricsData::to_prometheus`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsData {
+    pub windows_managed: usize,
+    pub workspaces: usize,
+    pub applications: usize,
+    pub screens: usize,
+    /// Window count per workspace
+    pub workspace_stats: BTreeMap<String, usize>,
+    pub render_failures: RenderFailureStats,
+    /// Reactor events handled since startup, by kind
+    pub events: BTreeMap<String, HandlerTimingData>,
+    /// Layout commands received since startup, by kind
+    pub layout_commands: BTreeMap<String, u64>,
+}
+
+/// How many events of one kind the reactor handled and how long they took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct HandlerTimingData {
+    pub count: u64,
+    pub total_us: u64,
+    pub max_us: u64,
+}
+
+impl MetricsData {
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for (suffix, value) in samples {
+                let _ = writeln!(out, "{name}{suffix} {value}");
+            }
+        };
+        let single = |value: &dyn ToString| vec![(String::new(), value.to_string())];
+
+        family(
+            "rift_windows_managed",
+            "gauge",
+            "Windows the reactor is tracking.",
+            single(&self.windows_managed),
+        );
+        family(
+            "rift_workspaces",
+            "gauge",
+            "Virtual workspaces.",
+            single(&self.workspaces),
+        );
+        family(
+            "rift_applications",
+            "gauge",
+            "Running applications.",
+            single(&self.applications),
+        );
+        family(
+            "rift_screens",
+            "gauge",
+            "Connected displays.",
+            single(&self.screens),
+        );
+        family(
+            "rift_workspace_windows",
+            "gauge",
+            "Windows per virtual workspace.",
+            labeled("workspace", &self.workspace_stats, |count| count.to_string()),
+        );
+        family(
+            "rift_render_failures_total",
+            "counter",
+            "Failed overlay renders.",
+            single(&self.render_failures.total_failures),
+        );
+        family(
+            "rift_render_disabled_windows",
+            "gauge",
+            "Overlay windows whose rendering is paused after repeated failures.",
+            single(&self.render_failures.disabled_windows),
+        );
+        family(
+            "rift_events_total",
+            "counter",
+            "Reactor events handled, by kind.",
+            labeled("kind", &self.events, |timing| timing.count.to_string()),
+        );
+        family(
+            "rift_event_handler_seconds_total",
+            "counter",
+            "Time spent handling reactor events, by kind.",
+            labeled("kind", &self.events, |timing| seconds(timing.total_us)),
+        );
+        family(
+            "rift_event_handler_max_seconds",
+            "gauge",
+            "Longest time spent handling one reactor event, by kind.",
+            labeled("kind", &self.events, |timing| seconds(timing.max_us)),
+        );
+        family(
+            "rift_layout_commands_total",
+            "counter",
+            "Layout commands received, by kind.",
+            labeled("command", &self.layout_commands, |count| count.to_string()),
+        );
+        out
+    }
+}
+
+fn labeled<T>(
+    label: &str,
+    values: &BTreeMap<String, T>,
+    value: impl Fn(&T) -> String,
+) -> Vec<(String, String)> {
+    values
+        .iter()
+        .map(|(key, v)| {
+            let key = key.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            (format!("{{{label}=\"{key}\"}}"), value(v))
+        })
+        .collect()
+}
+
+fn seconds(micros: u64) -> String { format!("{}", micros as f64 / 1_000_000.0) }
+
+/// `ReapplyAppRules` response: how many windows on active spaces the rules now
+/// classify differently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReapplyAppRulesData {
+    pub changed_windows: usize,
+}
+
+/// `Deserialize` response: which of the snapshot's windows were restored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeserializeStateData {
+    pub restored_windows: usize,
+    /// Windows in the snapshot that aren't open any more (or are now a different
+    /// window), dropped from the restored layout
+    pub unmatched_windows: Vec<WindowId>,
+    /// Open windows the snapshot didn't place, laid out as if newly opened
+    pub added_windows: usize,
+}
+
+/// `GetWindowTransaction` response: the frame-sync state the reactor keeps for a window.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowTransactionData {
+    pub window_server_id: u32,
+    pub window_id: Option<WindowId>,
+    pub last_sent_txid: TransactionId,
+    /// Txid reported by the latest frame change event; `None` if none arrived yet.
+    pub last_seen_txid: Option<TransactionId>,
+    /// Frame Rift is still waiting for the window to reach, if any.
+    #[serde_as(as = "Option<CGRectDef>")]
+    pub pending_target: Option<objc2_core_foundation::CGRect>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +263,30 @@ pub struct WindowData {
     pub info: WindowInfo,
 }
 
+/// `GetWindowInfo` response: the window plus where it sits in the layout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowInfoData {
+    #[serde(flatten)]
+    pub window: WindowData,
+    pub is_manageable: bool,
+    /// AX role and subrole read when the window was discovered; these drive app-rule
+    /// matching and manageability.
+    pub ax_role: Option<String>,
+    pub ax_subrole: Option<String>,
+    /// AX subrole that made Rift track this non-standard window as floating.
+    pub auto_float_subrole: Option<String>,
+    pub workspace_id: Option<String>,
+    /// Position in the workspace's layout order; `None` for floating windows.
+    pub index_in_workspace: Option<usize>,
+    pub constraints: Option<WindowLayoutConstraints>,
+    /// Marks set on the window with `mark_window`, sorted.
+    #[serde(default)]
+    pub marks: Vec<String>,
+    /// Whether `toggle_sticky` keeps the window on whichever workspace is shown.
+    #[serde(default)]
+    pub is_sticky: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApplicationData {
     pub pid: pid_t,
@@ -54,6 +303,9 @@ pub struct LayoutStateData {
     pub floating_windows: Vec<WindowId>,
     pub tiled_windows: Vec<WindowId>,
     pub focused_window: Option<WindowId>,
+    /// The space isn't currently managed on a display, so this is the layout
+    /// retained from when it last was and `focused_window` is always `None`
+    pub stale: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -81,6 +333,9 @@ impl Serialize for WindowData {
             frame: &'a objc2_core_foundation::CGRect,
             is_floating: bool,
             is_focused: bool,
+            /// Only present for minimized windows, which queries leave out unless asked.
+            #[serde(skip_serializing_if = "std::ops::Not::not")]
+            is_minimized: bool,
             bundle_id: Option<&'a String>,
             app_name: Option<&'a String>,
             window_server_id: Option<u32>,
@@ -92,6 +347,7 @@ impl Serialize for WindowData {
             frame: &self.info.frame,
             is_floating: self.is_floating,
             is_focused: self.is_focused,
+            is_minimized: self.info.is_minimized,
             bundle_id: self.info.bundle_id.as_ref(),
             app_name: self.app_name.as_ref(),
             window_server_id: self.info.sys_id.map(|id| id.as_u32()),
@@ -113,6 +369,8 @@ impl<'de> Deserialize<'de> for WindowData {
             frame: objc2_core_foundation::CGRect,
             is_floating: bool,
             is_focused: bool,
+            #[serde(default)]
+            is_minimized: bool,
             bundle_id: Option<String>,
             app_name: Option<String>,
             window_server_id: Option<u32>,
@@ -122,7 +380,7 @@ impl<'de> Deserialize<'de> for WindowData {
         let info = WindowInfo {
             is_standard: true,
             is_root: true,
-            is_minimized: false,
+            is_minimized: helper.is_minimized,
             is_resizable: true,
             min_size: None,
             max_size: None,
@@ -262,6 +520,60 @@ mod tests {
         assert_eq!(value, expected);
     }
 
+    #[test]
+    fn window_info_data_flattens_window_fields() {
+        let info = WindowInfo {
+            is_standard: true,
+            is_root: true,
+            is_minimized: false,
+            is_resizable: true,
+            min_size: None,
+            max_size: None,
+            title: "Test".to_string(),
+            frame: CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(10.0, 10.0)),
+            sys_id: None,
+            bundle_id: None,
+            path: None,
+            ax_role: None,
+            ax_subrole: None,
+        };
+        let data = WindowInfoData {
+            window: WindowData {
+                id: WindowId::new(5, 1),
+                is_floating: false,
+                is_focused: true,
+                app_name: None,
+                info,
+            },
+            is_manageable: true,
+            ax_role: Some("AXWindow".to_string()),
+            ax_subrole: Some("AXStandardWindow".to_string()),
+            auto_float_subrole: None,
+            workspace_id: Some("VirtualWorkspaceId(1v1)".to_string()),
+            index_in_workspace: Some(2),
+            constraints: None,
+            marks: vec!["mail".to_string()],
+            is_sticky: true,
+        };
+
+        let value = serde_json::to_value(&data).expect("serialize WindowInfoData");
+        assert_eq!(value["title"], "Test");
+        assert_eq!(value["is_focused"], true);
+        assert_eq!(value["index_in_workspace"], 2);
+        assert_eq!(value["ax_role"], "AXWindow");
+        assert_eq!(value["ax_subrole"], "AXStandardWindow");
+        assert_eq!(value["marks"], json!(["mail"]));
+        assert_eq!(value["is_sticky"], true);
+        assert!(value.get("window").is_none());
+
+        let round_trip: WindowInfoData =
+            serde_json::from_value(value).expect("deserialize WindowInfoData");
+        assert_eq!(round_trip.window.id, WindowId::new(5, 1));
+        assert_eq!(round_trip.index_in_workspace, Some(2));
+        assert_eq!(round_trip.ax_role.as_deref(), Some("AXWindow"));
+        assert_eq!(round_trip.ax_subrole.as_deref(), Some("AXStandardWindow"));
+    }
+
     #[test]
     fn display_data_serializes_with_legacy_shape() {
         let info = ScreenInfo {
@@ -293,4 +605,129 @@ mod tests {
         });
         assert_eq!(value, expected);
     }
+
+    #[test]
+    fn metrics_prometheus_output_is_valid_exposition_text() {
+        let data = MetricsData {
+            windows_managed: 3,
+            workspaces: 2,
+            applications: 1,
+            screens: 1,
+            workspace_stats: BTreeMap::from([
+                ("VirtualWorkspaceId(1v1)".to_string(), 2),
+                ("odd \"name\"\\".to_string(), 1),
+            ]),
+            render_failures: RenderFailureStats {
+                total_failures: 4,
+                total_disables: 1,
+                failing_windows: 1,
+                disabled_windows: 1,
+            },
+            events: BTreeMap::from([("window_created".to_string(), HandlerTimingData {
+                count: 2,
+                total_us: 1_500,
+                max_us: 1_000,
+            })]),
+            layout_commands: BTreeMap::from([("next_window".to_string(), 5)]),
+        };
+
+        let text = data.to_prometheus();
+        let mut declared = BTreeMap::new();
+        let mut samples = Vec::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let keyword = parts.next().unwrap();
+                let name = parts.next().expect("metric name");
+                let rest = parts.next().expect("comment text");
+                if keyword == "TYPE" {
+                    assert!(["counter", "gauge"].contains(&rest), "bad type in {line:?}");
+                    assert!(declared.insert(name.to_string(), rest).is_none());
+                } else {
+                    assert_eq!(keyword, "HELP", "unexpected comment {line:?}");
+                }
+                continue;
+            }
+            samples.push(parse_sample(line));
+        }
+
+        for (name, labels, _) in &samples {
+            assert!(declared.contains_key(name), "{name} has no TYPE line");
+            for (label, _) in labels {
+                assert!(
+                    label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                    "bad label name {label:?}"
+                );
+            }
+        }
+        let value = |name: &str, label: Option<&str>| {
+            samples
+                .iter()
+                .find(|(n, labels, _)| {
+                    n == name && labels.first().map(|(_, v)| v.as_str()) == label
+                })
+                .map(|(_, _, value)| *value)
+        };
+        assert_eq!(value("rift_windows_managed", None), Some(3.0));
+        assert_eq!(
+            value("rift_workspace_windows", Some("odd \"name\"\\")),
+            Some(1.0)
+        );
+        assert_eq!(value("rift_events_total", Some("window_created")), Some(2.0));
+        assert_eq!(
+            value("rift_event_handler_seconds_total", Some("window_created")),
+            Some(0.0015)
+        );
+        assert_eq!(
+            value("rift_layout_commands_total", Some("next_window")),
+            Some(5.0)
+        );
+    }
+
+    /// Parses `name{label="value",...} number`, unescaping label values.
+    fn parse_sample(line: &str) -> (String, Vec<(String, String)>, f64) {
+        let (series, value) =
+            line.rsplit_once(' ').unwrap_or_else(|| panic!("no value in {line:?}"));
+        let (name, body) = match series.split_once('{') {
+            Some((name, body)) => (
+                name,
+                body.strip_suffix('}').unwrap_or_else(|| panic!("unclosed {line:?}")),
+            ),
+            None => (series, ""),
+        };
+        assert!(
+            !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':'),
+            "bad metric name in {line:?}"
+        );
+
+        let mut labels = Vec::new();
+        let mut chars = body.chars().peekable();
+        while chars.peek().is_some() {
+            let label: String = chars.by_ref().take_while(|&c| c != '=').collect();
+            assert_eq!(chars.next(), Some('"'), "unquoted label in {line:?}");
+            let mut label_value = String::new();
+            loop {
+                match chars.next() {
+                    Some('\\') => match chars.next() {
+                        Some('\\') => label_value.push('\\'),
+                        Some('"') => label_value.push('"'),
+                        Some('n') => label_value.push('\n'),
+                        other => panic!("bad escape {other:?} in {line:?}"),
+                    },
+                    Some('"') => break,
+                    Some(c) => label_value.push(c),
+                    None => panic!("unterminated label in {line:?}"),
+                }
+            }
+            labels.push((label, label_value));
+            match chars.next() {
+                Some(',') | None => {}
+                other => panic!("unexpected {other:?} after label in {line:?}"),
+            }
+        }
+
+        let value = value.parse().unwrap_or_else(|_| panic!("bad value in {line:?}"));
+        (name.to_string(), labels, value)
+    }
 }
@@ -172,10 +172,18 @@ impl SpaceActivationPolicy {
             } else {
                 self.disabled_spaces.insert(ctx.space);
             }
-        } else if cfg.default_disable {
+        } else {
+            self.activate_space(cfg, ctx);
+        }
+    }
+
+    /// Like the enabling half of `toggle_space_activated`, but never disables
+    /// a space that is already enabled.
+    pub fn activate_space(&mut self, cfg: SpaceActivationConfig, ctx: ToggleSpaceContext) {
+        if cfg.default_disable {
             self.enabled_spaces.insert(ctx.space);
-            if let Some(uuid) = ctx.display_uuid.as_ref() {
-                self.enabled_displays.insert(uuid.clone());
+            if let Some(uuid) = ctx.display_uuid {
+                self.enabled_displays.insert(uuid);
             }
         } else {
             self.disabled_spaces.remove(&ctx.space);
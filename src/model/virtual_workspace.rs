@@ -7,12 +7,14 @@ use tracing::{error, warn};
 use crate::actor::app::WindowId;
 use crate::common::collections::{HashMap, HashSet};
 use crate::common::config::{
-    AppWorkspaceRule, LayoutMode, LayoutSettings, VirtualWorkspaceSettings, WorkspaceSelector,
+    AppWorkspaceRule, LayoutMode, LayoutSettings, VirtualWorkspaceSettings,
+    WindowConstraintOverrides, WorkspaceGroup, WorkspaceSelector,
 };
 use crate::common::log::trace_misc;
 use crate::layout_engine::Direction;
 use crate::layout_engine::systems::LayoutSystemKind;
 use crate::sys::app::pid_t;
+use crate::sys::axuielement::AX_WINDOW_ROLE;
 use crate::sys::geometry::CGRectDef;
 use crate::sys::screen::SpaceId;
 
@@ -39,6 +41,24 @@ pub enum WorkspaceError {
     InvalidWorkspaceId(VirtualWorkspaceId),
     InvalidWorkspaceIndex(usize),
     InconsistentState(String),
+    EmptyName,
+    DuplicateName(String),
+}
+
+impl WorkspaceError {
+    /// Stable identifier for logs and scripts that should not depend on the variant's
+    /// debug output.
+    pub fn code(&self) -> &'static str {
+        match self {
+            WorkspaceError::NoWorkspacesAvailable => "workspace_none_available",
+            WorkspaceError::AssignmentFailed => "workspace_assignment_failed",
+            WorkspaceError::InvalidWorkspaceId(_) => "workspace_invalid_id",
+            WorkspaceError::InvalidWorkspaceIndex(_) => "workspace_invalid_index",
+            WorkspaceError::InconsistentState(_) => "workspace_inconsistent_state",
+            WorkspaceError::EmptyName => "workspace_name_empty",
+            WorkspaceError::DuplicateName(_) => "workspace_name_duplicate",
+        }
+    }
 }
 
 /// Details about an app rule assignment when Rift will manage the window.
@@ -66,6 +86,10 @@ pub struct VirtualWorkspace {
     pub layout_system: LayoutSystemKind,
     #[serde(default)]
     pub layout_mode: LayoutMode,
+    /// Set once the mode has been chosen explicitly for this workspace, so
+    /// `workspace_rules` and the global mode no longer override it on reload.
+    #[serde(default)]
+    pub layout_mode_overridden: bool,
 }
 
 fn default_layout_system_kind() -> LayoutSystemKind {
@@ -82,6 +106,7 @@ impl VirtualWorkspace {
             last_focused: None,
             layout_system,
             layout_mode: mode,
+            layout_mode_overridden: false,
         }
     }
 
@@ -157,6 +182,8 @@ impl HideCorner {
 pub struct VirtualWorkspaceManager {
     pub(crate) workspaces: SlotMap<VirtualWorkspaceId, VirtualWorkspace>,
     workspaces_by_space: HashMap<SpaceId, Vec<VirtualWorkspaceId>>,
+    /// Previous and current workspace of each space. Every display keeps its
+    /// own history, so switching back on one never disturbs another.
     pub active_workspace_per_space:
         HashMap<SpaceId, (Option<VirtualWorkspaceId>, VirtualWorkspaceId)>,
     pub window_to_workspace: HashMap<(SpaceId, WindowId), VirtualWorkspaceId>,
@@ -183,6 +210,10 @@ pub struct VirtualWorkspaceManager {
     #[serde(skip)]
     pub workspace_rules: Vec<crate::common::config::WorkspaceLayoutRule>,
     #[serde(skip)]
+    pub workspace_groups: Vec<WorkspaceGroup>,
+    #[serde(skip)]
+    auto_float_subroles: Vec<String>,
+    #[serde(skip)]
     pub default_layout_mode: LayoutMode,
     #[serde(skip)]
     pub layout_settings: LayoutSettings,
@@ -231,6 +262,8 @@ impl VirtualWorkspaceManager {
             default_workspace,
             workspace_auto_back_and_forth: config.workspace_auto_back_and_forth,
             workspace_rules: config.workspace_rules.clone(),
+            workspace_groups: config.workspace_groups.clone(),
+            auto_float_subroles: config.auto_float_subroles.clone(),
             default_layout_mode: layout_settings.mode,
             layout_settings: layout_settings.clone(),
         };
@@ -239,6 +272,19 @@ impl VirtualWorkspaceManager {
         manager
     }
 
+    /// Takes the workspaces and window assignments of `imported`, keeping this
+    /// manager's settings.
+    pub(crate) fn import_state(&mut self, imported: VirtualWorkspaceManager) {
+        self.workspaces = imported.workspaces;
+        self.workspaces_by_space = imported.workspaces_by_space;
+        self.active_workspace_per_space = imported.active_workspace_per_space;
+        self.window_to_workspace = imported.window_to_workspace;
+        self.floating_positions = imported.floating_positions;
+        self.workspace_counter = imported.workspace_counter;
+        self.window_rule_floating.clear();
+        self.last_rule_decision.clear();
+    }
+
     pub fn update_settings(
         &mut self,
         config: &VirtualWorkspaceSettings,
@@ -246,6 +292,8 @@ impl VirtualWorkspaceManager {
     ) {
         self.app_rules = config.app_rules.clone();
         self.workspace_rules = config.workspace_rules.clone();
+        self.workspace_groups = config.workspace_groups.clone();
+        self.auto_float_subroles = config.auto_float_subroles.clone();
         self.default_layout_mode = layout_settings.mode;
         self.layout_settings = layout_settings.clone();
         self.default_workspace_count = config.default_workspace_count;
@@ -337,6 +385,66 @@ impl VirtualWorkspaceManager {
         self.default_layout_mode
     }
 
+    fn resolve_workspace_selector(
+        &mut self,
+        space: SpaceId,
+        selector: &WorkspaceSelector,
+    ) -> Option<usize> {
+        let workspaces = self.list_workspaces(space);
+        match selector {
+            WorkspaceSelector::Index(idx) => (*idx < workspaces.len()).then_some(*idx),
+            WorkspaceSelector::Name(name) => workspaces.iter().position(|(_, n)| n == name),
+        }
+    }
+
+    /// Workspaces that should be switched to alongside `workspace_index` on
+    /// `display`, according to the configured workspace groups.
+    ///
+    /// `displays` maps each active space to the UUID of its display; members on
+    /// displays not in this list are skipped. The originating display is never
+    /// included in the result.
+    pub fn linked_workspace_switches(
+        &mut self,
+        space: SpaceId,
+        display: &str,
+        workspace_index: usize,
+        displays: &[(SpaceId, String)],
+    ) -> Vec<(SpaceId, usize)> {
+        let groups = self.workspace_groups.clone();
+        let mut targets = Vec::new();
+        for group in &groups {
+            let is_member = group.members.iter().any(|member| {
+                member.display == display
+                    && self.resolve_workspace_selector(space, &member.workspace)
+                        == Some(workspace_index)
+            });
+            if !is_member {
+                continue;
+            }
+            for member in &group.members {
+                if member.display == display {
+                    continue;
+                }
+                let Some(&(other_space, _)) =
+                    displays.iter().find(|(_, uuid)| *uuid == member.display)
+                else {
+                    continue;
+                };
+                match self.resolve_workspace_selector(other_space, &member.workspace) {
+                    Some(idx) if !targets.contains(&(other_space, idx)) => {
+                        targets.push((other_space, idx))
+                    }
+                    Some(_) => {}
+                    None => warn!(
+                        "Workspace group references workspace {:?} which does not exist on display {}",
+                        member.workspace, member.display
+                    ),
+                }
+            }
+        }
+        targets
+    }
+
     pub fn desired_layout_mode_for_workspace(&self, index: usize, name: &str) -> LayoutMode {
         self.resolve_layout_mode_for_workspace(index, name)
     }
@@ -345,6 +453,10 @@ impl VirtualWorkspaceManager {
         self.workspaces_by_space.keys().copied().collect()
     }
 
+    pub fn is_space_initialized(&self, space: SpaceId) -> bool {
+        self.workspaces_by_space.contains_key(&space)
+    }
+
     pub fn remap_space(&mut self, old_space: SpaceId, new_space: SpaceId) {
         if old_space == new_space || !self.workspaces_by_space.contains_key(&old_space) {
             return;
@@ -458,6 +570,7 @@ impl VirtualWorkspaceManager {
         Ok(workspace_id)
     }
 
+    /// The workspace that was active on `space` before the current one.
     pub fn last_workspace(&self, space: SpaceId) -> Option<VirtualWorkspaceId> {
         self.active_workspace_per_space.get(&space)?.0
     }
@@ -484,12 +597,18 @@ impl VirtualWorkspaceManager {
         workspace_id: VirtualWorkspaceId,
     ) -> bool {
         trace_misc("set_active_workspace", || {
-            let active = self.active_workspace_per_space.get(&space).map(|tuple| tuple.1);
+            let current = self.active_workspace_per_space.get(&space).copied();
 
             let result = if self.workspaces.contains_key(workspace_id)
                 && self.workspaces.get(workspace_id).map(|w| w.space) == Some(space)
             {
-                self.active_workspace_per_space.insert(space, (active, workspace_id));
+                // Re-activating the current workspace keeps the history intact.
+                let last = match current {
+                    Some((last, active)) if active == workspace_id => last,
+                    Some((_, active)) => Some(active),
+                    None => None,
+                };
+                self.active_workspace_per_space.insert(space, (last, workspace_id));
                 true
             } else {
                 error!(
@@ -1055,6 +1174,27 @@ impl VirtualWorkspaceManager {
         }
     }
 
+    /// Remember the bottom-to-top order of the floating windows in the active
+    /// workspace of `space`.
+    pub fn store_floating_stacking_order(&mut self, space: SpaceId, order: Vec<WindowId>) {
+        if let Some(workspace_id) = self.active_workspace(space) {
+            self.floating_positions.entry((space, workspace_id)).or_default().stacking_order =
+                order;
+        }
+    }
+
+    /// Take the order saved by `store_floating_stacking_order` for the active
+    /// workspace of `space`.
+    pub fn take_floating_stacking_order(&mut self, space: SpaceId) -> Vec<WindowId> {
+        let Some(workspace_id) = self.active_workspace(space) else {
+            return Vec::new();
+        };
+        self.floating_positions
+            .get_mut(&(space, workspace_id))
+            .map(|positions| std::mem::take(&mut positions.stacking_order))
+            .unwrap_or_default()
+    }
+
     pub fn get_workspace_floating_positions(
         &self,
         space: SpaceId,
@@ -1096,22 +1236,34 @@ impl VirtualWorkspaceManager {
         workspaces
     }
 
+    /// Renames a workspace on `space`, returning its previous name. The name is
+    /// trimmed and must be non-empty and unique among the space's workspaces.
     pub fn rename_workspace(
         &mut self,
         space: SpaceId,
         workspace_id: VirtualWorkspaceId,
         new_name: String,
-    ) -> bool {
+    ) -> Result<String, WorkspaceError> {
         if self.workspaces.get(workspace_id).map(|w| w.space) != Some(space) {
-            return false;
+            return Err(WorkspaceError::InvalidWorkspaceId(workspace_id));
         }
-        if let Some(workspace) = self.workspaces.get_mut(workspace_id) {
-            workspace.name = new_name;
-
-            true
-        } else {
-            false
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err(WorkspaceError::EmptyName);
         }
+        let duplicate = self.workspaces_by_space.get(&space).is_some_and(|ids| {
+            ids.iter().any(|&id| {
+                id != workspace_id && self.workspaces.get(id).is_some_and(|ws| ws.name == new_name)
+            })
+        });
+        if duplicate {
+            return Err(WorkspaceError::DuplicateName(new_name.to_string()));
+        }
+        let workspace = self
+            .workspaces
+            .get_mut(workspace_id)
+            .ok_or(WorkspaceError::InvalidWorkspaceId(workspace_id))?;
+        Ok(std::mem::replace(&mut workspace.name, new_name.to_string()))
     }
 
     pub fn workspace_windows(
@@ -1169,6 +1321,7 @@ impl VirtualWorkspaceManager {
         let rule_match = self
             .find_matching_app_rule(app_bundle_id, app_name, window_title, ax_role, ax_subrole)
             .cloned();
+        let auto_float = self.auto_float_subrole(ax_role, ax_subrole).is_some();
 
         let existing_assignment = self.window_to_workspace.get(&(space, window_id)).copied();
 
@@ -1177,6 +1330,7 @@ impl VirtualWorkspaceManager {
                 self.window_rule_floating.remove(&(space, window_id));
                 return Ok(AppRuleResult::Unmanaged);
             }
+            let floating = rule.floating || auto_float;
 
             let target_workspace_id = if let Some(ref ws_sel) = rule.workspace {
                 let maybe_idx: Option<usize> = match ws_sel {
@@ -1235,27 +1389,27 @@ impl VirtualWorkspaceManager {
             };
 
             if let Some(existing_ws) = existing_assignment {
-                if rule.floating {
+                if floating {
                     self.window_rule_floating.insert((space, window_id), true);
                 } else {
                     self.window_rule_floating.remove(&(space, window_id));
                 }
                 return Ok(AppRuleResult::Managed(AppRuleAssignment {
                     workspace_id: existing_ws,
-                    floating: rule.floating,
+                    floating,
                     prev_rule_decision,
                 }));
             }
 
             if self.assign_window_to_workspace(space, window_id, target_workspace_id) {
-                if rule.floating {
+                if floating {
                     self.window_rule_floating.insert((space, window_id), true);
                 } else {
                     self.window_rule_floating.remove(&(space, window_id));
                 }
                 return Ok(AppRuleResult::Managed(AppRuleAssignment {
                     workspace_id: target_workspace_id,
-                    floating: rule.floating,
+                    floating,
                     prev_rule_decision,
                 }));
             } else {
@@ -1264,20 +1418,28 @@ impl VirtualWorkspaceManager {
         }
 
         if let Some(existing_ws) = existing_assignment {
-            self.window_rule_floating.remove(&(space, window_id));
+            if auto_float {
+                self.window_rule_floating.insert((space, window_id), true);
+            } else {
+                self.window_rule_floating.remove(&(space, window_id));
+            }
             return Ok(AppRuleResult::Managed(AppRuleAssignment {
                 workspace_id: existing_ws,
-                floating: false,
+                floating: auto_float,
                 prev_rule_decision,
             }));
         }
 
         let default_workspace_id = self.get_default_workspace(space)?;
         if self.assign_window_to_workspace(space, window_id, default_workspace_id) {
-            self.window_rule_floating.remove(&(space, window_id));
+            if auto_float {
+                self.window_rule_floating.insert((space, window_id), true);
+            } else {
+                self.window_rule_floating.remove(&(space, window_id));
+            }
             Ok(AppRuleResult::Managed(AppRuleAssignment {
                 workspace_id: default_workspace_id,
-                floating: false,
+                floating: auto_float,
                 prev_rule_decision,
             }))
         } else {
@@ -1286,6 +1448,99 @@ impl VirtualWorkspaceManager {
         }
     }
 
+    /// Display (UUID or name) that a managed app rule pins the window to, if any.
+    pub fn app_rule_display(
+        &self,
+        app_bundle_id: Option<&str>,
+        app_name: Option<&str>,
+        window_title: Option<&str>,
+        ax_role: Option<&str>,
+        ax_subrole: Option<&str>,
+    ) -> Option<&str> {
+        self.find_matching_app_rule(app_bundle_id, app_name, window_title, ax_role, ax_subrole)
+            .filter(|rule| rule.manage)
+            .and_then(|rule| rule.display.as_deref())
+    }
+
+    /// Whether a managed app rule assigns the window to a specific workspace.
+    pub fn app_rule_pins_workspace(
+        &self,
+        app_bundle_id: Option<&str>,
+        app_name: Option<&str>,
+        window_title: Option<&str>,
+        ax_role: Option<&str>,
+        ax_subrole: Option<&str>,
+    ) -> bool {
+        self.find_matching_app_rule(app_bundle_id, app_name, window_title, ax_role, ax_subrole)
+            .is_some_and(|rule| rule.manage && rule.workspace.is_some())
+    }
+
+    /// The configured auto-float subrole that `ax_role`/`ax_subrole` describe, if any.
+    pub fn auto_float_subrole<'a>(
+        &self,
+        ax_role: Option<&str>,
+        ax_subrole: Option<&'a str>,
+    ) -> Option<&'a str> {
+        if ax_role != Some(AX_WINDOW_ROLE) {
+            return None;
+        }
+        ax_subrole.filter(|subrole| self.auto_float_subroles.iter().any(|s| s == subrole))
+    }
+
+    /// Whether a managed app rule keeps the window on the display it is assigned to.
+    pub fn app_rule_confines_to_display(
+        &self,
+        app_bundle_id: Option<&str>,
+        app_name: Option<&str>,
+        window_title: Option<&str>,
+        ax_role: Option<&str>,
+        ax_subrole: Option<&str>,
+    ) -> bool {
+        self.find_matching_app_rule(app_bundle_id, app_name, window_title, ax_role, ax_subrole)
+            .is_some_and(|rule| rule.manage && rule.confine_to_display)
+    }
+
+    /// Whether a managed app rule keeps hovering the window from raising it.
+    pub fn app_rule_disables_focus_follows_mouse(
+        &self,
+        app_bundle_id: Option<&str>,
+        app_name: Option<&str>,
+        window_title: Option<&str>,
+        ax_role: Option<&str>,
+        ax_subrole: Option<&str>,
+    ) -> bool {
+        self.find_matching_app_rule(app_bundle_id, app_name, window_title, ax_role, ax_subrole)
+            .is_some_and(|rule| rule.manage && rule.disable_focus_follows_mouse)
+    }
+
+    /// The opacity a managed app rule sets for the window, if any.
+    pub fn app_rule_opacity(
+        &self,
+        app_bundle_id: Option<&str>,
+        app_name: Option<&str>,
+        window_title: Option<&str>,
+        ax_role: Option<&str>,
+        ax_subrole: Option<&str>,
+    ) -> Option<f32> {
+        self.find_matching_app_rule(app_bundle_id, app_name, window_title, ax_role, ax_subrole)
+            .filter(|rule| rule.manage)
+            .and_then(|rule| rule.opacity)
+    }
+
+    /// The size limits a managed app rule sets in place of the inferred ones, if any.
+    pub fn app_rule_constraints(
+        &self,
+        app_bundle_id: Option<&str>,
+        app_name: Option<&str>,
+        window_title: Option<&str>,
+        ax_role: Option<&str>,
+        ax_subrole: Option<&str>,
+    ) -> Option<WindowConstraintOverrides> {
+        self.find_matching_app_rule(app_bundle_id, app_name, window_title, ax_role, ax_subrole)
+            .filter(|rule| rule.manage)
+            .and_then(|rule| rule.constraints)
+    }
+
     fn get_default_workspace(
         &mut self,
         space: SpaceId,
@@ -1508,6 +1763,9 @@ impl VirtualWorkspaceManager {
 pub struct FloatingWindowPositions {
     #[serde_as(as = "HashMap<_, CGRectDef>")]
     positions: HashMap<WindowId, CGRect>,
+    /// Floating windows bottom to top as of when the workspace was last left.
+    #[serde(default)]
+    stacking_order: Vec<WindowId>,
 }
 
 impl FloatingWindowPositions {
@@ -1524,6 +1782,7 @@ impl FloatingWindowPositions {
     }
 
     fn remove_position(&mut self, window_id: WindowId) -> Option<CGRect> {
+        self.stacking_order.retain(|&wid| wid != window_id);
         self.positions.remove(&window_id)
     }
 
@@ -1531,6 +1790,7 @@ impl FloatingWindowPositions {
 
     fn remove_app_windows(&mut self, pid: pid_t) {
         self.positions.retain(|window_id, _| window_id.pid != pid);
+        self.stacking_order.retain(|window_id| window_id.pid != pid);
     }
 }
 
@@ -1733,6 +1993,10 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // Match by app_name -> workspace 1
             AppWorkspaceRule {
@@ -1745,6 +2009,10 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // Title substring -> workspace 0
             AppWorkspaceRule {
@@ -1757,6 +2025,10 @@ mod tests {
                 title_substring: Some("Preferences".into()),
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // Title regex -> workspace 2
             AppWorkspaceRule {
@@ -1769,6 +2041,10 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // AX role + subrole floating
             AppWorkspaceRule {
@@ -1781,6 +2057,10 @@ mod tests {
                 title_substring: None,
                 ax_role: Some("AXWindow".into()),
                 ax_subrole: Some("AXDialog".into()),
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // Workspace by name
             AppWorkspaceRule {
@@ -1793,6 +2073,10 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // Specificity tie breaking generic vs substring (generic workspace 0, specific workspace 2)
             AppWorkspaceRule {
@@ -1805,6 +2089,10 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             AppWorkspaceRule {
                 app_id: Some("com.example.tie".into()),
@@ -1816,6 +2104,10 @@ mod tests {
                 title_substring: Some("Editor".into()),
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // Reapplication: Bitwarden title becomes floating
             AppWorkspaceRule {
@@ -1828,6 +2120,10 @@ mod tests {
                 title_substring: Some("Bitwarden".into()),
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             AppWorkspaceRule {
                 app_id: Some("app.zen-browser.zen".into()),
@@ -1839,6 +2135,10 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             // Workspace override when specific rule matches different workspace + floating
             AppWorkspaceRule {
@@ -1851,6 +2151,10 @@ mod tests {
                 title_substring: None,
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
             AppWorkspaceRule {
                 app_id: Some("app.zen-browser.zen".into()),
@@ -1862,6 +2166,10 @@ mod tests {
                 title_substring: Some("bitwarden".into()),
                 ax_role: None,
                 ax_subrole: None,
+                display: None,
+                confine_to_display: false,
+                opacity: None,
+                ..Default::default()
             },
         ];
 
@@ -9,7 +9,10 @@ pub use nix::libc::pid_t;
 use objc2::rc::Retained;
 use objc2::runtime::AnyObject;
 use objc2::{AnyThread, DefinedClass, define_class, msg_send};
-use objc2_app_kit::{NSApplicationActivationPolicy, NSRunningApplication, NSWorkspace};
+use objc2_app_kit::{
+    NSApplicationActivationOptions, NSApplicationActivationPolicy, NSRunningApplication,
+    NSWorkspace,
+};
 use objc2_core_foundation::{CGRect, CGSize};
 use objc2_foundation::{NSCopying, NSObject, NSObjectProtocol, NSString, ns_string};
 use once_cell::sync::Lazy;
@@ -353,6 +356,13 @@ pub fn running_apps(bundle: Option<String>) -> impl Iterator<Item = (pid_t, AppI
         })
 }
 
+/// Activates rift itself. Rift has no regular windows, so no app is left holding the
+/// key window until something else is focused.
+pub fn resign_key_window() -> bool {
+    NSRunningApplication::currentApplication()
+        .activateWithOptions(NSApplicationActivationOptions::empty())
+}
+
 pub trait NSRunningApplicationExt {
     fn with_process_id(pid: pid_t) -> Option<Retained<Self>>;
     fn pid(&self) -> pid_t;
@@ -708,6 +708,20 @@ pub fn modifier_flag_for_key(key_code: KeyCode) -> Option<CGEventFlags> {
 
 pub fn is_modifier_key(key_code: KeyCode) -> bool { modifier_flag_for_key(key_code).is_some() }
 
+/// The keys a modifier-only hotkey can be pressed with: both keys of its family
+/// when no side was named (e.g. "Alt"), otherwise just its own key.
+pub fn modifier_keys_for_hotkey(hotkey: &Hotkey) -> Vec<KeyCode> {
+    for m in MOD_FAMILIES {
+        if hotkey.key_code == m.left_key || hotkey.key_code == m.right_key {
+            if hotkey.modifiers.contains(m.generic) {
+                return vec![m.left_key, m.right_key];
+            }
+            break;
+        }
+    }
+    vec![hotkey.key_code]
+}
+
 pub fn key_code_from_event(event: &CGEvent) -> Option<KeyCode> {
     let raw = CGEvent::integer_value_field(Some(event), CGEventField::KeyboardEventKeycode);
     if raw < 0 {
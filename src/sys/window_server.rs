@@ -153,7 +153,6 @@ impl WindowQuery {
     pub fn bounds(&self) -> CGRect { unsafe { SLSWindowIteratorGetBounds(self.iter) } }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn tags(&self) -> u64 { unsafe { SLSWindowIteratorGetTags(self.iter) } }
 
     #[inline]
@@ -204,6 +203,9 @@ pub struct WindowServerInfo {
     pub min_frame: CGSize,
     #[serde(with = "CGSizeDef")]
     pub max_frame: CGSize,
+    /// Set when the window server tags the window as present on all spaces.
+    #[serde(default)]
+    pub sticky: bool,
 }
 
 pub fn get_visible_windows_with_layer(layer: Option<i32>) -> Vec<WindowServerInfo> {
@@ -331,6 +333,7 @@ fn make_info(
             frame: cg_frame,
             min_frame: CGSize::ZERO,
             max_frame: CGSize::ZERO,
+            sticky: false,
         });
     }
 
@@ -347,6 +350,7 @@ pub fn get_windows(ids: &[WindowServerId]) -> Vec<WindowServerInfo> {
             frame: CGRect::new(CGPoint::new(0.0, 0.0), CGSize::new(800.0, 600.0)),
             min_frame: CGSize::ZERO,
             max_frame: CGSize::ZERO,
+            sticky: false,
         })
         .collect()
 }
@@ -374,6 +378,7 @@ pub fn get_windows(ids: &[WindowServerId]) -> Vec<WindowServerInfo> {
             frame: query.bounds(),
             min_frame,
             max_frame,
+            sticky: (query.tags() & SLSWindowTags::Sticky) != 0,
         });
     }
     out
@@ -419,6 +424,24 @@ pub fn get_window_at_point(mut point: CGPoint) -> Option<WindowServerId> {
     }
 }
 
+#[cfg(test)]
+thread_local! {
+    static TEST_CURSOR_LOCATION: std::cell::Cell<Option<CGPoint>> =
+        const { std::cell::Cell::new(None) };
+}
+
+/// Pretends the cursor is at `point` for `current_cursor_location` on this thread.
+#[cfg(test)]
+pub fn set_test_cursor_location(point: Option<CGPoint>) {
+    TEST_CURSOR_LOCATION.with(|location| location.set(point));
+}
+
+#[cfg(test)]
+pub fn current_cursor_location() -> Result<CGPoint, CGError> {
+    TEST_CURSOR_LOCATION.with(|location| location.get()).ok_or(CGError::Failure)
+}
+
+#[cfg(not(test))]
 pub fn current_cursor_location() -> Result<CGPoint, CGError> {
     let mut point = CGPoint::new(0.0, 0.0);
     cg_ok(unsafe { SLSGetCurrentCursorLocation(*G_CONNECTION, &mut point) })?;
@@ -430,6 +453,34 @@ pub fn window_under_cursor() -> Option<WindowServerId> {
     get_window_at_point(point)
 }
 
+#[cfg(test)]
+thread_local! {
+    static TEST_WINDOW_ALPHA: std::cell::Cell<(u32, Option<CGError>)> =
+        const { std::cell::Cell::new((0, None)) };
+}
+
+/// Makes `set_window_alpha` fail with `err` on this thread.
+#[cfg(test)]
+pub fn set_test_window_alpha_error(err: Option<CGError>) {
+    TEST_WINDOW_ALPHA.with(|alpha| alpha.set((alpha.get().0, err)));
+}
+
+/// How many times `set_window_alpha` was called on this thread.
+#[cfg(test)]
+pub fn test_window_alpha_calls() -> u32 { TEST_WINDOW_ALPHA.with(|alpha| alpha.get().0) }
+
+#[cfg(test)]
+pub fn set_window_alpha(_id: WindowServerId, _alpha: f32) -> Result<(), CGError> {
+    let (calls, err) = TEST_WINDOW_ALPHA.with(|alpha| alpha.get());
+    TEST_WINDOW_ALPHA.with(|alpha| alpha.set((calls + 1, err)));
+    err.map_or(Ok(()), Err)
+}
+
+#[cfg(not(test))]
+pub fn set_window_alpha(id: WindowServerId, alpha: f32) -> Result<(), CGError> {
+    cg_ok(unsafe { SLSSetWindowAlpha(*G_CONNECTION, id.as_u32(), alpha) })
+}
+
 #[cfg(test)]
 pub fn window_level(_wid: u32) -> Option<NSWindowLevel> { Some(0) }
 
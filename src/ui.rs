@@ -1,4 +1,5 @@
 pub mod common;
+pub mod focus_border;
 pub mod menu_bar;
 pub mod mission_control;
 pub mod stack_line;
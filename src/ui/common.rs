@@ -1,25 +1,141 @@
 use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 use objc2_core_foundation::{CFType, CGPoint, CGRect, CGSize};
 use objc2_core_graphics::CGContext;
 use objc2_quartz_core::{CALayer, CATransaction};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tracing::warn;
 
+use crate::common::collections::HashMap;
 use crate::model::server::WindowData;
 use crate::sys::skylight::{
     CFRelease, G_CONNECTION, SLSFlushWindowContentRegion, SLWindowContextCreate,
 };
 
+/// Consecutive context failures after which a window stops being rendered.
+const MAX_CONSECUTIVE_RENDER_FAILURES: u32 = 5;
+/// How long a failing window is skipped before rendering is attempted again.
+/// CGS window ids get reused, so the window is never disabled for good.
+const RENDER_DISABLED_FOR: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct RenderFailure {
+    consecutive: u32,
+    disabled_until: Option<Instant>,
+}
+
+static RENDER_FAILURES: Lazy<Mutex<HashMap<u32, RenderFailure>>> =
+    Lazy::new(|| Mutex::new(HashMap::default()));
+/// Set while `RENDER_FAILURES` is non-empty so successful renders can skip the lock.
+static HAS_RENDER_FAILURES: AtomicBool = AtomicBool::new(false);
+static TOTAL_RENDER_FAILURES: AtomicU64 = AtomicU64::new(0);
+static TOTAL_RENDER_DISABLES: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct RenderFailureStats {
+    pub total_failures: u64,
+    pub total_disables: u64,
+    pub failing_windows: usize,
+    pub disabled_windows: usize,
+}
+
+pub fn render_failure_stats() -> RenderFailureStats {
+    let now = Instant::now();
+    let failures = RENDER_FAILURES.lock();
+    RenderFailureStats {
+        total_failures: TOTAL_RENDER_FAILURES.load(Ordering::Relaxed),
+        total_disables: TOTAL_RENDER_DISABLES.load(Ordering::Relaxed),
+        failing_windows: failures.len(),
+        disabled_windows: failures
+            .values()
+            .filter(|f| f.disabled_until.is_some_and(|until| until > now))
+            .count(),
+    }
+}
+
+fn render_disabled(window_id: u32) -> bool {
+    if !HAS_RENDER_FAILURES.load(Ordering::Relaxed) {
+        return false;
+    }
+    let failures = RENDER_FAILURES.lock();
+    failures
+        .get(&window_id)
+        .and_then(|f| f.disabled_until)
+        .is_some_and(|until| until > Instant::now())
+}
+
+fn record_render_failure(window_id: u32) {
+    TOTAL_RENDER_FAILURES.fetch_add(1, Ordering::Relaxed);
+    let mut failures = RENDER_FAILURES.lock();
+    let failure = failures.entry(window_id).or_default();
+    failure.consecutive += 1;
+    HAS_RENDER_FAILURES.store(true, Ordering::Relaxed);
+    if failure.consecutive >= MAX_CONSECUTIVE_RENDER_FAILURES {
+        TOTAL_RENDER_DISABLES.fetch_add(1, Ordering::Relaxed);
+        warn!(
+            window_id,
+            failures = failure.consecutive,
+            "SLWindowContextCreate keeps failing; pausing rendering for this window"
+        );
+        failure.consecutive = 0;
+        failure.disabled_until = Some(Instant::now() + RENDER_DISABLED_FOR);
+    }
+}
+
+/// Drops the failure record of an overlay window that is being torn down, since
+/// its id may be handed to an unrelated window next.
+pub fn forget_render_failures(window_id: u32) { clear_render_failures(window_id); }
+
+fn clear_render_failures(window_id: u32) {
+    if !HAS_RENDER_FAILURES.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut failures = RENDER_FAILURES.lock();
+    failures.remove(&window_id);
+    if failures.is_empty() {
+        HAS_RENDER_FAILURES.store(false, Ordering::Relaxed);
+    }
+}
+
 pub fn render_layer_to_cgs_window(window_id: u32, size: CGSize, layer: &CALayer) {
+    render_scaled_layer_to_cgs_window(window_id, size, layer, 1.0);
+}
+
+/// Like [`render_layer_to_cgs_window`], but draws the layer scaled by `scale`
+/// around the center of the window.
+pub fn render_scaled_layer_to_cgs_window(
+    window_id: u32,
+    size: CGSize,
+    layer: &CALayer,
+    scale: f64,
+) {
+    if render_disabled(window_id) {
+        return;
+    }
     unsafe {
         let ctx: *mut CGContext =
             SLWindowContextCreate(*G_CONNECTION, window_id, ptr::null_mut() as *mut CFType);
         if ctx.is_null() {
+            record_render_failure(window_id);
             return;
         }
+        clear_render_failures(window_id);
 
         let clear = CGRect::new(CGPoint::new(0.0, 0.0), size);
         CGContext::clear_rect(Some(&*ctx), clear);
         CGContext::save_g_state(Some(&*ctx));
+        if scale != 1.0 {
+            CGContext::translate_ctm(
+                Some(&*ctx),
+                size.width * (1.0 - scale) / 2.0,
+                size.height * (1.0 - scale) / 2.0,
+            );
+            CGContext::scale_ctm(Some(&*ctx), scale, scale);
+        }
         CGContext::translate_ctm(Some(&*ctx), 0.0, size.height);
         CGContext::scale_ctm(Some(&*ctx), 1.0, -1.0);
         layer.renderInContext(&*ctx);
@@ -47,15 +163,29 @@ pub struct WindowLayoutMetrics {
     pub min_x: f64,
     pub min_y: f64,
     pub disp_h: f64,
+    /// Area the rects are drawn in; `rect_for` never returns a rect outside it.
+    pub content: CGRect,
+}
+
+/// `(min_x, min_y, max_x, max_y)` of `frame`, also for frames with a negative size.
+fn frame_extent(frame: CGRect) -> (f64, f64, f64, f64) {
+    let x1 = frame.origin.x + frame.size.width;
+    let y1 = frame.origin.y + frame.size.height;
+    (
+        frame.origin.x.min(x1),
+        frame.origin.y.min(y1),
+        frame.origin.x.max(x1),
+        frame.origin.y.max(y1),
+    )
 }
 
 impl WindowLayoutMetrics {
     pub fn rect_for(&self, window: &WindowData, min_size: f64, gap: f64) -> CGRect {
-        let wx = window.info.frame.origin.x - self.min_x;
-        let wy_top = window.info.frame.origin.y - self.min_y + window.info.frame.size.height;
-        let wy = self.disp_h - wy_top;
-        let ww = window.info.frame.size.width;
-        let wh = window.info.frame.size.height;
+        let (x0, y0, x1, y1) = frame_extent(window.info.frame);
+        let wx = x0 - self.min_x;
+        let wy = self.disp_h - (y1 - self.min_y);
+        let ww = x1 - x0;
+        let wh = y1 - y0;
 
         let mut rx = self.x_offset + wx * self.scale;
         let mut ry = self.y_offset + wy * self.scale;
@@ -71,6 +201,13 @@ impl WindowLayoutMetrics {
             rh -= gap;
         }
 
+        // `min_size` can grow a tile past the edge of the content; keep it inside.
+        let content = self.content;
+        rw = rw.min(content.size.width);
+        rh = rh.min(content.size.height);
+        rx = rx.clamp(content.origin.x, content.origin.x + content.size.width - rw);
+        ry = ry.clamp(content.origin.y, content.origin.y + content.size.height - rh);
+
         CGRect::new(CGPoint::new(rx, ry), CGSize::new(rw, rh))
     }
 }
@@ -82,32 +219,24 @@ pub fn compute_window_layout_metrics(
     scale_factor: f64,
     max_scale: Option<f64>,
 ) -> Option<WindowLayoutMetrics> {
-    if windows.is_empty() {
-        return None;
-    }
-
     let mut min_x = f64::INFINITY;
     let mut min_y = f64::INFINITY;
     let mut max_x = f64::NEG_INFINITY;
     let mut max_y = f64::NEG_INFINITY;
 
     for w in windows {
-        let x0 = w.info.frame.origin.x;
-        let y0 = w.info.frame.origin.y;
-        let x1 = x0 + w.info.frame.size.width;
-        let y1 = y0 + w.info.frame.size.height;
-        if x0 < min_x {
-            min_x = x0;
-        }
-        if y0 < min_y {
-            min_y = y0;
-        }
-        if x1 > max_x {
-            max_x = x1;
-        }
-        if y1 > max_y {
-            max_y = y1;
+        let (x0, y0, x1, y1) = frame_extent(w.info.frame);
+        if ![x0, y0, x1, y1].iter().all(|v| v.is_finite()) {
+            continue;
         }
+        min_x = min_x.min(x0);
+        min_y = min_y.min(y0);
+        max_x = max_x.max(x1);
+        max_y = max_y.max(y1);
+    }
+
+    if !min_x.is_finite() {
+        return None;
     }
 
     let disp_w = (max_x - min_x).max(1.0);
@@ -132,5 +261,121 @@ pub fn compute_window_layout_metrics(
         min_x,
         min_y,
         disp_h,
+        content: CGRect::new(CGPoint::new(cx, cy), CGSize::new(cw, ch)),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::app::WindowId;
+    use crate::sys::app::WindowInfo;
+    use crate::sys::geometry::SameAs;
+
+    fn window(idx: u32, frame: CGRect) -> WindowData {
+        WindowData {
+            id: WindowId::new(1, idx),
+            is_floating: false,
+            is_focused: false,
+            app_name: None,
+            info: WindowInfo {
+                is_standard: true,
+                is_root: true,
+                is_minimized: false,
+                is_resizable: true,
+                min_size: None,
+                max_size: None,
+                title: String::new(),
+                frame,
+                sys_id: None,
+                bundle_id: None,
+                path: None,
+                ax_role: None,
+                ax_subrole: None,
+            },
+        }
+    }
+
+    fn contains(outer: CGRect, inner: CGRect) -> bool {
+        const EPS: f64 = 1e-9;
+        inner.origin.x >= outer.origin.x - EPS
+            && inner.origin.y >= outer.origin.y - EPS
+            && inner.origin.x + inner.size.width <= outer.origin.x + outer.size.width + EPS
+            && inner.origin.y + inner.size.height <= outer.origin.y + outer.size.height + EPS
+    }
+
+    #[test]
+    fn repeated_render_failures_pause_a_window_until_it_is_retried() {
+        // An id no real window gets, since the failure table is shared.
+        let window_id = u32::MAX - 7;
+        for _ in 1..MAX_CONSECUTIVE_RENDER_FAILURES {
+            record_render_failure(window_id);
+        }
+        assert!(!render_disabled(window_id));
+        record_render_failure(window_id);
+        assert!(render_disabled(window_id));
+
+        // Once the pause is over the window is tried again, and failures count
+        // from zero.
+        RENDER_FAILURES.lock().get_mut(&window_id).unwrap().disabled_until =
+            Some(Instant::now() - Duration::from_millis(1));
+        assert!(!render_disabled(window_id));
+        record_render_failure(window_id);
+        assert!(!render_disabled(window_id));
+
+        forget_render_failures(window_id);
+        assert!(!RENDER_FAILURES.lock().contains_key(&window_id));
+    }
+
+    #[test]
+    fn rects_stay_in_bounds_with_a_display_at_negative_coordinates() {
+        // A monitor to the left of (and slightly above) the primary display, with a
+        // fractional frame as scaled resolutions produce.
+        let left = window(
+            1,
+            CGRect::new(CGPoint::new(-1440.5, -120.25), CGSize::new(1440.5, 900.75)),
+        );
+        let primary = window(2, CGRect::new(CGPoint::new(0., 0.), CGSize::new(1920., 1080.)));
+        // Grown to `min_size`, this would poke out past the right edge.
+        let tiny = window(3, CGRect::new(CGPoint::new(1919., -120.25), CGSize::new(1., 1.)));
+        let windows = vec![left, primary, tiny];
+        let bounds = CGRect::new(CGPoint::new(-50., 30.), CGSize::new(400., 250.));
+
+        let layout = compute_window_layout_metrics(&windows, bounds, 8.0, 1.0, None).unwrap();
+        assert_eq!(layout.min_x, -1440.5);
+        assert_eq!(layout.min_y, -120.25);
+
+        let rects: Vec<CGRect> = windows.iter().map(|w| layout.rect_for(w, 20.0, 1.0)).collect();
+        for rect in &rects {
+            assert!(
+                contains(layout.content, *rect),
+                "{rect:?} not in {:?}",
+                layout.content
+            );
+            assert!(contains(bounds, *rect));
+        }
+        // The left monitor's window is drawn left of the primary one, and higher up
+        // (the rects use a bottom-left origin).
+        assert!(rects[0].origin.x + rects[0].size.width <= rects[1].origin.x + 1.0);
+        assert!(
+            rects[0].origin.y + rects[0].size.height > rects[1].origin.y + rects[1].size.height
+        );
+    }
+
+    #[test]
+    fn frames_with_negative_sizes_are_normalized() {
+        let flipped = window(
+            1,
+            CGRect::new(CGPoint::new(-100., 50.), CGSize::new(-200., -50.)),
+        );
+        let normal = window(2, CGRect::new(CGPoint::new(-300., 0.), CGSize::new(200., 50.)));
+        let bounds = CGRect::new(CGPoint::new(0., 0.), CGSize::new(100., 100.));
+
+        let layout =
+            compute_window_layout_metrics(&[flipped.clone()], bounds, 0.0, 1.0, None).unwrap();
+        assert_eq!((layout.min_x, layout.min_y), (-300., 0.));
+        let from_flipped = layout.rect_for(&flipped, 0.0, 0.0);
+        assert!(from_flipped.same_as(layout.rect_for(&normal, 0.0, 0.0)));
+        assert!(contains(bounds, from_flipped));
+    }
+}
@@ -0,0 +1,111 @@
+use objc2::rc::Retained;
+use objc2_app_kit::NSStatusWindowLevel;
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use objc2_quartz_core::CALayer;
+use tracing::warn;
+
+use crate::common::config::FocusBorderSettings;
+use crate::sys::cgs_window::{CgsWindow, CgsWindowError};
+use crate::ui::common::{
+    forget_render_failures, render_layer_to_cgs_window, with_disabled_actions,
+};
+use crate::ui::stack_line::Color;
+
+#[derive(Debug, Clone, Copy)]
+pub struct FocusBorderConfig {
+    pub color: Color,
+    pub thickness: f64,
+}
+
+impl From<&FocusBorderSettings> for FocusBorderConfig {
+    fn from(settings: &FocusBorderSettings) -> Self {
+        let [r, g, b, a] = settings.color;
+        Self {
+            color: Color::new(r, g, b, a),
+            thickness: settings.thickness,
+        }
+    }
+}
+
+/// A click-through overlay window that outlines the focused window.
+pub struct FocusBorderWindow {
+    frame: CGRect,
+    config: FocusBorderConfig,
+    root_layer: Retained<CALayer>,
+    cgs_window: CgsWindow,
+}
+
+impl FocusBorderWindow {
+    pub fn new(window_frame: CGRect, config: FocusBorderConfig) -> Result<Self, CgsWindowError> {
+        let frame = Self::border_frame(window_frame, config.thickness);
+        let cgs_window = CgsWindow::new(frame)?;
+        if let Err(err) = cgs_window.set_opacity(false) {
+            warn!(error=?err, "failed to set focus border window opacity");
+        }
+        if let Err(err) = cgs_window.set_alpha(1.0) {
+            warn!(error=?err, "failed to set focus border window alpha");
+        }
+        if let Err(err) = cgs_window.set_level(NSStatusWindowLevel as i32) {
+            warn!(error=?err, "failed to set focus border window level");
+        }
+
+        let border = Self {
+            frame,
+            config,
+            root_layer: CALayer::layer(),
+            cgs_window,
+        };
+        border.update_layer();
+        Ok(border)
+    }
+
+    /// Outline `window_frame` and bring the border on screen.
+    pub fn show(&mut self, window_frame: CGRect) -> Result<(), CgsWindowError> {
+        let frame = Self::border_frame(window_frame, self.config.thickness);
+        if frame != self.frame {
+            self.cgs_window.set_shape(frame)?;
+            self.frame = frame;
+            self.update_layer();
+        }
+        self.present();
+        self.cgs_window.order_above(None)
+    }
+
+    pub fn hide(&self) -> Result<(), CgsWindowError> { self.cgs_window.order_out() }
+
+    pub fn set_config(&mut self, config: FocusBorderConfig) {
+        self.config = config;
+        self.update_layer();
+    }
+
+    /// The border is drawn just outside the window so it never covers content.
+    fn border_frame(window_frame: CGRect, thickness: f64) -> CGRect {
+        CGRect::new(
+            CGPoint::new(
+                window_frame.origin.x - thickness,
+                window_frame.origin.y - thickness,
+            ),
+            CGSize::new(
+                window_frame.size.width + thickness * 2.0,
+                window_frame.size.height + thickness * 2.0,
+            ),
+        )
+    }
+
+    fn update_layer(&self) {
+        with_disabled_actions(|| {
+            self.root_layer.setFrame(CGRect::new(CGPoint::new(0.0, 0.0), self.frame.size));
+            self.root_layer.setBorderWidth(self.config.thickness);
+            let color = self.config.color.to_nscolor();
+            self.root_layer.setBorderColor(Some(&color.CGColor()));
+        });
+    }
+
+    fn present(&self) {
+        render_layer_to_cgs_window(self.cgs_window.id(), self.frame.size, &self.root_layer);
+    }
+}
+
+impl Drop for FocusBorderWindow {
+    fn drop(&mut self) { forget_render_failures(self.cgs_window.id()); }
+}
@@ -1,8 +1,9 @@
 use core::ffi::c_void;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
 
 use crossbeam_channel::{Sender, unbounded};
 use dispatchr::queue;
@@ -36,7 +37,8 @@ use crate::sys::screen::{
 };
 use crate::sys::window_server::{CapturedWindowImage, WindowServerId};
 use crate::ui::common::{
-    compute_window_layout_metrics, render_layer_to_cgs_window, with_disabled_actions,
+    compute_window_layout_metrics, forget_render_failures, render_layer_to_cgs_window,
+    render_scaled_layer_to_cgs_window, with_disabled_actions,
 };
 
 #[derive(Debug, Clone)]
@@ -121,37 +123,47 @@ extern "C" fn refresh_coalesced_cb(ctx: *mut c_void) {
     overlay.refresh_previews();
 }
 
-struct FadeCompletionCtx {
+/// Overlay scale at the start of a scale-in (and the end of a scale-out).
+const FADE_START_SCALE: f64 = 0.96;
+
+struct FadeStepCtx {
     overlay_ptr_bits: usize,
     fade_id: u64,
-    final_alpha: f32,
+    // Holds the id of the overlay's running fade. The overlay owns the only
+    // strong reference, so a failed upgrade means it has been dropped and the
+    // pointer must not be touched.
+    active_fade: Weak<Cell<u64>>,
 }
 
-extern "C" fn fade_completion_callback(ctx: *mut c_void) {
+extern "C" fn fade_step_callback(ctx: *mut c_void) {
     if ctx.is_null() {
         return;
     }
     unsafe {
-        let boxed = Box::from_raw(ctx as *mut FadeCompletionCtx);
-        if boxed.overlay_ptr_bits == 0 {
+        let boxed = Box::from_raw(ctx as *mut FadeStepCtx);
+        let Some(active_fade) = boxed.active_fade.upgrade() else {
+            return;
+        };
+        if boxed.overlay_ptr_bits == 0 || active_fade.get() != boxed.fade_id {
             return;
         }
         if let Some(overlay) = (boxed.overlay_ptr_bits as *const MissionControlOverlay).as_ref() {
-            overlay.finish_fade(boxed.fade_id, boxed.final_alpha);
+            overlay.step_fade(boxed.fade_id);
         }
     }
 }
 
-fn schedule_fade_completion(overlay_ptr_bits: usize, fade_id: u64, final_alpha: f32) {
+fn schedule_fade_step(overlay_ptr_bits: usize, fade_id: u64, active_fade: &Rc<Cell<u64>>) {
     if overlay_ptr_bits == 0 {
         return;
     }
-    let ctx = Box::into_raw(Box::new(FadeCompletionCtx {
+    let ctx = Box::into_raw(Box::new(FadeStepCtx {
         overlay_ptr_bits,
         fade_id,
-        final_alpha,
+        active_fade: Rc::downgrade(active_fade),
     })) as *mut c_void;
-    queue::main().after_f(Time::NOW, ctx, fade_completion_callback);
+    // Roughly one step per frame at 60Hz.
+    queue::main().after_f(Time::new_after(Time::NOW, 16_000_000), ctx, fade_step_callback);
 }
 
 static WORKSPACE_BACKGROUND_COLOR: Lazy<Retained<CGColor>> =
@@ -182,9 +194,20 @@ pub enum MissionControlAction {
         window_id: WindowId,
         window_server_id: Option<WindowServerId>,
     },
+    /// Move a window to the workspace before or after its own, on the same display.
+    MoveWindowToWorkspace {
+        window_id: WindowId,
+        target: WorkspaceStep,
+    },
     Dismiss,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceStep {
+    Previous,
+    Next,
+}
+
 struct WorkspaceLabelText {
     text: String,
     attributed: CFRetained<CFString>,
@@ -537,6 +560,10 @@ enum WindowLayoutKind {
 
 struct FadeState {
     id: u64,
+    started: Instant,
+    duration_ms: f64,
+    from: f32,
+    to: f32,
 }
 
 impl MissionControlOverlay {
@@ -1115,6 +1142,26 @@ impl MissionControlOverlay {
         }
     }
 
+    fn move_selected_window_action(&self, target: WorkspaceStep) {
+        let action = {
+            let mut state = self.state.borrow_mut();
+            state.ensure_selection();
+            match (state.mode(), state.selection()) {
+                (
+                    Some(MissionControlMode::CurrentWorkspace(windows)),
+                    Some(Selection::Window(idx)),
+                ) => windows.get(idx.min(windows.len().saturating_sub(1))).map(|window| {
+                    MissionControlAction::MoveWindowToWorkspace { window_id: window.id, target }
+                }),
+                _ => None,
+            }
+        };
+
+        if let Some(action) = action {
+            self.emit_action(action);
+        }
+    }
+
     fn visible_workspaces<'a>(workspaces: &'a [WorkspaceData]) -> Vec<(usize, &'a WorkspaceData)> {
         workspaces
             .iter()
@@ -1612,10 +1659,14 @@ pub struct MissionControlOverlay {
     key_tap: RefCell<Option<crate::sys::event_tap::EventTap>>,
     fade_enabled: bool,
     fade_duration_ms: f64,
+    scale_enabled: bool,
     has_shown: RefCell<bool>,
     state: RefCell<MissionControlState>,
     fade_state: RefCell<Option<FadeState>>,
     fade_counter: AtomicU64,
+    active_fade: Rc<Cell<u64>>,
+    /// How far the overlay is shown, from 0.0 (hidden) to 1.0 (fully shown).
+    fade_progress: Cell<f32>,
     pending_hide: RefCell<bool>,
     refresh_pending: AtomicBool,
     scale: f64,
@@ -1674,10 +1725,13 @@ impl MissionControlOverlay {
             key_tap: RefCell::new(None),
             fade_enabled: config.settings.ui.mission_control.fade_enabled,
             fade_duration_ms: config.settings.ui.mission_control.fade_duration_ms,
+            scale_enabled: config.settings.ui.mission_control.scale_enabled,
             has_shown: RefCell::new(false),
             state: RefCell::new(MissionControlState::default()),
             fade_state: RefCell::new(None),
             fade_counter: AtomicU64::new(0),
+            active_fade: Rc::new(Cell::new(0)),
+            fade_progress: Cell::new(0.0),
             pending_hide: RefCell::new(false),
             refresh_pending: AtomicBool::new(false),
             scale,
@@ -1704,6 +1758,13 @@ impl MissionControlOverlay {
 
     pub fn set_fade_duration_ms(&mut self, ms: f64) { self.fade_duration_ms = ms.max(0.0); }
 
+    pub fn set_scale_enabled(&mut self, enabled: bool) { self.scale_enabled = enabled; }
+
+    fn animates(&self) -> bool { self.fade_enabled || self.scale_enabled }
+
+    /// True once the overlay is fully hidden, including any fade-out.
+    pub fn is_hidden(&self) -> bool { !*self.has_shown.borrow() && !*self.pending_hide.borrow() }
+
     fn current_screen_metrics(&self) -> (ScreenInfo, f64, CoordinateConverter) {
         if let Some((metrics, converter)) = self.gather_screen_metrics() {
             if let Some(cursor_metric) = self.screen_under_cursor_with(&metrics) {
@@ -1740,8 +1801,13 @@ impl MissionControlOverlay {
     }
 
     pub fn update(&self, mode: MissionControlMode) {
-        self.stop_active_fade();
+        // Content updates while fading in keep the fade going; only a pending
+        // fade-out is cancelled.
+        if *self.pending_hide.borrow() {
+            self.stop_active_fade();
+        }
         *self.pending_hide.borrow_mut() = false;
+        let fading_in = self.fade_state.borrow().is_some();
 
         {
             let (screen, scale, converter) = self.current_screen_metrics();
@@ -1786,15 +1852,17 @@ impl MissionControlOverlay {
             st.render_window_id = Some(self.cgs_window.id());
             st.render_size = Some(self.frame.size);
 
-            st.suppress_live_present = false;
+            st.suppress_live_present = fading_in && self.scale_enabled;
         }
         self.prewarm_previews();
 
-        if self.fade_enabled && !*self.has_shown.borrow() {
-            let _ = self.cgs_window.set_alpha(0.0);
-        } else {
-            let _ = self.cgs_window.set_alpha(1.0);
+        // Animate in from wherever a cancelled fade-out left off, so quickly
+        // re-showing the overlay doesn't flash.
+        let animate_in = self.animates() && !*self.has_shown.borrow();
+        if !animate_in && !fading_in {
+            self.fade_progress.set(1.0);
         }
+        let _ = self.cgs_window.set_alpha(self.current_alpha());
         let _ = self.cgs_window.order_above(None);
 
         let app = NSApplication::sharedApplication(self.mtm);
@@ -1803,8 +1871,8 @@ impl MissionControlOverlay {
 
         self.draw_and_present();
 
-        if self.fade_enabled && !*self.has_shown.borrow() {
-            self.fade_in();
+        if animate_in {
+            self.start_fade(1.0);
         }
         *self.has_shown.borrow_mut() = true;
     }
@@ -1817,9 +1885,9 @@ impl MissionControlOverlay {
             prev
         };
 
-        if self.fade_enabled && was_shown {
+        if self.animates() && was_shown {
             *self.pending_hide.borrow_mut() = true;
-            if !self.fade_out() {
+            if !self.start_fade(0.0) {
                 self.finalize_hide();
             }
         } else {
@@ -1841,91 +1909,103 @@ impl MissionControlOverlay {
             let _ = self.cgs_window.set_alpha(1.0);
             CATransaction::flush();
 
+            self.fade_progress.set(0.0);
             *self.has_shown.borrow_mut() = false;
             *self.pending_hide.borrow_mut() = false;
         });
     }
 
-    fn fade_in(&self) {
-        self.stop_active_fade();
-        let duration_ms = self.fade_duration_ms.max(0.0);
-        if duration_ms <= 0.0 {
-            let _ = self.cgs_window.set_alpha(1.0);
-            return;
-        }
-
-        let fade_id = self.fade_counter.fetch_add(1, Ordering::AcqRel) + 1;
-        let overlay_ptr_bits = self as *const MissionControlOverlay as usize;
-
-        CATransaction::begin();
-        CATransaction::setAnimationDuration(duration_ms / 1000.0);
-        self.root_layer.setOpacity(0.0);
-        self.root_layer.setOpacity(1.0);
-
-        CATransaction::commit();
-
-        schedule_fade_completion(overlay_ptr_bits, fade_id, 1.0f32);
-
-        self.fade_state.borrow_mut().replace(FadeState { id: fade_id });
-    }
-
-    fn fade_out(&self) -> bool {
+    /// Starts animating the overlay towards `to` (0.0 hidden, 1.0 shown),
+    /// replacing any fade already running. Returns false if there was
+    /// nothing to animate and the end state was applied immediately.
+    /// The overlay layer is rendered into a CGS window rather than composited
+    /// by CoreAnimation, so the fade is stepped on a timer instead of animated.
+    fn start_fade(&self, to: f32) -> bool {
         self.stop_active_fade();
-        let duration_ms = self.fade_duration_ms.max(0.0);
+        let from = self.fade_progress.get();
+        // A fade that reverses one cut short only covers the remaining distance.
+        let duration_ms = self.fade_duration_ms.max(0.0) * f64::from((to - from).abs());
         if duration_ms <= 0.0 {
-            let _ = self.cgs_window.set_alpha(0.0);
+            self.apply_fade_progress(to);
             return false;
         }
 
         let fade_id = self.fade_counter.fetch_add(1, Ordering::AcqRel) + 1;
         let overlay_ptr_bits = self as *const MissionControlOverlay as usize;
 
-        CATransaction::begin();
-        CATransaction::setAnimationDuration(duration_ms / 1000.0);
+        self.set_live_present_suppressed(self.scale_enabled);
+        self.fade_state.borrow_mut().replace(FadeState {
+            id: fade_id,
+            started: Instant::now(),
+            duration_ms,
+            from,
+            to,
+        });
+        self.active_fade.set(fade_id);
+        schedule_fade_step(overlay_ptr_bits, fade_id, &self.active_fade);
+        true
+    }
 
-        self.root_layer.setOpacity(1.0);
-        self.root_layer.setOpacity(0.0);
+    fn stop_active_fade(&self) {
+        self.fade_state.borrow_mut().take();
+        self.active_fade.set(0);
+    }
 
-        CATransaction::commit();
+    fn step_fade(&self, fade_id: u64) {
+        let Some((t, from, to)) = self.fade_state.try_borrow().ok().and_then(|slot| {
+            let fade = slot.as_ref().filter(|fade| fade.id == fade_id)?;
+            let elapsed_ms = fade.started.elapsed().as_secs_f64() * 1000.0;
+            Some(((elapsed_ms / fade.duration_ms).min(1.0), fade.from, fade.to))
+        }) else {
+            return;
+        };
 
-        schedule_fade_completion(overlay_ptr_bits, fade_id, 0.0f32);
+        // Ease out: quick start, gentle landing.
+        let eased = 1.0 - (1.0 - t).powi(3);
+        self.apply_fade_progress(from + (to - from) * eased as f32);
 
-        self.fade_state.borrow_mut().replace(FadeState { id: fade_id });
-        true
-    }
+        if t < 1.0 {
+            let overlay_ptr_bits = self as *const MissionControlOverlay as usize;
+            schedule_fade_step(overlay_ptr_bits, fade_id, &self.active_fade);
+            return;
+        }
 
-    fn stop_active_fade(&self) {
-        self.root_layer.removeAllAnimations();
         self.fade_state.borrow_mut().take();
+        self.active_fade.set(0);
+        self.set_live_present_suppressed(false);
+        if to <= 0.0 && *self.pending_hide.borrow() {
+            self.finalize_hide();
+        }
     }
 
-    fn finish_fade(&self, fade_id: u64, final_alpha: f32) {
-        match self.fade_state.try_borrow_mut() {
-            Ok(mut slot) => {
-                let matches = slot.as_ref().map_or(false, |state| state.id == fade_id);
-                if !matches {
-                    return;
-                }
-                slot.take();
-                drop(slot);
-            }
-            Err(_) => {
-                let overlay_ptr_bits = self as *const MissionControlOverlay as usize;
-                schedule_fade_completion(overlay_ptr_bits, fade_id, final_alpha);
-                return;
-            }
+    fn apply_fade_progress(&self, progress: f32) {
+        self.fade_progress.set(progress);
+        let _ = self.cgs_window.set_alpha(self.current_alpha());
+        if self.scale_enabled {
+            self.present();
         }
+    }
 
-        let _ = self.cgs_window.set_alpha(final_alpha);
+    fn current_alpha(&self) -> f32 {
+        if self.fade_enabled {
+            self.fade_progress.get()
+        } else {
+            1.0
+        }
+    }
 
-        let should_finalize = if final_alpha <= 0.0 {
-            *self.pending_hide.borrow()
+    fn current_render_scale(&self) -> f64 {
+        if self.scale_enabled {
+            let progress = f64::from(self.fade_progress.get());
+            FADE_START_SCALE + (1.0 - FADE_START_SCALE) * progress
         } else {
-            false
-        };
+            1.0
+        }
+    }
 
-        if should_finalize {
-            self.finalize_hide();
+    fn set_live_present_suppressed(&self, suppressed: bool) {
+        if let Ok(mut st) = self.state.try_borrow_mut() {
+            st.suppress_live_present = suppressed;
         }
     }
 
@@ -1952,7 +2032,16 @@ impl MissionControlOverlay {
             );
         });
 
-        render_layer_to_cgs_window(self.cgs_window.id(), self.frame.size, &self.root_layer);
+        self.present();
+    }
+
+    fn present(&self) {
+        render_scaled_layer_to_cgs_window(
+            self.cgs_window.id(),
+            self.frame.size,
+            &self.root_layer,
+            self.current_render_scale(),
+        );
     }
 
     fn emit_action(&self, action: MissionControlAction) {
@@ -1989,6 +2078,14 @@ impl MissionControlOverlay {
                 self.emit_action(MissionControlAction::Dismiss);
                 true
             }
+            123 if flags.contains(CGEventFlags::MaskShift) => {
+                self.move_selected_window_action(WorkspaceStep::Previous);
+                true
+            }
+            124 if flags.contains(CGEventFlags::MaskShift) => {
+                self.move_selected_window_action(WorkspaceStep::Next);
+                true
+            }
             123 => {
                 if self.adjust_selection(NavDirection::Left) {
                     self.draw_and_present();
@@ -2222,3 +2319,7 @@ impl MissionControlOverlay {
         }
     }
 }
+
+impl Drop for MissionControlOverlay {
+    fn drop(&mut self) { forget_render_failures(self.cgs_window.id()); }
+}
@@ -2,16 +2,22 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
 use objc2_app_kit::NSStatusWindowLevel;
-use objc2_core_foundation::{CGPoint, CGRect, CGSize};
-use objc2_quartz_core::CALayer;
+use objc2_core_foundation::{CFString, CGPoint, CGRect, CGSize};
+use objc2_quartz_core::{CALayer, CATextLayer, kCAAlignmentCenter, kCATruncationEnd};
 use tracing::warn;
 
 use crate::actor::app::WindowId;
 use crate::common::config::{HorizontalPlacement, VerticalPlacement};
 use crate::sys::cgs_window::{CgsWindow, CgsWindowError};
 use crate::sys::screen::SpaceId;
-use crate::ui::common::{render_layer_to_cgs_window, with_disabled_actions};
+use crate::ui::common::{
+    forget_render_failures, render_layer_to_cgs_window, with_disabled_actions,
+};
+
+/// Segments thinner than this are too small for a title and stay plain.
+const TITLE_MIN_THICKNESS: f64 = 12.0;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -89,6 +95,24 @@ pub struct GroupDisplayData {
     pub total_count: usize,
     pub selected_index: usize,
     pub window_ids: Vec<WindowId>,
+    /// Titles of `window_ids`, in the same order
+    pub window_titles: Vec<String>,
+    /// Whether each window's title should be drawn, indexed like `window_ids`
+    pub title_visible: Vec<bool>,
+}
+
+impl GroupDisplayData {
+    /// The title to draw in the segment at `index`, if it has one and the user
+    /// hasn't hidden it.
+    pub fn visible_title(&self, index: usize) -> Option<&str> {
+        if !self.title_visible.get(index).copied().unwrap_or(true) {
+            return None;
+        }
+        self.window_titles
+            .get(index)
+            .map(String::as_str)
+            .filter(|title| !title.is_empty())
+    }
 }
 
 pub type SegmentClickCallback = Rc<dyn Fn(usize)>;
@@ -99,6 +123,7 @@ struct IndicatorState {
     background_layer: Option<Retained<CALayer>>,
     separator_layers: Vec<Retained<CALayer>>,
     selected_layer: Option<Retained<CALayer>>,
+    title_layers: Vec<Retained<CATextLayer>>,
     click_callback: Option<SegmentClickCallback>,
     space_id: Option<SpaceId>,
 }
@@ -111,6 +136,7 @@ impl IndicatorState {
             background_layer: None,
             separator_layers: Vec::new(),
             selected_layer: None,
+            title_layers: Vec::new(),
             click_callback: None,
             space_id: None,
         }
@@ -261,6 +287,7 @@ impl GroupIndicatorWindow {
         state.background_layer = None;
         state.separator_layers.clear();
         state.selected_layer = None;
+        state.title_layers.clear();
     }
 
     fn update_layers(&self) {
@@ -284,6 +311,9 @@ impl GroupIndicatorWindow {
             self.update_separator_layers(&group_data, adjusted_bounds);
 
             self.update_selected_layer(&group_data, bounds);
+
+            self.ensure_title_layers(group_data.total_count);
+            self.update_title_layers(&group_data, adjusted_bounds);
         });
     }
 
@@ -305,6 +335,59 @@ impl GroupIndicatorWindow {
         }
     }
 
+    fn ensure_title_layers(&self, total_count: usize) {
+        let mut state = self.state.borrow_mut();
+
+        while state.title_layers.len() > total_count {
+            if let Some(layer) = state.title_layers.pop() {
+                layer.removeFromSuperlayer();
+            }
+        }
+
+        while state.title_layers.len() < total_count {
+            let layer = CATextLayer::layer();
+            // Keep titles above the selected segment, which is re-added on every update.
+            layer.setZPosition(1.0);
+            self.root_layer.addSublayer(&layer);
+            state.title_layers.push(layer);
+        }
+    }
+
+    fn update_title_layers(&self, group_data: &GroupDisplayData, bounds: CGRect) {
+        let state = self.state.borrow();
+        for (index, layer) in state.title_layers.iter().enumerate() {
+            let segment = Self::calculate_segment_frame(group_data, bounds, index);
+            let fits = segment.size.height >= TITLE_MIN_THICKNESS
+                && segment.size.width >= 2.0 * TITLE_MIN_THICKNESS;
+            let Some(title) = group_data.visible_title(index).filter(|_| fits) else {
+                layer.setHidden(true);
+                continue;
+            };
+
+            let font_size = (segment.size.height - 4.0).min(12.0);
+            layer.setFrame(CGRect::new(
+                CGPoint::new(
+                    segment.origin.x + 4.0,
+                    segment.origin.y + (segment.size.height - font_size) / 2.0 - 1.0,
+                ),
+                CGSize::new(segment.size.width - 8.0, font_size + 2.0),
+            ));
+            layer.setFontSize(font_size);
+            // Draw at retina resolution; the window server scales it down on 1x displays.
+            layer.setContentsScale(2.0);
+            let text = CFString::from_str(title);
+            let raw = text.as_ref() as *const AnyObject;
+            unsafe {
+                layer.setString(Some(&*raw));
+                layer.setAlignmentMode(kCAAlignmentCenter);
+                layer.setTruncationMode(kCATruncationEnd);
+            }
+            let fg = objc2_app_kit::NSColor::blackColor();
+            layer.setForegroundColor(Some(&fg.CGColor()));
+            layer.setHidden(false);
+        }
+    }
+
     /// Calculate adjusted bounds with proper corner handling only
     fn calculate_adjusted_bounds(
         &self,
@@ -669,3 +752,7 @@ impl GroupIndicatorWindow {
         render_layer_to_cgs_window(self.cgs_window.id(), frame.size, &self.root_layer);
     }
 }
+
+impl Drop for GroupIndicatorWindow {
+    fn drop(&mut self) { forget_render_failures(self.cgs_window.id()); }
+}